@@ -15,6 +15,12 @@ pub enum Error {
     #[error("{0}")]
     IO(#[from] std::io::Error),
 
+    #[error("Request failed - {0}")]
+    Request(#[from] crate::connection::Error),
+
+    #[error("Invalid response contents - {0}")]
+    InvalidContents(#[from] lyric::Error),
+
     #[error("Internal inconsistency - {0}")]
     Internal(String),
 
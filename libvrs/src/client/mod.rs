@@ -8,6 +8,7 @@ use crate::{
     connection::{Connection, Message, Request, Response, SubscriptionRequest, SubscriptionUpdate},
     rt::program::{Form, KeywordId},
 };
+use serde::de::DeserializeOwned;
 use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, warn};
@@ -129,6 +130,12 @@ impl Client {
         Ok(resp_rx.await?)
     }
 
+    /// Dispatch a request, deserializing contents of successful response into `T`
+    pub async fn request_as<T: DeserializeOwned>(&self, req: lyric::Form) -> Result<T> {
+        let contents = self.request(req).await?.contents?;
+        Ok(lyric::from_form(contents)?)
+    }
+
     /// Subscribe to a new topic
     pub async fn subscribe(&self, topic: KeywordId) -> Result<Subscription> {
         let (resp_tx, resp_rx) = oneshot::channel();
@@ -355,6 +362,48 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn request_as() {
+        use serde::Deserialize;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Info {
+            name: String,
+            pid: Option<i32>,
+        }
+
+        let (local, mut remote) = Connection::pair().unwrap();
+
+        tokio::spawn(async move {
+            while let Some(Ok(req)) = remote.recv_req().await {
+                let _ = remote
+                    .send_resp(Response {
+                        req_id: req.id,
+                        contents: Ok(req.contents),
+                    })
+                    .await;
+            }
+        });
+
+        let client = Client::new(local);
+        let info: Info = client
+            .request_as(Form::from_expr("(:name \"echo\" :pid nil)").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            info,
+            Info {
+                name: "echo".to_string(),
+                pid: None
+            }
+        );
+
+        assert_matches!(
+            client.request_as::<Info>(Form::Int(1)).await,
+            Err(Error::InvalidContents(_))
+        );
+    }
+
     #[tokio::test]
     async fn closed_after_remote_conn_drop() {
         use std::time::Duration;
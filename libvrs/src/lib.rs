@@ -9,11 +9,12 @@ pub use connection::{Connection, Request, Response};
 pub use client::Client;
 
 pub use rt::program::{
-    Bytecode, Env, Extern, Fiber, Form, KeywordId, Lambda, Locals, NativeAsyncFn, NativeFn,
-    NativeFnOp, Pattern, Program, Val,
+    proc_env, Bytecode, Env, Extern, Fiber, Form, KeywordId, Lambda, Locals, NativeAsyncFn,
+    NativeFn, NativeFnOp, Pattern, Program, Val,
 };
 pub use rt::{
-    Error, Process, ProcessExit, ProcessHandle, ProcessResult, ProcessSet, Result, Runtime,
+    Clock, Error, Process, ProcessExit, ProcessHandle, ProcessResult, ProcessSet, ProcessStatus, Result,
+    Runtime,
 }; // TODO: Should rt reexport from lib?
 
 /// The path to runtime socket
@@ -24,6 +25,19 @@ pub fn runtime_socket() -> PathBuf {
     home.as_path().join(runtime_socket_name())
 }
 
+/// The path to file scheduled jobs are saved to
+pub fn scheduler_jobs_path() -> PathBuf {
+    let data = dirs::data_local_dir()
+        .or_else(dirs::home_dir)
+        .expect("Could not retrieve find data or home directory");
+    let name = if cfg!(debug_assertions) {
+        "jobs-debug.ll"
+    } else {
+        "jobs.ll"
+    };
+    data.as_path().join("vrs").join(name)
+}
+
 /// The name of runtime socket
 pub fn runtime_socket_name() -> &'static str {
     if cfg!(debug_assertions) {
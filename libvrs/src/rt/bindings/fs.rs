@@ -1,29 +1,16 @@
 //! File System bindings for VRS Processes
 
-use crate::{Fiber, NativeAsyncFn, Val};
-use lyric::{parse, Error, Form, Result};
+use crate::{NativeAsyncFn, Val};
+use lyric::{kwargs, native_fn, parse, pprint, Error, Form, KeywordId, Result};
 use tokio::{
     fs::{File, OpenOptions},
     io::{AsyncReadExt, AsyncWriteExt},
 };
 
-pub(crate) fn fread_fn() -> NativeAsyncFn {
-    NativeAsyncFn {
-        doc: "(fread PATH) - Read the symbolic expression from file at PATH".to_string(),
-        func: |f, args| Box::new(fread_impl(f, args)),
-    }
-}
-
-async fn fread_impl(_fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
-    let path = match &args[..] {
-        [Val::String(s)] => shellexpand::tilde(s).to_string(),
-        e => {
-            return Err(Error::UnexpectedArguments(format!(
-                "fread expects first argument to be string. Got {:?}",
-                e
-            )))
-        }
-    };
+/// (fread PATH) - Read the symbolic expression from file at PATH
+#[native_fn]
+pub(crate) async fn fread(path: String) -> Result<Val> {
+    let path = shellexpand::tilde(&path).to_string();
 
     let mut file = File::open(path)
         .await
@@ -39,23 +26,15 @@ async fn fread_impl(_fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
     Ok(val)
 }
 
-pub(crate) fn fdump_fn() -> NativeAsyncFn {
-    NativeAsyncFn {
-        doc: "(fdump PATH FORM) - Dump the symbolic expression FORM to file at PATH".to_string(),
-        func: |f, args| Box::new(fdump_impl(f, args)),
-    }
-}
-
-async fn fdump_impl(_fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
-    let (path, val) = match &args[..] {
-        [Val::String(s), val] => (shellexpand::tilde(s).to_string(), val),
-        a => {
-            return Err(Error::UnexpectedArguments(format!(
-                "fread expects first argument to be string. Got {:?}",
-                a
-            )))
-        }
-    };
+/// (fdump PATH FORM [:pretty]) - Dump the symbolic expression FORM to file at PATH.
+/// If :pretty is specified, FORM is pretty printed over multiple lines.
+#[native_fn]
+pub(crate) async fn fdump(path: String, form: Form, #[rest] opts: Vec<Val>) -> Result<Val> {
+    let path = shellexpand::tilde(&path).to_string();
+    let pretty = matches!(
+        kwargs::flag(&opts, &KeywordId::from("pretty")),
+        Some(Val::Bool(true))
+    );
 
     let mut file = OpenOptions::new()
         .write(true)
@@ -65,7 +44,11 @@ async fn fdump_impl(_fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
         .await
         .map_err(|e| Error::Runtime(format!("Failed to open file - {e}")))?;
 
-    let val_str = Form::try_from(val.clone())?.to_string();
+    let val_str = if pretty {
+        pprint::pretty(&form, pprint::DEFAULT_WIDTH)
+    } else {
+        form.to_string()
+    };
     file.write_all(val_str.as_bytes())
         .await
         .map_err(|e| Error::Runtime(format!("Failed to write to file - {e}")))?;
@@ -77,5 +60,32 @@ async fn fdump_impl(_fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
     Ok(Val::keyword("ok"))
 }
 
-// TODO: Test Cases for fread
-// TODO: Test Cases for fdump
+#[cfg(test)]
+mod tests {
+    use crate::rt::{kernel, ProcessResult};
+    use crate::{Program, Val};
+
+    #[tokio::test]
+    async fn fdump_fread() {
+        let path = std::env::temp_dir().join(format!("vrs_fdump_{}.ll", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let k = kernel::start();
+        let prog = format!(
+            r#"(begin
+                (fdump "{path}" '(:name "vrs" :items (1 2 3)) :pretty)
+                (fread "{path}"))"#
+        );
+        let hdl = k
+            .spawn_prog(Program::from_expr(&prog).unwrap())
+            .await
+            .unwrap();
+        let exit = hdl.join().await.unwrap();
+        assert_eq!(
+            exit.status.unwrap(),
+            ProcessResult::Done(Val::from_expr("(:name \"vrs\" :items (1 2 3))").unwrap())
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+}
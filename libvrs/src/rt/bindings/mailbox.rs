@@ -1,13 +1,16 @@
 //! Bindings for Process Mailbox
 use crate::rt::{
-    mailbox::Message,
-    program::{Extern, Fiber, Lambda, NativeAsyncFn, Pattern, Val},
+    mailbox::{MailboxHandle, Message},
+    program::{Extern, Fiber, Lambda, NativeAsyncFn, NativeFn, NativeFnOp, Pattern, Seq, Val},
+    ProcessId, ProcessStatus,
 };
-use lyric::{compile, parse, Error, Result, SymbolId};
+use lyric::{compile, kwargs, Arity, Error, FromVal, IntoVal, KeywordId, Result};
+use std::time::Duration;
 
 pub(crate) fn send_fn() -> NativeAsyncFn {
     NativeAsyncFn {
         doc: "(send PID MSG) - Send process PID the message MSG".to_string(),
+        arity: Some(Arity::exact(2)),
         func: |f, args| Box::new(send_impl(f, args)),
     }
 }
@@ -15,48 +18,90 @@ pub(crate) fn send_fn() -> NativeAsyncFn {
 /// Binding to recv messages
 pub(crate) fn recv_fn() -> NativeAsyncFn {
     NativeAsyncFn {
-        doc: "(recv [PATTERN]) - Poll mailbox for a message. \
-              Optional PATTERN argument can match for messages matching specific patterns."
+        doc: "(recv [PATTERN ...] [:timeout MS]) - Poll mailbox for a message. \
+              Optional PATTERN arguments can match for messages matching specific patterns. \
+              With :timeout, returns a timeout error value if no message is received within \
+              MS milliseconds or DURATION. Trailing :timeout MS arguments are always read as the \
+              timeout, so use receive to match a :timeout message along with other patterns."
             .to_string(),
+        arity: Some(Arity::at_least(0)),
         func: |f, args| Box::new(recv_impl(f, args)),
     }
 }
 
+/// Binding to recv messages for receive
+pub(crate) fn recv_match_fn() -> NativeAsyncFn {
+    NativeAsyncFn {
+        doc: "(recv_match PATTERNS TIMEOUT) - Poll mailbox for a message matching any of \
+              PATTERNS, returning (:msg MSG). Unless TIMEOUT is nil, returns (:timeout) if no \
+              message is received within TIMEOUT milliseconds or DURATION. Used by receive."
+            .to_string(),
+        arity: Some(Arity::exact(2)),
+        func: |f, args| Box::new(recv_match_impl(f, args)),
+    }
+}
+
+/// Binding to consume messages as a lazy sequence
+pub(crate) fn recv_seq_fn() -> NativeFn {
+    NativeFn {
+        doc: "(recv_seq [PATTERN]) - Lazy sequence of messages received from mailbox. \
+              Optional PATTERN argument limits sequence to messages matching PATTERN."
+            .to_string(),
+        arity: Some(Arity::range(0, 1)),
+        func: |_, args| {
+            // (lambda () (loop (yield (recv 'PATTERN))))
+            let mut recv = vec![Val::NativeAsyncFn(recv_fn())];
+            recv.extend(
+                args.iter()
+                    .map(|p| Val::List(vec![Val::symbol("quote"), p.clone()])),
+            );
+            let body = Val::List(vec![
+                Val::symbol("loop"),
+                Val::List(vec![Val::symbol("yield"), Val::List(recv)]),
+            ]);
+            let generator = Lambda {
+                doc: None,
+                params: vec![],
+                code: compile(&body)?.into(),
+                parent: None,
+            };
+            Ok(NativeFnOp::Return(Val::Seq(Seq::from_fn(Val::Lambda(
+                generator,
+            )))))
+        },
+    }
+}
+
 /// Binding to list messages
 pub(crate) fn ls_msgs_fn() -> NativeAsyncFn {
     NativeAsyncFn {
         doc: "(ls_msgs) - Returns contents of mailbox without consuming messages or blocking when mailbox is empty.".to_string(),
+        arity: Some(Arity::exact(0)),
         func: |f, args| Box::new(ls_msgs_impl(f, args)),
     }
 }
 
 /// Binding for call
-pub(crate) fn call_fn() -> Lambda {
-    Lambda {
-        doc: Some("(call PID MSG) - Send process PID a message MSG and block until receiving a response for the message".to_string()),
-        params: vec![SymbolId::from("pid"), SymbolId::from("msg")],
-        code: compile(
-            &parse(
-                r#"
-            (begin
-                (def r (ref))
-                (send pid (list r (self) msg))
-                (get (recv (list r 'any)) 1))
-        "#,
-            )
-            .unwrap()
-            .into(),
-        )
-        .unwrap(),
-        parent: None,
+pub(crate) fn call_fn() -> NativeAsyncFn {
+    NativeAsyncFn {
+        doc: "(call PID MSG [:timeout MS]) - Send process PID a message MSG and block until receiving \
+              a response for the message. Raises an error if PID exits before responding, or if no \
+              response is received within MS milliseconds or DURATION, defaulting to 5 seconds."
+            .to_string(),
+        arity: Some(Arity::range(2, 4)),
+        func: |f, args| Box::new(call_impl(f, args)),
     }
 }
 
+/// Default time to wait for response to call
+pub(crate) const CALL_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Implementation for (send PID MSG)
 async fn send_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
     let src = fiber.locals().pid;
     let (dst, msg) = match &args[..] {
-        [Val::Extern(Extern::ProcessId(dst)), msg] => (dst, msg),
+        [Val::Extern(Extern::ProcessId(dst)), msg] => (*dst, msg),
+        [Val::Extern(Extern::Task(hdl)), msg] => (hdl.id(), msg),
         _ => {
             return Err(Error::UnexpectedArguments(
                 "Unexpected send call - (send DEST_PID DATA)".to_string(),
@@ -64,12 +109,12 @@ async fn send_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
         }
     };
 
-    if src == *dst {
+    if src == dst {
         fiber
             .locals()
             .self_handle
             .as_ref()
-            .expect("process should have self handle")
+            .ok_or(Error::Runtime("Process has no self handle".to_string()))?
             .notify_message(Message::new(src, msg.clone()))
             .await;
     } else {
@@ -80,7 +125,7 @@ async fn send_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
             .and_then(|k| k.upgrade())
             .ok_or(Error::Runtime("Kernel is missing for process".to_string()))?;
         kernel
-            .send_message(src, *dst, msg.clone())
+            .send_message(src, dst, msg.clone())
             .await
             .map_err(|e| Error::Runtime(format!("{e}")))?;
     }
@@ -88,24 +133,183 @@ async fn send_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
     Ok(msg.clone())
 }
 
-/// Implementation for (recv PAT)
-async fn recv_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
-    let pat = match &args[..] {
-        [pat] => Some(Pattern::from_val(pat.clone())),
-        [] => None,
-        _ => Some(Pattern::from_vals(&args[..])),
+/// Implementation for (call PID MSG [:timeout MS])
+async fn call_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
+    let (dst, msg, opts) = match &args[..] {
+        [dst, msg, opts @ ..] => (ProcessId::from_val(dst.clone())?, msg.clone(), opts),
+        _ => {
+            return Err(Error::UnexpectedArguments(
+                "Unexpected call - (call PID MSG [:timeout MS])".to_string(),
+            ))
+        }
     };
+    let timeout = match kwargs::get(opts, &KeywordId::from("timeout")) {
+        Some(timeout) => timeout_millis(&timeout)?,
+        None => CALL_TIMEOUT,
+    };
+    call(fiber, dst, msg, timeout).await
+}
+
+/// Send process `dst` a request with `msg`, and wait for its response
+pub(crate) async fn call(
+    fiber: &mut Fiber,
+    dst: ProcessId,
+    msg: Val,
+    timeout: Duration,
+) -> Result<Val> {
+    let src = fiber.locals().pid;
+    if src == dst {
+        return Err(Error::Runtime("Process cannot call itself".to_string()));
+    }
+    let kernel = fiber
+        .locals()
+        .kernel
+        .as_ref()
+        .and_then(|k| k.upgrade())
+        .ok_or(Error::Runtime("Kernel is missing for process".to_string()))?;
     let mailbox = fiber
         .locals()
         .self_handle
         .as_ref()
-        .expect("process should have self handle")
+        .ok_or(Error::Runtime("Process has no self handle".to_string()))?
         .mailbox();
+
+    // monitor ref doubles as request ref, identifying response
+    let mref = kernel
+        .monitor(src, dst)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    let req = Val::List(vec![Val::Ref(mref.clone()), src.into_val(), msg]);
+    kernel
+        .send_message(src, dst, req)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+
+    let resp = Val::List(vec![Val::Ref(mref.clone()), Val::symbol("_")]);
+    let down = Val::List(vec![
+        Val::keyword("down"),
+        Val::Ref(mref.clone()),
+        Val::symbol("_"),
+        Val::symbol("_"),
+    ]);
+    let _status = fiber.locals().enter_status(ProcessStatus::AwaitingRecv);
     let msg = mailbox
-        .poll(pat)
+        .poll_timeout(
+            Some(Pattern::from_vals(&[resp.clone(), down.clone()])),
+            timeout,
+        )
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+
+    let timed_out = msg.is_none();
+    let res = match msg.map(|m| m.contents) {
+        Some(Val::List(l)) if l.len() == 2 => Ok(l[1].clone()),
+        Some(Val::List(l)) => Err(Error::Runtime(format!(
+            "call to {dst} failed - process exited with {}",
+            l[3]
+        ))),
+        _ => Err(Error::Runtime(format!(
+            "call to {dst} timed out after {}ms",
+            timeout.as_millis()
+        ))),
+    };
+
+    kernel
+        .demonitor(mref.clone())
         .await
         .map_err(|e| Error::Runtime(format!("{e}")))?;
-    Ok(msg.contents)
+    let mut stale = vec![down];
+    if timed_out {
+        // drop response if it arrives after giving up on it
+        kernel
+            .unalias(src, mref)
+            .await
+            .map_err(|e| Error::Runtime(format!("{e}")))?;
+        stale.push(resp);
+    }
+    flush(mailbox, &stale).await?;
+    res
+}
+
+/// Drop messages matching any of `pats` from mailbox, without waiting for more
+pub(crate) async fn flush(mailbox: &MailboxHandle, pats: &[Val]) -> Result<()> {
+    let pat = Pattern::from_vals(pats);
+    while mailbox
+        .poll_timeout(Some(pat.clone()), Duration::ZERO)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?
+        .is_some()
+    {}
+    Ok(())
+}
+
+/// Parse timeout given as milliseconds or a duration
+pub(crate) fn timeout_millis(timeout: &Val) -> Result<Duration> {
+    match timeout {
+        Val::Int(ms) if *ms >= 0 => Ok(Duration::from_millis(*ms as u64)),
+        Val::Duration(d) => d.from_now(),
+        v => Err(Error::UnexpectedArguments(format!(
+            ":timeout should be nonnegative milliseconds or a duration - got {v}"
+        ))),
+    }
+}
+
+/// Implementation for (recv [PAT ...] [:timeout MS])
+async fn recv_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
+    let (pats, timeout) = match &args[..] {
+        [pats @ .., Val::Keyword(k), timeout] if k.as_str() == "timeout" => {
+            (pats, Some(timeout_millis(timeout)?))
+        }
+        pats => (pats, None),
+    };
+    let pat = match pats {
+        [pat] => Some(Pattern::from_val(pat.clone())),
+        [] => None,
+        _ => Some(Pattern::from_vals(pats)),
+    };
+    Ok(match poll_msg(fiber, pat, timeout).await? {
+        Some(msg) => msg,
+        None => Val::Error(Error::Timeout),
+    })
+}
+
+/// Implementation for (recv_match PATTERNS TIMEOUT)
+async fn recv_match_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
+    let (pats, timeout) = match &args[..] {
+        [Val::List(pats), Val::Nil] => (pats, None),
+        [Val::List(pats), timeout] => (pats, Some(timeout_millis(timeout)?)),
+        _ => {
+            return Err(Error::UnexpectedArguments(
+                "recv_match expects a list of patterns and a timeout or nil".to_string(),
+            ))
+        }
+    };
+    Ok(
+        match poll_msg(fiber, Some(Pattern::from_vals(pats)), timeout).await? {
+            Some(msg) => Val::List(vec![Val::keyword("msg"), msg]),
+            None => Val::List(vec![Val::keyword("timeout")]),
+        },
+    )
+}
+
+/// Poll mailbox for message matching `pat`, or None if `timeout` elapses first
+async fn poll_msg(
+    fiber: &Fiber,
+    pat: Option<Pattern>,
+    timeout: Option<Duration>,
+) -> Result<Option<Val>> {
+    let hdl = fiber
+        .locals()
+        .self_handle
+        .as_ref()
+        .ok_or(Error::Runtime("Process has no self handle".to_string()))?;
+    let _status = hdl.enter_status(ProcessStatus::AwaitingRecv);
+    let msg = match timeout {
+        Some(timeout) => hdl.mailbox().poll_timeout(pat, timeout).await,
+        None => hdl.mailbox().poll(pat).await.map(Some),
+    }
+    .map_err(|e| Error::Runtime(format!("{e}")))?;
+    Ok(msg.map(|msg| msg.contents))
 }
 
 /// Implementation for (ls_msgs)
@@ -204,6 +408,35 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn call_late_reply() {
+        let k = kernel::start();
+
+        let hdl = k
+            .spawn_prog(
+                Program::from_expr(
+                    "(begin
+                        (def server (spawn (lambda () (begin
+                            (def req (recv))
+                            (sleep (duration :millis 30))
+                            (send (get req 1) (list (get req 0) :late))))))
+                        (def res (try (call server :req :timeout 5)))
+                        (sleep (duration :millis 60))
+                        (list (err? res) (ls_msgs)))",
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let exit = hdl.join().await.unwrap();
+        assert_eq!(
+            exit.status.unwrap(),
+            ProcessResult::Done(Val::from_expr("(true ())").unwrap()),
+            "late reply to timed out call should be dropped"
+        );
+    }
+
     #[tokio::test]
     async fn ls_msgs_empty() {
         let k = kernel::start();
@@ -244,6 +477,34 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn recv_seq() {
+        let k = kernel::start();
+
+        let hdl = k
+            .spawn_prog(
+                Program::from_expr(
+                    "(begin
+                        (send (self) '(:num 1))
+                        (send (self) :skipped)
+                        (send (self) '(:num 2))
+                        (send (self) '(:num 3))
+                        (def nums (lazy_map (recv_seq '(:num _)) (fn (m) (get m 1))))
+                        (list (realize (take nums 2)) (ls_msgs)))",
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let exit = hdl.join().await.unwrap();
+        assert_eq!(
+            exit.status.unwrap(),
+            ProcessResult::Done(Val::from_expr("((1 2) (:skipped (:num 3)))").unwrap()),
+            "recv_seq should consume only matching messages as they are taken"
+        );
+    }
+
     #[tokio::test]
     async fn recv_with_pattern() {
         let k = kernel::start();
@@ -386,4 +647,88 @@ mod tests {
             )
         );
     }
+
+    #[tokio::test]
+    async fn recv_with_timeout() {
+        let k = kernel::start();
+
+        let prog = r#"(begin
+            (def timed_out (recv '(:reply _) :timeout 10))
+            (send (self) '(:reply 1))
+            (list (err? timed_out)
+                  (recv '(:reply _) :timeout (duration :millis 10))
+                  (recv :timeout 0)))
+        "#;
+        let hdl = k
+            .spawn_prog(Program::from_expr(prog).unwrap())
+            .await
+            .unwrap();
+
+        let exit = hdl.join().await.unwrap();
+        assert_eq!(
+            exit.status.unwrap(),
+            ProcessResult::Done(Val::List(vec![
+                Val::Bool(true),
+                Val::from_expr("(:reply 1)").unwrap(),
+                Val::Error(Error::Timeout),
+            ])),
+            "Messages sent after a timeout should still be received"
+        );
+    }
+
+    #[tokio::test]
+    async fn receive_clauses() {
+        let k = kernel::start();
+
+        let prog = r#"(begin
+            (send (self) :ignored)
+            (send (self) '(:two 2))
+            (defn next ()
+              (receive
+                ((:one x) (list :one x))
+                ((:two x) (list :two x))
+                (after 10 :timed_out)))
+            (list (next) (next) (receive (_ :any)) (ls_msgs)))
+        "#;
+        let hdl = k
+            .spawn_prog(Program::from_expr(prog).unwrap())
+            .await
+            .unwrap();
+
+        let exit = hdl.join().await.unwrap();
+        assert_eq!(
+            exit.status.unwrap(),
+            ProcessResult::Done(Val::from_expr("((:two 2) :timed_out :any ())").unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn receive_any_message() {
+        let k = kernel::start();
+
+        let prog = r#"(begin
+            (def _msg :global)
+            (send (self) (try (undefined_fn)))
+            (send (self) (recv :nothing :timeout 0))
+            (send (self) :timeout)
+            (list
+              (receive (e (err? e)) (after 10 :timed_out))
+              (receive (_ :timeout_error) (after 10 :timed_out))
+              (receive (:timeout :timeout_msg) (after 10 :timed_out))
+              (receive (:other :other) (after 10 _msg))))
+        "#;
+        let hdl = k
+            .spawn_prog(Program::from_expr(prog).unwrap())
+            .await
+            .unwrap();
+
+        let exit = hdl.join().await.unwrap();
+        assert_eq!(
+            exit.status.unwrap(),
+            ProcessResult::Done(
+                Val::from_expr("(true :timeout_error :timeout_msg :global)").unwrap()
+            ),
+            "Error values and :timeout should be received like other messages"
+        );
+    }
 }
@@ -3,9 +3,13 @@ mod mailbox;
 mod open;
 mod proc;
 mod pubsub;
+mod scheduler;
+mod select;
 mod service;
+mod supervisor;
 mod system;
 mod term;
+mod timer;
 
 pub(crate) use term::recv_req_fn;
 pub(crate) use term::send_resp_fn;
@@ -13,14 +17,30 @@ pub(crate) use term::send_resp_fn;
 pub(crate) use mailbox::call_fn;
 pub(crate) use mailbox::ls_msgs_fn;
 pub(crate) use mailbox::recv_fn;
+pub(crate) use mailbox::recv_match_fn;
+pub(crate) use mailbox::recv_seq_fn;
 pub(crate) use mailbox::send_fn;
 
+pub(crate) use select::select_recv_fn;
+
+pub(crate) use proc::await_fn;
+pub(crate) use proc::demonitor_fn;
+pub(crate) use proc::exit_fn;
 pub(crate) use proc::kill_fn;
+pub(crate) use proc::link_fn;
+pub(crate) use proc::monitor_fn;
 pub(crate) use proc::pid_fn;
+pub(crate) use proc::proc_info_fn;
+pub(crate) use proc::profile_fn;
 pub(crate) use proc::ps_fn;
 pub(crate) use proc::self_fn;
 pub(crate) use proc::sleep_fn;
+pub(crate) use proc::sleep_until_fn;
 pub(crate) use proc::spawn_fn;
+pub(crate) use proc::spawn_link_fn;
+pub(crate) use proc::spawn_task_fn;
+pub(crate) use proc::trap_exit_fn;
+pub(crate) use proc::unlink_fn;
 
 pub(crate) use system::exec_fn;
 pub(crate) use system::shell_expand_fn;
@@ -38,8 +58,21 @@ pub(crate) use service::register_fn;
 pub(crate) use service::spawn_srv_fn;
 pub(crate) use service::srv_fn;
 
+pub(crate) use supervisor::supervisor_fn;
+
+pub(crate) use scheduler::ls_jobs_fn;
+pub(crate) use scheduler::rm_job_fn;
+pub(crate) use scheduler::schedule_fn;
+pub(crate) use scheduler::scheduler_fn;
+
+pub(crate) use timer::cancel_timer_fn;
+pub(crate) use timer::interval_fn;
+pub(crate) use timer::read_timer_fn;
+pub(crate) use timer::send_after_fn;
+
 pub(crate) use pubsub::publish_fn;
 pub(crate) use pubsub::subscribe_fn;
+pub(crate) use pubsub::subscribe_seq_fn;
 
 pub(crate) use fs::fdump_fn;
 pub(crate) use fs::fread_fn;
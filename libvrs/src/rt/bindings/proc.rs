@@ -1,7 +1,11 @@
 //! Process Management Bindings
-use crate::rt::program::{Extern, Fiber, NativeAsyncFn, NativeFn, NativeFnOp, Program, Val};
-use crate::rt::ProcessId;
-use lyric::{Error, Result};
+use crate::rt::kernel::KernelHandle;
+use crate::rt::program::{
+    Child, Extern, Fiber, Lambda, NativeAsyncFn, NativeFn, NativeFnOp, Program, Val,
+};
+use crate::rt::{ProcessHandle, ProcessId, ProcessStatus};
+use lyric::profile::Metric;
+use lyric::{kwargs, native_fn, Arity, Error, KeywordId, Ref, Result};
 use std::time::Duration;
 use tokio::time;
 use tracing::debug;
@@ -10,6 +14,7 @@ use tracing::debug;
 pub(crate) fn self_fn() -> NativeFn {
     NativeFn {
         doc: "(self) - Returns process id of caller".to_string(),
+        arity: Some(Arity::exact(0)),
         func: |f, _| {
             let pid = f.locals().pid;
             Ok(NativeFnOp::Return(Val::Extern(Extern::ProcessId(pid))))
@@ -17,140 +22,435 @@ pub(crate) fn self_fn() -> NativeFn {
     }
 }
 
-/// binding to create a new PID
-pub(crate) fn pid_fn() -> NativeFn {
-    NativeFn {
-        doc: "(pid NUMBER) - Creates a new process id type for given NUMBER".to_string(),
-        func: |_, args| {
-            let pid = match args {
-                [Val::Int(pid)] => pid,
-                _ => {
-                    return Err(Error::UnexpectedArguments(
-                        "pid expects single integer argument".to_string(),
-                    ))
-                }
-            };
-            Ok(NativeFnOp::Return(Val::Extern(Extern::ProcessId(
-                ProcessId::from(*pid as usize),
-            ))))
-        },
-    }
+/// (pid NUMBER) - Creates a new process id type for given NUMBER
+#[native_fn]
+pub(crate) fn pid(number: i32) -> Result<ProcessId> {
+    Ok(ProcessId::from(number as usize))
 }
 
 /// Binding to list processes
 pub(crate) fn ps_fn() -> NativeAsyncFn {
     NativeAsyncFn {
-        doc: "(ps) - Returns a list of running process by process id".to_string(),
-        func: |f, _| Box::new(ps_impl(f)),
+        doc: "(ps [:verbose]) - Returns a list of running process by process id. \
+              With :verbose, returns information of each process as given by proc_info."
+            .to_string(),
+        arity: Some(Arity::range(0, 1)),
+        func: |f, args| Box::new(ps_impl(f, args)),
     }
 }
 
 /// Binding to kill process
 pub(crate) fn kill_fn() -> NativeAsyncFn {
     NativeAsyncFn {
-        doc: "(kill PID) - Kill process with process id PID".to_string(),
+        doc: "(kill PID [REASON]) - Kill process with process id PID. With REASON, the process \
+              exits with REASON instead of :killed."
+            .to_string(),
+        arity: Some(Arity::range(1, 2)),
         func: |f, args| Box::new(kill_impl(f, args)),
     }
 }
 
-/// Binding for sleep
-pub(crate) fn sleep_fn() -> NativeAsyncFn {
+/// Binding to await process result
+pub(crate) fn await_fn() -> NativeAsyncFn {
     NativeAsyncFn {
-        doc: "(sleep SECS) - Sleep current process for SECS seconds, blocking execution."
+        doc: "(await PID|TASK [:timeout SECS|DURATION]) - Wait for process to exit, returning \
+              (:ok VAL), (:err ERR) or (:cancelled), or (:timeout) if it is still running after \
+              the timeout. Children spawned by the caller and tasks can be awaited after they \
+              exit, while other processes must be running."
             .to_string(),
-        func: |_, args| {
-            Box::new({
-                async move {
-                    let secs = match args[..] {
-                        [Val::Int(secs)] => secs,
-                        _ => {
-                            return Err(Error::UnexpectedArguments(
-                                "sleep expects single integer argument".to_string(),
-                            ))
-                        }
-                    };
-                    debug!("sleep secs = {:?}", secs);
-                    time::sleep(Duration::from_secs(secs as u64)).await;
-                    Ok(Val::keyword("ok"))
-                }
-            })
-        },
+        arity: Some(Arity::range(1, 3)),
+        func: |f, args| Box::new(await_impl(f, args)),
     }
 }
 
-/// Binding for spawn
-pub(crate) fn spawn_fn() -> NativeAsyncFn {
-    NativeAsyncFn {
-        doc: "(spawn LAMBDA) - Spawn a new child process that runs LAMBDA in new process space."
-            .to_string(),
-        func: |f, args| Box::new(spawn_impl(f, args)),
-    }
+/// (exit REASON) - End the calling process with REASON, delivered to its links and monitors.
+/// Exiting with `:normal` is not treated as a failure by linked processes.
+#[native_fn]
+pub(crate) async fn exit(fiber: &mut Fiber, reason: Val) -> Result<Val> {
+    fiber
+        .locals()
+        .self_handle
+        .as_ref()
+        .expect("process should have self handle")
+        .exit(reason)
+        .await;
+    // process ends once exit message is handled
+    std::future::pending().await
 }
 
-/// Implementation for (ps)
-async fn ps_impl(fiber: &mut Fiber) -> Result<Val> {
+/// (sleep SECS|DURATION) - Sleep current process for SECS seconds or DURATION, blocking execution.
+#[native_fn]
+pub(crate) async fn sleep(fiber: &mut Fiber, duration: Val) -> Result<Val> {
+    debug!("sleep duration = {:?}", duration);
+    let duration = match duration {
+        Val::Int(secs) if secs >= 0 => Duration::from_secs(secs as u64),
+        Val::Duration(d) => d.from_now()?,
+        _ => {
+            return Err(Error::UnexpectedArguments(
+                "sleep expects nonnegative seconds or a duration".to_string(),
+            ))
+        }
+    };
+    let _status = fiber.locals().enter_status(ProcessStatus::Sleeping);
+    time::sleep(duration).await;
+    Ok(Val::keyword("ok"))
+}
+
+/// (sleep_until TIME) - Sleep current process until TIME, returning immediately if TIME is past.
+#[native_fn]
+pub(crate) async fn sleep_until(fiber: &mut Fiber, deadline: lyric::Time) -> Result<Val> {
+    debug!("sleep_until deadline = {}", deadline);
+    let _status = fiber.locals().enter_status(ProcessStatus::Sleeping);
+    time::sleep(lyric::Duration::until(&deadline)).await;
+    Ok(Val::keyword("ok"))
+}
+
+/// (spawn LAMBDA) - Spawn a new child process that runs LAMBDA in new process space.
+#[native_fn]
+pub(crate) async fn spawn(fiber: &mut Fiber, lambda: Lambda) -> Result<ProcessId> {
+    let prog = Program::from_lambda(lambda)?;
     let kernel = fiber
         .locals()
         .kernel
         .as_ref()
         .and_then(|k| k.upgrade())
-        .ok_or(Error::Runtime("Kernel is missing for process".into()))?;
-    let procs = kernel
-        .procs()
+        .ok_or(Error::Runtime("Kernel is missing for process".to_string()))?;
+    let hdl = kernel
+        .spawn_child(prog, fiber.locals().pid)
         .await
-        .map_err(|e| Error::Runtime(format!("{e}")))?
-        .into_iter()
-        .map(|pid| Val::Extern(Extern::ProcessId(pid)))
-        .collect::<Vec<_>>();
-    Ok(Val::List(procs))
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    fiber.locals_mut().add_child(hdl.clone());
+    Ok(hdl.id())
 }
 
-/// Implementation for (kill PID)
-async fn kill_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
-    let pid = match args[..] {
-        [Val::Extern(Extern::ProcessId(pid))] => pid,
-        [Val::Int(pid)] => ProcessId::from(pid as usize),
-        _ => {
-            return Err(Error::UnexpectedArguments(
-                "kill should have one integer argument".to_string(),
-            ))
+/// (spawn_task LAMBDA) - Spawn a new child process running LAMBDA, returning a task whose result
+/// can be retrieved with await. Tasks can be used anywhere a pid is expected.
+#[native_fn]
+pub(crate) async fn spawn_task(fiber: &mut Fiber, lambda: Lambda) -> Result<Val> {
+    let prog = Program::from_lambda(lambda)?;
+    let hdl = kernel(fiber)?
+        .spawn_child(prog, fiber.locals().pid)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    Ok(Val::Extern(Extern::Task(hdl)))
+}
+
+/// (spawn_link LAMBDA) - Spawn a new child process running LAMBDA that is linked to the caller.
+#[native_fn]
+pub(crate) async fn spawn_link(fiber: &mut Fiber, lambda: Lambda) -> Result<ProcessId> {
+    let prog = Program::from_lambda(lambda)?;
+    let hdl = kernel(fiber)?
+        .spawn_link(prog, fiber.locals().pid)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    fiber.locals_mut().add_child(hdl.clone());
+    Ok(hdl.id())
+}
+
+/// (link PID) - Link caller to process PID. Abnormal exits of either process kill the other,
+/// unless it traps exits.
+#[native_fn]
+pub(crate) async fn link(fiber: &mut Fiber, pid: ProcessId) -> Result<Val> {
+    kernel(fiber)?
+        .link(fiber.locals().pid, pid)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    Ok(Val::keyword("ok"))
+}
+
+/// (unlink PID) - Remove link between caller and process PID, if any.
+#[native_fn]
+pub(crate) async fn unlink(fiber: &mut Fiber, pid: ProcessId) -> Result<Val> {
+    kernel(fiber)?
+        .unlink(fiber.locals().pid, pid)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    Ok(Val::keyword("ok"))
+}
+
+/// (monitor PID) - Monitor process PID, returning a reference REF.
+/// When PID exits, `(:down REF PID REASON)` is sent to the caller's mailbox.
+#[native_fn]
+pub(crate) async fn monitor(fiber: &mut Fiber, pid: ProcessId) -> Result<Ref> {
+    kernel(fiber)?
+        .monitor(fiber.locals().pid, pid)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))
+}
+
+/// (demonitor REF) - Remove monitor with reference REF.
+#[native_fn]
+pub(crate) async fn demonitor(fiber: &mut Fiber, mref: Ref) -> Result<Val> {
+    kernel(fiber)?
+        .demonitor(mref)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    Ok(Val::keyword("ok"))
+}
+
+/// (trap_exit BOOL) - When BOOL is true, exits of linked processes are delivered as
+/// `(:exit PID REASON)` messages instead of killing the caller.
+#[native_fn]
+pub(crate) async fn trap_exit(fiber: &mut Fiber, trap: bool) -> Result<Val> {
+    kernel(fiber)?
+        .trap_exit(fiber.locals().pid, trap)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    Ok(Val::keyword("ok"))
+}
+
+/// (profile PID SECS [:folded PATH] [:metric :await]) - Profile process PID for SECS seconds,
+/// returning its functions hottest first. If :folded is specified, stacks are written to PATH in
+/// folded format for flamegraph tools, weighted by instructions or by microseconds awaited.
+#[native_fn]
+pub(crate) async fn profile(
+    fiber: &mut Fiber,
+    pid: ProcessId,
+    secs: i32,
+    #[rest] opts: Vec<Val>,
+) -> Result<Val> {
+    let metric = match kwargs::get(&opts, &KeywordId::from("metric")) {
+        None => Metric::Insts,
+        Some(Val::Keyword(k)) if k.as_str() == "insts" => Metric::Insts,
+        Some(Val::Keyword(k)) if k.as_str() == "await" => Metric::AwaitMicros,
+        Some(v) => {
+            return Err(Error::UnexpectedArguments(format!(
+                ":metric should be :insts or :await - got {v}"
+            )))
+        }
+    };
+    let folded_path = match kwargs::get(&opts, &KeywordId::from("folded")) {
+        Some(Val::String(path)) => Some(shellexpand::tilde(&path).to_string()),
+        Some(v) => {
+            return Err(Error::UnexpectedArguments(format!(
+                ":folded should be a path - got {v}"
+            )))
         }
+        None => None,
     };
+
     let kernel = fiber
         .locals()
         .kernel
         .as_ref()
         .and_then(|k| k.upgrade())
         .ok_or(Error::Runtime("Kernel is missing for process".to_string()))?;
-    kernel
-        .kill_proc(pid)
+    let profiler = kernel
+        .profiler(pid)
         .await
         .map_err(|e| Error::Runtime(format!("{e}")))?;
-    Ok(Val::keyword("ok"))
+
+    debug!("profile pid = {pid} secs = {secs}");
+    profiler.start();
+    time::sleep(Duration::from_secs(secs.max(0) as u64)).await;
+    let profile = profiler.stop();
+
+    if let Some(path) = folded_path {
+        tokio::fs::write(path, profile.folded(metric))
+            .await
+            .map_err(|e| Error::Runtime(format!("Failed to write folded stacks - {e}")))?;
+    }
+
+    let functions = profile
+        .functions()
+        .into_iter()
+        .map(|f| {
+            Val::List(vec![
+                Val::keyword("function"),
+                Val::String(f.name),
+                Val::keyword("insts"),
+                saturating_int(f.total.insts),
+                Val::keyword("own_insts"),
+                saturating_int(f.own.insts),
+                Val::keyword("await_ms"),
+                saturating_int(f.total.await_time.as_millis()),
+                Val::keyword("own_await_ms"),
+                saturating_int(f.own.await_time.as_millis()),
+            ])
+        })
+        .collect();
+    Ok(Val::List(functions))
 }
 
-/// Implementation for (spawn PROG)
-async fn spawn_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
-    let lambda = match args.as_slice() {
-        [Val::Lambda(l)] => l.clone(),
+/// Count as int value, saturating at largest int
+fn saturating_int(n: impl TryInto<i32>) -> Val {
+    Val::Int(n.try_into().unwrap_or(i32::MAX))
+}
+
+/// Get kernel handle for process running fiber
+fn kernel(fiber: &Fiber) -> Result<KernelHandle> {
+    fiber
+        .locals()
+        .kernel
+        .as_ref()
+        .and_then(|k| k.upgrade())
+        .ok_or(Error::Runtime("Kernel is missing for process".to_string()))
+}
+
+/// (proc_info PID) - Returns information of running process PID as list of `:pid`, `:parent`,
+/// `:names` it is registered as, `:spawned_at`, `:status` (`:running`, `:awaiting_recv`,
+/// `:sleeping`, `:awaiting_exec` or `:awaiting_exit`), `:mailbox_len`, `:insts` executed, and `:source` or entry function.
+#[native_fn]
+pub(crate) async fn proc_info(fiber: &mut Fiber, pid: ProcessId) -> Result<Val> {
+    let hdl = kernel(fiber)?
+        .proc_hdls()
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?
+        .into_iter()
+        .find(|hdl| hdl.id() == pid)
+        .ok_or(Error::Runtime(format!("No running process for {pid}")))?;
+    let info = procs_info(fiber, vec![hdl]).await?;
+    Ok(info.into_iter().next().unwrap_or(Val::Nil))
+}
+
+/// Implementation for (ps [:verbose])
+async fn ps_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
+    let verbose = match &args[..] {
+        [] => false,
+        [Val::Keyword(k)] if k.as_str() == "verbose" => true,
         _ => {
             return Err(Error::UnexpectedArguments(
-                "spawn expects single lambda".to_string(),
+                "ps expects optional :verbose flag".to_string(),
+            ))
+        }
+    };
+
+    let kernel = kernel(fiber)?;
+    if !verbose {
+        let procs = kernel
+            .procs()
+            .await
+            .map_err(|e| Error::Runtime(format!("{e}")))?
+            .into_iter()
+            .map(|pid| Val::Extern(Extern::ProcessId(pid)))
+            .collect::<Vec<_>>();
+        return Ok(Val::List(procs));
+    }
+
+    let mut hdls = kernel
+        .proc_hdls()
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    hdls.sort_by_key(|hdl| *hdl.id().inner());
+    Ok(Val::List(procs_info(fiber, hdls).await?))
+}
+
+/// Information of given processes, including names they are registered as
+async fn procs_info(fiber: &Fiber, hdls: Vec<ProcessHandle>) -> Result<Vec<Val>> {
+    let entries = match &fiber.locals().registry {
+        Some(registry) => registry
+            .all()
+            .await
+            .map_err(|e| Error::Runtime(format!("{e}")))?,
+        None => vec![],
+    };
+
+    let mut info = vec![];
+    for hdl in hdls {
+        let names = entries
+            .iter()
+            .filter(|e| e.pid() == hdl.id())
+            .map(|e| e.keyword().clone())
+            .collect();
+        // processes may exit while their info is gathered
+        if let Ok(i) = hdl.info(names).await {
+            info.push(i);
+        }
+    }
+    Ok(info)
+}
+
+/// Implementation for (await PID|TASK [:timeout SECS|DURATION])
+async fn await_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
+    let (hdl, opts) = match &args[..] {
+        [Val::Extern(Extern::Task(hdl)), opts @ ..] => (hdl.clone(), opts),
+        [Val::Extern(Extern::ProcessId(pid)), opts @ ..] => {
+            match fiber.locals().children.get(pid).cloned() {
+                Some(Child::Running(hdl)) => (hdl, opts),
+                Some(Child::Exited(result)) => {
+                    fiber.locals_mut().children.remove(pid);
+                    return Ok(result);
+                }
+                None => {
+                    let hdl = kernel(fiber)?
+                        .proc_hdls()
+                        .await
+                        .map_err(|e| Error::Runtime(format!("{e}")))?
+                        .into_iter()
+                        .find(|hdl| hdl.id() == *pid)
+                        .ok_or(Error::Runtime(format!("No running process for {pid}")))?;
+                    (hdl, opts)
+                }
+            }
+        }
+        _ => {
+            return Err(Error::UnexpectedArguments(
+                "await expects a pid or task".to_string(),
+            ))
+        }
+    };
+    if hdl.id() == fiber.locals().pid {
+        return Err(Error::Runtime("Process cannot await itself".to_string()));
+    }
+    let timeout = match kwargs::get(opts, &KeywordId::from("timeout")) {
+        None => None,
+        Some(Val::Int(secs)) if secs >= 0 => Some(Duration::from_secs(secs as u64)),
+        Some(Val::Duration(d)) => Some(d.from_now()?),
+        Some(v) => {
+            return Err(Error::UnexpectedArguments(format!(
+                ":timeout should be nonnegative seconds or a duration - got {v}"
+            )))
+        }
+    };
+
+    let pid = hdl.id();
+    let exit = {
+        let _status = fiber.locals().enter_status(ProcessStatus::AwaitingExit);
+        match timeout {
+            Some(timeout) => match time::timeout(timeout, hdl.join()).await {
+                Ok(exit) => exit,
+                Err(_) => return Ok(Val::List(vec![Val::keyword("timeout")])),
+            },
+            None => hdl.join().await,
+        }
+        .map_err(|e| Error::Runtime(format!("{e}")))?
+    };
+    fiber.locals_mut().children.remove(&pid);
+    Ok(exit.result())
+}
+
+/// Implementation for (kill PID [REASON])
+async fn kill_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
+    let (target, reason) = match &args[..] {
+        [target] => (target, None),
+        [target, reason] => (target, Some(reason.clone())),
+        _ => {
+            return Err(Error::UnexpectedArguments(
+                "kill expects a pid and optional reason".to_string(),
+            ))
+        }
+    };
+    let pid = match target {
+        Val::Extern(Extern::ProcessId(pid)) => *pid,
+        Val::Extern(Extern::Task(hdl)) => hdl.id(),
+        Val::Int(pid) => ProcessId::from(*pid as usize),
+        _ => {
+            return Err(Error::UnexpectedArguments(
+                "kill expects a pid or integer".to_string(),
             ))
         }
     };
-    let prog = Program::from_lambda(lambda)?;
     let kernel = fiber
         .locals()
         .kernel
         .as_ref()
         .and_then(|k| k.upgrade())
         .ok_or(Error::Runtime("Kernel is missing for process".to_string()))?;
-    let hdl = kernel
-        .spawn_prog(prog)
+    kernel
+        .kill_proc(pid, reason)
         .await
         .map_err(|e| Error::Runtime(format!("{e}")))?;
-    Ok(Val::Extern(Extern::ProcessId(hdl.id())))
+    Ok(Val::keyword("ok"))
 }
 
 #[cfg(test)]
@@ -192,6 +492,29 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn sleep_duration() {
+        let k = kernel::start();
+        let hdl = k
+            .spawn_prog(
+                Program::from_expr(
+                    "(list (sleep (duration :millis 10)) (sleep_until (now)) (try (sleep -1)))",
+                )
+                .unwrap(),
+            )
+            .await
+            .expect("Kernel should spawn new process");
+
+        let exit = hdl.join().await.unwrap();
+        assert_matches!(
+            exit.status.unwrap(),
+            ProcessResult::Done(Val::List(l)) if matches!(
+                l.as_slice(),
+                [Val::Keyword(a), Val::Keyword(b), Val::Error(_)] if a.as_str() == "ok" && b.as_str() == "ok"
+            )
+        );
+    }
+
     #[tokio::test]
     async fn ps() {
         let k = kernel::start();
@@ -209,6 +532,57 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn ps_verbose() {
+        let k = kernel::start();
+        let hdl = k
+            .spawn_prog(Program::from_expr("(ps :verbose)").unwrap())
+            .await
+            .unwrap();
+
+        let pid = Val::Extern(Extern::ProcessId(hdl.id()));
+        let info = match hdl.join().await.unwrap().status.unwrap() {
+            ProcessResult::Done(Val::List(info)) => info,
+            r => panic!("ps should return list of process info - got {r:?}"),
+        };
+        assert_matches!(
+            &info[..],
+            [Val::List(i)] if lyric::kwargs::get(i, &KeywordId::from("pid")) == Some(pid.clone())
+                && lyric::kwargs::get(i, &KeywordId::from("status")) == Some(Val::keyword("running"))
+                && lyric::kwargs::get(i, &KeywordId::from("source")) == Some(Val::string("(ps :verbose)"))
+        );
+    }
+
+    #[tokio::test]
+    async fn proc_info() {
+        let k = kernel::start();
+        let prog = r#"(begin
+            (defn napper () (begin (register :napper) (sleep 10)))
+            (def child (spawn napper))
+            (send child :hello)
+            (sleep (duration :millis 20))
+            (def info (proc_info child))
+            (list
+                (eq? (get info :parent) (self))
+                (get info :names)
+                (get info :status)
+                (get info :mailbox_len)
+                (> (get info :insts) 0)
+                (get info :source)
+                (err? (try (proc_info (pid 9999))))))
+        "#;
+        let hdl = k
+            .spawn_prog(Program::from_expr(prog).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            hdl.join().await.unwrap().status.unwrap(),
+            ProcessResult::Done(
+                Val::from_expr(r#"(true (:napper) :sleeping 1 true "(napper)" true)"#).unwrap()
+            )
+        );
+    }
+
     #[tokio::test]
     async fn kill() {
         use tokio::time;
@@ -236,6 +610,52 @@ mod tests {
         assert_eq!(killed_exit.status.unwrap(), ProcessResult::Cancelled);
     }
 
+    #[tokio::test]
+    async fn profile() {
+        let path = std::env::temp_dir().join(format!("vrs_profile_{}.folded", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let k = kernel::start();
+        let target = k
+            .spawn_prog(Program::from_expr("(begin (defn nap () (sleep 0)) (loop (nap)))").unwrap())
+            .await
+            .unwrap();
+
+        let prog = format!(
+            r#"(profile (pid {}) 1 :folded "{path}")"#,
+            target.id().inner()
+        );
+        let hdl = k
+            .spawn_prog(Program::from_expr(&prog).unwrap())
+            .await
+            .unwrap();
+        let functions = match hdl.join().await.unwrap().status.unwrap() {
+            ProcessResult::Done(Val::List(functions)) => functions,
+            r => panic!("profile should return list of functions - got {r:?}"),
+        };
+        assert!(functions.iter().any(|f| matches!(
+            f,
+            Val::List(f) if f[..2] == [Val::keyword("function"), Val::string("nap")]
+        )));
+
+        let folded = std::fs::read_to_string(path).unwrap();
+        assert!(folded.lines().any(|l| l.starts_with("<main>;nap ")));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn profile_unknown_process() {
+        let k = kernel::start();
+        let hdl = k
+            .spawn_prog(Program::from_expr("(profile (pid 99) 0)").unwrap())
+            .await
+            .unwrap();
+        assert_matches!(
+            hdl.join().await.unwrap().status,
+            Err(crate::rt::Error::EvaluationError(Error::Runtime(_)))
+        );
+    }
+
     #[tokio::test]
     async fn binding_spawn() {
         let k = kernel::start();
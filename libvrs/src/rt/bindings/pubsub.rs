@@ -1,15 +1,16 @@
 //! Pubsub Bindings
 use crate::rt::{
     mailbox::Message,
-    program::{Fiber, NativeAsyncFn, Val},
+    program::{Fiber, Lambda, NativeAsyncFn, Val},
 };
-use lyric::{Error, Result};
+use lyric::{compile, parse, Arity, Error, KeywordId, Result, SymbolId};
 use tracing::error;
 
 pub(crate) fn subscribe_fn() -> NativeAsyncFn {
     NativeAsyncFn {
         doc: "(subscribe TOPIC) - Subscribe current process to receive pubsub messages for TOPIC."
             .to_string(),
+        arity: Some(Arity::exact(1)),
         func: |f, args| Box::new(subscribe_impl(f, args)),
     }
 }
@@ -18,10 +19,33 @@ pub(crate) fn publish_fn() -> NativeAsyncFn {
     NativeAsyncFn {
         doc: "(publish TOPIC DATA) - Publish DATA over TOPIC, notifying all active subscribers."
             .to_string(),
+        arity: Some(Arity::exact(2)),
         func: |f, args| Box::new(publish_impl(f, args)),
     }
 }
 
+pub(crate) fn subscribe_seq_fn() -> Lambda {
+    Lambda {
+        doc: Some("(subscribe_seq TOPIC) - Subscribe current process to TOPIC, and return lazy sequence of data published over TOPIC.".to_string()),
+        params: vec![SymbolId::from("topic")],
+        code: compile(
+            &parse(
+                r#"
+            (begin
+                (subscribe topic)
+                (seq (fn ()
+                    (loop (yield (get (recv (list :topic_updated topic '_)) 2))))))
+        "#,
+            )
+            .unwrap()
+            .into(),
+        )
+        .unwrap()
+        .into(),
+        parent: None,
+    }
+}
+
 /// Implementation for (subscribe TOPIC)
 async fn subscribe_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
     let topic = match &args[..] {
@@ -32,6 +56,16 @@ async fn subscribe_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
             ))
         }
     };
+    subscribe(fiber, topic).await?;
+    Ok(Val::keyword("ok"))
+}
+
+/// Subscribe process to `topic`, delivering updates to its mailbox as
+/// `(:topic_updated TOPIC DATA)`. Processes are only subscribed once to each topic.
+pub(crate) async fn subscribe(fiber: &mut Fiber, topic: KeywordId) -> Result<()> {
+    if fiber.locals().subscriptions.contains(&topic) {
+        return Ok(());
+    }
     let pubsub = fiber
         .locals()
         .pubsub
@@ -51,6 +85,8 @@ async fn subscribe_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
         .await
         .map_err(|e| Error::Runtime(format!("Failed to subscribe on pubsub - {e}")))?;
 
+    fiber.locals_mut().subscriptions.insert(topic.clone());
+
     // TODO: Idiom for streaming result from =Subscription= to another sink via async task for proc subs + term subs
     tokio::spawn(async move {
         while let Some(ev) = sub.recv().await {
@@ -67,7 +103,7 @@ async fn subscribe_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
         }
     });
 
-    Ok(Val::keyword("ok"))
+    Ok(())
 }
 
 /// Implementation for (publish TOPIC VALUE)
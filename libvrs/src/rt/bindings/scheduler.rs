@@ -0,0 +1,113 @@
+//! Scheduler Bindings
+//! See also [crate::rt::scheduler]
+use std::path::PathBuf;
+
+use super::mailbox::{call, CALL_TIMEOUT};
+use crate::rt::program::{Fiber, NativeAsyncFn, Val};
+use crate::rt::registry::Registration;
+use crate::rt::scheduler::Scheduler;
+use lyric::{kwargs, native_fn, Error, KeywordId, Result};
+
+/// (scheduler [:path PATH]) - Run caller as the scheduler service, registered as :scheduler and
+/// running jobs added with `schedule`. With PATH, jobs are saved to the file at PATH and restored
+/// from it on start. Blocks until the scheduler fails.
+#[native_fn]
+pub(crate) async fn scheduler(fiber: &mut Fiber, #[rest] opts: Vec<Val>) -> Result<Val> {
+    let path = match kwargs::get(&opts, &KeywordId::from("path")) {
+        None => None,
+        Some(Val::String(path)) => Some(PathBuf::from(shellexpand::tilde(&path).to_string())),
+        Some(v) => {
+            return Err(Error::UnexpectedArguments(format!(
+                ":path should be a string - got {v}"
+            )))
+        }
+    };
+
+    let locals = fiber.locals();
+    let kernel = locals
+        .kernel
+        .as_ref()
+        .and_then(|k| k.upgrade())
+        .ok_or(Error::Runtime("Kernel is missing for process".to_string()))?;
+    let registry = locals
+        .registry
+        .clone()
+        .ok_or(Error::Runtime("Process has no registry handle".to_string()))?;
+    let hdl = locals
+        .self_handle
+        .clone()
+        .ok_or(Error::Runtime("Process has no self handle".to_string()))?;
+    let clock = kernel
+        .clock()
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+
+    registry
+        .register(Registration::new(KeywordId::from("scheduler")), hdl.clone())
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+
+    let mut scheduler = Scheduler::new(locals.pid, kernel, registry, hdl.mailbox().clone(), clock);
+    if let Some(path) = path {
+        scheduler = scheduler.path(path);
+    }
+    scheduler
+        .run()
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    Ok(Val::keyword("ok"))
+}
+
+/// (schedule NAME SCHEDULE [:spawn FORM] [:send (SRV MSG)] [:catch_up BOOL]) - Schedule job NAME to
+/// spawn a program running FORM, or send MSG to service SRV as a request like `call`, discarding
+/// its response. SCHEDULE is a cron expression like "30 7 * * mon-fri", :hourly, :daily, :weekly,
+/// :monthly, :yearly, or a calendar spec like (:weekday :mon :hour 7) with :minute, :hour, :day,
+/// :month and :weekday fields. Runs missed while the runtime was stopped or asleep are reported in
+/// `ls_jobs`, and run once with :catch_up.
+/// Replaces any job named NAME, returning info of the job.
+#[native_fn]
+pub(crate) async fn schedule(
+    fiber: &mut Fiber,
+    name: KeywordId,
+    spec: Val,
+    #[rest] opts: Vec<Val>,
+) -> Result<Val> {
+    let mut req = vec![Val::keyword("add_job"), Val::Keyword(name), spec];
+    req.extend(opts);
+    call_scheduler(fiber, Val::List(req)).await
+}
+
+/// (ls_jobs) - List scheduled jobs, with their schedule, action, next and last run times, and
+/// counts of runs and missed runs.
+#[native_fn]
+pub(crate) async fn ls_jobs(fiber: &mut Fiber) -> Result<Val> {
+    call_scheduler(fiber, Val::List(vec![Val::keyword("ls_jobs")])).await
+}
+
+/// (rm_job NAME) - Remove scheduled job NAME, returning whether it existed.
+#[native_fn]
+pub(crate) async fn rm_job(fiber: &mut Fiber, name: KeywordId) -> Result<Val> {
+    call_scheduler(
+        fiber,
+        Val::List(vec![Val::keyword("rm_job"), Val::Keyword(name)]),
+    )
+    .await
+}
+
+/// Call scheduler service with request, raising errors it responds with
+async fn call_scheduler(fiber: &mut Fiber, req: Val) -> Result<Val> {
+    let entry = fiber
+        .locals()
+        .registry
+        .as_ref()
+        .ok_or(Error::Runtime("Process has no registry handle".to_string()))?
+        .lookup(KeywordId::from("scheduler"))
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?
+        .ok_or(Error::Runtime("Scheduler is not running".to_string()))?;
+
+    match call(fiber, entry.pid(), req, CALL_TIMEOUT).await? {
+        Val::Error(e) => Err(e),
+        resp => Ok(resp),
+    }
+}
@@ -0,0 +1,242 @@
+//! Select Bindings
+//! Waits on mailbox, pubsub topics, timers and process exits at once
+use std::future::{self, Future};
+use std::pin::Pin;
+
+use futures::future::select_all;
+use lyric::{kwargs, native_fn, Error, FromVal, KeywordId, Ref, Result};
+use tokio::time;
+
+use super::mailbox::{flush, timeout_millis};
+use super::pubsub::subscribe;
+use crate::rt::program::{Fiber, NativeAsyncFn, Pattern, Val};
+use crate::rt::{ProcessId, ProcessStatus};
+
+/// A source select waits on
+enum Source {
+    /// Mailbox message matching pattern
+    Recv(Val),
+    /// Value published on topic matching pattern
+    Topic(KeywordId, Val),
+    /// Message of timer
+    Timer(Ref),
+    /// Exit reason of process matching pattern
+    Exit(ProcessId, Val),
+}
+
+impl Source {
+    fn from_val(val: &Val) -> Result<Self> {
+        let Val::List(l) = val else {
+            return Err(Error::UnexpectedArguments(format!(
+                "select source should be a list - got {val}"
+            )));
+        };
+        match &l[..] {
+            [Val::Keyword(k), pat] if k.as_str() == "recv" => Ok(Source::Recv(pat.clone())),
+            [Val::Keyword(k), Val::Keyword(topic), pat] if k.as_str() == "topic" => {
+                Ok(Source::Topic(topic.clone(), pat.clone()))
+            }
+            [Val::Keyword(k), Val::Ref(tref)] if k.as_str() == "timer" => {
+                Ok(Source::Timer(tref.clone()))
+            }
+            [Val::Keyword(k), pid, pat] if k.as_str() == "exit" => {
+                Ok(Source::Exit(ProcessId::from_val(pid.clone())?, pat.clone()))
+            }
+            _ => Err(Error::UnexpectedArguments(format!(
+                "Unexpected select source - {val}"
+            ))),
+        }
+    }
+}
+
+/// Source ready with a mailbox message matching pattern
+struct MsgSource {
+    idx: usize,
+    pat: Val,
+    /// Index of element of message source is ready with, or None for the whole message
+    elem: Option<usize>,
+}
+
+/// A timer source, completing with its index and value, or None on timeout
+type Waiting<'a> = Pin<Box<dyn Future<Output = Option<(usize, Val)>> + Send + 'a>>;
+
+/// (select_recv SOURCE ... [:timeout MS]) - Wait for the first ready SOURCE, returning its index
+/// and value as (IDX VAL). SOURCE is (:recv PAT) for a mailbox message matching PAT, (:topic TOPIC
+/// PAT) for a value published on TOPIC matching PAT, (:timer REF) for the message of timer REF, or
+/// (:exit PID PAT) for the exit reason of process PID matching PAT. Processes stay subscribed to
+/// topics of topic sources, so values published between selects are kept in the mailbox. Timers
+/// that are not pending are ready with nil. With :timeout, returns (:timeout) if no source is
+/// ready within MS milliseconds or DURATION. See also select.
+#[native_fn]
+pub(crate) async fn select_recv(fiber: &mut Fiber, #[rest] args: Vec<Val>) -> Result<Val> {
+    let timeout = kwargs::get(&args, &KeywordId::from("timeout"))
+        .map(|t| timeout_millis(&t))
+        .transpose()?;
+    let sources = args
+        .iter()
+        .take_while(|a| !matches!(a, Val::Keyword(_)))
+        .map(Source::from_val)
+        .collect::<Result<Vec<_>>>()?;
+
+    for source in &sources {
+        if let Source::Topic(topic, _) = source {
+            subscribe(fiber, topic.clone()).await?;
+        }
+    }
+
+    let locals = fiber.locals();
+    let pid = locals.pid;
+    let kernel = locals
+        .kernel
+        .as_ref()
+        .and_then(|k| k.upgrade())
+        .ok_or(Error::Runtime("Kernel is missing for process".to_string()))?;
+    let mailbox = locals
+        .self_handle
+        .as_ref()
+        .ok_or(Error::Runtime("Process has no self handle".to_string()))?
+        .mailbox()
+        .clone();
+    let rt_err = |e: crate::rt::Error| Error::Runtime(format!("{e}"));
+
+    // claim and monitor before waiting, so nothing ready in between is missed
+    let mut msg_srcs = vec![];
+    let mut timers = vec![];
+    let mut exits = vec![];
+    for (idx, source) in sources.into_iter().enumerate() {
+        match source {
+            Source::Recv(pat) => msg_srcs.push(MsgSource {
+                idx,
+                pat,
+                elem: None,
+            }),
+            Source::Topic(topic, pat) => msg_srcs.push(MsgSource {
+                idx,
+                pat: Val::List(vec![
+                    Val::keyword("topic_updated"),
+                    Val::Keyword(topic),
+                    pat,
+                ]),
+                elem: Some(2),
+            }),
+            Source::Timer(tref) => {
+                let claim = kernel.claim_timer(pid, tref).await.map_err(rt_err)?;
+                timers.push((idx, claim));
+            }
+            Source::Exit(dst, pat) => {
+                let mref = kernel.monitor(pid, dst).await.map_err(rt_err)?;
+                exits.push((
+                    mref.clone(),
+                    MsgSource {
+                        idx,
+                        pat: down_pattern(&mref, pat),
+                        elem: Some(3),
+                    },
+                ));
+            }
+        }
+    }
+    // down messages of monitors made by select are never given to other sources
+    let msg_srcs: Vec<_> = exits
+        .iter()
+        .map(|(_, src)| src)
+        .chain(msg_srcs.iter())
+        .collect();
+    let pats: Vec<Val> = msg_srcs.iter().map(|src| src.pat.clone()).collect();
+
+    let ready = {
+        let mut waiting: Vec<Waiting> = vec![];
+        for (idx, claim) in timers.iter_mut() {
+            waiting.push(Box::pin(async move {
+                match claim {
+                    Some(rx) => match rx.await {
+                        Ok(msg) => Some((*idx, msg.contents)),
+                        // timer was cancelled
+                        Err(_) => future::pending().await,
+                    },
+                    None => Some((*idx, Val::Nil)),
+                }
+            }));
+        }
+        if let Some(timeout) = timeout {
+            waiting.push(Box::pin(async move {
+                time::sleep(timeout).await;
+                None
+            }));
+        }
+        if waiting.is_empty() {
+            waiting.push(Box::pin(future::pending()));
+        }
+        let waiting = select_all(waiting);
+
+        let _status = locals.enter_status(ProcessStatus::AwaitingRecv);
+        if pats.is_empty() {
+            waiting.await.0
+        } else {
+            let mut ready = None;
+            let msg = mailbox
+                .poll_until(Some(Pattern::from_vals(&pats)), async {
+                    ready = Some(waiting.await.0);
+                })
+                .await
+                .map_err(rt_err)?;
+            match (msg, ready) {
+                (Some(msg), None) => Some(mailbox_ready(msg.contents, &msg_srcs)?),
+                (msg, ready) => {
+                    // requeue message delivered while another source became ready
+                    if let Some(msg) = msg {
+                        mailbox.requeue(msg).await.map_err(rt_err)?;
+                    }
+                    ready.expect("poll should only give up once a source is ready")
+                }
+            }
+        }
+    };
+
+    // deliver messages of claimed timers that fired without being selected, as the kernel
+    // would have. Closing claims first makes the kernel deliver messages of timers firing
+    // after to the mailbox instead.
+    for (_, claim) in timers {
+        if let Some(mut rx) = claim {
+            rx.close();
+            if let Ok(msg) = rx.try_recv() {
+                mailbox.push(msg).await.map_err(rt_err)?;
+            }
+        }
+    }
+    for (mref, _) in &exits {
+        kernel.demonitor(mref.clone()).await.map_err(rt_err)?;
+        // drop down message sent before monitor was removed, if any
+        flush(&mailbox, &[down_pattern(mref, Val::symbol("_"))]).await?;
+    }
+
+    Ok(match ready {
+        Some((idx, val)) => Val::List(vec![Val::Int(idx as i32), val]),
+        None => Val::List(vec![Val::keyword("timeout")]),
+    })
+}
+
+/// Pattern matching down message of monitor `mref` with reason matching `reason`
+fn down_pattern(mref: &Ref, reason: Val) -> Val {
+    Val::List(vec![
+        Val::keyword("down"),
+        Val::Ref(mref.clone()),
+        Val::symbol("_"),
+        reason,
+    ])
+}
+
+/// Index and value of first source ready with mailbox message
+fn mailbox_ready(msg: Val, srcs: &[&MsgSource]) -> Result<(usize, Val)> {
+    let src = srcs
+        .iter()
+        .find(|src| Pattern::from_val(src.pat.clone()).is_match(&msg))
+        .ok_or(Error::Runtime(format!(
+            "select received message matching no source - {msg}"
+        )))?;
+    let val = match (src.elem, &msg) {
+        (Some(elem), Val::List(l)) => l.get(elem).cloned().unwrap_or(Val::Nil),
+        _ => msg,
+    };
+    Ok((src.idx, val))
+}
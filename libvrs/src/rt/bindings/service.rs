@@ -2,7 +2,7 @@
 //! See also [super::registry]
 
 use lyric::builtin::cond::is_true;
-use lyric::{compile, kwargs, parse, Error, KeywordId, Result, SymbolId};
+use lyric::{compile, kwargs, parse, Arity, Error, KeywordId, Result, SymbolId};
 
 use crate::rt::program::{Extern, Fiber, Lambda, NativeAsyncFn, NativeFn, NativeFnOp, Val};
 use crate::rt::registry::Registration;
@@ -14,6 +14,7 @@ pub(crate) fn register_fn() -> NativeAsyncFn {
               Register caller as SVC_NAME in service registry, optionally providing \
               INTERFACE keyword argument for publishing available interface."
             .to_string(),
+        arity: Some(Arity::range(1, 3)),
         func: |f, args| Box::new(register_impl(f, args)),
     }
 }
@@ -105,6 +106,7 @@ pub(crate) fn ls_srv_fn() -> NativeAsyncFn {
     NativeAsyncFn {
         doc: "(ls_srv) - Returns a list containing all registered services and exported interface"
             .to_string(),
+        arity: Some(Arity::exact(0)),
         func: |f, args| Box::new(ls_srv_impl(f, args)),
     }
 }
@@ -151,7 +153,9 @@ pub(crate) fn find_srv_fn() -> Lambda {
                 .to_string(),
         ),
         params: vec![SymbolId::from("srv_name")],
-        code: compile(&parse("(info_srv srv_name :pid)").unwrap().into()).unwrap(),
+        code: compile(&parse("(info_srv srv_name :pid)").unwrap().into())
+            .unwrap()
+            .into(),
         parent: None,
     }
 }
@@ -174,7 +178,8 @@ pub(crate) fn bind_srv_fn() -> Lambda {
             .unwrap()
             .into(),
         )
-        .unwrap(),
+        .unwrap()
+        .into(),
         parent: None,
     }
 }
@@ -183,6 +188,7 @@ pub(crate) fn bind_srv_fn() -> Lambda {
 pub(crate) fn info_srv_fn() -> NativeAsyncFn {
     NativeAsyncFn {
         doc: "(info_srv SVC_NAME ATTR) - Returns the attribute ATTR for process registered as SVC_NAME in service registry.".to_string(),
+        arity: Some(Arity::exact(2)),
         func: |f, args| Box::new(info_srv_impl(f, args)),
     }
 }
@@ -248,6 +254,7 @@ async fn info_srv_impl(fiber: &mut Fiber, args: Vec<Val>) -> Result<Val> {
 pub(crate) fn def_bind_interface() -> NativeFn {
     NativeFn {
         doc: "(def_bind_interface SVC_NAME INTERFACE_DOC) - Runtime internal use only. Shim for service bindings".to_string(),
+        arity: Some(Arity::exact(2)),
         func: |f, args| {
             let (svc_name, interface_doc) =
                 match args {
@@ -313,6 +320,7 @@ pub(crate) fn spawn_srv_fn() -> NativeFn {
     NativeFn {
         doc: "(spawn_srv SVC_NAME [:interface INTERFACE]) - Spawn a separate process as service registered as SVC_NAME, \
               optionally exporting interface INTERFACE.".to_string(),
+        arity: Some(Arity::range(1, 3)),
         func: spawn_srv_impl,
     }
 }
@@ -355,6 +363,7 @@ pub(crate) fn srv_fn() -> NativeFn {
         doc: "(srv SVC_NAME [:interface INTERFACE]) - Register current process as SVC_NAME, \
               optionally exporting interface INTERFACE. This function blocks until service exits."
             .to_string(),
+        arity: Some(Arity::range(1, 3)),
         func: srv_impl,
     }
 }
@@ -372,7 +381,7 @@ fn srv_impl(f: &mut Fiber, args: &[Val]) -> Result<NativeFnOp> {
     //                 (try (match msg
     //                     ((:sym_a arg1 arg2) (sym_a arg1 arg2))
     //                     ((:sym_b) (sym_b))
-    //                     (_ '(:err "Unrecognized message")))))
+    //                     (_ <error Unrecognized message>))))
     //             (send src (list r resp))))
 
     let name = args.first().ok_or(Error::UnexpectedArguments(
@@ -419,16 +428,10 @@ fn srv_impl(f: &mut Fiber, args: &[Val]) -> Result<NativeFnOp> {
             match_form.push(Val::List(vec![pattern, lambda_call(sym, &lambda)]));
         }
     }
-    // catch-all
+    // catch-all, responding with error value like failed calls
     match_form.push(Val::List(vec![
         Val::symbol("_"),
-        Val::List(vec![
-            Val::symbol("quote"),
-            Val::List(vec![
-                Val::keyword("err"),
-                Val::string("Unrecognized message"),
-            ]),
-        ]),
+        Val::Error(Error::Runtime("Unrecognized message".to_string())),
     ]));
 
     let register_form = Val::List(vec![
@@ -478,11 +481,11 @@ fn srv_impl(f: &mut Fiber, args: &[Val]) -> Result<NativeFnOp> {
     Ok(NativeFnOp::Exec(bc))
 }
 
-/// Generates interface for calling exported lambda
+/// Generates interface for calling exported lambda, naming generated parameters by their base
 fn lambda_interface(symbol: &SymbolId, lambda: &Lambda) -> Val {
     Val::List(
         std::iter::once(Val::Keyword(symbol.clone().to_keyword()))
-            .chain(lambda.params.iter().map(|v| Val::Symbol(v.clone())))
+            .chain(lambda.params.iter().map(|v| Val::Symbol(v.base())))
             .collect::<Vec<_>>(),
     )
 }
@@ -492,7 +495,7 @@ fn lambda_call(symbol: &SymbolId, lambda: &Lambda) -> Val {
     //
     Val::List(
         std::iter::once(Val::Symbol(symbol.clone()))
-            .chain(lambda.params.iter().map(|v| Val::Symbol(v.clone())))
+            .chain(lambda.params.iter().map(|v| Val::Symbol(v.base())))
             .collect::<Vec<_>>(),
     )
 }
@@ -516,7 +519,7 @@ fn lambda_stub_for_interface(
     Lambda {
         doc: Some(doc),
         params,
-        code,
+        code: code.into(),
         parent: None,
     }
 }
@@ -534,7 +537,7 @@ mod tests {
         let lambda = Lambda {
             doc: None,
             params: vec![],
-            code: vec![Inst::PushConst(Val::Nil)],
+            code: vec![Inst::PushConst(Val::Nil)].into(),
             parent: None,
         };
 
@@ -549,7 +552,7 @@ mod tests {
         let lambda = Lambda {
             doc: None,
             params: vec![SymbolId::from("arg1"), SymbolId::from("arg2")],
-            code: vec![Inst::PushConst(Val::Nil)],
+            code: vec![Inst::PushConst(Val::Nil)].into(),
             parent: None,
         };
 
@@ -564,7 +567,7 @@ mod tests {
         let lambda = Lambda {
             doc: None,
             params: vec![],
-            code: vec![Inst::PushConst(Val::Nil)],
+            code: vec![Inst::PushConst(Val::Nil)].into(),
             parent: None,
         };
 
@@ -576,7 +579,7 @@ mod tests {
         let lambda = Lambda {
             doc: None,
             params: vec![SymbolId::from("arg1"), SymbolId::from("arg2")],
-            code: vec![Inst::PushConst(Val::Nil)],
+            code: vec![Inst::PushConst(Val::Nil)].into(),
             parent: None,
         };
 
@@ -605,7 +608,8 @@ mod tests {
                     code: compile(&v(r#"
                         (call (find_srv :launcher) (list :get_items))
                         "#))
-                    .unwrap(),
+                    .unwrap()
+                    .into(),
                     parent: None
                 }
             )
@@ -627,7 +631,8 @@ mod tests {
                     code: compile(&v(r#"
                         (call (find_srv :launcher) (list :add_item title cmd))
                         "#))
-                    .unwrap(),
+                    .unwrap()
+                    .into(),
                     parent: None,
                 }
             )
@@ -0,0 +1,119 @@
+//! Supervisor Bindings
+//! See also [crate::rt::supervisor]
+use std::time::Duration;
+
+use crate::rt::program::{Fiber, NativeAsyncFn, Program, Val};
+use crate::rt::supervisor::{ChildSpec, Restart, Strategy, Supervisor};
+use lyric::{kwargs, native_fn, Error, KeywordId, Result};
+
+/// (supervisor CHILD_SPECS [:strategy STRATEGY] [:intensity N] [:period SECS]) - Run caller as supervisor
+/// of CHILD_SPECS, each a list of `:start LAMBDA|FORM`, optional `:name NAME` to register child as, and
+/// `:restart` policy of `:permanent` (default), `:transient` or `:temporary`. STRATEGY is `:one_for_one`
+/// (default), `:one_for_all` or `:rest_for_one`. Raises an error after more than N restarts within SECS
+/// seconds, defaulting to 3 in 5. Blocks until the supervisor fails.
+#[native_fn]
+pub(crate) async fn supervisor(
+    fiber: &mut Fiber,
+    specs: Vec<Val>,
+    #[rest] opts: Vec<Val>,
+) -> Result<Val> {
+    let specs = specs
+        .into_iter()
+        .map(child_spec)
+        .collect::<Result<Vec<_>>>()?;
+
+    let strategy = match kwargs::get(&opts, &KeywordId::from("strategy")) {
+        None => Strategy::OneForOne,
+        Some(Val::Keyword(k)) if k.as_str() == "one_for_one" => Strategy::OneForOne,
+        Some(Val::Keyword(k)) if k.as_str() == "one_for_all" => Strategy::OneForAll,
+        Some(Val::Keyword(k)) if k.as_str() == "rest_for_one" => Strategy::RestForOne,
+        Some(v) => {
+            return Err(Error::UnexpectedArguments(format!(
+                ":strategy should be :one_for_one, :one_for_all or :rest_for_one - got {v}"
+            )))
+        }
+    };
+    let intensity = match kwargs::get(&opts, &KeywordId::from("intensity")) {
+        None => 3,
+        Some(Val::Int(n)) if n >= 0 => n as usize,
+        Some(v) => {
+            return Err(Error::UnexpectedArguments(format!(
+                ":intensity should be a nonnegative integer - got {v}"
+            )))
+        }
+    };
+    let period = match kwargs::get(&opts, &KeywordId::from("period")) {
+        None => Duration::from_secs(5),
+        Some(Val::Int(secs)) if secs > 0 => Duration::from_secs(secs as u64),
+        Some(v) => {
+            return Err(Error::UnexpectedArguments(format!(
+                ":period should be a positive integer - got {v}"
+            )))
+        }
+    };
+
+    let locals = fiber.locals();
+    let kernel = locals
+        .kernel
+        .as_ref()
+        .and_then(|k| k.upgrade())
+        .ok_or(Error::Runtime("Kernel is missing for process".to_string()))?;
+    let registry = locals
+        .registry
+        .clone()
+        .ok_or(Error::Runtime("Process has no registry handle".to_string()))?;
+    let mailbox = locals
+        .self_handle
+        .as_ref()
+        .ok_or(Error::Runtime("Process has no self handle".to_string()))?
+        .mailbox()
+        .clone();
+
+    Supervisor::new(locals.pid, kernel, registry, mailbox, specs)
+        .strategy(strategy)
+        .intensity(intensity, period)
+        .run()
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    Ok(Val::keyword("ok"))
+}
+
+/// Parse child spec list of `:start`, `:name` and `:restart` keyword arguments
+fn child_spec(spec: Val) -> Result<ChildSpec> {
+    let spec = spec.to_list()?;
+    let prog = match kwargs::get(&spec, &KeywordId::from("start")) {
+        Some(Val::Lambda(l)) => Program::from_lambda(l)?,
+        Some(form) => Program::from_val(form)?,
+        None => {
+            return Err(Error::UnexpectedArguments(
+                "child spec is missing :start".to_string(),
+            ))
+        }
+    };
+    let name = match kwargs::get(&spec, &KeywordId::from("name")) {
+        Some(Val::Keyword(k)) => Some(k),
+        None => None,
+        Some(v) => {
+            return Err(Error::UnexpectedArguments(format!(
+                ":name should be a keyword - got {v}"
+            )))
+        }
+    };
+    let restart = match kwargs::get(&spec, &KeywordId::from("restart")) {
+        None => Restart::Permanent,
+        Some(Val::Keyword(k)) if k.as_str() == "permanent" => Restart::Permanent,
+        Some(Val::Keyword(k)) if k.as_str() == "transient" => Restart::Transient,
+        Some(Val::Keyword(k)) if k.as_str() == "temporary" => Restart::Temporary,
+        Some(v) => {
+            return Err(Error::UnexpectedArguments(format!(
+                ":restart should be :permanent, :transient or :temporary - got {v}"
+            )))
+        }
+    };
+
+    Ok(ChildSpec {
+        prog,
+        name,
+        restart,
+    })
+}
@@ -2,65 +2,21 @@
 
 use std::process::Stdio;
 
-use crate::rt::program::{NativeAsyncFn, NativeFn, NativeFnOp, Val};
-use lyric::{Error, Result};
+use crate::rt::program::{Fiber, NativeAsyncFn, NativeFn, Val};
+use crate::rt::ProcessStatus;
+use lyric::{native_fn, Error, Result};
 use tokio::{io::AsyncReadExt, process::Command};
 use tracing::{debug, error};
 
-/// Binding for exec
-pub(crate) fn exec_fn() -> NativeAsyncFn {
-    NativeAsyncFn {
-        doc: "(exec PROG ARG1 ARG2 ... ARGN) - Execute external executable PROG passing optional command line arguments ARG1 to ARGN.".to_string(),
-        func: |_, args| Box::new(exec_impl(args)),
-    }
-}
-
-/// Binding for shell_expand
-pub(crate) fn shell_expand_fn() -> NativeFn {
-    NativeFn {
-        doc: "(shell_expand STRING) - Expand STRING using standard shell filename expansion."
-            .to_string(),
-        func: |_, args| {
-            let path = match args {
-                [Val::String(s)] => s,
-                _ => {
-                    return Err(Error::UnexpectedArguments(
-                        "shell_expand expects one argument".to_string(),
-                    ))
-                }
-            };
-            let path = shellexpand::tilde(path).to_string();
-            Ok(NativeFnOp::Return(Val::String(path)))
-        },
-    }
-}
-
-/// Implementation of (exec PROG ARGS...)
-async fn exec_impl(args: Vec<Val>) -> Result<Val> {
-    let (prog, args) = args.split_first().ok_or(Error::UnexpectedArguments(
-        " Unexpected arguments to exec = (exec PROG [ARGS...])".to_string(),
-    ))?;
-
-    let prog = match prog {
-        Val::String(s) => s.clone(),
-        _ => {
-            return Err(Error::UnexpectedArguments(
-                "Expected string as first argument".to_string(),
-            ))
-        }
-    };
-
-    let args = args
-        .iter()
-        .map(|a| match a {
-            Val::String(s) => Ok(s.clone()),
-            _ => Err(Error::UnexpectedArguments(
-                "exec can handle string arguments only".to_string(),
-            )),
-        })
-        .collect::<Result<Vec<_>>>()?;
-
+/// (exec PROG ARG1 ARG2 ... ARGN) - Execute external executable PROG passing optional command line arguments ARG1 to ARGN.
+#[native_fn]
+pub(crate) async fn exec(
+    fiber: &mut Fiber,
+    prog: String,
+    #[rest] args: Vec<String>,
+) -> Result<Val> {
     debug!("exec {:?} {:?}", &prog, &args);
+    let _status = fiber.locals().enter_status(ProcessStatus::AwaitingExec);
 
     let mut cmd = Command::new(prog.clone())
         .args(args.clone())
@@ -92,3 +48,9 @@ async fn exec_impl(args: Vec<Val>) -> Result<Val> {
         )))
     }
 }
+
+/// (shell_expand STRING) - Expand STRING using standard shell filename expansion.
+#[native_fn]
+pub(crate) fn shell_expand(path: String) -> Result<String> {
+    Ok(shellexpand::tilde(&path).to_string())
+}
@@ -3,12 +3,13 @@ use crate::{
     rt::program::{Extern, Fiber, NativeAsyncFn, Val},
     Response,
 };
-use lyric::{Error, Result};
+use lyric::{Arity, Error, Result};
 
 /// Binding for `recv_req` to receive requests over client connection
 pub(crate) fn recv_req_fn() -> NativeAsyncFn {
     NativeAsyncFn {
         doc: "(recv_req) - Receive request over client connection. This blocks until request is received.".to_string(),
+        arity: Some(Arity::exact(0)),
         func: |f, _| Box::new(recv_req_impl(f)),
     }
 }
@@ -34,6 +35,7 @@ async fn recv_req_impl(fiber: &mut Fiber) -> Result<Val> {
 pub(crate) fn send_resp_fn() -> NativeAsyncFn {
     NativeAsyncFn {
         doc: "(send_resp REQ_ID RESP) - Send response RESP over client connection for request identified by REQ_ID. This blocks until response is sent.".to_string(),
+        arity: Some(Arity::exact(2)),
         func: |f, args| Box::new(send_resp_impl(f, args)),
     }
 }
@@ -0,0 +1,96 @@
+//! Timer Bindings
+//! See also [crate::rt::timer]
+use std::time::Duration;
+
+use crate::rt::kernel::KernelHandle;
+use crate::rt::program::{Fiber, NativeAsyncFn, Val};
+use crate::rt::ProcessId;
+use lyric::{native_fn, Error, Ref, Result};
+
+/// (send_after MS PID MSG) - Send process PID the message MSG after MS milliseconds or DURATION,
+/// returning a timer reference for cancel_timer and read_timer.
+#[native_fn]
+pub(crate) async fn send_after(
+    fiber: &mut Fiber,
+    after: Val,
+    pid: ProcessId,
+    msg: Val,
+) -> Result<Ref> {
+    let after = millis(after)?;
+    kernel(fiber)?
+        .start_timer(fiber.locals().pid, pid, msg, after, false)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))
+}
+
+/// (interval MS PID MSG) - Send process PID the message MSG every MS milliseconds or DURATION
+/// until cancelled or PID exits, returning a timer reference for cancel_timer and read_timer.
+#[native_fn]
+pub(crate) async fn interval(
+    fiber: &mut Fiber,
+    every: Val,
+    pid: ProcessId,
+    msg: Val,
+) -> Result<Ref> {
+    let every = millis(every)?;
+    if every.is_zero() {
+        return Err(Error::UnexpectedArguments(
+            "interval should be positive".to_string(),
+        ));
+    }
+    kernel(fiber)?
+        .start_timer(fiber.locals().pid, pid, msg, every, true)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))
+}
+
+/// (cancel_timer REF) - Cancel timer REF, returning milliseconds remaining until it would have
+/// fired, or nil if it is no longer pending.
+#[native_fn]
+pub(crate) async fn cancel_timer(fiber: &mut Fiber, tref: Ref) -> Result<Val> {
+    let remaining = kernel(fiber)?
+        .cancel_timer(tref)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    Ok(remaining_val(remaining))
+}
+
+/// (read_timer REF) - Returns milliseconds remaining until timer REF fires, or nil if it is no
+/// longer pending.
+#[native_fn]
+pub(crate) async fn read_timer(fiber: &mut Fiber, tref: Ref) -> Result<Val> {
+    let remaining = kernel(fiber)?
+        .read_timer(tref)
+        .await
+        .map_err(|e| Error::Runtime(format!("{e}")))?;
+    Ok(remaining_val(remaining))
+}
+
+/// Parse milliseconds or a duration
+fn millis(val: Val) -> Result<Duration> {
+    match val {
+        Val::Int(ms) if ms >= 0 => Ok(Duration::from_millis(ms as u64)),
+        Val::Duration(d) => d.from_now(),
+        v => Err(Error::UnexpectedArguments(format!(
+            "timer should be nonnegative milliseconds or a duration - got {v}"
+        ))),
+    }
+}
+
+/// Remaining time of timer in milliseconds, or nil
+fn remaining_val(remaining: Option<Duration>) -> Val {
+    match remaining {
+        Some(d) => Val::Int(i32::try_from(d.as_millis()).unwrap_or(i32::MAX)),
+        None => Val::Nil,
+    }
+}
+
+/// Get kernel handle for process running fiber
+fn kernel(fiber: &Fiber) -> Result<KernelHandle> {
+    fiber
+        .locals()
+        .kernel
+        .as_ref()
+        .and_then(|k| k.upgrade())
+        .ok_or(Error::Runtime("Kernel is missing for process".to_string()))
+}
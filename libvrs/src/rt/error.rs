@@ -20,6 +20,12 @@ pub enum Error {
     #[error("Registry Error - {0}")]
     RegistryError(String),
 
+    #[error("Supervisor Error - {0}")]
+    SupervisorError(String),
+
+    #[error("Scheduler Error - {0}")]
+    SchedulerError(String),
+
     #[error("Process Exec Error - {0}")]
     ProcessExecError(lyric::Error),
 
@@ -1,15 +1,20 @@
 //! Runtime Kernel Task
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use super::mailbox::Message;
 use super::proc::{ProcessExit, ProcessHandle, ProcessSet};
 use super::program;
 use super::pubsub::{PubSub, PubSubHandle};
 use super::registry::Registry;
+use super::scheduler::Clock;
+use super::timer::{Timer, Timers};
 use crate::rt::term::Term;
 use crate::rt::{proc::Process, Error, ProcessId, Result};
 use crate::{Connection, Program};
+use lyric::{IntoVal, Profiler, Ref};
+use std::time::Duration;
 use tokio::sync::{mpsc, oneshot};
+use tokio::time::{self, Instant};
 use tracing::{debug, info};
 
 /// Handle to `Kernel`
@@ -26,12 +31,18 @@ pub(crate) struct WeakKernelHandle {
 
 /// Starts the kernel task, which manages processes on runtime
 pub(crate) fn start() -> KernelHandle {
+    start_with_clock(Clock::default())
+}
+
+/// Starts the kernel task, with scheduler keeping time with `clock`
+pub(crate) fn start_with_clock(clock: Clock) -> KernelHandle {
     let (ev_tx, mut ev_rx) = mpsc::channel(32);
 
     let handle = KernelHandle { ev_tx };
-    let mut kernel = Kernel::new(handle.clone());
+    let mut kernel = Kernel::new(handle.clone(), clock);
     tokio::spawn(async move {
         loop {
+            let deadline = kernel.timers.next_deadline();
             tokio::select! {
                 ev = ev_rx.recv() => match ev {
                     Some(ev) => kernel.handle_ev(ev).await?,
@@ -43,6 +54,7 @@ pub(crate) fn start() -> KernelHandle {
                 Some(Ok(result)) = kernel.procs.join_next() => {
                     kernel.handle_ev(Event::ProcessExit(result)).await?;
                 }
+                _ = sleep_until(deadline) => kernel.fire_timers().await,
             }
         }
         Ok::<(), Error>(())
@@ -53,9 +65,23 @@ pub(crate) fn start() -> KernelHandle {
 impl KernelHandle {
     /// Spawn a new program
     pub(crate) async fn spawn_prog(&self, prog: Program) -> Result<ProcessHandle> {
+        self.spawn(prog, None).await
+    }
+
+    /// Spawn a new program as child of process `parent`
+    pub(crate) async fn spawn_child(
+        &self,
+        prog: Program,
+        parent: ProcessId,
+    ) -> Result<ProcessHandle> {
+        self.spawn(prog, Some(parent)).await
+    }
+
+    /// Spawn a new program, with given parent if any
+    async fn spawn(&self, prog: Program, parent: Option<ProcessId>) -> Result<ProcessHandle> {
         let (tx, rx) = oneshot::channel();
         self.ev_tx
-            .send(Event::SpawnProg(prog, tx))
+            .send(Event::SpawnProg(prog, parent, tx))
             .await
             .map_err(|_| Error::NoMessageReceiver("spawn failed".to_string()))?;
         rx.await
@@ -75,6 +101,11 @@ impl KernelHandle {
 
     /// Get running process information
     pub(crate) async fn procs(&self) -> Result<Vec<ProcessId>> {
+        Ok(self.proc_hdls().await?.iter().map(|h| h.id()).collect())
+    }
+
+    /// Get handles of running processes
+    pub(crate) async fn proc_hdls(&self) -> Result<Vec<ProcessHandle>> {
         let (tx, rx) = oneshot::channel();
         self.ev_tx
             .send(Event::ListProcess(tx))
@@ -84,12 +115,185 @@ impl KernelHandle {
             .map_err(Error::FailedToReceiveResponseFromKernelTask)
     }
 
-    /// Kill specified process
-    pub(crate) async fn kill_proc(&self, pid: ProcessId) -> Result<()> {
+    /// Kill specified process, exiting it with reason if given
+    pub(crate) async fn kill_proc(
+        &self,
+        pid: ProcessId,
+        reason: Option<program::Val>,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.ev_tx
+            .send(Event::KillProcess(pid, reason, tx))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("kill_procs failed".to_string()))?;
+        rx.await
+            .map_err(Error::FailedToReceiveResponseFromKernelTask)?
+    }
+
+    /// Get profiler of specified process
+    pub(crate) async fn profiler(&self, pid: ProcessId) -> Result<Profiler> {
+        let (tx, rx) = oneshot::channel();
+        self.ev_tx
+            .send(Event::ProcessProfiler(pid, tx))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("profiler failed".to_string()))?;
+        rx.await
+            .map_err(Error::FailedToReceiveResponseFromKernelTask)?
+            .ok_or(Error::UnknownProcess)
+    }
+
+    /// Spawn a new program linked to process `parent`
+    pub(crate) async fn spawn_link(
+        &self,
+        prog: Program,
+        parent: ProcessId,
+    ) -> Result<ProcessHandle> {
+        let (tx, rx) = oneshot::channel();
+        self.ev_tx
+            .send(Event::SpawnLinkProg(prog, parent, tx))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("spawn_link failed".to_string()))?;
+        rx.await
+            .map_err(Error::FailedToReceiveResponseFromKernelTask)?
+    }
+
+    /// Link processes `a` and `b` so abnormal exits of one propagate to the other
+    pub(crate) async fn link(&self, a: ProcessId, b: ProcessId) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.ev_tx
+            .send(Event::Link(a, b, tx))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("link failed".to_string()))?;
+        rx.await
+            .map_err(Error::FailedToReceiveResponseFromKernelTask)?
+    }
+
+    /// Remove link between processes `a` and `b`, if any
+    pub(crate) async fn unlink(&self, a: ProcessId, b: ProcessId) -> Result<()> {
+        self.ev_tx
+            .send(Event::Unlink(a, b))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("unlink failed".to_string()))
+    }
+
+    /// Monitor process `target` from `watcher`, returning the monitor's reference
+    pub(crate) async fn monitor(&self, watcher: ProcessId, target: ProcessId) -> Result<Ref> {
+        let mref = Ref::new();
+        self.ev_tx
+            .send(Event::Monitor(watcher, target, mref.clone()))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("monitor failed".to_string()))?;
+        Ok(mref)
+    }
+
+    /// Remove monitor with reference `mref`. Once this returns, any down message of the monitor
+    /// is already in the watcher's mailbox, so it can be flushed from there.
+    pub(crate) async fn demonitor(&self, mref: Ref) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.ev_tx
+            .send(Event::Demonitor(mref, tx))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("demonitor failed".to_string()))?;
+        rx.await
+            .map_err(Error::FailedToReceiveResponseFromKernelTask)
+    }
+
+    /// Deactivate request reference `rref` of process `pid`, dropping a late `(REF RESP)` reply
+    /// to it instead of delivering it. Once this returns, a reply delivered before is already in
+    /// the mailbox of `pid`, so it can be flushed from there.
+    pub(crate) async fn unalias(&self, pid: ProcessId, rref: Ref) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.ev_tx
+            .send(Event::Unalias(pid, rref, tx))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("unalias failed".to_string()))?;
+        rx.await
+            .map_err(Error::FailedToReceiveResponseFromKernelTask)
+    }
+
+    /// Set whether process receives exit signals from linked processes as messages
+    pub(crate) async fn trap_exit(&self, pid: ProcessId, trap: bool) -> Result<()> {
+        self.ev_tx
+            .send(Event::TrapExit(pid, trap))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("trap_exit failed".to_string()))
+    }
+
+    /// Start timer sending `msg` from `src` to `dst` after `after`, repeating every `after` if
+    /// `repeat` is set. Returns reference for the timer.
+    pub(crate) async fn start_timer(
+        &self,
+        src: ProcessId,
+        dst: ProcessId,
+        msg: program::Val,
+        after: Duration,
+        repeat: bool,
+    ) -> Result<Ref> {
+        let tref = Ref::new();
+        let timer = Timer {
+            src,
+            dst,
+            msg,
+            interval: repeat.then_some(after),
+        };
+        self.ev_tx
+            .send(Event::StartTimer(tref.clone(), after, timer))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("start_timer failed".to_string()))?;
+        Ok(tref)
+    }
+
+    /// Cancel timer, returning time remaining if it was pending
+    pub(crate) async fn cancel_timer(&self, tref: Ref) -> Result<Option<Duration>> {
+        let (tx, rx) = oneshot::channel();
+        self.ev_tx
+            .send(Event::CancelTimer(tref, tx))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("cancel_timer failed".to_string()))?;
+        rx.await
+            .map_err(Error::FailedToReceiveResponseFromKernelTask)
+    }
+
+    /// Get time remaining until timer expires, if it is pending
+    pub(crate) async fn read_timer(&self, tref: Ref) -> Result<Option<Duration>> {
+        let (tx, rx) = oneshot::channel();
         self.ev_tx
-            .send(Event::KillProcess(pid))
+            .send(Event::ReadTimer(tref, tx))
             .await
-            .map_err(|_| Error::NoMessageReceiver("kill_procs failed".to_string()))
+            .map_err(|_| Error::NoMessageReceiver("read_timer failed".to_string()))?;
+        rx.await
+            .map_err(Error::FailedToReceiveResponseFromKernelTask)
+    }
+
+    /// Claim next message of timer started for `pid`, which is sent to the returned receiver
+    /// instead of the mailbox of `pid`. Messages are sent to the mailbox if the receiver is
+    /// dropped. Returns None if timer is not pending for `pid`.
+    pub(crate) async fn claim_timer(
+        &self,
+        pid: ProcessId,
+        tref: Ref,
+    ) -> Result<Option<oneshot::Receiver<Message>>> {
+        let (claim_tx, claim_rx) = oneshot::channel();
+        let (tx, rx) = oneshot::channel();
+        self.ev_tx
+            .send(Event::ClaimTimer(pid, tref, claim_tx, tx))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("claim_timer failed".to_string()))?;
+        let claimed = rx
+            .await
+            .map_err(Error::FailedToReceiveResponseFromKernelTask)?;
+        Ok(claimed.then_some(claim_rx))
+    }
+
+    /// Get clock scheduler keeps time with
+    pub(crate) async fn clock(&self) -> Result<Clock> {
+        let (tx, rx) = oneshot::channel();
+        self.ev_tx
+            .send(Event::Clock(tx))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("clock failed".to_string()))?;
+        rx.await
+            .map_err(Error::FailedToReceiveResponseFromKernelTask)
     }
 
     // TODO(sec): SRC IDs too flexible
@@ -131,12 +335,37 @@ impl std::cmp::PartialEq for WeakKernelHandle {
 /// Messages for [Kernel]
 #[derive(Debug)]
 pub enum Event {
-    SpawnProg(Program, oneshot::Sender<ProcessHandle>),
+    SpawnProg(Program, Option<ProcessId>, oneshot::Sender<ProcessHandle>),
     SpawnTermProc(Connection, oneshot::Sender<ProcessHandle>),
     ProcessExit(ProcessExit),
-    ListProcess(oneshot::Sender<Vec<ProcessId>>),
-    KillProcess(ProcessId),
+    ListProcess(oneshot::Sender<Vec<ProcessHandle>>),
+    KillProcess(ProcessId, Option<program::Val>, oneshot::Sender<Result<()>>),
+    ProcessProfiler(ProcessId, oneshot::Sender<Option<Profiler>>),
     ProcessSendMessage(ProcessId, ProcessId, program::Val),
+    SpawnLinkProg(Program, ProcessId, oneshot::Sender<Result<ProcessHandle>>),
+    Link(ProcessId, ProcessId, oneshot::Sender<Result<()>>),
+    Unlink(ProcessId, ProcessId),
+    Monitor(ProcessId, ProcessId, Ref),
+    Demonitor(Ref, oneshot::Sender<()>),
+    Unalias(ProcessId, Ref, oneshot::Sender<()>),
+    TrapExit(ProcessId, bool),
+    StartTimer(Ref, Duration, Timer),
+    CancelTimer(Ref, oneshot::Sender<Option<Duration>>),
+    ReadTimer(Ref, oneshot::Sender<Option<Duration>>),
+    ClaimTimer(
+        ProcessId,
+        Ref,
+        oneshot::Sender<Message>,
+        oneshot::Sender<bool>,
+    ),
+    Clock(oneshot::Sender<Clock>),
+}
+
+/// A monitor on a process, owned by `watcher`
+#[derive(Debug)]
+struct Monitor {
+    mref: Ref,
+    watcher: ProcessId,
 }
 
 /// The runtime kernel task
@@ -147,25 +376,43 @@ struct Kernel {
     next_proc_id: usize,
     registry: Registry,
     pubsub: PubSubHandle,
+    links: HashMap<ProcessId, HashSet<ProcessId>>,
+    monitors: HashMap<ProcessId, Vec<Monitor>>,
+    /// Deactivated request references of processes, whose late replies are dropped
+    unaliased: HashMap<ProcessId, HashSet<Ref>>,
+    trapping: HashSet<ProcessId>,
+    timers: Timers,
+    /// Claims on next message of timers
+    timer_claims: HashMap<Ref, oneshot::Sender<Message>>,
+    /// Clock scheduler keeps time with
+    clock: Clock,
 }
 
 impl Kernel {
-    pub fn new(handle: KernelHandle) -> Self {
+    pub fn new(handle: KernelHandle, clock: Clock) -> Self {
+        let pubsub = PubSub::spawn();
         Self {
             weak_hdl: handle.downgrade(),
             procs: ProcessSet::new(),
             proc_hdls: HashMap::new(),
             next_proc_id: 0,
-            registry: Registry::spawn(),
-            pubsub: PubSub::spawn(),
+            registry: Registry::spawn(pubsub.clone()),
+            pubsub,
+            links: HashMap::new(),
+            monitors: HashMap::new(),
+            unaliased: HashMap::new(),
+            trapping: HashSet::new(),
+            timers: Timers::default(),
+            timer_claims: HashMap::new(),
+            clock,
         }
     }
 
     pub async fn handle_ev(&mut self, ev: Event) -> Result<()> {
         debug!("handle_ev - {ev:?}");
         match ev {
-            Event::SpawnProg(prog, tx) => {
-                let proc = Process::from_prog(self.next_pid(), prog);
+            Event::SpawnProg(prog, parent, tx) => {
+                let proc = Process::from_prog(self.next_pid(), prog).parent(parent);
                 let hdl = self.spawn(proc)?;
                 let _ = tx.send(hdl);
                 Ok(())
@@ -177,14 +424,124 @@ impl Kernel {
                 let _ = tx.send(hdl);
                 Ok(())
             }
-            Event::ProcessExit(exit) => self.handle_exit(exit),
+            Event::ProcessExit(exit) => self.handle_exit(exit).await,
             Event::ListProcess(tx) => {
-                let ids = self.proc_hdls.keys().copied().collect();
-                let _ = tx.send(ids);
+                let hdls = self.proc_hdls.values().cloned().collect();
+                let _ = tx.send(hdls);
+                Ok(())
+            }
+            Event::KillProcess(pid, reason, tx) => {
+                let _ = tx.send(self.kill_proc(pid, reason).await);
+                Ok(())
+            }
+            Event::ProcessProfiler(pid, tx) => {
+                let profiler = self.proc_hdls.get(&pid).map(|hdl| hdl.profiler().clone());
+                let _ = tx.send(profiler);
+                Ok(())
+            }
+            Event::ProcessSendMessage(src, dst, msg) => {
+                self.dispatch_msg(src, dst, msg).await;
+                Ok(())
+            }
+            Event::SpawnLinkProg(prog, parent, tx) => {
+                let res = match self.proc_hdls.contains_key(&parent) {
+                    true => {
+                        let proc = Process::from_prog(self.next_pid(), prog).parent(Some(parent));
+                        let hdl = self.spawn(proc)?;
+                        self.link(parent, hdl.id());
+                        Ok(hdl)
+                    }
+                    false => Err(Error::UnknownProcess),
+                };
+                let _ = tx.send(res);
+                Ok(())
+            }
+            Event::Link(a, b, tx) => {
+                let res = match self.proc_hdls.contains_key(&a) && self.proc_hdls.contains_key(&b) {
+                    true => {
+                        self.link(a, b);
+                        Ok(())
+                    }
+                    false => Err(Error::UnknownProcess),
+                };
+                let _ = tx.send(res);
+                Ok(())
+            }
+            Event::Unlink(a, b) => {
+                self.unlink(a, b);
+                Ok(())
+            }
+            Event::Monitor(watcher, target, mref) => self.monitor(watcher, target, mref).await,
+            Event::Demonitor(mref, tx) => {
+                for monitors in self.monitors.values_mut() {
+                    monitors.retain(|m| m.mref != mref);
+                }
+                self.monitors.retain(|_, monitors| !monitors.is_empty());
+                let _ = tx.send(());
+                Ok(())
+            }
+            Event::Unalias(pid, rref, tx) => {
+                if self.proc_hdls.contains_key(&pid) {
+                    self.unaliased.entry(pid).or_default().insert(rref);
+                }
+                let _ = tx.send(());
+                Ok(())
+            }
+            Event::TrapExit(pid, trap) => {
+                if trap {
+                    self.trapping.insert(pid);
+                } else {
+                    self.trapping.remove(&pid);
+                }
+                Ok(())
+            }
+            Event::StartTimer(tref, after, timer) => {
+                self.timers.start(tref, after, timer);
+                Ok(())
+            }
+            Event::CancelTimer(tref, tx) => {
+                self.timer_claims.remove(&tref);
+                let _ = tx.send(self.timers.cancel(&tref));
+                Ok(())
+            }
+            Event::ReadTimer(tref, tx) => {
+                let _ = tx.send(self.timers.remaining(&tref));
+                Ok(())
+            }
+            Event::ClaimTimer(pid, tref, claim, tx) => {
+                let claimed = self.timers.get(&tref).is_some_and(|t| t.dst == pid);
+                if claimed {
+                    self.timer_claims.insert(tref, claim);
+                }
+                let _ = tx.send(claimed);
+                Ok(())
+            }
+            Event::Clock(tx) => {
+                let _ = tx.send(self.clock.clone());
                 Ok(())
             }
-            Event::KillProcess(pid) => self.kill_proc(pid).await,
-            Event::ProcessSendMessage(src, dst, msg) => self.dispatch_msg(src, dst, msg).await,
+        }
+    }
+
+    /// Send messages of expired timers, to claims on them if any. Timers for processes that
+    /// exited are cancelled.
+    async fn fire_timers(&mut self) {
+        for (tref, timer) in self.timers.expire(Instant::now()) {
+            let claim = self.timer_claims.remove(&tref);
+            if !self.proc_hdls.contains_key(&timer.dst) {
+                self.timers.cancel(&tref);
+                continue;
+            }
+            let msg = match claim {
+                Some(claim) => claim
+                    .send(Message::new(timer.src, timer.msg))
+                    .err()
+                    .map(|msg| msg.contents),
+                None => Some(timer.msg),
+            };
+            if let Some(msg) = msg {
+                self.dispatch_msg(timer.src, timer.dst, msg).await;
+            }
         }
     }
 
@@ -199,30 +556,128 @@ impl Kernel {
         Ok(hdl)
     }
 
-    /// Cleanup process that terminated with given result
-    fn handle_exit(&mut self, exit: ProcessExit) -> Result<()> {
-        match self.proc_hdls.remove(&exit.id) {
-            Some(_) => Ok(()),
-            None => panic!("Kernel notified of unmanaged process"),
+    /// Cleanup process that terminated with given result, notifying its monitors and links
+    async fn handle_exit(&mut self, exit: ProcessExit) -> Result<()> {
+        if self.proc_hdls.remove(&exit.id).is_none() {
+            panic!("Kernel notified of unmanaged process");
+        }
+        self.trapping.remove(&exit.id);
+        self.unaliased.remove(&exit.id);
+        for monitors in self.monitors.values_mut() {
+            monitors.retain(|m| m.watcher != exit.id);
+        }
+
+        let reason = exit.reason();
+        for m in self.monitors.remove(&exit.id).unwrap_or_default() {
+            let down = down_msg(m.mref, exit.id, reason.clone());
+            self.notify(exit.id, m.watcher, down).await;
+        }
+
+        for linked in self.links.remove(&exit.id).unwrap_or_default() {
+            if let Some(peers) = self.links.get_mut(&linked) {
+                peers.remove(&exit.id);
+                if peers.is_empty() {
+                    self.links.remove(&linked);
+                }
+            }
+
+            if self.trapping.contains(&linked) {
+                let msg = program::Val::List(vec![
+                    program::Val::keyword("exit"),
+                    exit.id.into_val(),
+                    reason.clone(),
+                ]);
+                self.notify(exit.id, linked, msg).await;
+            } else if exit.is_abnormal() {
+                if let Some(hdl) = self.proc_hdls.get(&linked) {
+                    info!(
+                        "proc {linked} exiting for linked proc {} - {reason}",
+                        exit.id
+                    );
+                    hdl.exit(reason.clone()).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a bidirectional link between processes
+    fn link(&mut self, a: ProcessId, b: ProcessId) {
+        if a == b {
+            return;
+        }
+        self.links.entry(a).or_default().insert(b);
+        self.links.entry(b).or_default().insert(a);
+    }
+
+    /// Remove a link between processes, if any
+    fn unlink(&mut self, a: ProcessId, b: ProcessId) {
+        for (from, to) in [(a, b), (b, a)] {
+            if let Some(peers) = self.links.get_mut(&from) {
+                peers.remove(&to);
+                if peers.is_empty() {
+                    self.links.remove(&from);
+                }
+            }
         }
     }
 
-    /// Kill specified process
-    async fn kill_proc(&self, pid: ProcessId) -> Result<()> {
+    /// Monitor `target` for `watcher`. Monitoring an unknown process immediately notifies
+    /// `watcher` with reason `:noproc`.
+    async fn monitor(&mut self, watcher: ProcessId, target: ProcessId, mref: Ref) -> Result<()> {
+        if self.proc_hdls.contains_key(&target) {
+            self.monitors
+                .entry(target)
+                .or_default()
+                .push(Monitor { mref, watcher });
+        } else {
+            let down = down_msg(mref, target, program::Val::keyword("noproc"));
+            self.notify(target, watcher, down).await;
+        }
+        Ok(())
+    }
+
+    /// Deliver message to process if it is still running
+    async fn notify(&self, src: ProcessId, dst: ProcessId, msg: program::Val) {
+        if let Some(hdl) = self.proc_hdls.get(&dst) {
+            hdl.notify_message(Message::new(src, msg)).await;
+        }
+    }
+
+    /// Kill specified process, exiting it with reason if given
+    async fn kill_proc(&self, pid: ProcessId, reason: Option<program::Val>) -> Result<()> {
         match self.proc_hdls.get(&pid) {
             Some(hdl) => {
-                hdl.kill().await;
+                match reason {
+                    Some(reason) => hdl.exit(reason).await,
+                    None => hdl.kill().await,
+                }
                 Ok(())
             }
             None => Err(Error::UnknownProcess),
         }
     }
 
-    /// Dispatc message from src to dst
-    async fn dispatch_msg(&self, src: ProcessId, dst: ProcessId, msg: program::Val) -> Result<()> {
-        let dst = self.proc_hdls.get(&dst).ok_or(Error::UnknownProcess)?;
-        dst.notify_message(Message::new(src, msg)).await;
-        Ok(())
+    /// Dispatch message from src to dst. Messages to processes that exited, and replies to
+    /// request references that were deactivated, are dropped.
+    async fn dispatch_msg(&mut self, src: ProcessId, dst: ProcessId, msg: program::Val) {
+        if let program::Val::List(l) = &msg {
+            if let [program::Val::Ref(rref), _] = &l[..] {
+                if let Some(refs) = self.unaliased.get_mut(&dst) {
+                    if refs.remove(rref) {
+                        if refs.is_empty() {
+                            self.unaliased.remove(&dst);
+                        }
+                        debug!("dropping late reply from {src} to {dst} for {rref:?}");
+                        return;
+                    }
+                }
+            }
+        }
+        match self.proc_hdls.get(&dst) {
+            Some(hdl) => hdl.notify_message(Message::new(src, msg)).await,
+            None => debug!("dropping message from {src} to exited process {dst}"),
+        }
     }
 
     /// Get the next process id
@@ -233,6 +688,24 @@ impl Kernel {
     }
 }
 
+/// Sleep until deadline, or forever without one
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Message delivered to monitoring processes - `(:down REF PID REASON)`
+fn down_msg(mref: Ref, pid: ProcessId, reason: program::Val) -> program::Val {
+    program::Val::List(vec![
+        program::Val::keyword("down"),
+        program::Val::Ref(mref),
+        pid.into_val(),
+        reason,
+    ])
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{Client, Connection, ProcessResult};
@@ -285,7 +758,7 @@ mod tests {
             .expect("Should not timeout - process should exit for error")
             .expect("Join should succeed");
 
-        assert_matches!(exit.status, Err(_));
+        assert_matches!(exit.status, Ok(ProcessResult::Disconnected));
         assert!(
             k.procs().await.unwrap().is_empty(),
             "Should terminate conn process for dropped conn"
@@ -352,7 +825,7 @@ mod tests {
             .await
             .expect("Kernel should spawn new process");
 
-        k.kill_proc(proc.id()).await.unwrap();
+        k.kill_proc(proc.id(), None).await.unwrap();
         let exit = timeout(Duration::from_millis(5), proc.join())
             .await
             .expect("Process should terminate")
@@ -362,6 +835,24 @@ mod tests {
         assert!(k.procs().await.unwrap().is_empty(),);
     }
 
+    #[tokio::test]
+    async fn kill_unknown_proc() {
+        let k = start();
+
+        assert_matches!(
+            k.kill_proc(99.into(), None).await,
+            Err(Error::UnknownProcess)
+        );
+        let proc = k
+            .spawn_prog(Program::from_expr(":alive").unwrap())
+            .await
+            .expect("Kernel should still spawn processes");
+        assert_eq!(
+            proc.join().await.unwrap().status.unwrap(),
+            ProcessResult::Done(program::Val::keyword("alive"))
+        );
+    }
+
     #[tokio::test]
     async fn kill_proc_from_proc() {
         use tokio::time;
@@ -401,6 +892,34 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn kill_linked_procs() {
+        let k = start();
+        let a = k
+            .spawn_prog(Program::from_expr("(loop (sleep 1))").unwrap())
+            .await
+            .unwrap();
+        let b = k
+            .spawn_prog(Program::from_expr("(loop (sleep 1))").unwrap())
+            .await
+            .unwrap();
+        k.link(a.id(), b.id()).await.unwrap();
+        assert_matches!(k.link(a.id(), 99.into()).await, Err(Error::UnknownProcess));
+
+        k.kill_proc(a.id(), None).await.unwrap();
+        let exit = timeout(Duration::from_millis(50), b.join())
+            .await
+            .expect("Linked process should terminate")
+            .unwrap();
+
+        assert_eq!(
+            exit.status.unwrap(),
+            ProcessResult::Exited(program::Val::keyword("killed")),
+            "Linked process should exit with reason of killed process"
+        );
+        assert!(k.procs().await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn spawn_progs() {
         let k = start();
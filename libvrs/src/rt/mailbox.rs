@@ -1,10 +1,13 @@
 //! A Process's Mailbox
 use std::collections::VecDeque;
+use std::future::Future;
+use std::time::Duration;
 
 use super::proc::ProcessId;
 use super::program::{Pattern, Val};
 use crate::rt::{Error, Result};
 use tokio::sync::{mpsc, oneshot};
+use tokio::time;
 use tracing::debug;
 
 /// Handle to mailbox
@@ -29,8 +32,11 @@ pub(crate) struct Message {
 #[derive(Debug)]
 enum Cmd {
     Push(Message),
+    Requeue(Message),
     GetAll(oneshot::Sender<Vec<Message>>),
+    Len(oneshot::Sender<usize>),
     Poll(Option<Pattern>, oneshot::Sender<Message>),
+    CancelPoll(oneshot::Sender<()>),
 }
 
 /// A pending handle for polling mailbox
@@ -50,6 +56,16 @@ impl MailboxHandle {
         Ok(())
     }
 
+    /// Put message taken from mailbox back in front of mailbox, so it is received before
+    /// messages that arrived after it
+    pub(crate) async fn requeue(&self, msg: Message) -> Result<()> {
+        self.tx
+            .send(Cmd::Requeue(msg))
+            .await
+            .map_err(|_| Error::NoMailbox)?;
+        Ok(())
+    }
+
     /// Get all messages from mailbox
     pub(crate) async fn all(&self) -> Result<Vec<Message>> {
         let (tx, rx) = oneshot::channel();
@@ -60,6 +76,16 @@ impl MailboxHandle {
         Ok(rx.await?)
     }
 
+    /// Get number of messages in mailbox
+    pub(crate) async fn len(&self) -> Result<usize> {
+        let (tx, rx) = oneshot::channel();
+        self.tx
+            .send(Cmd::Len(tx))
+            .await
+            .map_err(|_| Error::NoMailbox)?;
+        Ok(rx.await?)
+    }
+
     /// Poll mailbox for matching message.
     /// Blocks calling task until message is receive
     pub(crate) async fn poll(&self, pat: Option<Pattern>) -> Result<Message> {
@@ -70,6 +96,45 @@ impl MailboxHandle {
             .map_err(|_| Error::NoMailbox)?;
         Ok(rx.await?)
     }
+
+    /// Poll mailbox for matching message, giving up after `timeout`.
+    /// Returns None if no matching message is received in time.
+    pub(crate) async fn poll_timeout(
+        &self,
+        pat: Option<Pattern>,
+        timeout: Duration,
+    ) -> Result<Option<Message>> {
+        self.poll_until(pat, time::sleep(timeout)).await
+    }
+
+    /// Poll mailbox for matching message, giving up once `wake` completes.
+    /// Returns None if no matching message is received before then.
+    pub(crate) async fn poll_until(
+        &self,
+        pat: Option<Pattern>,
+        wake: impl Future<Output = ()>,
+    ) -> Result<Option<Message>> {
+        let (tx, mut rx) = oneshot::channel();
+        self.tx
+            .send(Cmd::Poll(pat, tx))
+            .await
+            .map_err(|_| Error::NoMailbox)?;
+        tokio::select! {
+            // prefer message when both are ready
+            biased;
+            msg = &mut rx => return Ok(Some(msg?)),
+            _ = wake => {}
+        }
+
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        self.tx
+            .send(Cmd::CancelPoll(cancel_tx))
+            .await
+            .map_err(|_| Error::NoMailbox)?;
+        cancel_rx.await?;
+        // message may have been delivered between timing out and cancelling poll
+        Ok(rx.try_recv().ok())
+    }
 }
 
 impl Mailbox {
@@ -82,11 +147,19 @@ impl Mailbox {
                 debug!("mailbox {}: {:?}", id, cmd);
                 match cmd {
                     Cmd::Push(msg) => mailbox.push(msg),
+                    Cmd::Requeue(msg) => mailbox.requeue(msg),
                     Cmd::GetAll(tx) => {
                         let msgs = mailbox.messages.iter().cloned().collect();
                         let _ = tx.send(msgs);
                     }
+                    Cmd::Len(tx) => {
+                        let _ = tx.send(mailbox.messages.len());
+                    }
                     Cmd::Poll(pat, tx) => mailbox.handle_poll(pat, tx),
+                    Cmd::CancelPoll(tx) => {
+                        mailbox.pending = None;
+                        let _ = tx.send(());
+                    }
                 }
             }
         });
@@ -95,6 +168,20 @@ impl Mailbox {
 
     /// Push a new message into mailbox. This may resolve pending requests
     fn push(&mut self, msg: Message) {
+        if let Some(msg) = self.fulfill_pending(msg) {
+            self.messages.push_back(msg);
+        }
+    }
+
+    /// Put message back in front of mailbox. This may resolve pending requests
+    fn requeue(&mut self, msg: Message) {
+        if let Some(msg) = self.fulfill_pending(msg) {
+            self.messages.push_front(msg);
+        }
+    }
+
+    /// Send message to pending poll matching it, or return message if there is none
+    fn fulfill_pending(&mut self, msg: Message) -> Option<Message> {
         let fufills_pending = match &self.pending {
             Some(pending) => match &pending.pattern {
                 Some(pat) => pat.is_match(&msg.contents),
@@ -105,9 +192,10 @@ impl Mailbox {
 
         if fufills_pending {
             let pending = self.pending.take().unwrap();
-            let _ = pending.tx.send(msg);
+            // keep message if poller is gone
+            pending.tx.send(msg).err()
         } else {
-            self.messages.push_back(msg);
+            Some(msg)
         }
     }
 
@@ -118,7 +206,7 @@ impl Mailbox {
                 let _ = tx.send(msg);
             }
             None => {
-                if self.pending.is_some() {
+                if self.pending.as_ref().is_some_and(|p| !p.tx.is_closed()) {
                     panic!("Unexpected poll on mailbox - mailbox should only be polled from process task");
                 }
                 self.pending = Some(PendingPoll { pattern, tx });
@@ -187,6 +275,28 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn requeue() {
+        let mb = Mailbox::spawn(0.into());
+        mb.push(Message::new(1.into(), Val::symbol("one")))
+            .await
+            .unwrap();
+        let taken = mb.poll(None).await.unwrap();
+        mb.push(Message::new(2.into(), Val::symbol("two")))
+            .await
+            .unwrap();
+        mb.requeue(taken).await.unwrap();
+
+        assert_eq!(
+            mb.all().await.unwrap(),
+            vec![
+                Message::new(1.into(), Val::symbol("one")),
+                Message::new(2.into(), Val::symbol("two")),
+            ],
+            "Requeued message should be received before messages that arrived after it"
+        );
+    }
+
     #[tokio::test]
     async fn poll_after_push() {
         let mb = Mailbox::spawn(0.into());
@@ -305,4 +415,31 @@ mod tests {
         assert_eq!(hdl.await.unwrap(), vec![msg4, msg1, msg2]);
         assert_eq!(mb.all().await.unwrap(), vec![msg3, msg5]);
     }
+
+    #[tokio::test]
+    async fn poll_timeout() {
+        let mb = Mailbox::spawn(0.into());
+
+        let msg = Message::new(1.into(), Val::keyword("late"));
+        assert_eq!(
+            mb.poll_timeout(None, Duration::from_millis(10))
+                .await
+                .unwrap(),
+            None,
+            "Should time out for empty mailbox"
+        );
+
+        mb.push(msg.clone()).await.unwrap();
+        assert_eq!(
+            mb.all().await.unwrap(),
+            vec![msg.clone()],
+            "Message after timeout should be kept in mailbox"
+        );
+        assert_eq!(
+            mb.poll_timeout(None, Duration::from_millis(10))
+                .await
+                .unwrap(),
+            Some(msg)
+        );
+    }
 }
@@ -5,12 +5,18 @@ pub mod program;
 mod pubsub;
 mod registry;
 mod runtime;
+mod scheduler;
+mod supervisor;
 mod term;
+mod timer;
 
 mod mailbox;
 mod proc;
 
 pub use error::Error;
 pub type Result<T> = std::result::Result<T, Error>;
-pub use proc::{Process, ProcessExit, ProcessHandle, ProcessId, ProcessResult, ProcessSet};
+pub use proc::{
+    Process, ProcessExit, ProcessHandle, ProcessId, ProcessResult, ProcessSet, ProcessStatus,
+};
 pub use runtime::Runtime;
+pub use scheduler::Clock;
@@ -8,6 +8,8 @@ use crate::rt::mailbox::{Mailbox, MailboxHandle};
 use crate::rt::{Error, Result};
 use crate::Program;
 use futures::future::{FutureExt, Shared};
+use lyric::{KeywordId, Profiler};
+use std::sync::{Arc, Mutex};
 use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinSet;
 use tracing::info;
@@ -24,6 +26,7 @@ pub struct Process {
     id: ProcessId,
     prog: Program,
     locals: Locals,
+    parent: Option<ProcessId>,
 }
 
 /// A handle to [Process]
@@ -33,6 +36,35 @@ pub struct ProcessHandle {
     hdl_tx: mpsc::Sender<Event>,
     mailbox: MailboxHandle,
     exit_rx: Shared<oneshot::Receiver<ProcessExit>>,
+    profiler: Profiler,
+    status: Arc<Mutex<ProcessStatus>>,
+    meta: Arc<ProcessMeta>,
+}
+
+/// What a running process is doing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcessStatus {
+    /// Executing or ready to execute
+    Running,
+    /// Waiting for a message in mailbox
+    AwaitingRecv,
+    /// Sleeping until a deadline
+    Sleeping,
+    /// Waiting for an executed command to finish
+    AwaitingExec,
+    /// Waiting for another process to exit
+    AwaitingExit,
+}
+
+/// Sets status of process until dropped, when process is running again
+pub(crate) struct StatusGuard(Arc<Mutex<ProcessStatus>>);
+
+/// Metadata recorded when process is spawned
+#[derive(Debug)]
+struct ProcessMeta {
+    parent: Option<ProcessId>,
+    spawned_at: lyric::Time,
+    source: String,
 }
 
 /// The result of process
@@ -42,6 +74,10 @@ pub enum ProcessResult {
     Done(Val),
     /// Cancelled for closed event loop
     Cancelled,
+    /// Exited explicitly with reason
+    Exited(Val),
+    /// Controlling terminal connection closed
+    Disconnected,
 }
 
 /// A record of process exiting
@@ -58,9 +94,16 @@ impl Process {
             id,
             prog,
             locals: Locals::new(id),
+            parent: None,
         }
     }
 
+    /// Set process that spawned process
+    pub(crate) fn parent(mut self, parent: Option<ProcessId>) -> Self {
+        self.parent = parent;
+        self
+    }
+
     /// Set kernel handle for process
     pub(crate) fn kernel(mut self, k: WeakKernelHandle) -> Self {
         self.locals.kernel(k);
@@ -93,38 +136,48 @@ impl Process {
         let (msg_tx, mut msg_rx) = mpsc::channel(32);
 
         let mailbox: MailboxHandle = Mailbox::spawn(self.id);
+        let profiler = Profiler::new();
         let proc_hdl = ProcessHandle {
             id: self.id,
             hdl_tx: msg_tx,
             exit_rx: exit_rx.shared(),
             mailbox,
+            profiler: profiler.clone(),
+            status: Arc::new(Mutex::new(ProcessStatus::Running)),
+            meta: Arc::new(ProcessMeta {
+                parent: self.parent,
+                spawned_at: lyric::Time::now(),
+                source: self.prog.source().to_string(),
+            }),
         };
         self.locals.handle(proc_hdl.clone());
+        let term = self.locals.term.clone();
 
         let mut fiber = self.prog.into_fiber(self.locals);
+        fiber.set_profiler(Some(profiler));
 
         procs.spawn(async move {
-            // TODO: Implement ProcessResult::Disconnected when Error::ConnectionClosed is returned
             // TODO: Use cancel token instead of msg_rx
-            let exit = tokio::select! {
+            let status = tokio::select! {
                 res = lyric::run(&mut fiber) => {
                     match res {
-                        Ok(v) => ProcessExit {
-                            id: self.id,
-                            status: Ok(ProcessResult::Done(v)),
-                        },
-                        Err(e) => ProcessExit {
-                            id: self.id,
-                            status: Err(Error::EvaluationError(e)),
+                        Ok(v) => Ok(ProcessResult::Done(v)),
+                        // errors from a closed terminal are from disconnecting
+                        Err(_) if term.as_ref().is_some_and(|t| t.is_closed()) => {
+                            Ok(ProcessResult::Disconnected)
                         }
+                        Err(e) => Err(Error::EvaluationError(e)),
                     }
                 },
                 Some(msg) = msg_rx.recv() => match msg {
-                    Event::Kill => ProcessExit {
-                        id: self.id,
-                        status: Ok(ProcessResult::Cancelled)
-                    }
+                    Event::Kill => Ok(ProcessResult::Cancelled),
+                    Event::Exit(reason) => Ok(ProcessResult::Exited(reason)),
                 },
+                _ = disconnected(&term) => Ok(ProcessResult::Disconnected),
+            };
+            let exit = ProcessExit {
+                id: self.id,
+                status,
             };
 
             let _ = exit_tx.send(exit.clone());
@@ -147,11 +200,24 @@ impl ProcessHandle {
         let _ = self.hdl_tx.send(Event::Kill).await;
     }
 
+    /// Send exit message to process, ending it with given reason. Effect is not immediate.
+    pub(crate) async fn exit(&self, reason: Val) {
+        let _ = self.hdl_tx.send(Event::Exit(reason)).await;
+    }
+
     /// Wait for process to end
     pub async fn join(self) -> Result<ProcessExit> {
         Ok(self.exit_rx.await?)
     }
 
+    /// Exit of process, if it has exited
+    pub(crate) fn exited(&self) -> Option<ProcessExit> {
+        self.exit_rx
+            .peek()
+            .and_then(|exit| exit.as_ref().ok())
+            .cloned()
+    }
+
     /// Send a new message to process's mailbox
     pub(crate) async fn notify_message(&self, msg: Message) {
         let _ = self.mailbox.push(msg).await;
@@ -161,6 +227,55 @@ impl ProcessHandle {
     pub(crate) fn mailbox(&self) -> &MailboxHandle {
         &self.mailbox
     }
+
+    /// Get profiler attached to process
+    pub(crate) fn profiler(&self) -> &Profiler {
+        &self.profiler
+    }
+
+    /// Get what process is currently doing
+    pub fn status(&self) -> ProcessStatus {
+        *self.status.lock().unwrap()
+    }
+
+    /// Set status of process until returned guard is dropped
+    pub(crate) fn enter_status(&self, status: ProcessStatus) -> StatusGuard {
+        *self.status.lock().unwrap() = status;
+        StatusGuard(self.status.clone())
+    }
+
+    /// Describe process as list of `:pid`, `:parent`, `:names` it is registered as, `:spawned_at`,
+    /// `:status`, `:mailbox_len`, `:insts` executed, and `:source` or entry function.
+    pub(crate) async fn info(&self, names: Vec<KeywordId>) -> Result<Val> {
+        let mailbox_len = self.mailbox.len().await?;
+        Ok(Val::List(vec![
+            Val::keyword("pid"),
+            Val::Extern(Extern::ProcessId(self.id)),
+            Val::keyword("parent"),
+            match self.meta.parent {
+                Some(pid) => Val::Extern(Extern::ProcessId(pid)),
+                None => Val::Nil,
+            },
+            Val::keyword("names"),
+            Val::List(names.into_iter().map(Val::Keyword).collect()),
+            Val::keyword("spawned_at"),
+            Val::Time(self.meta.spawned_at.clone()),
+            Val::keyword("status"),
+            Val::keyword(&self.status().to_string()),
+            Val::keyword("mailbox_len"),
+            Val::Int(i32::try_from(mailbox_len).unwrap_or(i32::MAX)),
+            Val::keyword("insts"),
+            Val::Int(i32::try_from(self.profiler.total_insts()).unwrap_or(i32::MAX)),
+            Val::keyword("source"),
+            Val::string(&self.meta.source),
+        ]))
+    }
+}
+
+impl Drop for StatusGuard {
+    fn drop(&mut self) {
+        *self.0.lock().unwrap() = ProcessStatus::Running;
+    }
 }
 
 impl std::cmp::PartialEq for ProcessHandle {
@@ -172,6 +287,15 @@ impl std::cmp::PartialEq for ProcessHandle {
 #[derive(Debug)]
 enum Event {
     Kill,
+    Exit(Val),
+}
+
+/// Resolves when controlling terminal disconnects, or never for processes without one
+async fn disconnected(term: &Option<TermHandle>) {
+    match term {
+        Some(term) => term.closed().await,
+        None => std::future::pending().await,
+    }
 }
 
 impl ProcessId {
@@ -186,6 +310,22 @@ impl From<usize> for ProcessId {
     }
 }
 
+impl lyric::FromVal<Extern, Locals> for ProcessId {
+    fn from_val(val: Val) -> lyric::Result<Self> {
+        match val {
+            Val::Extern(Extern::ProcessId(pid)) => Ok(pid),
+            Val::Extern(Extern::Task(hdl)) => Ok(hdl.id()),
+            _ => Err(lyric::Error::UnexpectedType("expected pid".to_string())),
+        }
+    }
+}
+
+impl lyric::IntoVal<Extern, Locals> for ProcessId {
+    fn into_val(self) -> Val {
+        Val::Extern(Extern::ProcessId(self))
+    }
+}
+
 impl std::fmt::Display for ProcessId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "<pid {}>", self.0)
@@ -197,6 +337,19 @@ impl std::fmt::Display for Extern {
         match self {
             Extern::ProcessId(pid) => write!(f, "{}", pid),
             Extern::RequestId(id) => write!(f, "<request_id {}>", id),
+            Extern::Task(hdl) => write!(f, "<task {}>", hdl.id().inner()),
+        }
+    }
+}
+
+impl std::fmt::Display for ProcessStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessStatus::Running => write!(f, "running"),
+            ProcessStatus::AwaitingRecv => write!(f, "awaiting_recv"),
+            ProcessStatus::Sleeping => write!(f, "sleeping"),
+            ProcessStatus::AwaitingExec => write!(f, "awaiting_exec"),
+            ProcessStatus::AwaitingExit => write!(f, "awaiting_exit"),
         }
     }
 }
@@ -206,11 +359,52 @@ impl std::fmt::Display for ProcessExit {
         match &self.status {
             Ok(ProcessResult::Done(v)) => write!(f, "DONE - {v}"),
             Ok(ProcessResult::Cancelled) => write!(f, "CANCELLED"),
+            Ok(ProcessResult::Exited(reason)) => write!(f, "EXITED - {reason}"),
+            Ok(ProcessResult::Disconnected) => write!(f, "DISCONNECTED"),
             Err(e) => write!(f, "ERROR - {e}"),
         }
     }
 }
 
+impl ProcessExit {
+    /// Whether process exited abnormally, i.e. for any reason other than `:normal`
+    pub fn is_abnormal(&self) -> bool {
+        self.reason() != Val::keyword("normal")
+    }
+
+    /// Exit reason delivered to linked and monitoring processes - `:normal`, `:killed`,
+    /// `:disconnected`, `(:error MSG)`, or the reason given to `exit` or `kill`
+    pub fn reason(&self) -> Val {
+        match &self.status {
+            Ok(ProcessResult::Done(_)) => Val::keyword("normal"),
+            Ok(ProcessResult::Cancelled) => Val::keyword("killed"),
+            Ok(ProcessResult::Exited(reason)) => reason.clone(),
+            Ok(ProcessResult::Disconnected) => Val::keyword("disconnected"),
+            Err(e) => Val::List(vec![Val::keyword("error"), Val::string(&e.to_string())]),
+        }
+    }
+
+    /// Result of process as `(:ok VAL)`, `(:err ERR)`, `(:cancelled)`, `(:exit REASON)`
+    /// or `(:disconnected)`
+    pub fn result(&self) -> Val {
+        match &self.status {
+            Ok(ProcessResult::Done(v)) => Val::List(vec![Val::keyword("ok"), v.clone()]),
+            Ok(ProcessResult::Cancelled) => Val::List(vec![Val::keyword("cancelled")]),
+            Ok(ProcessResult::Exited(reason)) => {
+                Val::List(vec![Val::keyword("exit"), reason.clone()])
+            }
+            Ok(ProcessResult::Disconnected) => Val::List(vec![Val::keyword("disconnected")]),
+            Err(Error::EvaluationError(e)) => {
+                Val::List(vec![Val::keyword("err"), Val::Error(e.clone())])
+            }
+            Err(e) => Val::List(vec![
+                Val::keyword("err"),
+                Val::Error(lyric::Error::Runtime(e.to_string())),
+            ]),
+        }
+    }
+}
+
 impl ProcessResult {
     pub fn unwrap(self) -> Val {
         match self {
@@ -1,13 +1,16 @@
 #![allow(dead_code)]
 //! Program that specifies a process
 
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
 use lyric::{Error, Result, SymbolId};
 
 use crate::ProcessHandle;
 
 use super::bindings;
 use super::kernel::WeakKernelHandle;
-use super::proc::ProcessId;
+use super::proc::{ProcessId, ProcessStatus, StatusGuard};
 use super::pubsub::PubSubHandle;
 use super::registry::Registry;
 use super::term::TermHandle;
@@ -17,6 +20,7 @@ use super::term::TermHandle;
 pub struct Program {
     code: Bytecode,
     env: Env,
+    source: String,
 }
 
 /// Form for vrs
@@ -52,11 +56,24 @@ pub type NativeAsyncFn = lyric::NativeAsyncFn<Extern, Locals>;
 /// Bytecode
 pub type Bytecode = lyric::Bytecode<Extern, Locals>;
 
+/// Lazy sequences for programs
+pub type Seq = lyric::Seq<Extern, Locals>;
+
 /// Extern type between Fiber and hosting program
 #[derive(Debug, Clone, PartialEq)]
 pub enum Extern {
     ProcessId(ProcessId),
     RequestId(u32), // TODO: Type request id as RequestId
+    /// Handle to a spawned task, whose result can be awaited after it exits
+    Task(ProcessHandle),
+}
+
+/// Child spawned by process
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Child {
+    Running(ProcessHandle),
+    /// Result of exited child, as returned by `await`
+    Exited(Val),
 }
 
 /// Locals for Program Fiber
@@ -74,6 +91,10 @@ pub struct Locals {
     pub(crate) self_handle: Option<ProcessHandle>,
     /// Handle to controlling terminal, if any
     pub(crate) term: Option<TermHandle>,
+    /// Spawned children, kept until awaited so results outlive the children
+    pub(crate) children: HashMap<ProcessId, Child>,
+    /// Topics process is subscribed to, whose updates are delivered to its mailbox
+    pub(crate) subscriptions: HashSet<KeywordId>,
 }
 
 impl Program {
@@ -81,17 +102,18 @@ impl Program {
         Self {
             code,
             env: proc_env(),
+            source: "<bytecode>".to_string(),
         }
     }
 
     pub fn from_val(val: Val) -> Result<Self> {
         let code = lyric::compile(&val)?;
-        Ok(Self::from_bytecode(code))
+        Ok(Self::from_bytecode(code).with_source(val.to_string()))
     }
 
     pub fn from_expr(expr: &str) -> Result<Self> {
         let val: Val = lyric::parse(expr)?.into();
-        Self::from_val(val)
+        Ok(Self::from_val(val)?.with_source(expr.trim().to_string()))
     }
 
     pub fn from_lambda(lambda: Lambda) -> Result<Self> {
@@ -101,12 +123,27 @@ impl Program {
             ));
         }
 
-        let code = lambda.code;
-        let env = lambda.parent.as_ref();
-        let prog = Self::from_bytecode(code).env(match env {
-            Some(env) => env.lock().unwrap().fork(),
-            None => proc_env(),
+        // Name entry function by symbol it is bound to in its defining environment, if any
+        let name = lambda.parent.as_ref().and_then(|env| {
+            env.lock()
+                .unwrap()
+                .iter()
+                .find(|(_, v)| matches!(v, Val::Lambda(l) if *l == lambda))
+                .map(|(sym, _)| sym.clone())
         });
+        let source = match name {
+            Some(name) => format!("({name})"),
+            None => "<lambda>".to_string(),
+        };
+
+        let code = Arc::unwrap_or_clone(lambda.code);
+        let env = lambda.parent.as_ref();
+        let prog = Self::from_bytecode(code)
+            .env(match env {
+                Some(env) => env.lock().unwrap().fork(),
+                None => proc_env(),
+            })
+            .with_source(source);
 
         Ok(prog)
     }
@@ -116,6 +153,17 @@ impl Program {
         self
     }
 
+    /// Set source or entry function program is described by
+    pub fn with_source(mut self, source: String) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Source or entry function of program
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
     pub fn into_fiber(self, locals: Locals) -> Fiber {
         Fiber::from_bytecode(self.code, self.env, locals)
     }
@@ -132,6 +180,7 @@ pub fn term_prog() -> Program {
     Program::from_expr(prog)
         .expect("Connection program should compile")
         .env(term_env())
+        .with_source("<term>".to_string())
 }
 
 impl Locals {
@@ -143,6 +192,8 @@ impl Locals {
             pubsub: None,
             self_handle: None,
             term: None,
+            children: HashMap::new(),
+            subscriptions: HashSet::new(),
         }
     }
 
@@ -170,6 +221,24 @@ impl Locals {
         self.term = Some(term);
         self
     }
+
+    /// Keep spawned child until awaited. Handles of children that exited since are replaced with
+    /// their results.
+    pub(crate) fn add_child(&mut self, hdl: ProcessHandle) {
+        for child in self.children.values_mut() {
+            if let Child::Running(running) = child {
+                if let Some(exit) = running.exited() {
+                    *child = Child::Exited(exit.result());
+                }
+            }
+        }
+        self.children.insert(hdl.id(), Child::Running(hdl));
+    }
+
+    /// Set status of current process until returned guard is dropped
+    pub(crate) fn enter_status(&self, status: ProcessStatus) -> Option<StatusGuard> {
+        self.self_handle.as_ref().map(|h| h.enter_status(status))
+    }
 }
 
 impl PartialEq for Program {
@@ -186,7 +255,10 @@ pub fn proc_env() -> Env {
         e.bind_native_async(SymbolId::from("recv"), bindings::recv_fn())
             .bind_native_async(SymbolId::from("ls_msgs"), bindings::ls_msgs_fn())
             .bind_native_async(SymbolId::from("send"), bindings::send_fn())
-            .bind_lambda(SymbolId::from("call"), bindings::call_fn());
+            .bind_native(SymbolId::from("recv_seq"), bindings::recv_seq_fn())
+            .bind_native_async(SymbolId::from("recv_match"), bindings::recv_match_fn())
+            .bind_native_async(SymbolId::from("call"), bindings::call_fn())
+            .bind_native_async(SymbolId::from("select_recv"), bindings::select_recv_fn());
     }
 
     {
@@ -201,12 +273,24 @@ pub fn proc_env() -> Env {
     }
 
     {
-        e.bind_native_async(SymbolId::from("kill"), bindings::kill_fn())
+        e.bind_native_async(SymbolId::from("await"), bindings::await_fn())
+            .bind_native_async(SymbolId::from("exit"), bindings::exit_fn())
+            .bind_native_async(SymbolId::from("kill"), bindings::kill_fn())
             .bind_native(SymbolId::from("pid"), bindings::pid_fn())
+            .bind_native_async(SymbolId::from("proc_info"), bindings::proc_info_fn())
+            .bind_native_async(SymbolId::from("profile"), bindings::profile_fn())
             .bind_native_async(SymbolId::from("ps"), bindings::ps_fn())
             .bind_native(SymbolId::from("self"), bindings::self_fn())
             .bind_native_async(SymbolId::from("sleep"), bindings::sleep_fn())
-            .bind_native_async(SymbolId::from("spawn"), bindings::spawn_fn());
+            .bind_native_async(SymbolId::from("sleep_until"), bindings::sleep_until_fn())
+            .bind_native_async(SymbolId::from("spawn"), bindings::spawn_fn())
+            .bind_native_async(SymbolId::from("spawn_link"), bindings::spawn_link_fn())
+            .bind_native_async(SymbolId::from("spawn_task"), bindings::spawn_task_fn())
+            .bind_native_async(SymbolId::from("link"), bindings::link_fn())
+            .bind_native_async(SymbolId::from("unlink"), bindings::unlink_fn())
+            .bind_native_async(SymbolId::from("monitor"), bindings::monitor_fn())
+            .bind_native_async(SymbolId::from("demonitor"), bindings::demonitor_fn())
+            .bind_native_async(SymbolId::from("trap_exit"), bindings::trap_exit_fn());
     }
 
     {
@@ -231,8 +315,30 @@ pub fn proc_env() -> Env {
             .bind_native_async(SymbolId::from("ls_srv"), bindings::ls_srv_fn());
     }
 
+    {
+        e.bind_native_async(SymbolId::from("supervisor"), bindings::supervisor_fn());
+    }
+
+    {
+        e.bind_native_async(SymbolId::from("scheduler"), bindings::scheduler_fn())
+            .bind_native_async(SymbolId::from("schedule"), bindings::schedule_fn())
+            .bind_native_async(SymbolId::from("ls_jobs"), bindings::ls_jobs_fn())
+            .bind_native_async(SymbolId::from("rm_job"), bindings::rm_job_fn());
+    }
+
+    {
+        e.bind_native_async(SymbolId::from("send_after"), bindings::send_after_fn())
+            .bind_native_async(SymbolId::from("interval"), bindings::interval_fn())
+            .bind_native_async(SymbolId::from("cancel_timer"), bindings::cancel_timer_fn())
+            .bind_native_async(SymbolId::from("read_timer"), bindings::read_timer_fn());
+    }
+
     {
         e.bind_native_async(SymbolId::from("subscribe"), bindings::subscribe_fn())
+            .bind_lambda(
+                SymbolId::from("subscribe_seq"),
+                bindings::subscribe_seq_fn(),
+            )
             .bind_native_async(SymbolId::from("publish"), bindings::publish_fn());
     }
 
@@ -1,4 +1,6 @@
 //! Process Registry
+//! Changes to the registry are published over the `:registry` topic as `(:registered NAME PID)` and
+//! `(:deregistered NAME PID)`.
 use nanoid::nanoid;
 use std::collections::HashMap;
 use tokio::sync::{mpsc, oneshot};
@@ -9,6 +11,7 @@ use tracing::error;
 use crate::rt::program::Val;
 use crate::{Error, Extern, ProcessExit, ProcessHandle, Result};
 
+use super::pubsub::PubSubHandle;
 use super::ProcessId;
 
 /// Handle to [Registry]
@@ -22,6 +25,7 @@ pub struct Registry {
 pub struct RegistryTask {
     weak_tx: mpsc::WeakSender<Cmd>,
     entries: HashMap<KeywordId, Entry>,
+    pubsub: PubSubHandle,
 }
 
 /// Identifier for Entries
@@ -46,12 +50,12 @@ pub struct Registration {
 }
 
 impl Registry {
-    /// Spawn a new registry task
-    pub fn spawn() -> Registry {
+    /// Spawn a new registry task, publishing changes over `pubsub`
+    pub(crate) fn spawn(pubsub: PubSubHandle) -> Registry {
         let (tx, mut rx) = mpsc::channel(32);
         let weak_tx = tx.downgrade();
         tokio::spawn(async move {
-            let mut registry = RegistryTask::new(weak_tx);
+            let mut registry = RegistryTask::new(weak_tx, pubsub);
             while let Some(cmd) = rx.recv().await {
                 registry.handle_cmd(cmd).await
             }
@@ -69,6 +73,17 @@ impl Registry {
         resp_rx.await?
     }
 
+    /// Register process under keyword, unless it already registered itself under keyword.
+    /// Registrations of other processes under keyword are overwritten.
+    pub async fn ensure(&self, keyword: KeywordId, proc: ProcessHandle) -> Result<()> {
+        let (resp_tx, resp_rx) = oneshot::channel();
+        self.tx
+            .send(Cmd::Ensure(keyword, proc, resp_tx))
+            .await
+            .map_err(|_| Error::NoMessageReceiver("registry task is dead".to_string()))?;
+        resp_rx.await?
+    }
+
     /// Lookup given process for name
     pub async fn lookup(&self, keyword: KeywordId) -> Result<Option<Entry>> {
         let (resp_tx, resp_rx) = oneshot::channel();
@@ -97,23 +112,35 @@ impl std::cmp::PartialEq for Registry {
 }
 
 impl RegistryTask {
-    fn new(weak_tx: mpsc::WeakSender<Cmd>) -> Self {
+    fn new(weak_tx: mpsc::WeakSender<Cmd>, pubsub: PubSubHandle) -> Self {
         Self {
             weak_tx,
             entries: HashMap::new(),
+            pubsub,
         }
     }
 
     async fn handle_cmd(&mut self, cmd: Cmd) {
         match cmd {
             Cmd::Register(registration, proc, resp_tx) => {
-                let _ = resp_tx.send(self.handle_register(registration, proc));
+                let _ = resp_tx.send(self.handle_register(registration, proc).await);
+            }
+            Cmd::Ensure(keyword, proc, resp_tx) => {
+                let res = match self.entries.get(&keyword) {
+                    Some(e) if e.pid() == proc.id() => Ok(()),
+                    _ => {
+                        let mut registration = Registration::new(keyword);
+                        registration.overwrite(true);
+                        self.handle_register(registration, proc).await
+                    }
+                };
+                let _ = resp_tx.send(res);
             }
             Cmd::Lookup(keyword, resp_tx) => {
                 let _ = resp_tx.send(self.entries.get(&keyword).cloned());
             }
             Cmd::NotifyExit(keyword, id, exit) => {
-                self.handle_exit(keyword, id, exit);
+                self.handle_exit(keyword, id, exit).await;
             }
             Cmd::GetAll(resp_tx) => {
                 let _ = resp_tx.send(self.entries.values().cloned().collect());
@@ -121,10 +148,18 @@ impl RegistryTask {
         }
     }
 
-    fn handle_register(&mut self, registration: Registration, handle: ProcessHandle) -> Result<()> {
+    async fn handle_register(
+        &mut self,
+        registration: Registration,
+        handle: ProcessHandle,
+    ) -> Result<()> {
         let keyword = &registration.keyword;
 
-        if !registration.overwrite && self.entries.contains_key(keyword) {
+        let registered_to_other = self
+            .entries
+            .get(keyword)
+            .is_some_and(|e| e.pid() != handle.id());
+        if !registration.overwrite && registered_to_other {
             return Err(Error::RegistryError(format!(
                 "Registered process exists for {}",
                 keyword
@@ -147,15 +182,19 @@ impl RegistryTask {
             let _ = tx.send(Cmd::NotifyExit(kwd, entry_id, exit)).await;
         });
 
+        let pid = entry.pid();
         self.entries.insert(keyword.clone(), entry);
+        self.publish("registered", keyword.clone(), pid).await;
 
         Ok(())
     }
 
-    fn handle_exit(&mut self, keyword: KeywordId, id: EntryId, exit: Result<ProcessExit>) {
+    async fn handle_exit(&mut self, keyword: KeywordId, id: EntryId, exit: Result<ProcessExit>) {
         match self.entries.get(&keyword) {
             Some(e) if e.id == id => {
+                let pid = e.pid();
                 self.entries.remove(&keyword);
+                self.publish("deregistered", keyword, pid).await;
             }
             _ => {
                 error!(
@@ -165,6 +204,22 @@ impl RegistryTask {
             }
         };
     }
+
+    /// Publish `(EVENT NAME PID)` over the `:registry` topic
+    async fn publish(&self, event: &str, keyword: KeywordId, pid: ProcessId) {
+        let change = Val::List(vec![
+            Val::keyword(event),
+            Val::Keyword(keyword),
+            Val::Extern(Extern::ProcessId(pid)),
+        ]);
+        if let Err(e) = self
+            .pubsub
+            .publish(&KeywordId::from("registry"), change)
+            .await
+        {
+            error!("registry failed to publish {event} - {e}");
+        }
+    }
 }
 
 impl Entry {
@@ -246,6 +301,7 @@ impl From<String> for EntryId {
 
 enum Cmd {
     Register(Registration, ProcessHandle, oneshot::Sender<Result<()>>),
+    Ensure(KeywordId, ProcessHandle, oneshot::Sender<Result<()>>),
     Lookup(KeywordId, oneshot::Sender<Option<Entry>>),
     NotifyExit(KeywordId, EntryId, Result<ProcessExit>),
     GetAll(oneshot::Sender<Vec<Entry>>),
@@ -253,6 +309,7 @@ enum Cmd {
 
 #[cfg(test)]
 mod tests {
+    use crate::rt::pubsub::PubSub;
     use crate::{rt::kernel, Program};
 
     use super::*;
@@ -260,7 +317,7 @@ mod tests {
 
     #[tokio::test]
     async fn empty() {
-        let r = Registry::spawn();
+        let r = Registry::spawn(PubSub::spawn());
         assert_matches!(
             r.lookup(KeywordId::from("unknown_keyword")).await.unwrap(),
             None
@@ -270,7 +327,7 @@ mod tests {
 
     #[tokio::test]
     async fn register() {
-        let r = Registry::spawn();
+        let r = Registry::spawn(PubSub::spawn());
         let k = kernel::start();
 
         let prog = Program::from_expr("(loop (sleep 1))").unwrap();
@@ -295,7 +352,7 @@ mod tests {
 
     #[tokio::test]
     async fn register_duplicate() {
-        let r = Registry::spawn();
+        let r = Registry::spawn(PubSub::spawn());
         let k = kernel::start();
 
         let prog = Program::from_expr("(loop (sleep 1))").unwrap();
@@ -316,7 +373,7 @@ mod tests {
 
     #[tokio::test]
     async fn register_duplicate_overwrite() {
-        let r = Registry::spawn();
+        let r = Registry::spawn(PubSub::spawn());
         let k = kernel::start();
 
         let prog = Program::from_expr("(loop (sleep 1))").unwrap();
@@ -338,9 +395,43 @@ mod tests {
                         "Lookup should return newer registration");
     }
 
+    #[tokio::test]
+    async fn ensure() {
+        let r = Registry::spawn(PubSub::spawn());
+        let k = kernel::start();
+
+        let prog = Program::from_expr("(loop (sleep 1))").unwrap();
+        let hdl_a = k.spawn_prog(prog.clone()).await.unwrap();
+        let hdl_b = k.spawn_prog(prog).await.unwrap();
+
+        let mut reg_a = Registration::new(KeywordId::from("A"));
+        reg_a.interface(vec![Val::keyword("interface_a")]);
+        r.register(reg_a.clone(), hdl_a.clone())
+            .await
+            .expect("registration should succeed");
+
+        r.ensure(KeywordId::from("A"), hdl_a.clone())
+            .await
+            .expect("ensure should succeed");
+        assert_matches!(r.lookup(KeywordId::from("A")).await.unwrap(),
+                        Some(e) if e.registration == reg_a,
+                        "ensure should keep existing registration of same process");
+
+        r.register(reg_a, hdl_a.clone())
+            .await
+            .expect("process should be able to register itself again");
+
+        r.ensure(KeywordId::from("A"), hdl_b.clone())
+            .await
+            .expect("ensure should succeed");
+        assert_matches!(r.lookup(KeywordId::from("A")).await.unwrap(),
+                        Some(e) if e.pid() == hdl_b.id(),
+                        "ensure should overwrite registration of other process");
+    }
+
     #[tokio::test]
     async fn deregister_on_proc_exit() {
-        let r = Registry::spawn();
+        let r = Registry::spawn(PubSub::spawn());
         let k = kernel::start();
 
         let prog = Program::from_expr("(recv)").unwrap();
@@ -365,9 +456,48 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn publish_changes() {
+        let pubsub = PubSub::spawn();
+        let r = Registry::spawn(pubsub.clone());
+        let k = kernel::start();
+        let mut sub = pubsub
+            .subscribe(&KeywordId::from("registry"))
+            .await
+            .unwrap();
+
+        let prog = Program::from_expr("(recv)").unwrap();
+        let hdl = k.spawn_prog(prog).await.unwrap();
+        let pid = Val::Extern(Extern::ProcessId(hdl.id()));
+
+        r.register(Registration::new(KeywordId::from("A")), hdl.clone())
+            .await
+            .expect("registration should succeed");
+        hdl.kill().await;
+        hdl.join().await.expect("should complete");
+
+        assert_eq!(
+            sub.recv().await,
+            Some(Val::List(vec![
+                Val::keyword("registered"),
+                Val::keyword("A"),
+                pid.clone()
+            ]))
+        );
+        assert_eq!(
+            sub.recv().await,
+            Some(Val::List(vec![
+                Val::keyword("deregistered"),
+                Val::keyword("A"),
+                pid
+            ])),
+            "Exit of registered process should be published"
+        );
+    }
+
     #[tokio::test]
     async fn get_all() {
-        let r = Registry::spawn();
+        let r = Registry::spawn(PubSub::spawn());
         let k = kernel::start();
 
         let prog = Program::from_expr("(loop (sleep 1))").unwrap();
@@ -391,7 +521,7 @@ mod tests {
             .await
             .expect("Should be able to retrieve entries")
             .into_iter()
-            .map(|e| (e.registration))
+            .map(|e| e.registration)
             .collect();
         assert!(entries.contains(
             Registration::new(KeywordId::from("A")).interface(vec![Val::keyword("interface_a")])
@@ -408,7 +538,7 @@ mod tests {
             .await
             .expect("Should be able to retrieve entries")
             .into_iter()
-            .map(|e| (e.registration.keyword))
+            .map(|e| e.registration.keyword)
             .collect();
         assert!(
             !entries.contains(&KeywordId::from("A")),
@@ -1,7 +1,10 @@
 //! Runtime
+use std::path::Path;
+
 use super::kernel::{self, KernelHandle};
+use super::scheduler::Clock;
 use crate::rt::{ProcessHandle, Result};
-use crate::{Connection, Program};
+use crate::{Connection, Program, Val};
 
 /// Handle to Runtime's public interface
 pub struct Runtime {
@@ -15,6 +18,12 @@ impl Runtime {
         Self { kernel_task }
     }
 
+    /// Create new runtime instance, whose scheduler keeps time with `clock`
+    pub fn with_clock(clock: Clock) -> Self {
+        let kernel_task = kernel::start_with_clock(clock);
+        Self { kernel_task }
+    }
+
     /// Notify the runtime of new connection to handle
     pub async fn handle_conn(&self, conn: Connection) -> Result<ProcessHandle> {
         self.kernel_task.spawn_for_conn(conn).await
@@ -24,6 +33,16 @@ impl Runtime {
     pub async fn run(&self, prog: Program) -> Result<ProcessHandle> {
         self.kernel_task.spawn_prog(prog).await
     }
+
+    /// Start scheduler service, saving its jobs to file at `path`
+    pub async fn start_scheduler(&self, path: &Path) -> Result<ProcessHandle> {
+        let form = Val::List(vec![
+            Val::symbol("scheduler"),
+            Val::keyword("path"),
+            Val::String(path.display().to_string()),
+        ]);
+        self.run(Program::from_val(form)?).await
+    }
 }
 
 impl Default for Runtime {
@@ -0,0 +1,1041 @@
+//! Scheduler service for running jobs on cron schedules
+//! Jobs spawn a program or message a service whenever their schedule is due, and can be saved to
+//! a file to survive runtime restarts.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use jiff::{civil, Span, Timestamp, ToSpan, Zoned};
+use lyric::{kwargs, pprint, FromVal, IntoVal, KeywordId, Ref};
+use tokio::sync::watch;
+use tokio::time;
+use tracing::{info, warn};
+
+use super::kernel::KernelHandle;
+use super::mailbox::MailboxHandle;
+use super::program::{Program, Val};
+use super::registry::Registry;
+use super::{Error, ProcessId, Result};
+
+/// How late a run can start and still count as on time
+const GRACE: Duration = Duration::from_secs(60);
+
+/// Longest sleep on system clock, so changes to wall clock time like suspends are noticed
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+const WEEKDAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Minutes matched by a cron expression like `"30 7 * * mon-fri"`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether days of month and weekdays are both restricted, so days matching either run
+    either_day: bool,
+}
+
+/// Source of wall clock time for scheduler. The system clock is used by default, and virtual
+/// clocks that only move when advanced can be used to test schedules.
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+    /// Current time of virtual clock, if clock is virtual
+    virtual_now: Option<Arc<watch::Sender<Zoned>>>,
+}
+
+/// What a job does when it runs
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Action {
+    /// Spawn a program running form
+    Spawn(Val),
+    /// Send message to registered service
+    Send(KeywordId, Val),
+}
+
+/// A scheduled job
+#[derive(Debug, Clone)]
+pub(crate) struct Job {
+    name: KeywordId,
+    /// Schedule as given, e.g. `"0 7 * * *"` or `:daily`
+    spec: Val,
+    cron: Cron,
+    action: Action,
+    /// Whether job runs once for runs missed while it couldn't run
+    catch_up: bool,
+    next_run: Option<Zoned>,
+    last_run: Option<Zoned>,
+    runs: usize,
+    missed: usize,
+}
+
+/// Scheduled jobs, in order added
+#[derive(Debug, Default)]
+pub(crate) struct Jobs(Vec<Job>);
+
+/// Scheduler running within a process, running jobs as they're due
+pub(crate) struct Scheduler {
+    pid: ProcessId,
+    kernel: KernelHandle,
+    registry: Registry,
+    mailbox: MailboxHandle,
+    clock: Clock,
+    /// File jobs are saved to, if any
+    path: Option<PathBuf>,
+    jobs: Jobs,
+}
+
+impl Cron {
+    /// Parse cron expression with minute, hour, day of month, month and weekday fields, or a
+    /// nickname like `@daily`
+    pub(crate) fn parse(expr: &str) -> Result<Self> {
+        let expr = match expr.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            expr => expr,
+        };
+        let [minute, hour, day, month, weekday] = expr.split_whitespace().collect::<Vec<_>>()[..]
+        else {
+            return Err(Error::SchedulerError(format!(
+                "cron expression should have 5 fields - got \"{expr}\""
+            )));
+        };
+
+        let weekdays = parse_field(weekday, 0, 7, WEEKDAYS)?;
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[])?,
+            hours: parse_field(hour, 0, 23, &[])?,
+            days: parse_field(day, 1, 31, &[])?,
+            months: parse_field(month, 1, 12, MONTHS)?,
+            // sunday is both 0 and 7
+            weekdays: (weekdays | weekdays >> 7) & 0x7f,
+            either_day: !day.starts_with('*') && !weekday.starts_with('*'),
+        })
+    }
+
+    /// Parse schedule given as cron expression, nickname like `:daily`, or calendar spec like
+    /// `(:weekday :mon :hour 7)`
+    pub(crate) fn from_spec(spec: &Val) -> Result<Self> {
+        match spec {
+            Val::String(expr) => Self::parse(expr),
+            Val::Keyword(k)
+                if ["yearly", "monthly", "weekly", "daily", "hourly"].contains(&k.as_str()) =>
+            {
+                Self::parse(&format!("@{}", k.as_str()))
+            }
+            Val::List(fields) => Self::parse(&calendar_expr(fields)?),
+            v => Err(Error::SchedulerError(format!(
+                "schedule should be a cron expression, :hourly, :daily, :weekly, :monthly, \
+                 :yearly or calendar spec - got {v}"
+            ))),
+        }
+    }
+
+    /// First time after `t` matching schedule, in time zone of `t`
+    pub(crate) fn next_after(&self, t: &Zoned) -> Option<Zoned> {
+        let mut dt = t
+            .datetime()
+            .with()
+            .second(0)
+            .subsec_nanosecond(0)
+            .build()
+            .ok()?
+            .checked_add(1.minute())
+            .ok()?;
+        // schedules like "0 0 30 2 *" never match, and leap days can be 8 years apart
+        let last_year = dt.year() + 8;
+        while dt.year() <= last_year {
+            if !has(self.months, dt.month()) {
+                dt = dt
+                    .first_of_month()
+                    .start_of_day()
+                    .checked_add(1.month())
+                    .ok()?;
+            } else if !self.matches_day(dt.date()) {
+                dt = dt
+                    .date()
+                    .tomorrow()
+                    .ok()?
+                    .to_datetime(civil::Time::midnight());
+            } else if !has(self.hours, dt.hour()) {
+                dt = dt
+                    .with()
+                    .minute(0)
+                    .build()
+                    .ok()?
+                    .checked_add(1.hour())
+                    .ok()?;
+            } else if !has(self.minutes, dt.minute()) {
+                dt = dt.checked_add(1.minute()).ok()?;
+            } else {
+                // times skipped by DST transitions run after them, and repeated times run once
+                let next = dt.to_zoned(t.time_zone().clone()).ok()?;
+                if next.timestamp() > t.timestamp() {
+                    return Some(next);
+                }
+                dt = dt.checked_add(1.minute()).ok()?;
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, date: civil::Date) -> bool {
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().to_sunday_zero_offset());
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+impl Clock {
+    /// System clock
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// Virtual clock starting at `now`
+    pub fn virtual_at(now: Zoned) -> Self {
+        Self {
+            virtual_now: Some(Arc::new(watch::Sender::new(now))),
+        }
+    }
+
+    /// Move virtual clock forward by `span`, waking schedulers with jobs due by then. Has no
+    /// effect on the system clock.
+    pub fn advance(&self, span: Span) {
+        if let Some(now) = &self.virtual_now {
+            now.send_modify(|now| {
+                if let Ok(later) = now.checked_add(span) {
+                    *now = later;
+                }
+            });
+        }
+    }
+
+    /// Current time of clock
+    pub fn now(&self) -> Zoned {
+        match &self.virtual_now {
+            None => Zoned::now(),
+            Some(now) => now.borrow().clone(),
+        }
+    }
+
+    /// Sleep until `deadline`, or indefinitely without one. Sleeps on system clock wake after
+    /// [MAX_SLEEP] at the latest.
+    async fn sleep_until(&self, deadline: Option<Zoned>) {
+        match &self.virtual_now {
+            None => {
+                let wait = deadline.map_or(MAX_SLEEP, |deadline| {
+                    let millis =
+                        deadline.timestamp().as_millisecond() - Timestamp::now().as_millisecond();
+                    Duration::from_millis(millis.max(0) as u64).min(MAX_SLEEP)
+                });
+                time::sleep(wait).await
+            }
+            Some(now) => match deadline {
+                Some(deadline) => {
+                    let _ = now
+                        .subscribe()
+                        .wait_for(|now| now.timestamp() >= deadline.timestamp())
+                        .await;
+                }
+                None => std::future::pending().await,
+            },
+        }
+    }
+}
+
+impl Job {
+    /// Create job from options `:spawn FORM` or `:send (SRV MSG)`, and optional `:catch_up BOOL`
+    pub(crate) fn new(name: KeywordId, spec: Val, opts: &[Val], now: &Zoned) -> Result<Self> {
+        let cron = Cron::from_spec(&spec)?;
+        let action = match (
+            kwargs::get(opts, &KeywordId::from("spawn")),
+            kwargs::get(opts, &KeywordId::from("send")),
+        ) {
+            (Some(form), None) => {
+                Program::from_val(form.clone())?;
+                Action::Spawn(form)
+            }
+            (None, Some(Val::List(l))) => match &l[..] {
+                [Val::Keyword(srv), msg] => Action::Send(srv.clone(), msg.clone()),
+                _ => {
+                    return Err(Error::SchedulerError(format!(
+                        ":send should be a list of service name and message - got {}",
+                        Val::List(l)
+                    )))
+                }
+            },
+            _ => {
+                return Err(Error::SchedulerError(
+                    "job should have one of :spawn FORM or :send (SRV MSG)".to_string(),
+                ))
+            }
+        };
+        let catch_up = match kwargs::get(opts, &KeywordId::from("catch_up")) {
+            None => false,
+            Some(Val::Bool(catch_up)) => catch_up,
+            Some(v) => {
+                return Err(Error::SchedulerError(format!(
+                    ":catch_up should be a boolean - got {v}"
+                )))
+            }
+        };
+
+        let job = Self {
+            next_run: cron.next_after(now),
+            name,
+            spec,
+            cron,
+            action,
+            catch_up,
+            last_run: None,
+            runs: 0,
+            missed: 0,
+        };
+        // jobs are saved as source, so they should read back unchanged
+        let record = job.record();
+        let src = pprint::pretty_val(&record, pprint::DEFAULT_WIDTH);
+        if Val::from_expr(&src).ok() != Some(record) {
+            return Err(Error::SchedulerError(format!(
+                "job {} should only contain readable values",
+                job.name
+            )));
+        }
+        Ok(job)
+    }
+
+    /// Restore job from record made by [Job::record]
+    fn from_record(record: &Val, now: &Zoned) -> Result<Self> {
+        let fields = match record {
+            Val::List(fields) => fields,
+            v => {
+                return Err(Error::SchedulerError(format!(
+                    "job record should be a list - got {v}"
+                )))
+            }
+        };
+        let get = |key: &str| kwargs::get(fields, &KeywordId::from(key));
+        let time = |key: &str| match get(key) {
+            Some(Val::String(t)) => t
+                .parse::<Zoned>()
+                .map(Some)
+                .map_err(|e| Error::SchedulerError(format!("invalid :{key} in job record - {e}"))),
+            _ => Ok(None),
+        };
+        let count = |key: &str| match get(key) {
+            Some(Val::Int(n)) if n >= 0 => n as usize,
+            _ => 0,
+        };
+
+        let (name, spec) = match (get("name"), get("schedule")) {
+            (Some(Val::Keyword(name)), Some(spec)) => (name, spec),
+            _ => {
+                return Err(Error::SchedulerError(format!(
+                    "job record should have :name and :schedule - got {record}"
+                )))
+            }
+        };
+        let mut job = Self::new(name, spec, fields, now)?;
+        if let Some(next_run) = time("next_run")? {
+            job.next_run = Some(next_run);
+        }
+        job.last_run = time("last_run")?;
+        job.runs = count("runs");
+        job.missed = count("missed");
+        Ok(job)
+    }
+
+    /// Job info for `ls_jobs`
+    pub(crate) fn info(&self) -> Val {
+        self.to_val(|t| Val::Time(lyric::Time(t.clone())))
+    }
+
+    /// Job record saved across restarts
+    fn record(&self) -> Val {
+        self.to_val(|t| Val::String(t.to_string()))
+    }
+
+    /// Job as plist, with times converted by `time`
+    fn to_val(&self, time: fn(&Zoned) -> Val) -> Val {
+        let action = match &self.action {
+            Action::Spawn(form) => ("spawn", form.clone()),
+            Action::Send(srv, msg) => (
+                "send",
+                Val::List(vec![Val::Keyword(srv.clone()), msg.clone()]),
+            ),
+        };
+        let fields = [
+            ("name", Val::Keyword(self.name.clone())),
+            ("schedule", self.spec.clone()),
+            action,
+            ("catch_up", Val::Bool(self.catch_up)),
+            ("next_run", self.next_run.as_ref().map_or(Val::Nil, time)),
+            ("last_run", self.last_run.as_ref().map_or(Val::Nil, time)),
+            ("runs", Val::Int(self.runs as i32)),
+            ("missed", Val::Int(self.missed as i32)),
+        ];
+        Val::List(
+            fields
+                .into_iter()
+                .flat_map(|(key, val)| [Val::keyword(key), val])
+                .collect(),
+        )
+    }
+
+    /// Update job for time `now`, returning whether it should run. Scheduled times that passed
+    /// more than [GRACE] ago are missed, though jobs that catch up run once for them.
+    fn due(&mut self, now: &Zoned) -> bool {
+        let mut scheduled = 0;
+        let mut latest = None;
+        while let Some(t) = self.next_run.take_if(|t| t.timestamp() <= now.timestamp()) {
+            scheduled += 1;
+            self.next_run = self.cron.next_after(&t);
+            latest = Some(t);
+        }
+        let Some(latest) = latest else {
+            return false;
+        };
+
+        let late = now.timestamp().as_millisecond() - latest.timestamp().as_millisecond();
+        let run = late <= GRACE.as_millis() as i64 || self.catch_up;
+        let missed = scheduled - usize::from(run);
+        if missed > 0 {
+            warn!("job {} missed {missed} scheduled runs", self.name);
+            self.missed += missed;
+        }
+        if run {
+            self.runs += 1;
+            self.last_run = Some(now.clone());
+        }
+        run
+    }
+}
+
+impl Jobs {
+    /// Add job, replacing any job with the same name
+    pub(crate) fn add(&mut self, job: Job) {
+        match self.0.iter_mut().find(|j| j.name == job.name) {
+            Some(existing) => *existing = job,
+            None => self.0.push(job),
+        }
+    }
+
+    /// Remove job named `name`, returning whether it existed
+    pub(crate) fn remove(&mut self, name: &KeywordId) -> bool {
+        let len = self.0.len();
+        self.0.retain(|j| &j.name != name);
+        self.0.len() != len
+    }
+
+    /// Time of next run of any job
+    pub(crate) fn next_run(&self) -> Option<Zoned> {
+        self.0
+            .iter()
+            .filter_map(|j| j.next_run.as_ref())
+            .min_by_key(|t| t.timestamp())
+            .cloned()
+    }
+
+    /// Update jobs for time `now`, returning names and actions of jobs that should run
+    pub(crate) fn due(&mut self, now: &Zoned) -> Vec<(KeywordId, Action)> {
+        self.0
+            .iter_mut()
+            .filter_map(|j| j.due(now).then(|| (j.name.clone(), j.action.clone())))
+            .collect()
+    }
+
+    /// Info of all jobs, for `ls_jobs`
+    pub(crate) fn info(&self) -> Val {
+        Val::List(self.0.iter().map(Job::info).collect())
+    }
+
+    /// Load jobs saved to `path`, if it exists
+    async fn load(path: &Path, now: &Zoned) -> Result<Self> {
+        let src = match tokio::fs::read_to_string(path).await {
+            Ok(src) => src,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(Error::IOError(format!(
+                    "Failed to read jobs from {} - {e}",
+                    path.display()
+                )))
+            }
+        };
+        match Val::from_expr(&src)? {
+            Val::Nil => Ok(Self::default()),
+            Val::List(records) => records
+                .iter()
+                .map(|r| Job::from_record(r, now))
+                .collect::<Result<Vec<_>>>()
+                .map(Jobs),
+            v => Err(Error::SchedulerError(format!(
+                "jobs file {} should contain a list of jobs - got {v}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Save jobs to `path`, replacing it once written
+    async fn save(&self, path: &Path) -> Result<()> {
+        let records = Val::List(self.0.iter().map(Job::record).collect());
+        let src = pprint::pretty_val(&records, pprint::DEFAULT_WIDTH);
+
+        let io_err = |e: std::io::Error| {
+            Error::IOError(format!("Failed to save jobs to {} - {e}", path.display()))
+        };
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(io_err)?;
+        }
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, src).await.map_err(io_err)?;
+        tokio::fs::rename(&tmp, path).await.map_err(io_err)
+    }
+}
+
+impl Scheduler {
+    pub(crate) fn new(
+        pid: ProcessId,
+        kernel: KernelHandle,
+        registry: Registry,
+        mailbox: MailboxHandle,
+        clock: Clock,
+    ) -> Self {
+        Self {
+            pid,
+            kernel,
+            registry,
+            mailbox,
+            clock,
+            path: None,
+            jobs: Jobs::default(),
+        }
+    }
+
+    /// Save jobs to file at `path`, restoring jobs saved there when started
+    pub(crate) fn path(mut self, path: PathBuf) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    /// Run jobs as they're due, and handle requests made with `call`. Saved jobs that can't be
+    /// restored are set aside, starting with no jobs instead. Returns error if the mailbox of the
+    /// scheduler is gone.
+    pub(crate) async fn run(mut self) -> Result<()> {
+        if let Some(path) = self.path.clone() {
+            match Jobs::load(&path, &self.clock.now()).await {
+                Ok(jobs) => {
+                    self.jobs = jobs;
+                    info!(
+                        "scheduler {} restored {} jobs from {}",
+                        self.pid,
+                        self.jobs.0.len(),
+                        path.display()
+                    );
+                }
+                Err(e) => {
+                    warn!("scheduler {} starting without jobs - {e}", self.pid);
+                    self.set_aside(&path).await;
+                }
+            }
+        }
+
+        loop {
+            self.run_due().await;
+            let next_run = self.jobs.next_run();
+            let msg = self
+                .mailbox
+                .poll_until(None, self.clock.sleep_until(next_run))
+                .await?;
+            if let Some(msg) = msg {
+                self.handle_request(msg.contents).await?;
+            }
+        }
+    }
+
+    /// Run jobs that are due
+    async fn run_due(&mut self) {
+        let now = self.clock.now();
+        if self
+            .jobs
+            .next_run()
+            .is_none_or(|t| t.timestamp() > now.timestamp())
+        {
+            return;
+        }
+
+        for (name, action) in self.jobs.due(&now) {
+            if let Err(e) = self.run_action(action).await {
+                warn!("scheduler {} failed to run job {name} - {e}", self.pid);
+            }
+        }
+        if let Err(e) = self.save().await {
+            warn!("scheduler {} {e}", self.pid);
+        }
+    }
+
+    async fn run_action(&self, action: Action) -> Result<()> {
+        match action {
+            Action::Spawn(form) => {
+                let hdl = self.kernel.spawn_prog(Program::from_val(form)?).await?;
+                info!("scheduler {} spawned job {}", self.pid, hdl.id());
+            }
+            Action::Send(srv, msg) => {
+                let entry = self.registry.lookup(srv.clone()).await?.ok_or_else(|| {
+                    Error::SchedulerError(format!("no service registered as {srv}"))
+                })?;
+                // sent as request like `call`, though its response is discarded
+                let req = Val::List(vec![Val::Ref(Ref::new()), self.pid.into_val(), msg]);
+                self.kernel.send_message(self.pid, entry.pid(), req).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Respond to request `(REF PID MSG)` sent by `call`. Responses `(REF RESP)` to requests sent
+    /// by jobs are discarded.
+    async fn handle_request(&mut self, req: Val) -> Result<()> {
+        let (r, src, msg) = match &req {
+            Val::List(l) if matches!(&l[..], [Val::Ref(_), _]) => return Ok(()),
+            Val::List(l) if l.len() == 3 => match ProcessId::from_val(l[1].clone()) {
+                Ok(src) => (l[0].clone(), src, l[2].clone()),
+                Err(_) => {
+                    warn!("scheduler {} ignored unexpected message {req}", self.pid);
+                    return Ok(());
+                }
+            },
+            _ => {
+                warn!("scheduler {} ignored unexpected message {req}", self.pid);
+                return Ok(());
+            }
+        };
+
+        let resp = self
+            .handle(msg)
+            .await
+            .unwrap_or_else(|e| Val::Error(lyric::Error::Runtime(format!("{e}"))));
+        self.kernel
+            .send_message(self.pid, src, Val::List(vec![r, resp]))
+            .await
+    }
+
+    /// Handle `(:add_job NAME SCHEDULE OPTS...)`, `(:ls_jobs)` and `(:rm_job NAME)` requests
+    async fn handle(&mut self, msg: Val) -> Result<Val> {
+        let unrecognized = || Error::SchedulerError("Unrecognized message".to_string());
+        let Val::List(l) = msg else {
+            return Err(unrecognized());
+        };
+        match &l[..] {
+            [Val::Keyword(req), Val::Keyword(name), spec, opts @ ..]
+                if req.as_str() == "add_job" =>
+            {
+                let job = Job::new(name.clone(), spec.clone(), opts, &self.clock.now())?;
+                let info = job.info();
+                info!("scheduler {} added job {name}", self.pid);
+                self.jobs.add(job);
+                self.save().await?;
+                Ok(info)
+            }
+            [Val::Keyword(req)] if req.as_str() == "ls_jobs" => Ok(self.jobs.info()),
+            [Val::Keyword(req), Val::Keyword(name)] if req.as_str() == "rm_job" => {
+                let removed = self.jobs.remove(name);
+                if removed {
+                    self.save().await?;
+                }
+                Ok(Val::Bool(removed))
+            }
+            _ => Err(unrecognized()),
+        }
+    }
+
+    /// Move jobs file at `path` that couldn't be restored aside, so it isn't replaced when jobs
+    /// are saved. Jobs aren't saved if it can't be moved.
+    async fn set_aside(&mut self, path: &Path) {
+        let mut bad = path.as_os_str().to_owned();
+        bad.push(".bad");
+        match tokio::fs::rename(path, &bad).await {
+            Ok(()) => warn!(
+                "scheduler {} moved jobs file {} to {}",
+                self.pid,
+                path.display(),
+                PathBuf::from(bad).display()
+            ),
+            Err(e) => {
+                warn!(
+                    "scheduler {} won't save jobs, failed to move jobs file {} aside - {e}",
+                    self.pid,
+                    path.display()
+                );
+                self.path = None;
+            }
+        }
+    }
+
+    /// Save jobs to file, if any
+    async fn save(&self) -> Result<()> {
+        match &self.path {
+            Some(path) => self.jobs.save(path).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether bit `n` is set in `bits`
+fn has(bits: u64, n: i8) -> bool {
+    bits & (1 << n) != 0
+}
+
+/// Parse cron field of comma separated values, ranges like `1-5` or `*`, with optional steps like
+/// `*/15`, into a bitset. Values may be given by `names`, counting from `min`.
+fn parse_field(field: &str, min: u8, max: u8, names: &[&str]) -> Result<u64> {
+    let invalid = || Error::SchedulerError(format!("invalid cron field \"{field}\""));
+    let value = |s: &str| {
+        let n = match names.iter().position(|name| name.eq_ignore_ascii_case(s)) {
+            Some(idx) => idx as u8 + min,
+            None => s.parse::<u8>().map_err(|_| invalid())?,
+        };
+        if (min..=max).contains(&n) {
+            Ok(n)
+        } else {
+            Err(invalid())
+        }
+    };
+
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // start with step runs until max, like `5/15`
+            None if part.contains('/') => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if step == 0 || start > end {
+            return Err(invalid());
+        }
+        for n in (start..=end).step_by(step) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+/// Cron expression for calendar spec like `(:weekday :mon :hour 7)`. Fields smaller than the
+/// largest given field default to their first value, and larger fields match any value.
+fn calendar_expr(fields: &[Val]) -> Result<String> {
+    const KEYS: &[&str] = &["minute", "hour", "day", "month", "weekday"];
+    for key in fields.iter().step_by(2) {
+        if !matches!(key, Val::Keyword(k) if KEYS.contains(&k.as_str())) {
+            return Err(Error::SchedulerError(format!(
+                "calendar spec fields should be :minute, :hour, :day, :month or :weekday - got {key}"
+            )));
+        }
+    }
+    let field = |key: &str| {
+        kwargs::get(fields, &KeywordId::from(key))
+            .map(|v| calendar_field(&v))
+            .transpose()
+    };
+    let (minute, hour, day, month, weekday) = (
+        field("minute")?,
+        field("hour")?,
+        field("day")?,
+        field("month")?,
+        field("weekday")?,
+    );
+    if [&minute, &hour, &day, &month, &weekday]
+        .iter()
+        .all(|f| f.is_none())
+    {
+        return Err(Error::SchedulerError(
+            "calendar spec should have at least one field".to_string(),
+        ));
+    }
+
+    let has_date = day.is_some() || month.is_some() || weekday.is_some();
+    let has_hour = has_date || hour.is_some();
+    let or = |field: Option<String>, default: &str| field.unwrap_or_else(|| default.to_string());
+    Ok(format!(
+        "{} {} {} {} {}",
+        or(minute, if has_hour { "0" } else { "*" }),
+        or(hour, if has_date { "0" } else { "*" }),
+        or(
+            day,
+            if month.is_some() && weekday.is_none() {
+                "1"
+            } else {
+                "*"
+            }
+        ),
+        or(month, "*"),
+        or(weekday, "*"),
+    ))
+}
+
+/// Cron field for calendar spec value - an integer, name like `:mon`, or list of them
+fn calendar_field(val: &Val) -> Result<String> {
+    match val {
+        Val::Int(n) => Ok(n.to_string()),
+        Val::Keyword(k) => Ok(k.as_str().to_string()),
+        Val::List(l) if !l.is_empty() => Ok(l
+            .iter()
+            .map(calendar_field)
+            .collect::<Result<Vec<_>>>()?
+            .join(",")),
+        v => Err(Error::SchedulerError(format!(
+            "calendar spec values should be integers, names or lists of them - got {v}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rt::kernel;
+    use crate::rt::mailbox::Mailbox;
+    use crate::rt::program::{proc_env, Extern};
+    use crate::rt::pubsub::PubSub;
+    use crate::rt::registry::Registration;
+    use crate::ProcessResult;
+    use lyric::SymbolId;
+
+    fn utc(t: &str) -> Zoned {
+        format!("{t}[UTC]").parse().unwrap()
+    }
+
+    fn next(expr: &str, t: &str) -> Option<Zoned> {
+        Cron::parse(expr).unwrap().next_after(&utc(t))
+    }
+
+    fn daily_job(now: &Zoned, catch_up: bool) -> Job {
+        let opts = Val::from_expr(&format!("(:spawn '(+ 1 1) :catch_up {catch_up})")).unwrap();
+        Job::new(
+            KeywordId::from("morning"),
+            Val::String("0 7 * * *".to_string()),
+            &opts.to_list().unwrap(),
+            now,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn cron_next_after() {
+        assert_eq!(
+            next("30 7 * * mon-fri", "2024-07-13T08:00"),
+            Some(utc("2024-07-15T07:30")),
+            "Weekend should be skipped"
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2024-07-13T10:07:12"),
+            Some(utc("2024-07-13T10:15"))
+        );
+        assert_eq!(
+            next("0 9 * jan,jul *", "2024-07-31T10:00"),
+            Some(utc("2025-01-01T09:00"))
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2024-03-01T00:00"),
+            Some(utc("2028-02-29T00:00"))
+        );
+        assert_eq!(next("0 0 30 2 *", "2024-03-01T00:00"), None);
+        assert_eq!(
+            next("0 0 1 * mon", "2024-07-29T00:00"),
+            Some(utc("2024-08-01T00:00")),
+            "Days matching either day of month or weekday should run"
+        );
+        assert_eq!(
+            next("0 0 * * 7", "2024-07-13T00:00"),
+            Some(utc("2024-07-14T00:00")),
+            "7 should be sunday"
+        );
+        assert_eq!(
+            next("@hourly", "2024-07-13T10:00"),
+            Some(utc("2024-07-13T11:00"))
+        );
+    }
+
+    #[test]
+    fn cron_invalid() {
+        for expr in [
+            "61 * * * *",
+            "* * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "0 0 * * fun",
+        ] {
+            assert!(Cron::parse(expr).is_err(), "{expr} should be invalid");
+        }
+    }
+
+    #[test]
+    fn calendar_spec() {
+        for (spec, expr) in [
+            ("(:weekday :mon :hour 7)", "0 7 * * mon"),
+            ("(:minute 15)", "15 * * * *"),
+            ("(:month 1)", "0 0 1 1 *"),
+            ("(:day (1 15) :hour 9 :minute 30)", "30 9 1,15 * *"),
+            (":daily", "@daily"),
+        ] {
+            assert_eq!(
+                Cron::from_spec(&Val::from_expr(spec).unwrap()).unwrap(),
+                Cron::parse(expr).unwrap(),
+                "{spec} should be {expr}"
+            );
+        }
+        assert!(Cron::from_spec(&Val::from_expr("(:hours 7)").unwrap()).is_err());
+        assert!(Cron::from_spec(&Val::from_expr(":fortnightly").unwrap()).is_err());
+    }
+
+    #[test]
+    fn missed_runs() {
+        let start = utc("2024-07-14T06:59:30");
+
+        let mut job = daily_job(&start, false);
+        assert!(
+            job.due(&utc("2024-07-14T07:00:30")),
+            "Run should be on time"
+        );
+        assert_eq!((job.runs, job.missed), (1, 0));
+
+        // runtime stopped for 3 days
+        assert!(!job.due(&utc("2024-07-17T12:00")));
+        assert_eq!((job.runs, job.missed), (1, 3));
+        assert_eq!(job.next_run, Some(utc("2024-07-18T07:00")));
+
+        let mut job = daily_job(&start, true);
+        assert!(
+            job.due(&utc("2024-07-17T12:00")),
+            "Missed runs should catch up"
+        );
+        assert_eq!((job.runs, job.missed), (1, 4 - 1));
+        assert_eq!(job.last_run, Some(utc("2024-07-17T12:00")));
+    }
+
+    #[test]
+    fn job_records() {
+        let now = utc("2024-07-14T06:59:30");
+        let opts = Val::from_expr(r#"(:spawn '(exec "say" "\"good morning\""))"#).unwrap();
+        let mut job = Job::new(
+            KeywordId::from("morning"),
+            Val::from_expr("(:hour 7)").unwrap(),
+            &opts.to_list().unwrap(),
+            &now,
+        )
+        .unwrap();
+        job.due(&utc("2024-07-14T07:00"));
+
+        let src = pprint::pretty_val(&job.record(), pprint::DEFAULT_WIDTH);
+        let restored = Job::from_record(&Val::from_expr(&src).unwrap(), &now).unwrap();
+        assert_eq!(restored.info(), job.info());
+
+        let opts = Val::List(vec![
+            Val::keyword("spawn"),
+            Val::Extern(Extern::ProcessId(0.into())),
+        ]);
+        assert!(
+            Job::new(KeywordId::from("pid"), Val::keyword("daily"), &[opts], &now).is_err(),
+            "Jobs should only be made of readable values"
+        );
+        let opts = Val::List(vec![
+            Val::keyword("send"),
+            Val::List(vec![Val::keyword("srv"), Val::keyword("not readable")]),
+        ]);
+        assert!(
+            Job::new(KeywordId::from("kw"), Val::keyword("daily"), &[opts], &now).is_err(),
+            "Jobs should only be made of values that read back unchanged"
+        );
+    }
+
+    #[test]
+    fn job_with_record() {
+        let now = utc("2024-07-14T06:59:30");
+        let opts = Val::from_expr("(:send (:srv (:update #(point :x 1 :y (2 3)))))").unwrap();
+        let job = Job::new(
+            KeywordId::from("record"),
+            Val::keyword("daily"),
+            &opts.to_list().unwrap(),
+            &now,
+        )
+        .unwrap();
+
+        let src = pprint::pretty_val(&job.record(), pprint::DEFAULT_WIDTH);
+        let restored = Job::from_record(&Val::from_expr(&src).unwrap(), &now).unwrap();
+        assert_eq!(restored.record(), job.record());
+    }
+
+    #[tokio::test]
+    async fn run_on_virtual_clock() {
+        let k = kernel::start();
+        // notified once collector receives first message
+        let observer = k
+            .spawn_prog(Program::from_expr("(recv)").unwrap())
+            .await
+            .unwrap();
+        let mut env = proc_env();
+        env.define(SymbolId::from("observer"), observer.id().into_val());
+        let collector = Program::from_expr(
+            "(begin
+                (def first (get (recv) 2))
+                (send observer :received)
+                (list first (get (recv) 2)))",
+        )
+        .unwrap()
+        .env(env);
+        let collector = k.spawn_prog(collector).await.unwrap();
+        let registry = Registry::spawn(PubSub::spawn());
+        registry
+            .register(
+                Registration::new(KeywordId::from("collector")),
+                collector.clone(),
+            )
+            .await
+            .unwrap();
+
+        let start = utc("2024-07-14T06:59:30");
+        let clock = Clock::virtual_at(start.clone());
+        let pid = ProcessId::from(1000);
+        let mut scheduler = Scheduler::new(pid, k, registry, Mailbox::spawn(pid), clock.clone());
+        let opts = Val::from_expr("(:send (:collector :morning))").unwrap();
+        scheduler.jobs.add(
+            Job::new(
+                KeywordId::from("morning"),
+                Val::String("0 7 * * *".to_string()),
+                &opts.to_list().unwrap(),
+                &start,
+            )
+            .unwrap(),
+        );
+        tokio::spawn(scheduler.run());
+
+        clock.advance(1.minute());
+        time::timeout(Duration::from_secs(1), observer.join())
+            .await
+            .expect("Job should run once clock reaches first deadline")
+            .unwrap();
+        clock.advance(1.day());
+
+        let exit = time::timeout(Duration::from_secs(1), collector.join())
+            .await
+            .expect("Jobs should run as clock advances")
+            .unwrap();
+        assert_eq!(
+            exit.status.unwrap(),
+            ProcessResult::Done(Val::from_expr("(:morning :morning)").unwrap())
+        );
+    }
+}
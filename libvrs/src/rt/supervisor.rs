@@ -0,0 +1,229 @@
+//! Supervisors for restarting child processes
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use lyric::{FromVal, KeywordId};
+use tracing::{info, warn};
+
+use super::kernel::KernelHandle;
+use super::mailbox::MailboxHandle;
+use super::program::{Pattern, Program, Val};
+use super::registry::Registry;
+use super::{Error, ProcessHandle, ProcessId, Result};
+
+/// When a child is restarted after exiting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Restart {
+    /// Always restarted
+    Permanent,
+    /// Restarted only after abnormal exits
+    Transient,
+    /// Never restarted
+    Temporary,
+}
+
+/// Which children are restarted alongside a restarted child
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Strategy {
+    /// Only the exited child
+    OneForOne,
+    /// All children
+    OneForAll,
+    /// The exited child and children started after it
+    RestForOne,
+}
+
+/// Specification for starting a supervised child
+#[derive(Debug, Clone)]
+pub(crate) struct ChildSpec {
+    pub(crate) prog: Program,
+    pub(crate) name: Option<KeywordId>,
+    pub(crate) restart: Restart,
+}
+
+/// Supervisor running within a process, restarting its children as they exit
+pub(crate) struct Supervisor {
+    pid: ProcessId,
+    kernel: KernelHandle,
+    registry: Registry,
+    mailbox: MailboxHandle,
+    strategy: Strategy,
+    intensity: usize,
+    period: Duration,
+    children: Vec<Child>,
+    /// Children stopped by supervisor, whose exit messages are ignored
+    stopped: HashSet<ProcessId>,
+    restarts: VecDeque<Instant>,
+}
+
+/// A supervised child, and its handle while running
+struct Child {
+    spec: ChildSpec,
+    hdl: Option<ProcessHandle>,
+}
+
+impl Supervisor {
+    pub(crate) fn new(
+        pid: ProcessId,
+        kernel: KernelHandle,
+        registry: Registry,
+        mailbox: MailboxHandle,
+        specs: Vec<ChildSpec>,
+    ) -> Self {
+        Self {
+            pid,
+            kernel,
+            registry,
+            mailbox,
+            strategy: Strategy::OneForOne,
+            intensity: 3,
+            period: Duration::from_secs(5),
+            children: specs
+                .into_iter()
+                .map(|spec| Child { spec, hdl: None })
+                .collect(),
+            stopped: HashSet::new(),
+            restarts: VecDeque::new(),
+        }
+    }
+
+    /// Set restart strategy
+    pub(crate) fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set maximum number of restarts allowed within `period`
+    pub(crate) fn intensity(mut self, intensity: usize, period: Duration) -> Self {
+        self.intensity = intensity;
+        self.period = period;
+        self
+    }
+
+    /// Start children and supervise them. Returns error when restart intensity is exceeded,
+    /// or when a linked process that isn't a child exits abnormally.
+    pub(crate) async fn run(mut self) -> Result<()> {
+        self.kernel.trap_exit(self.pid, true).await?;
+        for idx in 0..self.children.len() {
+            self.start_child(idx).await?;
+        }
+
+        let exits = Pattern::from_val(Val::from_expr("(:exit _ _)")?);
+        loop {
+            let msg = self.mailbox.poll(Some(exits.clone())).await?;
+            let (pid, reason) = match msg.contents {
+                Val::List(l) if l.len() == 3 => (ProcessId::from_val(l[1].clone())?, l[2].clone()),
+                _ => continue,
+            };
+            if self.stopped.remove(&pid) {
+                continue;
+            }
+
+            let normal = reason == Val::keyword("normal");
+            let idx = match self.children.iter().position(|c| c.pid() == Some(pid)) {
+                Some(idx) => idx,
+                None if normal => continue,
+                None => {
+                    self.stop_all().await;
+                    return Err(Error::SupervisorError(format!(
+                        "linked process {pid} exited - {reason}"
+                    )));
+                }
+            };
+            self.children[idx].hdl = None;
+            info!("supervisor {} child {pid} exited - {reason}", self.pid);
+
+            let restart = match self.children[idx].spec.restart {
+                Restart::Permanent => true,
+                Restart::Transient => !normal,
+                Restart::Temporary => false,
+            };
+            if restart {
+                self.restart(idx).await?;
+            }
+        }
+    }
+
+    /// Restart exited child at `idx`, along with siblings dictated by strategy
+    async fn restart(&mut self, idx: usize) -> Result<()> {
+        let now = Instant::now();
+        while self
+            .restarts
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > self.period)
+        {
+            self.restarts.pop_front();
+        }
+        self.restarts.push_back(now);
+        if self.restarts.len() > self.intensity {
+            self.stop_all().await;
+            return Err(Error::SupervisorError(format!(
+                "exceeded {} restarts in {}s",
+                self.intensity,
+                self.period.as_secs()
+            )));
+        }
+
+        let group = match self.strategy {
+            Strategy::OneForOne => idx..idx + 1,
+            Strategy::OneForAll => 0..self.children.len(),
+            Strategy::RestForOne => idx..self.children.len(),
+        };
+
+        let mut restarting = vec![idx];
+        for i in group.rev().filter(|i| *i != idx) {
+            if self.stop_child(i).await && self.children[i].spec.restart != Restart::Temporary {
+                restarting.push(i);
+            }
+        }
+        restarting.sort();
+
+        for i in restarting {
+            self.start_child(i).await?;
+        }
+        Ok(())
+    }
+
+    /// Start child at `idx`, registering it under its name if any
+    async fn start_child(&mut self, idx: usize) -> Result<()> {
+        let child = &mut self.children[idx];
+        let hdl = self
+            .kernel
+            .spawn_link(child.spec.prog.clone(), self.pid)
+            .await?;
+        info!("supervisor {} started child {}", self.pid, hdl.id());
+
+        if let Some(name) = &child.spec.name {
+            self.registry.ensure(name.clone(), hdl.clone()).await?;
+        }
+        child.hdl = Some(hdl);
+        Ok(())
+    }
+
+    /// Stop child at `idx` and wait for it to exit. Returns whether child was running.
+    async fn stop_child(&mut self, idx: usize) -> bool {
+        let hdl = match self.children[idx].hdl.take() {
+            Some(hdl) => hdl,
+            None => return false,
+        };
+        self.stopped.insert(hdl.id());
+        hdl.kill().await;
+        if let Err(e) = hdl.join().await {
+            warn!("supervisor {} failed to join stopped child - {e}", self.pid);
+        }
+        true
+    }
+
+    /// Stop all children in reverse start order
+    async fn stop_all(&mut self) {
+        for idx in (0..self.children.len()).rev() {
+            self.stop_child(idx).await;
+        }
+    }
+}
+
+impl Child {
+    fn pid(&self) -> Option<ProcessId> {
+        self.hdl.as_ref().map(|hdl| hdl.id())
+    }
+}
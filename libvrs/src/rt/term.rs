@@ -58,6 +58,16 @@ impl TermHandle {
             .map_err(|e| Error::NoMessageReceiver(format!("send_response failed - {e}")))?;
         Ok(())
     }
+
+    /// Whether terminal connection has closed
+    pub(crate) fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Wait for terminal connection to close
+    pub(crate) async fn closed(&self) {
+        self.tx.closed().await
+    }
 }
 
 impl Term {
@@ -74,8 +84,9 @@ impl Term {
             active_subs: Default::default(),
         };
         tokio::spawn(async move {
-            if let Err(e) = t.run().await {
-                error!("term error - {e}");
+            match t.run().await {
+                Ok(()) | Err(Error::ConnectionClosed) => info!("term disconnected"),
+                Err(e) => error!("term error - {e}"),
             }
         });
         TermHandle { tx }
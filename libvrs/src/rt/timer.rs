@@ -0,0 +1,281 @@
+//! Timers for delayed and periodic messages
+//! Timers are driven by the kernel task, so pending timers don't each need a task of their own.
+use std::collections::HashMap;
+use std::time::Duration;
+
+use lyric::Ref;
+use tokio::time::Instant;
+
+use super::program::Val;
+use super::ProcessId;
+
+/// A timer sending message `msg` from `src` to `dst` when it expires
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Timer {
+    pub(crate) src: ProcessId,
+    pub(crate) dst: ProcessId,
+    pub(crate) msg: Val,
+    /// Period of repeating timer
+    pub(crate) interval: Option<Duration>,
+}
+
+/// Number of slots in timer wheel
+const SLOTS: usize = 256;
+
+/// Resolution of timer wheel
+const TICK: Duration = Duration::from_millis(1);
+
+/// Pending timers, kept in a hashed timer wheel. Each slot holds timers due on ticks congruent
+/// to it, so starting and cancelling timers is constant time, and timers due in later rounds of
+/// the wheel stay in their slot until the wheel comes around to them.
+#[derive(Debug)]
+pub(crate) struct Timers {
+    /// Instant of tick 0
+    origin: Instant,
+    /// Ids of timers by slot of their tick. Ids of cancelled timers are discarded lazily.
+    slots: Vec<Vec<u64>>,
+    /// Earliest tick that may hold timers yet to expire
+    tick: u64,
+    pending: HashMap<u64, Entry>,
+    ids: HashMap<Ref, u64>,
+    next_id: u64,
+}
+
+/// A pending timer
+#[derive(Debug)]
+struct Entry {
+    tref: Ref,
+    deadline: Instant,
+    timer: Timer,
+}
+
+impl Default for Timers {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+            slots: vec![vec![]; SLOTS],
+            tick: 0,
+            pending: HashMap::new(),
+            ids: HashMap::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl Timers {
+    /// Start timer identified by `tref` that expires after `after`
+    pub(crate) fn start(&mut self, tref: Ref, after: Duration, timer: Timer) {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        let deadline = Instant::now() + after;
+        self.schedule(id, deadline);
+        self.ids.insert(tref.clone(), id);
+        self.pending.insert(
+            id,
+            Entry {
+                tref,
+                deadline,
+                timer,
+            },
+        );
+    }
+
+    /// Cancel timer, returning time remaining if it was pending
+    pub(crate) fn cancel(&mut self, tref: &Ref) -> Option<Duration> {
+        let id = self.ids.remove(tref)?;
+        let entry = self.pending.remove(&id)?;
+        Some(entry.deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Timer, if it is pending
+    pub(crate) fn get(&self, tref: &Ref) -> Option<&Timer> {
+        Some(&self.pending.get(self.ids.get(tref)?)?.timer)
+    }
+
+    /// Time remaining until timer expires, if it is pending
+    pub(crate) fn remaining(&self, tref: &Ref) -> Option<Duration> {
+        let entry = self.pending.get(self.ids.get(tref)?)?;
+        Some(entry.deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Instant to next check for expired timers, if any are pending. This is the deadline of the
+    /// next timer to expire, or the end of the next occupied tick if its timers are all due in
+    /// later rounds of the wheel.
+    pub(crate) fn next_deadline(&mut self) -> Option<Instant> {
+        for tick in self.tick..self.tick + SLOTS as u64 {
+            let slot = &mut self.slots[tick as usize % SLOTS];
+            slot.retain(|id| self.pending.contains_key(id));
+            let wake = slot
+                .iter()
+                .map(|id| {
+                    let deadline = self.pending[id].deadline;
+                    match tick_of(self.origin, deadline) <= tick {
+                        true => deadline,
+                        false => instant_of(self.origin, tick + 1),
+                    }
+                })
+                .min();
+            if wake.is_some() {
+                return wake;
+            }
+        }
+        None
+    }
+
+    /// Remove timers expired by `now`, rescheduling repeating timers
+    pub(crate) fn expire(&mut self, now: Instant) -> Vec<(Ref, Timer)> {
+        let now_tick = tick_of(self.origin, now);
+        let mut fired = vec![];
+        for tick in self.tick..=now_tick.min(self.tick + SLOTS as u64 - 1) {
+            let slot = &mut self.slots[tick as usize % SLOTS];
+            slot.retain(|id| match self.pending.get(id) {
+                Some(entry) if entry.deadline <= now => {
+                    fired.push(*id);
+                    false
+                }
+                Some(_) => true,
+                None => false,
+            });
+        }
+        self.tick = self.tick.max(now_tick);
+        fired.sort_by_key(|id| (self.pending[id].deadline, *id));
+
+        let mut expired = vec![];
+        for id in fired {
+            let entry = self
+                .pending
+                .get_mut(&id)
+                .expect("fired timer should be pending");
+            expired.push((entry.tref.clone(), entry.timer.clone()));
+
+            match entry.timer.interval {
+                Some(interval) => {
+                    // skip missed periods instead of firing in bursts
+                    entry.deadline = (entry.deadline + interval).max(now + interval);
+                    let deadline = entry.deadline;
+                    self.schedule(id, deadline);
+                }
+                None => {
+                    let entry = self.pending.remove(&id).unwrap();
+                    self.ids.remove(&entry.tref);
+                }
+            }
+        }
+        expired
+    }
+
+    /// Add timer to slot of its deadline, or of the earliest tick not yet expired if it's past
+    fn schedule(&mut self, id: u64, deadline: Instant) {
+        let tick = tick_of(self.origin, deadline).max(self.tick);
+        self.slots[tick as usize % SLOTS].push(id);
+    }
+}
+
+/// Tick of timer wheel that `instant` falls in
+fn tick_of(origin: Instant, instant: Instant) -> u64 {
+    (instant.saturating_duration_since(origin).as_nanos() / TICK.as_nanos()) as u64
+}
+
+/// Instant that tick of timer wheel starts at
+fn instant_of(origin: Instant, tick: u64) -> Instant {
+    origin + Duration::from_nanos((TICK.as_nanos() as u64).saturating_mul(tick))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timer(msg: &str, interval: Option<Duration>) -> Timer {
+        Timer {
+            src: 0.into(),
+            dst: 1.into(),
+            msg: Val::keyword(msg),
+            interval,
+        }
+    }
+
+    #[test]
+    fn expire_in_deadline_order() {
+        let mut timers = Timers::default();
+        let (a, b, c) = (Ref::new(), Ref::new(), Ref::new());
+        timers.start(a.clone(), Duration::from_millis(30), timer("a", None));
+        timers.start(b.clone(), Duration::from_millis(10), timer("b", None));
+        timers.start(c.clone(), Duration::from_secs(60), timer("c", None));
+
+        let expired = timers.expire(Instant::now() + Duration::from_millis(50));
+        assert_eq!(
+            expired,
+            vec![(b.clone(), timer("b", None)), (a.clone(), timer("a", None))]
+        );
+        assert_eq!(timers.remaining(&a), None, "Expired timers are removed");
+        assert!(timers.remaining(&c).is_some());
+    }
+
+    #[test]
+    fn later_round() {
+        let mut timers = Timers::default();
+        let (a, b) = (Ref::new(), Ref::new());
+        let after = TICK * SLOTS as u32 + Duration::from_millis(5);
+        timers.start(a.clone(), after, timer("a", None));
+        timers.start(b.clone(), Duration::from_millis(5), timer("b", None));
+
+        let start = Instant::now();
+        assert_eq!(
+            timers.expire(start + Duration::from_millis(10)),
+            vec![(b.clone(), timer("b", None))],
+            "Timer due in later round should stay in its slot"
+        );
+        let wake = timers.next_deadline().unwrap();
+        assert!(wake <= start + after, "Wheel should wake by deadline");
+        assert_eq!(timers.expire(start + after / 2), vec![]);
+        assert_eq!(
+            timers.expire(start + after + Duration::from_millis(1)),
+            vec![(a.clone(), timer("a", None))]
+        );
+        assert_eq!(timers.next_deadline(), None);
+    }
+
+    #[test]
+    fn cancel() {
+        let mut timers = Timers::default();
+        let a = Ref::new();
+        timers.start(a.clone(), Duration::from_secs(10), timer("a", None));
+
+        assert!(timers
+            .cancel(&a)
+            .is_some_and(|d| d > Duration::from_secs(9)));
+        assert_eq!(
+            timers.cancel(&a),
+            None,
+            "Timer should only be cancelled once"
+        );
+        assert_eq!(timers.next_deadline(), None);
+        assert_eq!(
+            timers.expire(Instant::now() + Duration::from_secs(20)),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn interval() {
+        let mut timers = Timers::default();
+        let a = Ref::new();
+        let every = Some(Duration::from_millis(10));
+        timers.start(a.clone(), Duration::from_millis(10), timer("a", every));
+
+        let start = Instant::now();
+        assert_eq!(
+            timers.expire(start + Duration::from_millis(15)),
+            vec![(a.clone(), timer("a", every))]
+        );
+        assert_eq!(
+            timers.expire(start + Duration::from_millis(15)),
+            vec![],
+            "Interval should be rescheduled for next period"
+        );
+        assert_eq!(timers.expire(start + Duration::from_millis(25)).len(), 1);
+        assert!(timers.cancel(&a).is_some());
+        assert_eq!(timers.expire(start + Duration::from_secs(1)), vec![]);
+    }
+}
@@ -1,6 +1,6 @@
 //! E2E tests for Runtime and Client
 use assert_matches::assert_matches;
-use lyric::Form;
+use lyric::{Form, KeywordId, Record, SymbolId};
 use vrs::{Client, Connection, Response, Runtime};
 
 #[tokio::test]
@@ -58,6 +58,47 @@ async fn request_response_multi() {
     );
 }
 
+#[tokio::test]
+async fn request_response_record() {
+    use lyric::parse as p;
+
+    let runtime = Runtime::new();
+    let (local, remote) = Connection::pair().unwrap();
+    let client = Client::new(remote);
+    runtime.handle_conn(local).await.unwrap();
+
+    client
+        .request(p("(defrecord point (x y))").unwrap())
+        .await
+        .unwrap();
+    let point = client
+        .request(p("(point 1 2)").unwrap())
+        .await
+        .unwrap()
+        .contents
+        .unwrap();
+    assert_eq!(
+        point,
+        Form::Record(Record {
+            name: SymbolId::from("point"),
+            fields: vec![
+                (KeywordId::from("x"), Form::Int(1)),
+                (KeywordId::from("y"), Form::Int(2)),
+            ],
+        })
+    );
+
+    let resp = client
+        .request(Form::List(vec![Form::symbol("point_y"), point]))
+        .await
+        .unwrap();
+    assert_eq!(
+        resp.contents,
+        Ok(Form::Int(2)),
+        "records received from connection should be usable as records"
+    );
+}
+
 #[tokio::test]
 async fn request_response_parallel() {
     let (local, remote) = Connection::pair().unwrap();
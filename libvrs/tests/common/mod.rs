@@ -0,0 +1,31 @@
+// Helpers shared by runtime tests
+#![allow(dead_code)]
+use std::time::Duration;
+use tokio::time::timeout;
+use vrs::{ProcessResult, Program, Runtime};
+
+/// Helpers for waiting on services to register, using changes published over the `:registry`
+/// topic. Subscribes the process to the topic, so programs should start with these.
+pub const WAIT: &str = r#"
+    (subscribe :registry)
+    (defn next_registered (name)
+      (get (get (recv (list :topic_updated :registry (list :registered name '_))) 2) 2))
+    (defn registered (name)
+      (let ((p (try (find_srv name))))
+        (if (ok? p) p (next_registered name))))
+    (defn reregistered (name pid)
+      (let ((p (next_registered name)))
+        (if (eq? p pid) (reregistered name pid) p)))
+    (defn deregistered (name pid)
+      (recv (list :topic_updated :registry (list :deregistered name pid))))
+"#;
+
+/// Run program to completion, returning its result
+pub async fn run(rt: &Runtime, prog: &str) -> vrs::Result<ProcessResult> {
+    let hdl = rt.run(Program::from_expr(prog).unwrap()).await.unwrap();
+    timeout(Duration::from_secs(2), hdl.join())
+        .await
+        .expect("Should not timeout")
+        .unwrap()
+        .status
+}
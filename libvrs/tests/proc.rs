@@ -137,3 +137,221 @@ async fn spawn_env_lambda_nested_isolated() {
         "calling set_var from spawned child should not affect parent's variables"
     );
 }
+
+#[tokio::test]
+async fn eval_in_sandbox() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def sandbox (make_env :allow '(self)))
+        (list
+            (eq? (self) (eval '(self) sandbox))
+            (try (eval '(exec "echo" "escaped") sandbox))
+            (try (eval '(kill (self)) sandbox))))
+    "#;
+    let prog = Program::from_expr(prog).unwrap();
+    let hdl = rt.run(prog).await.unwrap();
+
+    let exit = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("Should not timeout")
+        .unwrap();
+
+    assert_matches!(
+        exit.status.unwrap(),
+        ProcessResult::Done(Val::List(l)) if matches!(
+            l.as_slice(),
+            [Val::Bool(true), Val::Error(_), Val::Error(_)]
+        )
+    );
+}
+
+#[tokio::test]
+async fn monitor_down_messages() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def child (spawn (lambda () (begin (recv) (undefined_fn)))))
+        (def r (monitor child))
+        (def ignored (monitor child))
+        (demonitor ignored)
+        (send child :go)
+        (def (:down down_ref down_pid reason) (recv))
+        (def (:down _ _ noproc) (begin (monitor (pid 9999)) (recv)))
+        (list (eq? r down_ref) (eq? child down_pid) (get reason 0) noproc (ls_msgs)))
+    "#;
+    let hdl = rt.run(Program::from_expr(prog).unwrap()).await.unwrap();
+
+    let exit = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("Should not timeout")
+        .unwrap();
+
+    assert_eq!(
+        exit.status.unwrap(),
+        ProcessResult::Done(Val::from_expr("(true true :error :noproc ())").unwrap())
+    );
+}
+
+#[tokio::test]
+async fn link_propagates_abnormal_exit() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (spawn_link (lambda () (undefined_fn)))
+        (recv))
+    "#;
+    let hdl = rt.run(Program::from_expr(prog).unwrap()).await.unwrap();
+
+    let exit = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("Linked process should be killed")
+        .unwrap();
+    assert_matches!(
+        exit.status.unwrap(),
+        ProcessResult::Exited(Val::List(reason)) if reason[0] == Val::keyword("error"),
+        "Linked process should exit with reason of failed process"
+    );
+}
+
+#[tokio::test]
+async fn exit_reasons() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (spawn_link (lambda () (exit :normal)))
+        (def a (spawn (lambda () (begin (recv) (exit (list :shutdown 1)) :unreachable))))
+        (def b (spawn (lambda () (recv))))
+        (monitor a)
+        (monitor b)
+        (send a :go)
+        (def a_reason (get (recv) 3))
+        (kill b :bye)
+        (def b_reason (get (recv) 3))
+        (list a_reason b_reason (await (spawn_task (lambda () (exit :done))))))
+    "#;
+    let hdl = rt.run(Program::from_expr(prog).unwrap()).await.unwrap();
+
+    let exit = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("Should not timeout")
+        .unwrap();
+    assert_eq!(
+        exit.status.unwrap(),
+        ProcessResult::Done(Val::from_expr("((:shutdown 1) :bye (:exit :done))").unwrap())
+    );
+}
+
+#[tokio::test]
+async fn link_ignores_normal_exit() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def child (spawn_link (lambda () :done)))
+        (monitor child)
+        (recv)
+        :alive)
+    "#;
+    let hdl = rt.run(Program::from_expr(prog).unwrap()).await.unwrap();
+
+    let exit = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("Should not timeout")
+        .unwrap();
+    assert_eq!(
+        exit.status.unwrap(),
+        ProcessResult::Done(Val::keyword("alive"))
+    );
+}
+
+#[tokio::test]
+async fn trap_exit_receives_exit_messages() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (trap_exit true)
+        (def child (spawn_link (lambda () (undefined_fn))))
+        (def (:exit exit_pid reason) (recv))
+        (list (eq? child exit_pid) (get reason 0)))
+    "#;
+    let hdl = rt.run(Program::from_expr(prog).unwrap()).await.unwrap();
+
+    let exit = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("Should not timeout")
+        .unwrap();
+    assert_eq!(
+        exit.status.unwrap(),
+        ProcessResult::Done(Val::from_expr("(true :error)").unwrap())
+    );
+}
+
+#[tokio::test]
+async fn await_tasks() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def tasks (map '(1 2 3 4) (lambda (x) (spawn_task (lambda () (+ x x))))))
+        (def results (map tasks (lambda (t) (await t))))
+        (def (:err _) (await (spawn_task (lambda () (undefined_fn)))))
+        (def sleeper (spawn_task (lambda () (sleep 10))))
+        (def timed_out (await sleeper :timeout (duration :millis 10)))
+        (kill sleeper)
+        (list results timed_out (await sleeper)))
+    "#;
+    let hdl = rt.run(Program::from_expr(prog).unwrap()).await.unwrap();
+
+    let exit = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("Should not timeout")
+        .unwrap();
+    assert_eq!(
+        exit.status.unwrap(),
+        ProcessResult::Done(
+            Val::from_expr("(((:ok 2) (:ok 4) (:ok 6) (:ok 8)) (:timeout) (:cancelled))").unwrap()
+        )
+    );
+}
+
+#[tokio::test]
+async fn await_running_pid() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def child (spawn (lambda () (begin (sleep (duration :millis 50)) :slept))))
+        (await child))
+    "#;
+    let hdl = rt.run(Program::from_expr(prog).unwrap()).await.unwrap();
+
+    let exit = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("Should not timeout")
+        .unwrap();
+    assert_eq!(
+        exit.status.unwrap(),
+        ProcessResult::Done(Val::from_expr("(:ok :slept)").unwrap())
+    );
+}
+
+#[tokio::test]
+async fn await_exited_child() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def child (spawn (lambda () :done)))
+        (recv (list :down (monitor child) '_ '_))
+        (def other (spawn (lambda () :other)))
+        (list (await child) (err? (try (await child))) (await other)))
+    "#;
+    let hdl = rt.run(Program::from_expr(prog).unwrap()).await.unwrap();
+
+    let exit = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("Should not timeout")
+        .unwrap();
+    assert_eq!(
+        exit.status.unwrap(),
+        ProcessResult::Done(Val::from_expr("((:ok :done) true (:ok :other))").unwrap()),
+        "Exited child should be awaited once"
+    );
+}
@@ -208,3 +208,29 @@ async fn two_process_pubsub_many() {
         .unwrap()
     );
 }
+
+#[tokio::test]
+async fn subscribe_seq() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def updates (subscribe_seq :my_topic))
+        (spawn (lambda () (begin
+            (publish :my_topic 1)
+            (publish :my_topic 2)
+            (publish :my_topic 3))))
+        (realize (take (for (x updates :when (not? (eq? x 2))) (list :got x)) 2))
+    )"#;
+    let prog = Program::from_expr(prog).unwrap();
+    let hdl = rt.run(prog).await.unwrap();
+
+    let exit = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("shouldn't timeout")
+        .unwrap();
+
+    assert_eq!(
+        exit.status.unwrap(),
+        ProcessResult::Done(Val::from_expr("((:got 1) (:got 3))").unwrap())
+    );
+}
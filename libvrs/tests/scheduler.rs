@@ -0,0 +1,187 @@
+// Test scheduler runtime bindings
+use std::path::PathBuf;
+use std::time::Duration;
+
+use jiff::{ToSpan, Zoned};
+use tokio::time::timeout;
+use vrs::{Clock, ProcessResult, Program, Runtime, Val};
+
+mod common;
+use common::{run, WAIT};
+
+/// Path to jobs file unique to test
+fn jobs_path(test: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("vrs-test-{}", std::process::id()))
+        .join(format!("{test}.ll"))
+}
+
+fn utc(t: &str) -> Zoned {
+    format!("{t}[UTC]").parse().unwrap()
+}
+
+#[tokio::test]
+async fn schedule_jobs() {
+    let rt = Runtime::new();
+
+    let prog = format!(
+        r#"(begin {WAIT}
+        (spawn (lambda () (scheduler)))
+        (registered :scheduler)
+        (schedule :morning "30 7 * * mon-fri" :spawn '(exec "true"))
+        (schedule :tick :hourly :send '(:echo :tick) :catch_up true)
+        (def names (map (ls_jobs) (lambda (j) (get j :name))))
+        (list names (rm_job :tick) (rm_job :tick) (len (ls_jobs))))
+    "#
+    );
+    assert_eq!(
+        run(&rt, &prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("((:morning :tick) true false 1)").unwrap())
+    );
+}
+
+#[tokio::test]
+async fn schedule_errors() {
+    let rt = Runtime::new();
+
+    let prog = format!(
+        r#"(begin {WAIT}
+        (def missing (err? (try (ls_jobs))))
+        (spawn (lambda () (scheduler)))
+        (registered :scheduler)
+        (list
+            missing
+            (err? (try (schedule :bad "61 * * * *" :spawn '(+ 1 1))))
+            (err? (try (schedule :bad '(:hours 7) :spawn '(+ 1 1))))
+            (err? (try (schedule :bad :daily)))
+            (ls_jobs)))
+    "#
+    );
+    assert_eq!(
+        run(&rt, &prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(true true true true ())").unwrap())
+    );
+}
+
+#[tokio::test]
+async fn jobs_persist_across_restarts() {
+    let path = jobs_path("jobs_persist_across_restarts");
+    let _ = std::fs::remove_file(&path);
+
+    let clock = Clock::virtual_at(utc("2024-07-14T06:59:30"));
+    let rt = Runtime::with_clock(clock.clone());
+    rt.start_scheduler(&path).await.unwrap();
+    let prog = format!(
+        r#"(begin {WAIT}
+        (registered :scheduler)
+        (schedule :morning "0 7 * * *" :spawn '(begin (register :morning_job) (recv)))
+        :ok)
+    "#
+    );
+    run(&rt, &prog).await.unwrap();
+
+    clock.advance(1.minute());
+    let prog = format!(
+        r#"(begin {WAIT}
+        (registered :morning_job)
+        (get (get (ls_jobs) 0) :runs))
+    "#
+    );
+    assert_eq!(
+        run(&rt, &prog).await.unwrap(),
+        ProcessResult::Done(Val::Int(1))
+    );
+
+    // runtime restarts after being stopped for 3 days
+    let restarted = Runtime::with_clock(Clock::virtual_at(utc("2024-07-17T12:00")));
+    restarted.start_scheduler(&path).await.unwrap();
+    let prog = format!(
+        r#"(begin {WAIT}
+        (registered :scheduler)
+        (def (job) (ls_jobs))
+        (list (get job :name) (get job :schedule) (get job :runs) (get job :missed)))
+    "#
+    );
+    assert_eq!(
+        run(&restarted, &prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr(r#"(:morning "0 7 * * *" 1 3)"#).unwrap()),
+        "Restored job should report runs missed while runtime was stopped"
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn corrupt_jobs_file() {
+    let path = jobs_path("corrupt_jobs_file");
+    let mut bad = path.clone().into_os_string();
+    bad.push(".bad");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "((:name :broken))").unwrap();
+
+    let rt = Runtime::new();
+    rt.start_scheduler(&path).await.unwrap();
+    let prog = format!(
+        r#"(begin {WAIT}
+        (registered :scheduler)
+        (def before (ls_jobs))
+        (schedule :fresh :daily :spawn '(+ 1 1))
+        (list before (map (ls_jobs) (lambda (j) (get j :name)))))
+    "#
+    );
+    assert_eq!(
+        run(&rt, &prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(() (:fresh))").unwrap()),
+        "Scheduler should start without jobs it can't restore"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&bad).unwrap(),
+        "((:name :broken))",
+        "Jobs file that can't be restored should be kept"
+    );
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&bad);
+}
+
+#[tokio::test]
+async fn send_job_to_srv() {
+    let clock = Clock::virtual_at(utc("2024-07-14T06:59:30"));
+    let rt = Runtime::with_clock(clock.clone());
+
+    let collector = rt
+        .run(Program::from_expr("(begin (register :collector) (recv))").unwrap())
+        .await
+        .unwrap();
+    let pinger = r#"(begin
+        (defn ping (n) (send (find_srv :collector) (list :pinged n)))
+        (srv :pinger :interface '(ping)))
+    "#;
+    rt.run(Program::from_expr(pinger).unwrap()).await.unwrap();
+    let prog = format!(
+        r#"(begin {WAIT}
+        (spawn (lambda () (scheduler)))
+        (registered :scheduler)
+        (registered :pinger)
+        (registered :collector)
+        (schedule :ping "0 7 * * *" :send '(:pinger (:ping 1)))
+        :ok)
+    "#
+    );
+    run(&rt, &prog).await.unwrap();
+
+    clock.advance(1.minute());
+    let exit = timeout(Duration::from_secs(2), collector.join())
+        .await
+        .expect("Job should message service")
+        .unwrap();
+    assert_eq!(
+        exit.status.unwrap(),
+        ProcessResult::Done(Val::from_expr("(:pinged 1)").unwrap())
+    );
+
+    let prog = "(list (get (get (ls_jobs) 0) :runs) (ok? (try (find_srv :pinger))))";
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(1 true)").unwrap()),
+        "Service should keep running after handling job"
+    );
+}
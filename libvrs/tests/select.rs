@@ -0,0 +1,141 @@
+//! Select tests
+use vrs::{ProcessResult, Runtime, Val};
+
+mod common;
+use common::run;
+
+#[tokio::test]
+async fn select_mailbox() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (send (self) :ignored)
+        (send (self) '(:ok 1))
+        (defn next ()
+          (select
+            ((recv (:ok v)) (list :ok v))
+            ((topic :select_mailbox v) (list :topic v))
+            (after 10 :timed_out)))
+        (list (next) (next) (ls_msgs)))
+    "#;
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("((:ok 1) :timed_out (:ignored))").unwrap())
+    );
+}
+
+#[tokio::test]
+async fn select_topic() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (spawn (lambda () (begin
+            (sleep (duration :millis 20))
+            (publish :select_topic :skipped)
+            (publish :select_topic '(:update 2)))))
+        (list
+          (select
+            ((recv :never) :mailbox)
+            ((topic :select_topic (:update v)) (list :topic v)))
+          (ls_msgs)))
+    "#;
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(
+            Val::from_expr("((:topic 2) ((:topic_updated :select_topic :skipped)))").unwrap()
+        ),
+        "Select should leave topic updates it didn't match in mailbox"
+    );
+}
+
+#[tokio::test]
+async fn select_topic_subscribed() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (subscribe :select_subscribed)
+        (publish :select_subscribed '(:update 1))
+        (defn next ()
+          (select
+            ((topic :select_subscribed (:update v)) v)
+            (after 100 :timed_out)))
+        (def first (next))
+        (publish :select_subscribed '(:update 2))
+        (sleep (duration :millis 10))
+        (list first (next) (ls_msgs)))
+    "#;
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(1 2 ())").unwrap()),
+        "Select should reuse subscription of process, without losing or duplicating updates"
+    );
+}
+
+#[tokio::test]
+async fn select_timeout_message() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def _sel :global)
+        (def m :outer)
+        (send (self) (recv :nothing :timeout 0))
+        (send (self) '(:timeout))
+        (send (self) '(:val 5))
+        (defn next ()
+          (select ((recv (:val m)) m) ((recv e) (list :got e)) (after 10 :timed_out)))
+        (list (err? (get (next) 1)) (next) (next) (next) m _sel))
+    "#;
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(
+            Val::from_expr("(true (:got (:timeout)) 5 :timed_out :outer :global)").unwrap()
+        ),
+        "Messages should not be mistaken for timeouts, and clauses should not leak bindings"
+    );
+}
+
+#[tokio::test]
+async fn select_timer() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def t (send_after 10 (self) :fired))
+        (def late (send_after 30 (self) :late))
+        (def first
+          (select
+            ((recv :never) :mailbox)
+            ((timer late) :late)
+            ((timer t) :timer)))
+        (def done (select ((timer t) :done)))
+        (list first done (recv :late) (ls_msgs)))
+    "#;
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(:timer :done :late ())").unwrap()),
+        "Messages of timers that were not selected should be delivered to mailbox"
+    );
+}
+
+#[tokio::test]
+async fn select_exit() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def child (spawn (lambda () (begin (sleep (duration :millis 10)) (exit :boom)))))
+        (def reason
+          (select
+            ((recv :never) :mailbox)
+            ((exit child r) (list :exited r))
+            (after 1000 :timed_out)))
+        (def done (spawn (lambda () :done)))
+        (sleep (duration :millis 10))
+        (list reason
+              (select ((exit done (:error _)) :failed) (after 10 :timed_out))
+              (ls_msgs)))
+    "#;
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("((:exited :boom) :timed_out ())").unwrap()),
+        "Down messages of select should not be left in mailbox"
+    );
+}
@@ -1,7 +1,12 @@
 // Test service runtime bindings
 
+use std::time::Duration;
+use tokio::time::timeout;
 use vrs::{ProcessResult, Program, Runtime, Val};
 
+mod common;
+use common::WAIT;
+
 #[tokio::test]
 async fn srv_echo() {
     let rt = Runtime::new();
@@ -78,10 +83,9 @@ async fn srv_echo_invalid_msg() {
     let resp = req.join().await.unwrap();
     assert_eq!(
         resp.status.unwrap(),
-        ProcessResult::Done(Val::List(vec![
-            Val::keyword("err"),
-            Val::string("Unrecognized message")
-        ]))
+        ProcessResult::Done(Val::Error(lyric::Error::Runtime(
+            "Unrecognized message".to_string()
+        )))
     );
 }
 
@@ -103,10 +107,9 @@ async fn srv_echo_invalid_arg() {
     let resp = req.join().await.unwrap();
     assert_eq!(
         resp.status.unwrap(),
-        ProcessResult::Done(Val::List(vec![
-            Val::keyword("err"),
-            Val::string("Unrecognized message")
-        ]))
+        ProcessResult::Done(Val::Error(lyric::Error::Runtime(
+            "Unrecognized message".to_string()
+        )))
     );
 }
 
@@ -132,5 +135,120 @@ async fn spawn_echo_svc() {
     );
 }
 
+#[tokio::test]
+async fn srv_multi_clause() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+         (spawn (lambda () (begin
+            (defn area
+                (((:square side)) (+ side side))
+                (((:rect w h)) :when (eq? w h) :square)
+                ((shape) (list :unknown shape)))
+            (srv :shapes :interface '(area))
+         )))
+         (list
+            (info_srv :shapes :interface)
+            (call (find_srv :shapes) '(:area (:square 2)))
+            (call (find_srv :shapes) '(:area (:rect 1 1)))
+            (call (find_srv :shapes) '(:area :circle))))
+    "#;
+    let prog = Program::from_expr(prog).unwrap();
+    let hdl = rt.run(prog).await.unwrap();
+
+    let resp = hdl.join().await.unwrap();
+    assert_eq!(
+        resp.status.unwrap(),
+        ProcessResult::Done(
+            Val::from_expr("(((:area shape)) 4 :square (:unknown :circle))").unwrap()
+        )
+    );
+}
+
+#[tokio::test]
+async fn srv_extends_protocol() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+         (def items_module "(begin
+            (defprotocol item (describe (item)))
+            (defimpl item :url (describe (item) (list :link (get item 1)))))")
+         (spawn (lambda () (begin
+            (eval (read items_module))
+            (defrecord note (title))
+            (defimpl item note (describe (n) (list :note (note_title n))))
+            (defn describe_note (title) (describe (note title)))
+            (srv :items :interface '(describe describe_note))
+         )))
+         (list
+            (call (find_srv :items) '(:describe (:url "http://example.com")))
+            (call (find_srv :items) '(:describe_note "todo"))))
+    "#;
+    let prog = Program::from_expr(prog).unwrap();
+    let hdl = rt.run(prog).await.unwrap();
+
+    let resp = hdl.join().await.unwrap();
+    assert_eq!(
+        resp.status.unwrap(),
+        ProcessResult::Done(
+            Val::from_expr(r#"((:link "http://example.com") (:note "todo"))"#).unwrap()
+        )
+    );
+}
+
+#[tokio::test]
+async fn call_fails_fast() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+         (def crashing (spawn (lambda () (begin (recv) (undefined_fn)))))
+         (def exited (spawn (lambda () :done)))
+         (def stuck (spawn (lambda () (loop (recv)))))
+         (recv (list :down (monitor exited) '_ '_))
+         (list
+            (err? (try (call crashing :hello)))
+            (err? (try (call exited :hello)))
+            (err? (try (call stuck :hello :timeout 10)))
+            (ls_msgs)))
+    "#;
+    let hdl = rt.run(Program::from_expr(prog).unwrap()).await.unwrap();
+
+    let resp = timeout(Duration::from_secs(1), hdl.join())
+        .await
+        .expect("Calls should not block")
+        .unwrap();
+    assert_eq!(
+        resp.status.unwrap(),
+        ProcessResult::Done(Val::from_expr("(true true true ())").unwrap()),
+        "Calls should raise errors without leaving monitor messages in mailbox"
+    );
+}
+
+#[tokio::test]
+async fn bind_srv_errors() {
+    let rt = Runtime::new();
+
+    let prog = format!(
+        r#"(begin {WAIT}
+         (spawn (lambda () (begin
+            (defn fail (x) (undefined_fn x))
+            (defn echo (x) x)
+            (srv :failing :interface '(fail echo))
+         )))
+         (registered :failing)
+         (bind_srv :failing)
+         (list (echo :hi) (err? (fail 1))))
+    "#
+    );
+    let hdl = rt.run(Program::from_expr(&prog).unwrap()).await.unwrap();
+
+    let resp = hdl.join().await.unwrap();
+    assert_eq!(
+        resp.status.unwrap(),
+        ProcessResult::Done(Val::from_expr("(:hi true)").unwrap()),
+        "Service errors should be returned as error values"
+    );
+}
+
 // TODO: Test srv w/o service name errors
 // TODO: Test srv w/o :interface errors
@@ -0,0 +1,126 @@
+// Test supervisor runtime bindings
+use assert_matches::assert_matches;
+use vrs::{Error, ProcessResult, Runtime, Val};
+
+mod common;
+use common::{run, WAIT};
+
+#[tokio::test]
+async fn restart_one_for_one() {
+    let rt = Runtime::new();
+
+    let prog = format!(
+        r#"(begin {WAIT}
+        (defn worker () (loop (recv)))
+        (spawn (lambda () (supervisor (list
+            (list :name :a :start worker)
+            (list :name :b :start worker)))))
+        (def a (registered :a))
+        (def b (registered :b))
+        (kill a)
+        (def restarted (reregistered :a a))
+        (list (eq? a restarted) (eq? b (find_srv :b))))
+    "#
+    );
+    assert_eq!(
+        run(&rt, &prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(false true)").unwrap()),
+        "Only killed child should be restarted and re-registered"
+    );
+}
+
+#[tokio::test]
+async fn restart_one_for_all() {
+    let rt = Runtime::new();
+
+    let prog = format!(
+        r#"(begin {WAIT}
+        (defn worker () (loop (recv)))
+        (spawn (lambda () (supervisor (list
+            (list :name :a :start worker)
+            (list :name :b :start worker)
+            (list :name :c :start worker :restart :temporary))
+            :strategy :one_for_all)))
+        (def a (registered :a))
+        (def b (registered :b))
+        (def c (registered :c))
+        (kill b)
+        (deregistered :c c)
+        (def restarted (reregistered :b b))
+        (list (eq? a (find_srv :a)) (eq? b restarted) (ok? (try (find_srv :c)))))
+    "#
+    );
+    assert_eq!(
+        run(&rt, &prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(false false false)").unwrap()),
+        "All children should be restarted, except temporary children"
+    );
+}
+
+#[tokio::test]
+async fn restart_rest_for_one() {
+    let rt = Runtime::new();
+
+    let prog = format!(
+        r#"(begin {WAIT}
+        (defn worker () (loop (recv)))
+        (spawn (lambda () (supervisor (list
+            (list :name :a :start worker)
+            (list :name :b :start worker)
+            (list :name :c :start worker))
+            :strategy :rest_for_one)))
+        (def a (registered :a))
+        (def b (registered :b))
+        (def c (registered :c))
+        (kill b)
+        (def restarted (reregistered :c c))
+        (list (eq? a (find_srv :a)) (eq? b (find_srv :b)) (eq? c restarted)))
+    "#
+    );
+    assert_eq!(
+        run(&rt, &prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(true false false)").unwrap()),
+        "Killed child and children started after it should be restarted"
+    );
+}
+
+#[tokio::test]
+async fn restart_policies() {
+    let rt = Runtime::new();
+
+    // the permanent child is killed once the others exit, so the supervisor has handled their
+    // exits by the time it restarts the permanent child
+    let prog = r#"(begin
+        (def parent (self))
+        (spawn (lambda () (supervisor (list
+            (list :restart :transient :start (lambda () (send parent (list :transient (self)))))
+            (list :restart :temporary :start (lambda () (begin (send parent (list :temporary (self))) (undefined_fn))))
+            (list :restart :permanent :start (lambda () (begin (send parent (list :permanent (self))) (recv))))))))
+        (defn started (name) (match (recv (list name '_)) ((_ pid) pid)))
+        (defn wait_exit (pid) (recv (list :down (monitor pid) '_ '_)))
+        (wait_exit (started :transient))
+        (wait_exit (started :temporary))
+        (kill (started :permanent))
+        (started :permanent)
+        (ls_msgs))
+    "#;
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(Val::List(vec![])),
+        "Only permanent child should be restarted"
+    );
+}
+
+#[tokio::test]
+async fn restart_intensity() {
+    let rt = Runtime::new();
+
+    let prog = r#"(supervisor
+        (list (list :start (lambda () (undefined_fn))))
+        :intensity 2)
+    "#;
+    assert_matches!(
+        run(&rt, prog).await,
+        Err(Error::EvaluationError(lyric::Error::Runtime(msg))) if msg.contains("exceeded 2 restarts")
+    );
+}
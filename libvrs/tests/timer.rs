@@ -0,0 +1,75 @@
+//! Timer binding tests
+use vrs::{ProcessResult, Runtime, Val};
+
+mod common;
+use common::run;
+
+#[tokio::test]
+async fn send_after() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def t (send_after 20 (self) :ping))
+        (def pending (read_timer t))
+        (list (ls_msgs) (if (> pending 0) (< pending 21) false) (recv :ping) (read_timer t)))
+    "#;
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(() true :ping nil)").unwrap())
+    );
+}
+
+#[tokio::test]
+async fn cancel_timer() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def t (send_after (duration :millis 20) (self) :late))
+        (def remaining (cancel_timer t))
+        (list (> remaining 0) (cancel_timer t) (err? (recv :late :timeout 50))))
+    "#;
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(true nil true)").unwrap())
+    );
+}
+
+#[tokio::test]
+async fn interval() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (def t (interval 5 (self) :tick))
+        (list (recv :tick) (recv :tick) (recv :tick)
+              (ok? (cancel_timer t))
+              (begin (sleep (duration :millis 20)) (ls_msgs))))
+    "#;
+    let msgs = match run(&rt, prog).await.unwrap() {
+        ProcessResult::Done(Val::List(l)) => l,
+        r => panic!("Should return list - got {r:?}"),
+    };
+    assert_eq!(
+        Val::List(msgs[..4].to_vec()),
+        Val::from_expr("(:tick :tick :tick true)").unwrap()
+    );
+    assert!(
+        matches!(&msgs[4], Val::List(l) if l.len() <= 1),
+        "Cancelled interval should stop sending messages - got {}",
+        msgs[4]
+    );
+}
+
+#[tokio::test]
+async fn many_timers() {
+    let rt = Runtime::new();
+
+    let prog = r#"(begin
+        (defn start (n) (if (eq? n 0) :started (begin (send_after 10 (self) :tick) (start (+ n -1)))))
+        (defn collect (n) (if (eq? n 0) :collected (begin (recv :tick) (collect (+ n -1)))))
+        (list (start 500) (collect 500)))
+    "#;
+    assert_eq!(
+        run(&rt, prog).await.unwrap(),
+        ProcessResult::Done(Val::from_expr("(:started :collected)").unwrap())
+    );
+}
@@ -0,0 +1,292 @@
+//! Procedural macros for defining lyric native functions
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Expr, FnArg, Ident, ItemFn, Lit, Meta, Pat,
+    Type,
+};
+
+/// Define a native function binding from a typed Rust function.
+///
+/// Annotating `fn NAME` generates `fn NAME_fn() -> NativeFn` (or `NativeAsyncFn` for `async fn`)
+/// that checks arity, converts each argument via `FromVal`, and converts the result via `IntoVal`.
+/// The doc comment of `NAME` is used as doc string of the native function.
+///
+/// - A leading `&mut Fiber` parameter receives the calling fiber
+/// - Trailing `Option<_>` parameters are optional and receive `None` when omitted
+/// - A final parameter marked `#[rest]` receives remaining arguments, e.g. `#[rest] args: Vec<String>`
+///
+/// `Fiber`, `Val`, and `NativeFn` / `NativeAsyncFn` are resolved in the calling scope. If `NAME` is
+/// generic, its type parameters are forwarded, e.g. `NativeFn<T, L>`.
+///
+/// ```ignore
+/// /// (exec PROG ARG1 ARG2 ... ARGN) - Execute external executable PROG
+/// #[native_fn]
+/// async fn exec(prog: String, #[rest] args: Vec<String>) -> Result<Val> { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn native_fn(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(Span::call_site(), "native_fn does not take arguments")
+            .to_compile_error()
+            .into();
+    }
+    let item = parse_macro_input!(item as ItemFn);
+    match expand(item) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Parameter of annotated function
+enum Param {
+    Fiber,
+    Required(Ident, Type),
+    Optional(Ident, Type),
+    Rest(Ident, Type),
+}
+
+fn expand(mut item: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let name = item.sig.ident.clone();
+    let doc = doc_string(&item.attrs).ok_or(syn::Error::new(
+        name.span(),
+        "native_fn requires a doc comment to use as doc string",
+    ))?;
+    let params = parse_params(&mut item)?;
+
+    let is_async = item.sig.asyncness.is_some();
+    let vis = &item.vis;
+    let doc_attrs = item.attrs.iter().filter(|a| a.path().is_ident("doc"));
+    let binding_name = format_ident!("{}_fn", name);
+    let (impl_generics, ty_generics, where_clause) = item.sig.generics.split_for_impl();
+    let turbofish = ty_generics.as_turbofish();
+    let fn_ty = if is_async {
+        quote! { NativeAsyncFn }
+    } else {
+        quote! { NativeFn }
+    };
+
+    let lisp_name = name.to_string();
+    let min_args = params
+        .iter()
+        .filter(|p| matches!(p, Param::Required(_, _)))
+        .count();
+    let max_args = params
+        .iter()
+        .filter(|p| matches!(p, Param::Required(_, _) | Param::Optional(_, _)))
+        .count();
+    let has_rest = params.iter().any(|p| matches!(p, Param::Rest(_, _)));
+    let expected = if has_rest {
+        format!("at least {min_args} argument(s)")
+    } else if min_args == max_args {
+        format!("{min_args} argument(s)")
+    } else {
+        format!("{min_args} to {max_args} argument(s)")
+    };
+    let arity = if has_rest {
+        quote! { ::lyric::Arity::at_least(#min_args) }
+    } else {
+        quote! { ::lyric::Arity::range(#min_args, #max_args) }
+    };
+    let arity_check = if has_rest {
+        quote! { __args.len() < #min_args }
+    } else {
+        quote! { __args.len() < #min_args || __args.len() > #max_args }
+    };
+
+    let mut conversions = vec![];
+    let mut call_args = vec![];
+    for (idx, p) in params.iter().enumerate() {
+        let var = format_ident!("__arg{}", idx);
+        let (ident, ty, val) = match p {
+            Param::Fiber => {
+                call_args.push(quote! { __fiber });
+                continue;
+            }
+            Param::Required(ident, ty) => (ident, ty, quote! { __args.next().unwrap() }),
+            Param::Optional(ident, ty) => (
+                ident,
+                ty,
+                quote! { __args.next().unwrap_or(::lyric::Val::Nil) },
+            ),
+            Param::Rest(ident, ty) => (ident, ty, quote! { ::lyric::Val::List(__args.collect()) }),
+        };
+        let param_name = ident.to_string().to_uppercase();
+        conversions.push(quote! {
+            let #var = <#ty as ::lyric::FromVal<_, _>>::from_val(#val).map_err(|e| {
+                ::lyric::Error::UnexpectedArguments(format!(
+                    "{} - invalid argument {} - {}",
+                    #lisp_name, #param_name, e
+                ))
+            })?;
+        });
+        call_args.push(quote! { #var });
+    }
+
+    let uses_fiber = params.iter().any(|p| matches!(p, Param::Fiber));
+    let fiber_arg = if uses_fiber {
+        quote! { __fiber }
+    } else {
+        quote! { _ }
+    };
+
+    let check_and_convert = quote! {
+        if #arity_check {
+            return Err(::lyric::Error::UnexpectedArguments(format!(
+                "{} expects {} - got {}",
+                #lisp_name,
+                #expected,
+                __args.len()
+            )));
+        }
+        #[allow(unused_mut, unused_variables)]
+        let mut __args = __args.into_iter();
+        #(#conversions)*
+    };
+
+    let func = if is_async {
+        quote! {
+            |#fiber_arg, __args| {
+                Box::new(async move {
+                    #check_and_convert
+                    Ok(::lyric::IntoVal::into_val(#name #turbofish(#(#call_args),*).await?))
+                })
+            }
+        }
+    } else {
+        quote! {
+            |#fiber_arg, __args| {
+                let __args = __args.to_vec();
+                #check_and_convert
+                Ok(::lyric::NativeFnOp::Return(::lyric::IntoVal::into_val(
+                    #name #turbofish(#(#call_args),*)?
+                )))
+            }
+        }
+    };
+
+    let generic_args = item.sig.generics.type_params().map(|t| &t.ident);
+    let generic_args = quote! { #(#generic_args),* };
+    let ret_ty = if generic_args.is_empty() {
+        quote! { #fn_ty }
+    } else {
+        quote! { #fn_ty<#generic_args> }
+    };
+
+    Ok(quote! {
+        #item
+
+        #(#doc_attrs)*
+        #vis fn #binding_name #impl_generics () -> #ret_ty #where_clause {
+            #fn_ty {
+                doc: #doc.to_string(),
+                arity: Some(#arity),
+                func: #func,
+            }
+        }
+    })
+}
+
+/// Collect doc comment of item into single line doc string
+fn doc_string(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+                Expr::Lit(lit) => match &lit.lit {
+                    Lit::Str(s) => Some(s.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" "))
+    }
+}
+
+/// Parse parameters of annotated fn, stripping `#[rest]` markers
+fn parse_params(item: &mut ItemFn) -> syn::Result<Vec<Param>> {
+    let mut params = vec![];
+    let num_inputs = item.sig.inputs.len();
+    for (idx, input) in item.sig.inputs.iter_mut().enumerate() {
+        let pat_ty = match input {
+            FnArg::Typed(pat_ty) => pat_ty,
+            FnArg::Receiver(r) => {
+                return Err(syn::Error::new(r.span(), "native_fn cannot take self"));
+            }
+        };
+
+        let is_rest = pat_ty.attrs.iter().any(|a| a.path().is_ident("rest"));
+        pat_ty.attrs.retain(|a| !a.path().is_ident("rest"));
+
+        if idx == 0 && is_fiber_ref(&pat_ty.ty) {
+            params.push(Param::Fiber);
+            continue;
+        }
+
+        let ident = match pat_ty.pat.as_ref() {
+            Pat::Ident(p) => p.ident.clone(),
+            p => {
+                return Err(syn::Error::new(
+                    p.span(),
+                    "native_fn parameters must be identifiers",
+                ))
+            }
+        };
+        let ty = pat_ty.ty.as_ref().clone();
+
+        if is_rest {
+            if idx != num_inputs - 1 {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "#[rest] must be the last parameter",
+                ));
+            }
+            params.push(Param::Rest(ident, ty));
+        } else if is_option(&ty) {
+            params.push(Param::Optional(ident, ty));
+        } else {
+            if params.iter().any(|p| matches!(p, Param::Optional(_, _))) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "required parameters cannot follow optional parameters",
+                ));
+            }
+            params.push(Param::Required(ident, ty));
+        }
+    }
+    Ok(params)
+}
+
+/// Whether or not type is `&mut Fiber` or `&mut Fiber<..>`
+fn is_fiber_ref(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(r) if r.mutability.is_some() => last_segment_is(&r.elem, "Fiber"),
+        _ => false,
+    }
+}
+
+/// Whether or not type is `Option<..>`
+fn is_option(ty: &Type) -> bool {
+    last_segment_is(ty, "Option")
+}
+
+fn last_segment_is(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident == name)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
@@ -1,10 +1,11 @@
 //! Conditional expressions
-use crate::{Error, Extern, Locals, NativeFn, NativeFnOp, Result, Val};
+use crate::{Arity, Error, Extern, Locals, NativeFn, NativeFnOp, Result, Val};
 
 /// Language bindng for `eq?`
 pub fn eq_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(eq? LHS RHS) - returns true if LHS equals RHS, otherwise false".to_string(),
+        arity: Some(Arity::exact(2)),
         func: |_, args| {
             let (lhs, rhs) = match args {
                 [lhs, rhs] => (lhs, rhs),
@@ -23,6 +24,7 @@ pub fn eq_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub fn contains_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(contains? LIST ELEM) - returns true if LIST contains ELEM, otherwise false. LIST can be str or list".to_string(),
+        arity: Some(Arity::exact(2)),
         func: |_, args| match args {
             [Val::List(l), target] => Ok(NativeFnOp::Return(Val::Bool(l.contains(target)))),
             [Val::String(s), Val::String(target)] => {
@@ -40,6 +42,7 @@ pub fn contains_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub fn not_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(not? EXPR) - returns the negation of truthiness of EXPR".to_string(),
+        arity: Some(Arity::exact(1)),
         func: |_, args| match args {
             [cond] => Ok(NativeFnOp::Return(Val::Bool(!is_true(cond)?))),
             _ => Err(Error::UnexpectedArguments(
@@ -53,6 +56,7 @@ pub fn not_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub fn empty_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(empty? SEXP) - returns true if SEXP is empty".to_string(),
+        arity: Some(Arity::exact(1)),
         func: |_, args| match args {
             [Val::List(l)] => Ok(NativeFnOp::Return(Val::Bool(l.is_empty()))),
             [Val::String(s)] => Ok(NativeFnOp::Return(Val::Bool(s.is_empty()))),
@@ -69,6 +73,7 @@ pub fn empty_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub fn is_keyword_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(keyword? EXPR) - returns true if EXPR is keyword. Otherwise false".to_string(),
+        arity: Some(Arity::exact(1)),
         func: |_, args| match args {
             [Val::Keyword(_)] => Ok(NativeFnOp::Return(Val::Bool(true))),
             [_] => Ok(NativeFnOp::Return(Val::Bool(false))),
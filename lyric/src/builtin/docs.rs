@@ -1,8 +1,10 @@
-use crate::{Error, Extern, Locals, NativeFn, NativeFnOp, Val};
+use super::protocol;
+use crate::{Arity, Error, Extern, Locals, NativeFn, NativeFnOp, Val};
 
 pub(crate) fn help_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
-        doc: "(help SYMBOL) - Returns docstring for SYMBOL if any".to_string(),
+        doc: "(help SYMBOL) - Returns docstring for SYMBOL if any. For protocols, lists methods and implementations".to_string(),
+        arity: Some(Arity::exact(1)),
         func: |_, args| {
             let docstring = match args {
                 [Val::Lambda(l)] => l
@@ -11,9 +13,12 @@ pub(crate) fn help_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
                     .unwrap_or("<missing documentation>".to_string()),
                 [Val::NativeFn(f)] => f.doc.clone(),
                 [Val::NativeAsyncFn(f)] => f.doc.clone(),
+                [val @ Val::Record(_)] if protocol::describe(val).is_some() => {
+                    protocol::describe(val).unwrap()
+                }
                 _ => {
                     return Err(Error::UnexpectedArguments(format!(
-                        "help expects one callable object as argument - got {:?}",
+                        "help expects one callable object or protocol as argument - got {:?}",
                         args
                     )))
                 } // TODO: Better args display format?
@@ -0,0 +1,61 @@
+//! Builtins for dynamically scoped variables
+//!
+//! Dynamic variables are defined with `defdynamic`, and rebound with `parameterize` for the extent
+//! of its body, including functions called from it. Bindings are kept in the call frame entered by
+//! `parameterize`, so they are local to the fiber, and restored when the frame returns or is
+//! unwound by an error.
+use crate::{Arity, Error, Extern, Locals, NativeFn, NativeFnOp, SymbolId, Val};
+use nanoid::nanoid;
+
+/// Dynamic variable, with value used outside of any `parameterize` rebinding it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dynamic<T: Extern, L: Locals> {
+    id: String,
+    /// Symbol dynamic variable is defined as
+    pub name: SymbolId,
+    /// Value when dynamic variable is not rebound
+    pub default: Box<Val<T, L>>,
+}
+
+impl<T: Extern, L: Locals> Dynamic<T, L> {
+    /// Create a new dynamic variable
+    pub fn new(name: SymbolId, default: Val<T, L>) -> Self {
+        Self {
+            id: nanoid!(),
+            name,
+            default: Box::new(default),
+        }
+    }
+
+    /// Same dynamic variable with new default value
+    pub(crate) fn with_default(&self, default: Val<T, L>) -> Self {
+        Self {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            default: Box::new(default),
+        }
+    }
+
+    /// Whether or not given dynamic variable is the same variable, regardless of its default
+    pub(crate) fn is(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+/// Create dynamic variable. Not bound in environment - `defdynamic` expands into it.
+pub(crate) fn make_dynamic_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(make_dynamic NAME DEFAULT) - Create dynamic variable named NAME with DEFAULT value"
+            .to_string(),
+        arity: Some(Arity::exact(2)),
+        func: |_, args| match args {
+            [Val::Symbol(name), default] => Ok(NativeFnOp::Return(Val::Dynamic(Dynamic::new(
+                name.clone(),
+                default.clone(),
+            )))),
+            _ => Err(Error::UnexpectedArguments(
+                "make_dynamic expects a symbol and a default value".to_string(),
+            )),
+        },
+    }
+}
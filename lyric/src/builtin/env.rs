@@ -1,19 +1,27 @@
 //! Environment related bindings
-use crate::{Error, Extern, Locals, NativeFn, NativeFnOp, Val};
+use crate::{
+    check, kwargs, Arity, Env, EnvVal, Error, Extern, KeywordId, Locals, NativeFn, NativeFnOp, Val,
+};
 
 /// Binding for ls_env builtin for dumping environment variables in current scope
 pub fn ls_env_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
-        doc: "(ls_env EXPR) - Returns list of symbols defined in environment".to_string(),
+        doc: "(ls_env [ENV]) - Returns list of symbols defined in current environment, or in ENV"
+            .to_string(),
+        arity: Some(Arity::range(0, 1)),
         func: |f, args| {
-            if !args.is_empty() {
-                return Err(Error::UnexpectedArguments(
-                    "ls_env is not expected to have arguments".to_string(),
-                ));
-            }
+            let env = match args {
+                [] => f.cur_env(),
+                [Val::Env(e)] => &e.0,
+                _ => {
+                    return Err(Error::UnexpectedArguments(
+                        "ls_env expects an optional environment".to_string(),
+                    ))
+                }
+            };
             let mut res = vec![];
             {
-                let env = f.cur_env().lock().unwrap();
+                let env = env.lock().unwrap();
                 for (sym, _) in env.iter() {
                     res.push(Val::Symbol(sym.clone()));
                 }
@@ -22,3 +30,127 @@ pub fn ls_env_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
         },
     }
 }
+
+/// Binding for check builtin for static checks against current environment
+pub fn check_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(check FORM) - Statically checks FORM against current environment without evaluating it. \
+              Returns list of warnings, each a list of warning kind keyword and message."
+            .to_string(),
+        arity: Some(Arity::exact(1)),
+        func: |f, args| {
+            let form = match args {
+                [form] => form,
+                _ => {
+                    return Err(Error::UnexpectedArguments(
+                        "check expects a single form as argument".to_string(),
+                    ))
+                }
+            };
+            let warnings = {
+                let env = f.cur_env().lock().unwrap();
+                check::check(form, &env)
+            };
+            let res = warnings
+                .into_iter()
+                .map(|w| Val::List(vec![Val::Keyword(w.kind()), Val::String(w.to_string())]))
+                .collect();
+            Ok(NativeFnOp::Return(Val::List(res)))
+        },
+    }
+}
+
+/// Binding to create new environments
+pub fn make_env_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(make_env [:base :standard|:core|:empty|:current|ENV] [:allow (SYMBOL ...)]) - Create new environment for evaluating forms with (eval FORM ENV). \
+              Base :standard has standard builtins and is the default, :core has builtins for working with values only, and :empty has no bindings. \
+              Base :current or ENV extends current environment or ENV, so its bindings are visible but definitions stay in new environment. \
+              With :allow, bindings of SYMBOLs in current environment are copied over, e.g. to grant access to selected process builtins."
+            .to_string(),
+        arity: Some(Arity::range(0, 4)),
+        func: |f, args| {
+            let mut env = match kwargs::get(args, &KeywordId::from("base")) {
+                None => Env::standard(),
+                Some(Val::Keyword(k)) if k.as_str() == "standard" => Env::standard(),
+                Some(Val::Keyword(k)) if k.as_str() == "core" => Env::core(),
+                Some(Val::Keyword(k)) if k.as_str() == "empty" => Env::empty(),
+                Some(Val::Keyword(k)) if k.as_str() == "current" => Env::extend(f.cur_env()),
+                Some(Val::Env(e)) => Env::extend(&e.0),
+                Some(v) => {
+                    return Err(Error::UnexpectedArguments(format!(
+                        "make_env :base should be :standard, :core, :empty, :current, or an environment - got {v}"
+                    )))
+                }
+            };
+            match kwargs::get(args, &KeywordId::from("allow")) {
+                None => (),
+                Some(Val::List(symbols)) => {
+                    let cur_env = f.cur_env().lock().unwrap();
+                    for s in symbols {
+                        let Val::Symbol(s) = s else {
+                            return Err(Error::UnexpectedArguments(format!(
+                                "make_env :allow expects a list of symbols - got {s}"
+                            )));
+                        };
+                        let val = cur_env
+                            .get(&s)
+                            .ok_or_else(|| Error::UndefinedSymbol(s.clone()))?;
+                        env.define(s, val);
+                    }
+                }
+                Some(v) => {
+                    return Err(Error::UnexpectedArguments(format!(
+                        "make_env :allow expects a list of symbols - got {v}"
+                    )))
+                }
+            }
+            Ok(NativeFnOp::Return(Val::Env(EnvVal::new(env))))
+        },
+    }
+}
+
+/// Binding to get symbol's value in environment
+pub fn env_get_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(env_get ENV SYMBOL) - Value of SYMBOL in environment ENV, raising an error if SYMBOL is undefined"
+            .to_string(),
+        arity: Some(Arity::exact(2)),
+        func: |f, args| match args {
+            [Val::Env(e), Val::Symbol(s)] => {
+                let val = e
+                    .0
+                    .lock()
+                    .unwrap()
+                    .get(s)
+                    .ok_or_else(|| Error::UndefinedSymbol(s.clone()))?;
+                let val = match val {
+                    Val::Dynamic(d) => f.dynamic_value(&d),
+                    v => v,
+                };
+                Ok(NativeFnOp::Return(val))
+            }
+            _ => Err(Error::UnexpectedArguments(
+                "env_get expects an environment and a symbol".to_string(),
+            )),
+        },
+    }
+}
+
+/// Binding to define symbol in environment
+pub fn env_define_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(env_define ENV SYMBOL VAL) - Define SYMBOL as VAL in environment ENV, returning VAL"
+            .to_string(),
+        arity: Some(Arity::exact(3)),
+        func: |_, args| match args {
+            [Val::Env(e), Val::Symbol(s), val] => {
+                e.0.lock().unwrap().define(s.clone(), val.clone());
+                Ok(NativeFnOp::Return(val.clone()))
+            }
+            _ => Err(Error::UnexpectedArguments(
+                "env_define expects an environment, a symbol, and a value".to_string(),
+            )),
+        },
+    }
+}
@@ -0,0 +1,312 @@
+//! JSON encoding and decoding builtins
+//!
+//! Values map to JSON as follows:
+//! - `nil` <-> `null`, booleans <-> booleans, strings <-> strings
+//! - Integers <-> numbers. Numbers that are not 32-bit integers, e.g. floats or millisecond
+//!   timestamps, decode as strings of their JSON representation.
+//! - Lists <-> arrays, except keyword alists like `(:a 1 :b 2)` which encode as objects
+//! - Objects decode as keyword alists. Keys that can't be read back as keywords, e.g. `"a b"`,
+//!   are kept as strings, like `(:id 1 "a b" 2)`. Records encode as objects of their fields.
+//! - Keywords and symbols encode as strings of their names
+//! - Times encode as RFC3339 strings, and durations as ISO 8601 duration strings
+//!
+//! Empty objects decode as `()`, which encodes back as an empty array.
+//!
+//! Other values like lambdas, refs, environments, or extern values like process ids have no JSON mapping, and
+//! fail to encode unless encoded with `:lossy` as strings of their display representation.
+use crate::{kwargs, Arity, Error, Extern, KeywordId, Locals, NativeFn, NativeFnOp, Result, Val};
+use serde_json::{Map, Number, Value};
+
+/// Options for encoding values as JSON
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EncodeOptions {
+    /// Pretty print over multiple lines
+    pub pretty: bool,
+    /// Encode values without JSON mapping as display strings instead of failing
+    pub lossy: bool,
+}
+
+/// Binding for `json_encode`
+pub(crate) fn json_encode_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(json_encode VAL [:pretty] [:lossy]) - Encode VAL as JSON string. \
+              Keyword alists and records encode as objects, and lists as arrays. \
+              With :pretty, JSON is printed over multiple lines. \
+              With :lossy, values without JSON mapping like lambdas encode as display strings instead of raising an error."
+            .to_string(),
+        arity: Some(Arity::range(1, 3)),
+        func: |_, args| match args {
+            [val, opts @ ..] => {
+                let opts = EncodeOptions {
+                    pretty: is_flag_set(opts, "pretty"),
+                    lossy: is_flag_set(opts, "lossy"),
+                };
+                Ok(NativeFnOp::Return(Val::String(encode(val, opts)?)))
+            }
+            _ => Err(Error::UnexpectedArguments(
+                "json_encode expects a value and optional flags".to_string(),
+            )),
+        },
+    }
+}
+
+/// Binding for `json_decode`
+pub(crate) fn json_decode_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(json_decode STRING) - Decode JSON STRING. \
+              Objects decode as keyword alists, arrays as lists, and null as nil. \
+              Numbers that are not 32-bit integers decode as strings."
+            .to_string(),
+        arity: Some(Arity::exact(1)),
+        func: |_, args| match args {
+            [Val::String(s)] => Ok(NativeFnOp::Return(decode(s)?)),
+            _ => Err(Error::UnexpectedArguments(
+                "json_decode expects a string".to_string(),
+            )),
+        },
+    }
+}
+
+/// Encode value as JSON string
+pub fn encode<T: Extern, L: Locals>(val: &Val<T, L>, opts: EncodeOptions) -> Result<String> {
+    let value = to_json(val, opts)?;
+    let res = if opts.pretty {
+        serde_json::to_string_pretty(&value)
+    } else {
+        serde_json::to_string(&value)
+    };
+    res.map_err(|e| Error::Serialization(format!("Failed to encode JSON - {e}")))
+}
+
+/// Decode value from JSON string
+pub fn decode<T: Extern, L: Locals>(s: &str) -> Result<Val<T, L>> {
+    let value: Value = serde_json::from_str(s)
+        .map_err(|e| Error::Serialization(format!("Failed to decode JSON - {e}")))?;
+    Ok(from_json(value))
+}
+
+/// Convert value to JSON value
+pub fn to_json<T: Extern, L: Locals>(val: &Val<T, L>, opts: EncodeOptions) -> Result<Value> {
+    let value = match val {
+        Val::Nil => Value::Null,
+        Val::Bool(b) => Value::Bool(*b),
+        Val::Int(i) => Value::Number(Number::from(*i)),
+        Val::String(s) => Value::String(s.clone()),
+        Val::Keyword(k) => Value::String(k.as_str().to_string()),
+        Val::Symbol(s) => Value::String(s.as_str().to_string()),
+        Val::Time(t) => Value::String(t.rfc3339()),
+        Val::Duration(d) => Value::String(d.0.to_string()),
+        Val::List(l) if is_alist(l) => {
+            let mut obj = Map::new();
+            for kv in l.chunks(2) {
+                let key = match &kv[0] {
+                    Val::Keyword(k) => k.as_str().to_string(),
+                    Val::String(s) => s.clone(),
+                    _ => unreachable!("alist keys are keywords or strings"),
+                };
+                obj.insert(key, to_json(&kv[1], opts)?);
+            }
+            Value::Object(obj)
+        }
+        Val::List(l) => Value::Array(
+            l.iter()
+                .map(|v| to_json(v, opts))
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        Val::Record(r) => {
+            let mut obj = Map::new();
+            for (k, v) in r.fields.iter() {
+                obj.insert(k.as_str().to_string(), to_json(v, opts)?);
+            }
+            Value::Object(obj)
+        }
+        Val::Lambda(_)
+        | Val::NativeFn(_)
+        | Val::NativeAsyncFn(_)
+        | Val::Bytecode(_)
+        | Val::Error(_)
+        | Val::Ref(_)
+        | Val::Seq(_)
+        | Val::Env(_)
+        | Val::Dynamic(_)
+        | Val::Extern(_) => {
+            if !opts.lossy {
+                return Err(Error::Serialization(format!(
+                    "{val} has no JSON representation - encode with :lossy to encode it as string"
+                )));
+            }
+            Value::String(val.to_string())
+        }
+    };
+    Ok(value)
+}
+
+/// Convert JSON value to value
+pub fn from_json<T: Extern, L: Locals>(value: Value) -> Val<T, L> {
+    match value {
+        Value::Null => Val::Nil,
+        Value::Bool(b) => Val::Bool(b),
+        Value::Number(n) => match n.as_i64().and_then(|i| i32::try_from(i).ok()) {
+            Some(i) => Val::Int(i),
+            None => Val::String(n.to_string()),
+        },
+        Value::String(s) => Val::String(s),
+        Value::Array(a) => Val::List(a.into_iter().map(from_json).collect()),
+        Value::Object(obj) => Val::List(
+            obj.into_iter()
+                .flat_map(|(k, v)| {
+                    let key = if is_keyword_name(&k) {
+                        Val::Keyword(KeywordId::from(k))
+                    } else {
+                        Val::String(k)
+                    };
+                    [key, from_json(v)]
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Whether list is a nonempty list alternating keys and values, where keys are keywords or
+/// strings that can't be keywords, and at least one key is a keyword
+fn is_alist<T: Extern, L: Locals>(l: &[Val<T, L>]) -> bool {
+    let keys = || l.iter().step_by(2);
+    !l.is_empty()
+        && l.len().is_multiple_of(2)
+        && keys().all(|k| match k {
+            Val::Keyword(_) => true,
+            Val::String(s) => !is_keyword_name(s),
+            _ => false,
+        })
+        && keys().any(|k| matches!(k, Val::Keyword(_)))
+}
+
+/// Whether or not object key reads back as keyword of same name
+fn is_keyword_name(key: &str) -> bool {
+    !key.is_empty()
+        && !key.contains(|ch: char| ch.is_whitespace() || matches!(ch, '(' | ')' | '\''))
+}
+
+fn is_flag_set<T: Extern, L: Locals>(opts: &[Val<T, L>], flag: &str) -> bool {
+    matches!(
+        kwargs::flag(opts, &KeywordId::from(flag)),
+        Some(Val::Bool(true))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Record;
+    use assert_matches::assert_matches;
+    use void::Void;
+
+    type Val = super::Val<Void, ()>;
+
+    #[test]
+    fn decode_values() {
+        let val: Val =
+            decode(r#"{"name": "vrs", "tags": ["a", 1, null, true], "nested": {"x": 1}}"#).unwrap();
+        assert_eq!(
+            val,
+            Val::from_expr(r#"(:name "vrs" :tags ("a" 1 nil true) :nested (:x 1))"#).unwrap()
+        );
+        assert_eq!(decode::<Void, ()>("{}").unwrap(), Val::List(vec![]));
+        assert_matches!(decode::<Void, ()>("{"), Err(Error::Serialization(_)));
+    }
+
+    #[test]
+    fn decode_numbers() {
+        assert_eq!(
+            decode::<Void, ()>("-2147483648").unwrap(),
+            Val::Int(i32::MIN)
+        );
+        assert_eq!(
+            decode::<Void, ()>(r#"{"pct": 0.5, "at": 1721030400000}"#).unwrap(),
+            Val::from_expr(r#"(:pct "0.5" :at "1721030400000")"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn decode_keys() {
+        let val: Val = decode(r#"{"id": 1, "a b": 2, "": 3, "(x)": 4}"#).unwrap();
+        assert_eq!(
+            val,
+            Val::from_expr(r#"(:id 1 "a b" 2 "" 3 "(x)" 4)"#).unwrap()
+        );
+        assert_eq!(
+            Val::from_expr(&val.to_string()).unwrap(),
+            val,
+            "Decoded objects should read back unchanged"
+        );
+    }
+
+    #[test]
+    fn encode_values() {
+        let val =
+            Val::from_expr(r#"(:name "vrs" :tags (a :b 1 nil) :pair (:ok 1) :empty ())"#).unwrap();
+        assert_eq!(
+            encode(&val, EncodeOptions::default()).unwrap(),
+            r#"{"name":"vrs","tags":["a","b",1,null],"pair":{"ok":1},"empty":[]}"#
+        );
+        assert_eq!(
+            encode(
+                &Val::from_expr("(:a 1)").unwrap(),
+                EncodeOptions {
+                    pretty: true,
+                    lossy: false
+                }
+            )
+            .unwrap(),
+            "{\n  \"a\": 1\n}"
+        );
+        assert_eq!(
+            encode(
+                &Val::from_expr(r#"((:a "b" "c" "d") ("a b" 1))"#).unwrap(),
+                EncodeOptions::default()
+            )
+            .unwrap(),
+            r#"[["a","b","c","d"],["a b",1]]"#,
+            "Lists with keys that aren't keywords encode as arrays"
+        );
+        let record = Val::Record(Record {
+            name: "point".into(),
+            fields: vec![("x".into(), Val::Int(1)), ("y".into(), Val::Int(2))],
+        });
+        assert_eq!(
+            encode(&record, EncodeOptions::default()).unwrap(),
+            r#"{"x":1,"y":2}"#
+        );
+    }
+
+    #[test]
+    fn encode_unsupported() {
+        let val = Val::List(vec![Val::keyword("f"), Val::NativeFn(json_encode_fn())]);
+        assert_matches!(
+            encode(&val, EncodeOptions::default()),
+            Err(Error::Serialization(_))
+        );
+        assert_eq!(
+            encode(
+                &val,
+                EncodeOptions {
+                    pretty: false,
+                    lossy: true
+                }
+            )
+            .unwrap(),
+            r#"{"f":"<nativefn>"}"#
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        for json in [
+            r#"{"items":[{"title":"a","id":1},{"title":"b","id":2}],"ok":true}"#,
+            r#"{"list":[],"pair":["ok",1],"a b":{"c":null}}"#,
+        ] {
+            let val: Val = decode(json).unwrap();
+            assert_eq!(encode(&val, EncodeOptions::default()).unwrap(), json);
+        }
+    }
+}
@@ -1,7 +1,8 @@
 //! List builtins
+use super::record;
 use crate::{
-    compile, kwargs, parse, Error, Extern, Inst, Lambda, Locals, NativeFn, NativeFnOp, SymbolId,
-    Val,
+    compile, kwargs, parse, Arity, Error, Extern, Inst, Lambda, Locals, NativeFn, NativeFnOp,
+    SymbolId, Val,
 };
 
 /// Language bindng for `list`
@@ -9,6 +10,7 @@ pub fn list_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(list ELEM_1 ELEM_2 .. ELEM_N) - Creates a new list containing arguments of form. Each argument ELEM is evaluated.\
               Arguments are optional.".to_string(),
+        arity: Some(Arity::at_least(0)),
         func: |_, args| Ok(NativeFnOp::Return(Val::List(args.to_vec()))),
     }
 }
@@ -17,6 +19,7 @@ pub fn list_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub fn push_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(push LIST ELEM) - Creates a new list containing elements of LIST with ELEM appended at end".to_string(),
+        arity: Some(Arity::exact(2)),
         func: |_, args| match args {
             [Val::List(l), elem] => {
                 let mut l = l.to_vec();
@@ -33,7 +36,8 @@ pub fn push_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 /// Language bindng for `get`
 pub fn get_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
-        doc: "(get LIST ATTR) - Returns element within LIST for given ATTR, which can be 0-indexed position in list, or keywords for association lists. Negative indexes return from end of list.".to_string(),
+        doc: "(get LIST ATTR) - Returns element within LIST for given ATTR, which can be 0-indexed position in list, or keywords for association lists. Getting undefined field of record is an error. Negative indexes return from end of list.".to_string(),
+        arity: Some(Arity::exact(2)),
         func: |_, x| match x {
             [Val::List(l), Val::Int(idx)] => {
                 let index = if *idx >= 0 {
@@ -51,8 +55,11 @@ pub fn get_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
             [Val::List(l), Val::Keyword(target)] => Ok(NativeFnOp::Return(
                 kwargs::get(l, target).unwrap_or(Val::Nil),
             )),
+            [Val::Record(r), Val::Keyword(field)] => Ok(NativeFnOp::Return(
+                record::get_field(r, field)?.clone(),
+            )),
             _ => Err(Error::UnexpectedArguments(
-                "get expects a list and indexing argument".to_string(),
+                "get expects a list or record and indexing argument".to_string(),
             )),
         },
     }
@@ -62,6 +69,7 @@ pub fn get_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub fn len_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(len LIST) - Returns number of elements in LIST".to_string(),
+        arity: Some(Arity::exact(1)),
         func: |_, x| match x {
             [Val::List(l)] => Ok(NativeFnOp::Return(Val::Int(l.len().try_into().unwrap()))),
             _ => Err(Error::UnexpectedArguments(
@@ -76,6 +84,7 @@ pub fn len_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub(crate) fn map_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(map LIST CALLABLE) - Creates a new list containing elements of LIST transformed by CALLABLE".to_string(),
+        arity: Some(Arity::exact(2)),
         func: |_, args| match args {
             [Val::List(l), val] if val.is_callable() => {
                 let mut bc = vec![Inst::GetSym(SymbolId::from("list"))];
@@ -114,7 +123,8 @@ pub(crate) fn filter_fn<T: Extern, L: Locals>() -> Lambda<T, L> {
             .unwrap()
             .into(),
         )
-        .unwrap(),
+        .unwrap()
+        .into(),
         parent: None,
     }
 }
@@ -1,11 +1,15 @@
 //! Math builtins
-use crate::{Extern, Locals, NativeFn, NativeFnOp, Result, Val};
+use crate::builtin::time;
+use crate::{Arity, Error, Extern, Locals, NativeFn, NativeFnOp, Result, Val};
+use std::cmp::Ordering;
 
 /// Native binding for `+`
 pub fn plus_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
-        doc: "(+ LHS RHS) - If LHS and RHS are integers, returns sum of LHS and RHS.\
-              If LHS and RHS are lists, returns a new list containing elements of LHS followed by elements of RHS.".to_string(),
+        doc: "(+ ARG1 ARG2 ... ARGN) - If arguments are integers, returns sum of arguments.\
+              If arguments are lists, returns a new list containing elements of each argument in order.\
+              If first argument is a time or duration, returns it with remaining durations added.".to_string(),
+        arity: Some(Arity::at_least(1)),
         func: |_, args| match args {
             [Val::Int(_), ..] => {
                 Ok(NativeFnOp::Return(
@@ -17,6 +21,13 @@ pub fn plus_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
                     plus_concat_list(args)?
                 ))
             }
+            [first @ (Val::Time(_) | Val::Duration(_)), rest @ ..] => {
+                let mut res = first.clone();
+                for arg in rest {
+                    res = time::add(&res, arg)?;
+                }
+                Ok(NativeFnOp::Return(res))
+            }
             _ => panic!("only supports ints"),
         },
     }
@@ -24,10 +35,12 @@ pub fn plus_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 
 /// Native binding for `+` for adding integers
 fn plus_add_ints<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Val<T, L>> {
-    let mut res = 0;
+    let mut res: i32 = 0;
     for arg in args {
         let num = arg.as_int()?;
-        res += num;
+        res = res
+            .checked_add(*num)
+            .ok_or_else(|| overflow_err("+", args))?;
     }
     Ok(Val::Int(res))
 }
@@ -42,4 +55,118 @@ fn plus_concat_list<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Val<T, L
     Ok(Val::List(result))
 }
 
+/// Native binding for `-`
+pub fn minus_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(- ARG1 ARG2 ... ARGN) - Returns ARG1 with remaining arguments subtracted, or negation of ARG1 if it is the only argument. \
+              Arguments can be integers, times and durations subtracted from a time, durations, or two times for duration between them.".to_string(),
+        arity: Some(Arity::at_least(1)),
+        func: |_, args| match args {
+            [Val::Int(i)] => Ok(NativeFnOp::Return(Val::Int(
+                i.checked_neg().ok_or_else(|| overflow_err("-", args))?,
+            ))),
+            [Val::Duration(d)] => Ok(NativeFnOp::Return(Val::Duration(time::Duration(
+                d.0.negate(),
+            )))),
+            [first, rest @ ..] if !rest.is_empty() => {
+                let mut res = first.clone();
+                for arg in rest {
+                    res = match (&res, arg) {
+                        (Val::Int(a), Val::Int(b)) => Val::Int(
+                            a.checked_sub(*b).ok_or_else(|| overflow_err("-", args))?,
+                        ),
+                        (lhs, rhs) => time::sub(lhs, rhs)?,
+                    };
+                }
+                Ok(NativeFnOp::Return(res))
+            }
+            _ => Err(Error::UnexpectedArguments(
+                "- expects integers, times, or durations".to_string(),
+            )),
+        },
+    }
+}
+
+/// Error for integer overflow in call to `func` with `args`
+fn overflow_err<T: Extern, L: Locals>(func: &str, args: &[Val<T, L>]) -> Error {
+    let args = args
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ");
+    Error::Runtime(format!("Integer overflow in ({func} {args})"))
+}
+
+/// Native binding for `<`
+pub fn lt_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc:
+            "(< ARG1 ARG2 ... ARGN) - Returns true if arguments are in strictly increasing order. \
+              Arguments should all be integers, strings, times, or durations."
+                .to_string(),
+        arity: Some(Arity::at_least(2)),
+        func: |_, args| compare_chain("<", args, Ordering::is_lt),
+    }
+}
+
+/// Native binding for `<=`
+pub fn le_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(<= ARG1 ARG2 ... ARGN) - Returns true if arguments are in nondecreasing order. \
+              Arguments should all be integers, strings, times, or durations."
+            .to_string(),
+        arity: Some(Arity::at_least(2)),
+        func: |_, args| compare_chain("<=", args, Ordering::is_le),
+    }
+}
+
+/// Native binding for `>`
+pub fn gt_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc:
+            "(> ARG1 ARG2 ... ARGN) - Returns true if arguments are in strictly decreasing order. \
+              Arguments should all be integers, strings, times, or durations."
+                .to_string(),
+        arity: Some(Arity::at_least(2)),
+        func: |_, args| compare_chain(">", args, Ordering::is_gt),
+    }
+}
+
+/// Native binding for `>=`
+pub fn ge_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(>= ARG1 ARG2 ... ARGN) - Returns true if arguments are in nonincreasing order. \
+              Arguments should all be integers, strings, times, or durations."
+            .to_string(),
+        arity: Some(Arity::at_least(2)),
+        func: |_, args| compare_chain(">=", args, Ordering::is_ge),
+    }
+}
+
+/// Check if each pair of adjacent arguments is ordered as expected
+fn compare_chain<T: Extern, L: Locals>(
+    name: &str,
+    args: &[Val<T, L>],
+    expected: fn(Ordering) -> bool,
+) -> Result<NativeFnOp<T, L>> {
+    if args.len() < 2 {
+        return Err(Error::UnexpectedArguments(format!(
+            "{name} expects at least two arguments"
+        )));
+    }
+    for pair in args.windows(2) {
+        let ord = match (&pair[0], &pair[1]) {
+            (Val::Int(a), Val::Int(b)) => a.cmp(b),
+            (Val::String(a), Val::String(b)) => a.cmp(b),
+            (a, b) => time::compare(a, b).ok_or_else(|| {
+                Error::UnexpectedArguments(format!("{name} cannot compare {a} and {b}"))
+            })??,
+        };
+        if !expected(ord) {
+            return Ok(NativeFnOp::Return(Val::Bool(false)));
+        }
+    }
+    Ok(NativeFnOp::Return(Val::Bool(true)))
+}
+
 // TODO: Write tests for +
@@ -1,12 +1,18 @@
 //! Builtin functions
 pub mod cond;
 pub mod docs;
+pub mod dynamic;
 pub mod env;
+pub mod json;
 pub mod list;
 pub mod log;
 pub mod math;
+pub mod protocol;
+pub mod record;
 pub mod refs;
+pub mod seq;
 pub mod string;
+pub mod time;
 pub mod types;
 
 // TODO: Builtins to instruction?
@@ -17,7 +23,13 @@ pub(crate) use cond::eq_fn;
 pub(crate) use cond::is_keyword_fn;
 pub(crate) use cond::not_fn;
 pub(crate) use docs::help_fn;
+pub(crate) use env::check_fn;
+pub(crate) use env::env_define_fn;
+pub(crate) use env::env_get_fn;
 pub(crate) use env::ls_env_fn;
+pub(crate) use env::make_env_fn;
+pub(crate) use json::json_decode_fn;
+pub(crate) use json::json_encode_fn;
 pub(crate) use list::filter_fn;
 pub(crate) use list::get_fn;
 pub(crate) use list::len_fn;
@@ -25,14 +37,35 @@ pub(crate) use list::list_fn;
 pub(crate) use list::map_fn;
 pub(crate) use list::push_fn;
 pub(crate) use log::dbg_fn;
+pub(crate) use math::ge_fn;
+pub(crate) use math::gt_fn;
+pub(crate) use math::le_fn;
+pub(crate) use math::lt_fn;
+pub(crate) use math::minus_fn;
 pub(crate) use math::plus_fn;
+pub(crate) use protocol::type_of_fn;
 pub(crate) use refs::ref_fn;
+pub(crate) use seq::iterate_fn;
+pub(crate) use seq::lazy_filter_fn;
+pub(crate) use seq::lazy_map_fn;
+pub(crate) use seq::next_fn;
+pub(crate) use seq::realize_fn;
+pub(crate) use seq::repeat_fn;
+pub(crate) use seq::seq_fn;
+pub(crate) use seq::take_fn;
 pub(crate) use string::display_fn;
 pub(crate) use string::format_fn;
 pub(crate) use string::join_fn;
+pub(crate) use string::pprint_fn;
 pub(crate) use string::read_fn;
 pub(crate) use string::split_fn;
 pub(crate) use string::str_fn;
+pub(crate) use time::duration_fn;
+pub(crate) use time::now_fn;
+pub(crate) use time::time_fields_fn;
+pub(crate) use time::time_format_fn;
+pub(crate) use time::time_in_tz_fn;
+pub(crate) use time::time_parse_fn;
 pub(crate) use types::err_fn;
 pub(crate) use types::ok_fn;
 
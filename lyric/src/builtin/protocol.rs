@@ -0,0 +1,284 @@
+//! Builtins for protocols defined by `defprotocol` and extended by `defimpl`
+//!
+//! Protocols are bound as `protocol` records with fields `:name`, `:doc`, `:methods` as list of
+//! `(METHOD PARAMS DOC)`, and `:impls` as dispatch table of `(TYPE METHOD CALLABLE)`.
+use crate::{
+    Arity, Error, Extern, Inst, KeywordId, Locals, NativeFn, NativeFnOp, Record, Result, SymbolId,
+    Val,
+};
+
+/// Name of record type for protocols
+const PROTOCOL: &str = "protocol";
+
+/// Type matching any value in `defimpl`
+const DEFAULT_TYPE: &str = "_";
+
+/// View of protocol record
+struct Protocol<'a, T: Extern, L: Locals> {
+    name: &'a SymbolId,
+    doc: Option<&'a String>,
+    methods: &'a [Val<T, L>],
+    impls: &'a [Val<T, L>],
+}
+
+/// Create a new protocol record with given methods and dispatch table
+pub(crate) fn protocol_val<T: Extern, L: Locals>(
+    name: &SymbolId,
+    doc: Option<&String>,
+    methods: Vec<Val<T, L>>,
+    impls: Vec<Val<T, L>>,
+) -> Val<T, L> {
+    Val::Record(Record {
+        name: SymbolId::from(PROTOCOL),
+        fields: vec![
+            (KeywordId::from("name"), Val::Symbol(name.clone())),
+            (
+                KeywordId::from("doc"),
+                doc.map(|d| Val::String(d.clone())).unwrap_or(Val::Nil),
+            ),
+            (KeywordId::from("methods"), Val::List(methods)),
+            (KeywordId::from("impls"), Val::List(impls)),
+        ],
+    })
+}
+
+/// Type used to dispatch protocol methods on given value - record name for records, keyword tag
+/// for lists starting with keywords, nil for nil, and builtin type name otherwise
+pub(crate) fn type_of<T: Extern, L: Locals>(val: &Val<T, L>) -> Val<T, L> {
+    let name = match val {
+        Val::Record(r) => return Val::Symbol(r.name.clone()),
+        Val::List(l) => match l.first() {
+            Some(Val::Keyword(k)) => return Val::Keyword(k.clone()),
+            _ => "list",
+        },
+        Val::Nil => return Val::Nil,
+        Val::Bool(_) => "bool",
+        Val::Int(_) => "int",
+        Val::String(_) => "string",
+        Val::Symbol(_) => "symbol",
+        Val::Keyword(_) => "keyword",
+        Val::Lambda(_) | Val::NativeFn(_) | Val::NativeAsyncFn(_) => "fn",
+        Val::Bytecode(_) => "bytecode",
+        Val::Error(_) => "error",
+        Val::Ref(_) => "ref",
+        Val::Seq(_) => "seq",
+        Val::Env(_) => "env",
+        Val::Dynamic(_) => "dynamic",
+        Val::Time(_) => "time",
+        Val::Duration(_) => "duration",
+        Val::Extern(_) => "extern",
+    };
+    Val::symbol(name)
+}
+
+/// Language binding for `type_of`
+pub(crate) fn type_of_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(type_of VAL) - Returns type protocol methods dispatch on for VAL - record name for records, keyword tag for lists starting with keyword, or builtin type name like int or string".to_string(),
+        arity: Some(Arity::exact(1)),
+        func: |_, args| match args {
+            [val] => Ok(NativeFnOp::Return(type_of(val))),
+            _ => Err(Error::UnexpectedArguments(
+                "type_of expects one argument".to_string(),
+            )),
+        },
+    }
+}
+
+/// Call implementation of protocol method for type of first argument
+pub(crate) fn protocol_dispatch_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(protocol_dispatch NAME METHOD ARG_1 .. ARG_N) - Call METHOD of protocol NAME implemented for type of ARG_1".to_string(),
+        arity: Some(Arity::at_least(2)),
+        func: |f, args| {
+            let (name, method, args) = match args {
+                [Val::Symbol(name), Val::Symbol(method), args @ ..] if !args.is_empty() => {
+                    (name, method, args)
+                }
+                _ => {
+                    return Err(Error::UnexpectedArguments(
+                        "protocol_dispatch expects a protocol name, method, and arguments"
+                            .to_string(),
+                    ))
+                }
+            };
+
+            // Protocol is bound in environment method closes over, outside scope of method
+            // parameters that may shadow it
+            let parent = f.cur_env().lock().unwrap().parent().cloned();
+            let proto_val = parent
+                .and_then(|p| p.lock().unwrap().get(name))
+                .ok_or_else(|| Error::UndefinedSymbol(name.clone()))?;
+            let proto = Protocol::from_val(&proto_val)?;
+
+            let ty = type_of(&args[0]);
+            let callable = proto.find_impl(&ty, method).ok_or_else(|| {
+                Error::NoImplementation(format!("{} of protocol {} for {}", method, name, ty))
+            })?;
+
+            let mut bc = vec![Inst::PushConst(callable.clone())];
+            bc.extend(args.iter().cloned().map(Inst::PushConst));
+            bc.push(Inst::CallFunc(args.len()));
+            Ok(NativeFnOp::Exec(bc))
+        },
+    }
+}
+
+/// Copy of protocol with implementations of methods for given type added
+pub(crate) fn protocol_extend_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(protocol_extend PROTOCOL TYPE METHOD_1 CALLABLE_1 .. METHOD_N CALLABLE_N) - Copy of PROTOCOL with METHODS implemented by CALLABLES for TYPE".to_string(),
+        arity: Some(Arity::at_least(2)),
+        func: |_, args| {
+            let (proto_val, ty, impls) = match args {
+                [proto, ty @ (Val::Symbol(_) | Val::Keyword(_) | Val::Nil), impls @ ..]
+                    if impls.len().is_multiple_of(2) =>
+                {
+                    (proto, ty, impls)
+                }
+                _ => {
+                    return Err(Error::UnexpectedArguments(
+                        "protocol_extend expects a protocol, type, and pairs of methods and callables".to_string(),
+                    ))
+                }
+            };
+            let proto = Protocol::from_val(proto_val)?;
+
+            let mut table = proto.impls.to_vec();
+            for kv in impls.chunks(2) {
+                let (method, callable) = match kv {
+                    [Val::Symbol(m), c] if c.is_callable() => (m, c),
+                    _ => {
+                        return Err(Error::UnexpectedArguments(
+                            "protocol_extend expects method symbols and callables".to_string(),
+                        ))
+                    }
+                };
+                let params = proto.method(method).ok_or_else(|| {
+                    Error::UnexpectedArguments(format!(
+                        "{method} is not a method of protocol {}",
+                        proto.name
+                    ))
+                })?;
+                if let Val::Lambda(l) = callable {
+                    if l.params.len() != params.len() {
+                        return Err(Error::UnexpectedArguments(format!(
+                            "{method} of protocol {} expects {} parameters - got {}",
+                            proto.name,
+                            params.len(),
+                            l.params.len()
+                        )));
+                    }
+                }
+                table.retain(|entry| match entry.as_list().map(|e| &e[..]) {
+                    Ok([t, Val::Symbol(m), _]) => !(t == ty && m == method),
+                    _ => true,
+                });
+                table.push(Val::List(vec![
+                    ty.clone(),
+                    Val::Symbol(method.clone()),
+                    callable.clone(),
+                ]));
+            }
+
+            Ok(NativeFnOp::Return(protocol_val(
+                proto.name,
+                proto.doc,
+                proto.methods.to_vec(),
+                table,
+            )))
+        },
+    }
+}
+
+/// Human readable description of protocol and its dispatch table, if value is a protocol
+pub(crate) fn describe<T: Extern, L: Locals>(val: &Val<T, L>) -> Option<String> {
+    let proto = Protocol::from_val(val).ok()?;
+    let mut out = format!("(defprotocol {})", proto.name);
+    if let Some(doc) = proto.doc {
+        out.push_str(&format!(" - {doc}"));
+    }
+
+    out.push_str("\nMethods:");
+    for m in proto.methods {
+        if let Ok([Val::Symbol(name), Val::List(params), doc]) = m.as_list().map(|m| &m[..]) {
+            let usage = std::iter::once(name.to_string())
+                .chain(params.iter().map(|p| p.to_string().to_uppercase()))
+                .collect::<Vec<_>>()
+                .join(" ");
+            match doc {
+                Val::String(doc) => out.push_str(&format!("\n  ({usage}) - {doc}")),
+                _ => out.push_str(&format!("\n  ({usage})")),
+            }
+        }
+    }
+
+    out.push_str("\nImplementations:");
+    let mut types: Vec<(String, Vec<String>)> = vec![];
+    for entry in proto.impls {
+        if let Ok([ty, method, _]) = entry.as_list().map(|e| &e[..]) {
+            let ty = ty.to_string();
+            match types.iter_mut().find(|(t, _)| *t == ty) {
+                Some((_, methods)) => methods.push(method.to_string()),
+                None => types.push((ty, vec![method.to_string()])),
+            }
+        }
+    }
+    if types.is_empty() {
+        out.push_str("\n  <none>");
+    }
+    for (ty, methods) in types {
+        out.push_str(&format!("\n  {ty} - {}", methods.join(" ")));
+    }
+    Some(out)
+}
+
+impl<'a, T: Extern, L: Locals> Protocol<'a, T, L> {
+    fn from_val(val: &'a Val<T, L>) -> Result<Self> {
+        let invalid = || Error::UnexpectedType(format!("expected protocol - got {val}"));
+        let record = match val {
+            Val::Record(r) if r.name.as_str() == PROTOCOL => r,
+            _ => return Err(invalid()),
+        };
+        let field = |name: &str| record.get(&KeywordId::from(name)).ok_or_else(invalid);
+        Ok(Self {
+            name: field("name")?.as_symbol()?,
+            doc: field("doc")?.as_string().ok(),
+            methods: field("methods")?.as_list()?,
+            impls: field("impls")?.as_list()?,
+        })
+    }
+
+    /// Parameters of given method, if protocol has method
+    fn method(&self, method: &SymbolId) -> Option<&'a [Val<T, L>]> {
+        self.methods
+            .iter()
+            .find_map(|m| match m.as_list().ok()?.as_slice() {
+                [Val::Symbol(name), Val::List(params), _] if name == method => {
+                    Some(params.as_slice())
+                }
+                _ => None,
+            })
+    }
+
+    /// Find implementation of method for type. Tagged lists fall back to `list` implementation,
+    /// and all types fall back to default `_` implementation.
+    fn find_impl(&self, ty: &Val<T, L>, method: &SymbolId) -> Option<&'a Val<T, L>> {
+        let mut candidates = vec![ty.clone()];
+        if matches!(ty, Val::Keyword(_)) {
+            candidates.push(Val::symbol("list"));
+        }
+        candidates.push(Val::symbol(DEFAULT_TYPE));
+
+        candidates.iter().find_map(|candidate| {
+            self.impls
+                .iter()
+                .find_map(|entry| match entry.as_list().ok()?.as_slice() {
+                    [t, Val::Symbol(m), callable] if t == candidate && m == method => {
+                        Some(callable)
+                    }
+                    _ => None,
+                })
+        })
+    }
+}
@@ -0,0 +1,108 @@
+//! Builtins for record types defined by `defrecord`
+//!
+//! These are not bound in environment. `defrecord` expands into lambdas calling them with record
+//! name and fields quoted.
+use crate::{
+    Arity, Error, Extern, KeywordId, Locals, NativeFn, NativeFnOp, Record, Result, SymbolId, Val,
+};
+
+/// Create a new record from name, field keywords, and field values
+pub(crate) fn make_record_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(make_record NAME FIELDS VALUE_1 .. VALUE_N) - Create record NAME with FIELDS set to VALUES".to_string(),
+        arity: Some(Arity::at_least(2)),
+        func: |_, args| match args {
+            [Val::Symbol(name), Val::List(fields), values @ ..] if fields.len() == values.len() => {
+                let fields = fields
+                    .iter()
+                    .zip(values)
+                    .map(|(k, v)| Ok((k.as_keyword()?.clone(), v.clone())))
+                    .collect::<Result<_>>()?;
+                Ok(NativeFnOp::Return(Val::Record(Record {
+                    name: name.clone(),
+                    fields,
+                })))
+            }
+            _ => Err(Error::UnexpectedArguments(
+                "make_record expects a name, field list, and value for each field".to_string(),
+            )),
+        },
+    }
+}
+
+/// Check if value is a record of given name
+pub(crate) fn is_record_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(is_record NAME VAL) - Returns true if VAL is a record NAME, otherwise false"
+            .to_string(),
+        arity: Some(Arity::exact(2)),
+        func: |_, args| match args {
+            [Val::Symbol(name), val] => Ok(NativeFnOp::Return(Val::Bool(
+                matches!(val, Val::Record(r) if &r.name == name),
+            ))),
+            _ => Err(Error::UnexpectedArguments(
+                "is_record expects a name and value".to_string(),
+            )),
+        },
+    }
+}
+
+/// Get field of record of given name
+pub(crate) fn record_get_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(record_get NAME FIELD RECORD) - Get FIELD of RECORD, which must be a record NAME"
+            .to_string(),
+        arity: Some(Arity::exact(3)),
+        func: |_, args| match args {
+            [Val::Symbol(name), Val::Keyword(field), val] => {
+                let record = expect_record(name, val)?;
+                Ok(NativeFnOp::Return(get_field(record, field)?.clone()))
+            }
+            _ => Err(Error::UnexpectedArguments(
+                "record_get expects a name, field, and record".to_string(),
+            )),
+        },
+    }
+}
+
+/// Copy record of given name with a single field updated
+pub(crate) fn record_update_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(record_update NAME RECORD FIELD VALUE) - Copy of RECORD, which must be a record NAME, with FIELD set to VALUE".to_string(),
+        arity: Some(Arity::exact(4)),
+        func: |_, args| match args {
+            [Val::Symbol(name), val, Val::Keyword(field), value] => {
+                let mut record = expect_record(name, val)?.clone();
+                get_field(&record, field)?;
+                for (k, v) in record.fields.iter_mut() {
+                    if k == field {
+                        *v = value.clone();
+                    }
+                }
+                Ok(NativeFnOp::Return(Val::Record(record)))
+            }
+            _ => Err(Error::UnexpectedArguments(
+                "record_update expects a name, record, field keyword, and value".to_string(),
+            )),
+        },
+    }
+}
+
+/// Get field of record, or error if record type has no such field
+pub(crate) fn get_field<'a, V>(record: &'a Record<V>, field: &KeywordId) -> Result<&'a V> {
+    record.get(field).ok_or_else(|| {
+        Error::UnexpectedArguments(format!("{} record has no field {}", record.name, field))
+    })
+}
+
+fn expect_record<'a, T: Extern, L: Locals>(
+    name: &SymbolId,
+    val: &'a Val<T, L>,
+) -> Result<&'a Record<Val<T, L>>> {
+    match val {
+        Val::Record(r) if &r.name == name => Ok(r),
+        _ => Err(Error::UnexpectedType(format!(
+            "expected {name} record - got {val}"
+        ))),
+    }
+}
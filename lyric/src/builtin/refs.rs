@@ -1,19 +1,30 @@
 //! Builtins for unique reference type
 //! This type is used to fill similar function as `make_ref()` in Erlang
-use crate::{Extern, Locals, NativeFn, NativeFnOp, Val};
+use crate::{Arity, Extern, Locals, NativeFn, NativeFnOp, Val};
 use nanoid::nanoid;
 
 /// Unique reference type
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Ref(pub(crate) String);
 
+impl Ref {
+    /// Create a new unique reference
+    pub fn new() -> Self {
+        Self(nanoid!())
+    }
+}
+
+impl Default for Ref {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Binding to create a new unique reference
 pub fn ref_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(ref) - Creates a new unique reference in runtime".to_string(),
-        func: |_, _| {
-            let r = Ref(nanoid!());
-            Ok(NativeFnOp::Return(Val::Ref(r)))
-        },
+        arity: Some(Arity::exact(0)),
+        func: |_, _| Ok(NativeFnOp::Return(Val::Ref(Ref::new()))),
     }
 }
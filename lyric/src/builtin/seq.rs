@@ -0,0 +1,213 @@
+//! Builtins for lazy sequences
+//!
+//! Sequences are created from lists, or from generator functions yielding their elements. Lambdas
+//! below refer to `seq`, `next`, and `end_of_seq` natives directly, so they are unaffected by
+//! bindings shadowing them.
+use crate::{
+    compile, parse, Arity, Bytecode, Error, Extern, Lambda, Locals, NativeFn, NativeFnOp, Seq,
+    SymbolId, Val,
+};
+
+/// Binding for `seq`
+pub(crate) fn seq_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(seq SOURCE) - Lazy sequence of elements of list SOURCE, or of values yielded by calling SOURCE with no arguments. Sequences are returned as is.".to_string(),
+        arity: Some(Arity::exact(1)),
+        func: |_, args| match args {
+            [src] => Ok(NativeFnOp::Return(Val::Seq(to_seq(src)?))),
+            _ => Err(Error::UnexpectedArguments(
+                "seq expects one argument".to_string(),
+            )),
+        },
+    }
+}
+
+/// Binding for `next`
+pub(crate) fn next_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(next SEQ) - Next element of sequence SEQ. Raises an end of sequence error when SEQ is exhausted.".to_string(),
+        arity: Some(Arity::exact(1)),
+        func: |_, args| match args {
+            [Val::Seq(s)] => Ok(NativeFnOp::Next(s.clone())),
+            _ => Err(Error::UnexpectedArguments(
+                "next expects a sequence".to_string(),
+            )),
+        },
+    }
+}
+
+/// Binding for `take`
+pub(crate) fn take_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(take SEQ N) - Lazy sequence of up to N first elements of SEQ".to_string(),
+        arity: Some(Arity::exact(2)),
+        func: |_, args| match args {
+            [src, Val::Int(n)] if *n >= 0 => Ok(NativeFnOp::Return(Val::Seq(Seq::take(
+                to_seq(src)?,
+                *n as usize,
+            )))),
+            _ => Err(Error::UnexpectedArguments(
+                "take expects a sequence and a nonnegative count".to_string(),
+            )),
+        },
+    }
+}
+
+/// Binding for `lazy_map`
+pub(crate) fn lazy_map_fn<T: Extern, L: Locals>() -> Lambda<T, L> {
+    Lambda {
+        doc: Some(
+            "(lazy_map SEQ CALLABLE) - Lazy sequence of CALLABLE applied to elements of SEQ"
+                .to_string(),
+        ),
+        params: vec![SymbolId::from("src"), SymbolId::from("callable")],
+        code: compile_with_natives(
+            r#"
+            (begin
+                (def it (seq src))
+                (seq (fn () (loop (yield (callable (next it)))))))
+        "#,
+        )
+        .into(),
+        parent: None,
+    }
+}
+
+/// Binding for `lazy_filter`
+pub(crate) fn lazy_filter_fn<T: Extern, L: Locals>() -> Lambda<T, L> {
+    Lambda {
+        doc: Some(
+            "(lazy_filter SEQ CALLABLE) - Lazy sequence of elements of SEQ filtered by CALLABLE"
+                .to_string(),
+        ),
+        params: vec![SymbolId::from("src"), SymbolId::from("callable")],
+        code: compile_with_natives(
+            r#"
+            (begin
+                (def it (seq src))
+                (seq (fn () (loop
+                    (def elem (next it))
+                    (if (callable elem) (yield elem))))))
+        "#,
+        )
+        .into(),
+        parent: None,
+    }
+}
+
+/// Binding for `iterate`
+pub(crate) fn iterate_fn<T: Extern, L: Locals>() -> Lambda<T, L> {
+    Lambda {
+        doc: Some("(iterate CALLABLE INIT) - Infinite lazy sequence of INIT, (CALLABLE INIT), (CALLABLE (CALLABLE INIT)), and so on".to_string()),
+        params: vec![SymbolId::from("callable"), SymbolId::from("init")],
+        code: compile_with_natives(
+            r#"
+            (seq (fn () (begin
+                (def elem init)
+                (loop
+                    (yield elem)
+                    (set elem (callable elem))))))
+        "#,
+        ).into(),
+        parent: None,
+    }
+}
+
+/// Binding for `repeat`
+pub(crate) fn repeat_fn<T: Extern, L: Locals>() -> Lambda<T, L> {
+    Lambda {
+        doc: Some("(repeat VAL) - Infinite lazy sequence repeating VAL".to_string()),
+        params: vec![SymbolId::from("val")],
+        code: compile_with_natives("(seq (fn () (loop (yield val))))").into(),
+        parent: None,
+    }
+}
+
+/// Binding for `realize`
+pub(crate) fn realize_fn<T: Extern, L: Locals>() -> Lambda<T, L> {
+    Lambda {
+        doc: Some(
+            "(realize SEQ) - List of all elements of SEQ. Never returns for infinite sequences."
+                .to_string(),
+        ),
+        params: vec![SymbolId::from("src")],
+        code: compile_with_natives(
+            r#"
+            (begin
+                (def it (seq src))
+                (def realized (list))
+                (end_of_seq
+                    (try (loop (set realized (push realized (next it)))))
+                    realized))
+        "#,
+        )
+        .into(),
+        parent: None,
+    }
+}
+
+/// Call callable on each element of sequence. Not bound in environment - `doseq` expands into it.
+pub(crate) fn for_each_fn<T: Extern, L: Locals>() -> Lambda<T, L> {
+    Lambda {
+        doc: Some("(for_each SEQ CALLABLE) - Call CALLABLE on each element of SEQ".to_string()),
+        params: vec![SymbolId::from("src"), SymbolId::from("callable")],
+        code: compile_with_natives(
+            r#"
+            (begin
+                (def it (seq src))
+                (end_of_seq (try (loop (callable (next it)))) nil))
+        "#,
+        )
+        .into(),
+        parent: None,
+    }
+}
+
+/// Return given value if result is end of sequence error, otherwise raise error in result
+fn end_of_seq_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(end_of_seq RESULT VAL) - VAL if RESULT is end of sequence error, otherwise RESULT or its error raised".to_string(),
+        arity: Some(Arity::exact(2)),
+        func: |_, args| match args {
+            [Val::Error(Error::EndOfSeq), val] => Ok(NativeFnOp::Return(val.clone())),
+            [Val::Error(e), _] => Err(e.clone()),
+            [res, _] => Ok(NativeFnOp::Return(res.clone())),
+            _ => Err(Error::UnexpectedArguments(
+                "end_of_seq expects a result and a value".to_string(),
+            )),
+        },
+    }
+}
+
+/// Sequence from sequence, list, or generator function
+fn to_seq<T: Extern, L: Locals>(src: &Val<T, L>) -> crate::Result<Seq<T, L>> {
+    match src {
+        Val::Seq(s) => Ok(s.clone()),
+        Val::List(l) => Ok(Seq::from_list(l.clone())),
+        c if c.is_callable() => Ok(Seq::from_fn(c.clone())),
+        _ => Err(Error::UnexpectedType(format!(
+            "expected sequence, list, or callable - got {src}"
+        ))),
+    }
+}
+
+/// Compile builtin source with `seq`, `next`, and `end_of_seq` symbols replaced by natives
+fn compile_with_natives<T: Extern, L: Locals>(src: &str) -> Bytecode<T, L> {
+    let natives = [
+        ("seq", Val::NativeFn(seq_fn())),
+        ("next", Val::NativeFn(next_fn())),
+        ("end_of_seq", Val::NativeFn(end_of_seq_fn())),
+    ];
+    compile(&substitute(parse(src).unwrap().into(), &natives)).unwrap()
+}
+
+fn substitute<T: Extern, L: Locals>(val: Val<T, L>, natives: &[(&str, Val<T, L>)]) -> Val<T, L> {
+    match val {
+        Val::Symbol(s) => match natives.iter().find(|(name, _)| *name == s.as_str()) {
+            Some((_, native)) => native.clone(),
+            None => Val::Symbol(s),
+        },
+        Val::List(l) => Val::List(l.into_iter().map(|v| substitute(v, natives)).collect()),
+        v => v,
+    }
+}
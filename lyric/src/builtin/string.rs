@@ -1,10 +1,11 @@
-use crate::{Error, Extern, Locals, NativeFn, NativeFnOp, Result, Val};
+use crate::{pprint, Arity, Error, Extern, Locals, NativeFn, NativeFnOp, Result, Val};
 use dyn_fmt::AsStrFormatExt;
 
 pub(crate) fn str_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(str ARG1 ARG2 ... ARGN) - Returns a new string by concatenating each argument coerced into string.\
               Arguments are optional.".to_string(),
+        arity: Some(Arity::at_least(0)),
         func: |_, args| {
             let mut result = String::new();
             for v in args {
@@ -18,6 +19,7 @@ pub(crate) fn str_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub(crate) fn display_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(display ARG1 ARG2 ... ARGN) - Returns a new string by concatenating each argument as a display string.".to_string(),
+        arity: Some(Arity::at_least(0)),
         func: |_, args| {
             let mut result = String::new();
             for v in args {
@@ -31,6 +33,7 @@ pub(crate) fn display_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub(crate) fn join_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(join SEP ARG1 ARG2 ... ARGN) - Returns a new string by concatenating each argument separated by SEP.".to_string(),
+        arity: Some(Arity::at_least(1)),
         func: |_, args| {
             let separator = args
                 .first()
@@ -54,6 +57,7 @@ pub(crate) fn join_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub(crate) fn split_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(split SEP STR) - Returns a list separating string STR by SEP.".to_string(),
+        arity: Some(Arity::exact(2)),
         func: |_, args| {
             let substrings = match args {
                 [Val::String(sep), Val::String(string)] => string.split(sep),
@@ -72,6 +76,7 @@ pub(crate) fn split_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub(crate) fn format_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(format FORMAT ARG1 ARG2 ... ARGN) - Returns a new string by templating FORMAT with arguments coerced into strings.".to_string(),
+        arity: Some(Arity::at_least(1)),
         func: |_, args| {
             let format = args
                 .first()
@@ -95,6 +100,7 @@ pub(crate) fn format_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub(crate) fn read_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(read STRING) - Returns a symbolic expression by parsing STRING.".to_string(),
+        arity: Some(Arity::exact(1)),
         func: |_, args| {
             let expr = match args {
                 [Val::String(s)] => s,
@@ -113,6 +119,27 @@ pub(crate) fn read_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     }
 }
 
+pub(crate) fn pprint_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(pprint VAL [WIDTH]) - Returns a pretty printed string of VAL, breaking lines to fit within WIDTH columns.".to_string(),
+        arity: Some(Arity::range(1, 2)),
+        func: |_, args| {
+            let (val, width) = match args {
+                [val] => (val, pprint::DEFAULT_WIDTH),
+                [val, Val::Int(width)] if *width > 0 => (val, *width as usize),
+                _ => {
+                    return Err(Error::UnexpectedArguments(
+                        "pprint expects a value and optional positive integer width".to_string(),
+                    ))
+                }
+            };
+            Ok(NativeFnOp::Return(Val::String(pprint::pretty_val(
+                val, width,
+            ))))
+        },
+    }
+}
+
 // TODO: Test cases for str:
 // (str "a " "b " "c")
 // (str "a" " " "b" " " "c") # => "a b c"
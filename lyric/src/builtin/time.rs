@@ -0,0 +1,467 @@
+//! Builtins for times and durations
+//!
+//! Times are instants within a time zone, and durations are spans of calendar and clock units like
+//! 1 year or 90 minutes. Calendar units are relative - adding a month to a time adds a calendar
+//! month in its time zone, and comparing durations with calendar units compares them from now.
+use crate::{kwargs, Arity, Error, Extern, KeywordId, Locals, NativeFn, NativeFnOp, Result, Val};
+use jiff::{
+    civil,
+    fmt::{
+        strtime::BrokenDownTime,
+        temporal::{Pieces, PiecesOffset},
+    },
+    tz::TimeZone,
+    Span, Unit, Zoned,
+};
+use std::cmp::Ordering;
+
+/// Instant in time within a time zone
+#[derive(Debug, Clone, PartialEq)]
+pub struct Time(pub Zoned);
+
+/// Span of calendar and clock units
+#[derive(Debug, Clone)]
+pub struct Duration(pub Span);
+
+/// Units accepted by `duration`, from largest to smallest
+const UNITS: &[&str] = &[
+    "years", "months", "weeks", "days", "hours", "minutes", "seconds", "millis",
+];
+
+/// Binding for `now`
+pub(crate) fn now_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(now [TZ]) - Current time in system time zone, or in time zone named TZ like \"America/New_York\"".to_string(),
+        arity: Some(Arity::range(0, 1)),
+        func: |_, args| {
+            let now = Zoned::now();
+            let now = match args {
+                [] => now,
+                [Val::String(tz)] => in_tz(&now, tz)?,
+                _ => {
+                    return Err(Error::UnexpectedArguments(
+                        "now expects an optional time zone name".to_string(),
+                    ))
+                }
+            };
+            Ok(NativeFnOp::Return(Val::Time(Time(now))))
+        },
+    }
+}
+
+/// Binding for `duration`
+pub(crate) fn duration_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(duration [:years N] [:months N] [:weeks N] [:days N] [:hours N] [:minutes N] [:seconds N] [:millis N]) - Duration of given units. \
+              Durations can also be parsed from strings, e.g. (duration \"1h 30m\") or (duration \"PT1H30M\")."
+            .to_string(),
+        arity: Some(Arity::at_least(0)),
+        func: |_, args| {
+            let span = match args {
+                [Val::String(s)] => s.parse::<Span>().map_err(|e| {
+                    Error::UnexpectedArguments(format!("Invalid duration {s} - {e}"))
+                })?,
+                _ => span_of_units(args)?,
+            };
+            Ok(NativeFnOp::Return(Val::Duration(Duration(span))))
+        },
+    }
+}
+
+/// Binding for `time_parse`
+pub(crate) fn time_parse_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(time_parse STR [FORMAT]) - Parse time from RFC3339 string like \"2024-07-14T21:14:00-04:00\", optionally with time zone like \"2024-07-14T21:14:00-04:00[America/New_York]\". \
+              With FORMAT, STR is parsed with strftime-style format like \"%Y-%m-%d %H:%M\". \
+              Times without offset or time zone are in system time zone."
+            .to_string(),
+        arity: Some(Arity::range(1, 2)),
+        func: |_, args| {
+            let time = match args {
+                [Val::String(s)] => parse_rfc3339(s),
+                [Val::String(s), Val::String(format)] => parse_format(s, format),
+                _ => {
+                    return Err(Error::UnexpectedArguments(
+                        "time_parse expects a string and optional format string".to_string(),
+                    ))
+                }
+            }
+            .map_err(|e| Error::UnexpectedArguments(format!("Invalid time {} - {e}", args[0])))?;
+            Ok(NativeFnOp::Return(Val::Time(Time(time))))
+        },
+    }
+}
+
+/// Binding for `time_format`
+pub(crate) fn time_format_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(time_format TIME [FORMAT]) - Format TIME as RFC3339 string, or with strftime-style FORMAT like \"%a %b %e %H:%M %Z\"".to_string(),
+        arity: Some(Arity::range(1, 2)),
+        func: |_, args| {
+            let formatted = match args {
+                [Val::Time(t)] => t.rfc3339(),
+                [Val::Time(t), Val::String(format)] => BrokenDownTime::from(&t.0)
+                    .to_string(format)
+                    .map_err(|e| {
+                        Error::UnexpectedArguments(format!("Invalid time format {format} - {e}"))
+                    })?,
+                _ => {
+                    return Err(Error::UnexpectedArguments(
+                        "time_format expects a time and optional format string".to_string(),
+                    ))
+                }
+            };
+            Ok(NativeFnOp::Return(Val::String(formatted)))
+        },
+    }
+}
+
+/// Binding for `time_in_tz`
+pub(crate) fn time_in_tz_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(time_in_tz TIME TZ) - Same instant as TIME in time zone named TZ like \"Asia/Seoul\" or \"UTC\"".to_string(),
+        arity: Some(Arity::exact(2)),
+        func: |_, args| match args {
+            [Val::Time(t), Val::String(tz)] => {
+                Ok(NativeFnOp::Return(Val::Time(Time(in_tz(&t.0, tz)?))))
+            }
+            _ => Err(Error::UnexpectedArguments(
+                "time_in_tz expects a time and time zone name".to_string(),
+            )),
+        },
+    }
+}
+
+/// Binding for `time_fields`
+pub(crate) fn time_fields_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(time_fields TIME) - Calendar and clock fields of TIME in its time zone, as (:year Y :month M :day D :hour H :minute M :second S :weekday W :tz TZ). \
+              Weekday counts from 1 for Monday to 7 for Sunday."
+            .to_string(),
+        arity: Some(Arity::exact(1)),
+        func: |_, args| match args {
+            [Val::Time(t)] => {
+                let z = &t.0;
+                let fields = [
+                    ("year", Val::Int(z.year().into())),
+                    ("month", Val::Int(z.month().into())),
+                    ("day", Val::Int(z.day().into())),
+                    ("hour", Val::Int(z.hour().into())),
+                    ("minute", Val::Int(z.minute().into())),
+                    ("second", Val::Int(z.second().into())),
+                    (
+                        "weekday",
+                        Val::Int(z.weekday().to_monday_one_offset().into()),
+                    ),
+                    ("tz", Val::String(t.tz_name())),
+                ];
+                Ok(NativeFnOp::Return(Val::List(
+                    fields
+                        .into_iter()
+                        .flat_map(|(k, v)| [Val::keyword(k), v])
+                        .collect(),
+                )))
+            }
+            _ => Err(Error::UnexpectedArguments(
+                "time_fields expects a time".to_string(),
+            )),
+        },
+    }
+}
+
+/// Sum of time or durations with durations
+pub(crate) fn add<T: Extern, L: Locals>(lhs: &Val<T, L>, rhs: &Val<T, L>) -> Result<Val<T, L>> {
+    match (lhs, rhs) {
+        (Val::Time(t), Val::Duration(d)) => Ok(Val::Time(Time(
+            t.0.checked_add(d.0).map_err(arithmetic_err)?,
+        ))),
+        (Val::Duration(a), Val::Duration(b)) => Ok(Val::Duration(Duration(
+            a.0.checked_add((b.0, &Zoned::now()))
+                .map_err(arithmetic_err)?,
+        ))),
+        _ => Err(Error::UnexpectedArguments(format!(
+            "Cannot add {rhs} to {lhs} - expected durations added to time or duration"
+        ))),
+    }
+}
+
+/// Difference of times, or of time or durations with durations
+pub(crate) fn sub<T: Extern, L: Locals>(lhs: &Val<T, L>, rhs: &Val<T, L>) -> Result<Val<T, L>> {
+    match (lhs, rhs) {
+        (Val::Time(t), Val::Duration(d)) => Ok(Val::Time(Time(
+            t.0.checked_sub(d.0).map_err(arithmetic_err)?,
+        ))),
+        (Val::Time(a), Val::Time(b)) => Ok(Val::Duration(Duration(
+            a.0.since(&b.0).map_err(arithmetic_err)?,
+        ))),
+        (Val::Duration(a), Val::Duration(b)) => Ok(Val::Duration(Duration(
+            a.0.checked_sub((b.0, &Zoned::now()))
+                .map_err(arithmetic_err)?,
+        ))),
+        _ => Err(Error::UnexpectedArguments(format!(
+            "Cannot subtract {rhs} from {lhs} - expected times or durations"
+        ))),
+    }
+}
+
+/// Order of two times or two durations, if values are comparable
+pub(crate) fn compare<T: Extern, L: Locals>(
+    lhs: &Val<T, L>,
+    rhs: &Val<T, L>,
+) -> Option<Result<Ordering>> {
+    match (lhs, rhs) {
+        (Val::Time(a), Val::Time(b)) => Some(Ok(a.0.cmp(&b.0))),
+        (Val::Duration(a), Val::Duration(b)) => {
+            Some(a.0.compare((b.0, &Zoned::now())).map_err(arithmetic_err))
+        }
+        _ => None,
+    }
+}
+
+impl Time {
+    /// Current time in system time zone
+    pub fn now() -> Self {
+        Self(Zoned::now())
+    }
+
+    /// Time as RFC3339 string with offset of its time zone
+    pub fn rfc3339(&self) -> String {
+        self.0
+            .timestamp()
+            .display_with_offset(self.0.offset())
+            .to_string()
+    }
+
+    /// Name of time zone, or its offset if it has no name
+    pub fn tz_name(&self) -> String {
+        match self.0.time_zone().iana_name() {
+            Some(name) => name.to_string(),
+            None => self.0.offset().to_string(),
+        }
+    }
+}
+
+impl Duration {
+    /// Duration until given time from now, or zero if time is in the past
+    pub fn until(time: &Time) -> std::time::Duration {
+        time.0
+            .duration_since(&Zoned::now())
+            .try_into()
+            .unwrap_or_default()
+    }
+
+    /// Duration from now as clock time, or zero if duration is negative
+    pub fn from_now(&self) -> Result<std::time::Duration> {
+        let now = Zoned::now();
+        let secs = self.0.total((Unit::Second, &now)).map_err(arithmetic_err)?;
+        if secs <= 0.0 {
+            return Ok(std::time::Duration::ZERO);
+        }
+        std::time::Duration::try_from_secs_f64(secs)
+            .map_err(|e| Error::Runtime(format!("Invalid duration {self} - {e}")))
+    }
+}
+
+impl PartialEq for Duration {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.fieldwise() == other.0.fieldwise()
+    }
+}
+
+impl std::fmt::Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<time {}>", self.0)
+    }
+}
+
+impl std::fmt::Display for Duration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<duration {:#}>", self.0)
+    }
+}
+
+/// Span of units given as keyword arguments
+fn span_of_units<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Span> {
+    if !args.len().is_multiple_of(2)
+        || args
+            .iter()
+            .step_by(2)
+            .any(|k| !matches!(k, Val::Keyword(k) if UNITS.contains(&k.as_str())))
+    {
+        return Err(Error::UnexpectedArguments(format!(
+            "duration expects pairs of units and integers, with units in :{}",
+            UNITS.join(" :")
+        )));
+    }
+
+    let mut span = Span::new();
+    for unit in UNITS {
+        let n = match kwargs::get(args, &KeywordId::from(*unit)) {
+            Some(Val::Int(n)) => i64::from(n),
+            Some(v) => {
+                return Err(Error::UnexpectedArguments(format!(
+                    "duration expects integer for :{unit} - got {v}"
+                )))
+            }
+            None => continue,
+        };
+        span = match *unit {
+            "years" => span.try_years(n),
+            "months" => span.try_months(n),
+            "weeks" => span.try_weeks(n),
+            "days" => span.try_days(n),
+            "hours" => span.try_hours(n),
+            "minutes" => span.try_minutes(n),
+            "seconds" => span.try_seconds(n),
+            _ => span.try_milliseconds(n),
+        }
+        .map_err(|e| Error::UnexpectedArguments(format!("Invalid duration - {e}")))?;
+    }
+    Ok(span)
+}
+
+/// Parse RFC3339 time, with optional RFC9557 time zone annotation
+fn parse_rfc3339(s: &str) -> std::result::Result<Zoned, jiff::Error> {
+    let pieces = Pieces::parse(s)?;
+    if pieces.time_zone_annotation().is_some() {
+        return s.parse();
+    }
+    let tz = match pieces.offset() {
+        Some(PiecesOffset::Zulu) => TimeZone::UTC,
+        Some(offset) => TimeZone::fixed(offset.to_numeric_offset()),
+        None => TimeZone::system(),
+    };
+    let time = pieces.time().unwrap_or(civil::Time::midnight());
+    pieces.date().to_datetime(time).to_zoned(tz)
+}
+
+/// Parse time with strftime-style format
+fn parse_format(s: &str, format: &str) -> std::result::Result<Zoned, jiff::Error> {
+    let tm = BrokenDownTime::parse(format, s)?;
+    if tm.offset().is_some() || tm.iana_time_zone().is_some() {
+        tm.to_zoned()
+    } else {
+        tm.to_datetime()?.to_zoned(TimeZone::system())
+    }
+}
+
+fn in_tz(time: &Zoned, tz: &str) -> Result<Zoned> {
+    time.in_tz(tz)
+        .map_err(|e| Error::UnexpectedArguments(format!("Invalid time zone {tz} - {e}")))
+}
+
+fn arithmetic_err(e: jiff::Error) -> Error {
+    Error::Runtime(format!("Invalid time arithmetic - {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use void::Void;
+
+    type Val = super::Val<Void, ()>;
+
+    fn time(s: &str) -> Val {
+        Val::Time(Time(parse_rfc3339(s).unwrap()))
+    }
+
+    fn duration(s: &str) -> Val {
+        Val::Duration(Duration(s.parse().unwrap()))
+    }
+
+    #[test]
+    fn parse_and_format() {
+        let t = parse_rfc3339("2024-07-14T21:14:00-04:00").unwrap();
+        assert_eq!(Time(t.clone()).rfc3339(), "2024-07-14T21:14:00-04:00");
+        assert_eq!(Time(t).tz_name(), "-04");
+
+        let t = parse_rfc3339("2024-07-14T21:14:00Z").unwrap();
+        assert_eq!(Time(t).tz_name(), "UTC");
+
+        let t = parse_rfc3339("2024-07-14T21:14:00-04:00[America/New_York]").unwrap();
+        assert_eq!(Time(t.clone()).tz_name(), "America/New_York");
+        assert_eq!(
+            BrokenDownTime::from(&t)
+                .to_string("%Y/%m/%d %H:%M")
+                .unwrap(),
+            "2024/07/14 21:14"
+        );
+
+        let t = parse_format("2024-07-14 21:14 +09:00", "%Y-%m-%d %H:%M %:z").unwrap();
+        assert_eq!(Time(t).rfc3339(), "2024-07-14T21:14:00+09:00");
+
+        assert!(parse_rfc3339("July 14th").is_err());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let t = time("2024-01-31T00:00:00Z");
+        assert_eq!(
+            add(&t, &duration("1 month")).unwrap(),
+            time("2024-02-29T00:00:00Z")
+        );
+        assert_eq!(
+            sub(&t, &duration("1d 12h")).unwrap(),
+            time("2024-01-29T12:00:00Z")
+        );
+        assert_eq!(
+            sub(&time("2024-02-01T06:00:00Z"), &t).unwrap(),
+            duration("30h")
+        );
+        assert_eq!(
+            add(&duration("1h"), &duration("30m")).unwrap(),
+            duration("1h 30m")
+        );
+        assert_matches!(add(&t, &t), Err(Error::UnexpectedArguments(_)));
+    }
+
+    #[test]
+    fn from_now() {
+        let hour: jiff::Span = "1h".parse().unwrap();
+        assert_eq!(
+            Duration(hour).from_now().unwrap(),
+            std::time::Duration::from_secs(3600)
+        );
+        assert_eq!(
+            Duration(hour.negate()).from_now().unwrap(),
+            std::time::Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_matches!(
+            compare(
+                &time("2024-01-01T00:00:00Z"),
+                &time("2024-01-01T09:00:00+09:00")
+            ),
+            Some(Ok(Ordering::Equal))
+        );
+        assert_matches!(
+            compare(&duration("90m"), &duration("1h")),
+            Some(Ok(Ordering::Greater))
+        );
+        assert_matches!(
+            compare(&duration("1 year"), &duration("364d")),
+            Some(Ok(Ordering::Greater))
+        );
+        assert_matches!(compare(&duration("1h"), &Val::Int(1)), None);
+    }
+
+    #[test]
+    fn units() {
+        let span = span_of_units::<Void, ()>(&[
+            Val::keyword("hours"),
+            Val::Int(1),
+            Val::keyword("minutes"),
+            Val::Int(30),
+        ])
+        .unwrap();
+        assert_eq!(Val::Duration(Duration(span)), duration("1h 30m"));
+        assert_matches!(
+            span_of_units::<Void, ()>(&[Val::keyword("fortnights"), Val::Int(1)]),
+            Err(Error::UnexpectedArguments(_))
+        );
+    }
+}
@@ -1,10 +1,11 @@
 //! Builtins for types
 
-use crate::{Error, Extern, Locals, NativeFn, NativeFnOp, Val};
+use crate::{Arity, Error, Extern, Locals, NativeFn, NativeFnOp, Val};
 
 pub(crate) fn ok_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(ok? FORM) - Returns false if FORM is an error value, otherwise true".to_string(),
+        arity: Some(Arity::exact(1)),
         func: |_, args| {
             if args.len() != 1 {
                 return Err(Error::UnexpectedArguments(
@@ -22,6 +23,7 @@ pub(crate) fn ok_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
 pub(crate) fn err_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
     NativeFn {
         doc: "(err? FORM) - Returns true if FORM is an error value, otherwise false".to_string(),
+        arity: Some(Arity::exact(1)),
         func: |_, args| {
             if args.len() != 1 {
                 return Err(Error::UnexpectedArguments(
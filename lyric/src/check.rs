@@ -0,0 +1,929 @@
+//! Static checks for Lyric expressions
+//!
+//! Checks an expression against an [Env] without running it, reporting:
+//! - Symbols that are not bound in any enclosing scope or the environment
+//! - Calls to known functions with unexpected number of arguments
+//! - Special forms that would fail to compile
+//! - `def` and `defn` shadowing bindings of enclosing scopes or the environment
+//! - Expressions that never run, e.g. those following `loop`
+use crate::codegen::{
+    comprehension_parts, defimpl_parts, defn_clauses, defprotocol_parts, defrecord_parts, RecordDef,
+};
+use crate::{Env, Error, Extern, KeywordId, Locals, SymbolId, Val};
+use std::collections::HashMap;
+
+/// Warning reported by static checks
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    UnboundSymbol(SymbolId),
+    ArityMismatch {
+        name: SymbolId,
+        expected: Arity,
+        got: usize,
+    },
+    MalformedForm(String),
+    Shadowing(SymbolId),
+    Unreachable(String),
+}
+
+/// Number of arguments accepted by a callable
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+/// Check given expression against bindings in environment
+pub fn check<T: Extern, L: Locals>(expr: &Val<T, L>, env: &Env<T, L>) -> Vec<Warning> {
+    let mut checker = Checker {
+        env,
+        scopes: vec![HashMap::new()],
+        warnings: vec![],
+    };
+    checker.hoist(expr);
+    checker.check_expr(expr);
+    checker.warnings
+}
+
+impl Warning {
+    /// Keyword identifying kind of warning
+    pub fn kind(&self) -> KeywordId {
+        KeywordId::from(match self {
+            Warning::UnboundSymbol(_) => "unbound_symbol",
+            Warning::ArityMismatch { .. } => "arity_mismatch",
+            Warning::MalformedForm(_) => "malformed_form",
+            Warning::Shadowing(_) => "shadowing",
+            Warning::Unreachable(_) => "unreachable",
+        })
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::UnboundSymbol(s) => write!(f, "Unbound symbol - {s}"),
+            Warning::ArityMismatch {
+                name,
+                expected,
+                got,
+            } => write!(f, "{name} expects {expected} - got {got}"),
+            Warning::MalformedForm(msg) => write!(f, "Malformed expression - {msg}"),
+            Warning::Shadowing(s) => write!(f, "Definition of {s} shadows an existing binding"),
+            Warning::Unreachable(expr) => write!(f, "Unreachable expression after loop - {expr}"),
+        }
+    }
+}
+
+impl Arity {
+    /// Arity accepting exactly `n` arguments
+    pub fn exact(n: usize) -> Self {
+        Self {
+            min: n,
+            max: Some(n),
+        }
+    }
+
+    /// Arity accepting `min` or more arguments
+    pub fn at_least(min: usize) -> Self {
+        Self { min, max: None }
+    }
+
+    /// Arity accepting from `min` to `max` arguments
+    pub fn range(min: usize, max: usize) -> Self {
+        Self {
+            min,
+            max: Some(max),
+        }
+    }
+
+    /// Check if given number of arguments is accepted
+    pub fn accepts(&self, n: usize) -> bool {
+        n >= self.min && self.max.is_none_or(|max| n <= max)
+    }
+
+    /// Arity of callable value, if known
+    pub fn of_val<T: Extern, L: Locals>(val: &Val<T, L>) -> Option<Self> {
+        match val {
+            Val::Lambda(l) => Some(Self::exact(l.params.len())),
+            Val::NativeFn(f) => f.arity,
+            Val::NativeAsyncFn(f) => f.arity,
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |n: usize| if n == 1 { "argument" } else { "arguments" };
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{max} {}", plural(max)),
+            Some(max) => write!(f, "{} to {max} arguments", self.min),
+            None => write!(f, "at least {} {}", self.min, plural(self.min)),
+        }
+    }
+}
+
+/// Walks expression with stack of lexical scopes
+struct Checker<'a, T: Extern, L: Locals> {
+    env: &'a Env<T, L>,
+    /// Symbols bound in each scope, with arity if binding is a known callable
+    scopes: Vec<HashMap<SymbolId, Option<Arity>>>,
+    warnings: Vec<Warning>,
+}
+
+impl<T: Extern, L: Locals> Checker<'_, T, L> {
+    fn check_expr(&mut self, expr: &Val<T, L>) {
+        match expr {
+            Val::Symbol(s) if self.lookup(s).is_none() => {
+                self.warn(Warning::UnboundSymbol(s.clone()));
+            }
+            Val::List(l) => match l.split_first() {
+                None => self.malformed("Empty list expression"),
+                Some((Val::Symbol(s), args)) => match s.as_str() {
+                    "begin" | "loop" => self.check_body(args),
+                    "def" => self.check_def(args),
+                    "defn" => self.check_defn(args),
+                    "defrecord" => self.check_defrecord(args),
+                    "defprotocol" => self.check_defprotocol(args),
+                    "defimpl" => self.check_defimpl(args),
+                    "fn" => self.check_fn(args),
+                    "lambda" => self.check_lambda(args),
+                    "let" => self.check_let(args),
+                    "match" => self.check_match(args),
+                    "receive" => self.check_receive(args),
+                    "select" => self.check_select(args),
+                    "for" | "doseq" => self.check_comprehension(s.as_str(), args),
+                    "set" => self.check_set(args),
+                    "if" => self.check_if(args),
+                    "cond" => self.check_cond(args),
+                    "quote" => self.check_nargs("quote", args, Arity::exact(1)),
+                    "defdynamic" => self.check_defdynamic(args),
+                    "parameterize" => self.check_parameterize(args),
+                    "try" => {
+                        self.check_nargs("try", args, Arity::exact(1));
+                        self.check_body(args);
+                    }
+                    "eval" => {
+                        self.check_nargs("eval", args, Arity::range(1, 2));
+                        self.check_body(args);
+                    }
+                    "yield" => {
+                        self.check_nargs("yield", args, Arity::range(0, 1));
+                        self.check_body(args);
+                    }
+                    _ => self.check_call(s, args),
+                },
+                Some((f, args)) => {
+                    self.check_expr(f);
+                    self.check_body(args);
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Check sequence of expressions evaluated in order
+    fn check_body(&mut self, body: &[Val<T, L>]) {
+        for (idx, expr) in body.iter().enumerate() {
+            self.check_expr(expr);
+            if is_form(expr, "loop") {
+                if let Some(next) = body.get(idx + 1) {
+                    self.warn(Warning::Unreachable(next.to_string()));
+                }
+                return;
+            }
+        }
+    }
+
+    fn check_call(&mut self, name: &SymbolId, args: &[Val<T, L>]) {
+        match self.lookup(name) {
+            None => self.warn(Warning::UnboundSymbol(name.clone())),
+            Some(Some(arity)) if !arity.accepts(args.len()) => {
+                self.warn(Warning::ArityMismatch {
+                    name: name.clone(),
+                    expected: arity,
+                    got: args.len(),
+                });
+            }
+            Some(_) => (),
+        }
+        self.check_body(args);
+    }
+
+    fn check_def(&mut self, args: &[Val<T, L>]) {
+        match args {
+            [Val::Symbol(s), value] => {
+                self.check_shadowing(s);
+                self.check_expr(value);
+            }
+            [pat, value] => {
+                for s in pattern_symbols(pat) {
+                    self.check_shadowing(&s);
+                }
+                self.check_expr(value);
+            }
+            _ => self.malformed("def expects a symbol or pattern and a value"),
+        }
+    }
+
+    fn check_defn(&mut self, args: &[Val<T, L>]) {
+        match args {
+            [Val::Symbol(name), rest @ ..] => {
+                self.check_shadowing(name);
+                match defn_clauses(rest) {
+                    Some(Ok(defn)) => {
+                        for c in defn.clauses {
+                            let symbols = c.patterns.iter().flat_map(pattern_symbols).collect();
+                            let body = c.guard.into_iter().chain(c.body).cloned();
+                            self.with_scope(symbols, &body.collect::<Vec<_>>());
+                        }
+                    }
+                    Some(Err(Error::InvalidExpression(msg))) => self.malformed(&msg),
+                    Some(Err(e)) => self.malformed(&e.to_string()),
+                    None => self.check_fn_parts("defn", rest),
+                }
+            }
+            _ => self.malformed("defn expects a symbol as name"),
+        }
+    }
+
+    fn check_defrecord(&mut self, args: &[Val<T, L>]) {
+        match defrecord_parts(args) {
+            Ok(def) => {
+                for (symbol, _) in record_bindings(&def) {
+                    self.check_shadowing(&symbol);
+                }
+            }
+            Err(Error::InvalidExpression(msg)) => self.malformed(&msg),
+            Err(e) => self.malformed(&e.to_string()),
+        }
+    }
+
+    fn check_defprotocol(&mut self, args: &[Val<T, L>]) {
+        match defprotocol_parts(args) {
+            Ok(def) => {
+                self.check_shadowing(def.name);
+                for m in def.methods {
+                    self.check_shadowing(m.name);
+                }
+            }
+            Err(Error::InvalidExpression(msg)) => self.malformed(&msg),
+            Err(e) => self.malformed(&e.to_string()),
+        }
+    }
+
+    fn check_defimpl(&mut self, args: &[Val<T, L>]) {
+        match defimpl_parts(args) {
+            Ok(def) => {
+                if self.lookup(def.protocol).is_none() {
+                    self.warn(Warning::UnboundSymbol(def.protocol.clone()));
+                }
+                for m in def.methods {
+                    self.check_scope("defimpl", m.params, m.body);
+                }
+            }
+            Err(Error::InvalidExpression(msg)) => self.malformed(&msg),
+            Err(e) => self.malformed(&e.to_string()),
+        }
+    }
+
+    fn check_fn(&mut self, args: &[Val<T, L>]) {
+        self.check_fn_parts("fn", args);
+    }
+
+    /// Check `(PARAMS [DOC] BODY...)` shared by `fn` and `defn`
+    fn check_fn_parts(&mut self, name: &str, args: &[Val<T, L>]) {
+        let (params, body) = match args {
+            [params, Val::String(_), body @ ..] if !body.is_empty() => (params, body),
+            [params, body @ ..] if !body.is_empty() => (params, body),
+            _ => {
+                return self.malformed(&format!(
+                    "{name} expects a parameter list and nonempty body"
+                ))
+            }
+        };
+        self.check_scope(name, params, body);
+    }
+
+    fn check_lambda(&mut self, args: &[Val<T, L>]) {
+        match args {
+            [params, Val::String(_), body] | [params, body] => {
+                self.check_scope("lambda", params, std::slice::from_ref(body))
+            }
+            _ => self.malformed("lambda expects a parameter list, optional doc, and body"),
+        }
+    }
+
+    /// Check body in new scope binding given parameter list
+    fn check_scope(&mut self, name: &str, params: &Val<T, L>, body: &[Val<T, L>]) {
+        let params = match params {
+            Val::List(params) => params
+                .iter()
+                .map(|p| match p {
+                    Val::Symbol(s) => Some(s.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>(),
+            _ => None,
+        };
+        let Some(params) = params else {
+            return self.malformed(&format!("{name} expects a list of symbols as parameters"));
+        };
+        self.with_scope(params, body);
+    }
+
+    fn check_let(&mut self, args: &[Val<T, L>]) {
+        let Some((Val::List(bindings), body)) = args.split_first() else {
+            return self.malformed("let expects a list of bindings");
+        };
+
+        let mut symbols = vec![];
+        for b in bindings {
+            match b {
+                Val::List(pair) => match &pair[..] {
+                    [Val::Symbol(s), value] => {
+                        self.check_expr(value);
+                        symbols.push(s.clone());
+                    }
+                    _ => return self.malformed("let bindings should be a symbol and value pair"),
+                },
+                _ => return self.malformed("let bindings should be lists"),
+            }
+        }
+        self.with_scope(symbols, body);
+    }
+
+    fn check_defdynamic(&mut self, args: &[Val<T, L>]) {
+        match args {
+            [Val::Symbol(s), default] => {
+                self.check_shadowing(s);
+                self.check_expr(default);
+            }
+            _ => self.malformed("defdynamic expects a symbol and a default value"),
+        }
+    }
+
+    fn check_parameterize(&mut self, args: &[Val<T, L>]) {
+        let Some((Val::List(bindings), body)) = args.split_first() else {
+            return self.malformed("parameterize expects a list of bindings");
+        };
+
+        for b in bindings {
+            match b {
+                Val::List(pair) => match &pair[..] {
+                    [Val::Symbol(s), value] => {
+                        if self.lookup(s).is_none() {
+                            self.warn(Warning::UnboundSymbol(s.clone()));
+                        }
+                        self.check_expr(value);
+                    }
+                    _ => {
+                        return self
+                            .malformed("parameterize bindings should be a symbol and value pair")
+                    }
+                },
+                _ => return self.malformed("parameterize bindings should be lists"),
+            }
+        }
+        self.check_body(body);
+    }
+
+    fn check_match(&mut self, args: &[Val<T, L>]) {
+        let Some((expr, clauses)) = args.split_first() else {
+            return self.malformed("match expects at least one argument");
+        };
+        self.check_expr(expr);
+        for c in clauses {
+            match c {
+                Val::List(c) => match &c[..] {
+                    [pat, body] => {
+                        self.with_scope(pattern_symbols(pat), std::slice::from_ref(body))
+                    }
+                    _ => self.malformed("match clause list expects two elements"),
+                },
+                _ => self.malformed("match clauses should be lists"),
+            }
+        }
+    }
+
+    fn check_receive(&mut self, args: &[Val<T, L>]) {
+        for (idx, c) in args.iter().enumerate() {
+            match c {
+                Val::List(c) => match &c[..] {
+                    [Val::Symbol(s), timeout, body] if s.as_str() == "after" => {
+                        if idx != args.len() - 1 {
+                            self.malformed("after clause should be last clause of receive");
+                        }
+                        self.check_expr(timeout);
+                        self.check_expr(body);
+                    }
+                    [pat, body] => {
+                        self.with_scope(pattern_symbols(pat), std::slice::from_ref(body))
+                    }
+                    _ => self.malformed("receive clause list expects two elements"),
+                },
+                _ => self.malformed("receive clauses should be lists"),
+            }
+        }
+    }
+
+    fn check_select(&mut self, args: &[Val<T, L>]) {
+        for (idx, c) in args.iter().enumerate() {
+            match c {
+                Val::List(c) => match &c[..] {
+                    [Val::Symbol(s), timeout, body] if s.as_str() == "after" => {
+                        if idx != args.len() - 1 {
+                            self.malformed("after clause should be last clause of select");
+                        }
+                        self.check_expr(timeout);
+                        self.check_expr(body);
+                    }
+                    [Val::List(source), body] => match &source[..] {
+                        [Val::Symbol(s), pat] if s.as_str() == "recv" => {
+                            self.with_scope(pattern_symbols(pat), std::slice::from_ref(body))
+                        }
+                        [Val::Symbol(s), topic, pat] if s.as_str() == "topic" => {
+                            self.check_expr(topic);
+                            self.with_scope(pattern_symbols(pat), std::slice::from_ref(body))
+                        }
+                        [Val::Symbol(s), tref] if s.as_str() == "timer" => {
+                            self.check_expr(tref);
+                            self.check_expr(body);
+                        }
+                        [Val::Symbol(s), pid, rest @ ..] if s.as_str() == "exit" && rest.len() <= 1 => {
+                            self.check_expr(pid);
+                            let symbols = rest.first().map(pattern_symbols).unwrap_or_default();
+                            self.with_scope(symbols, std::slice::from_ref(body))
+                        }
+                        _ => self.malformed(
+                            "select source should be (recv PAT), (topic TOPIC PAT), (timer REF) or (exit PID [REASON])",
+                        ),
+                    },
+                    _ => self.malformed("select clause list expects a source and body"),
+                },
+                _ => self.malformed("select clauses should be lists"),
+            }
+        }
+    }
+
+    fn check_comprehension(&mut self, name: &str, args: &[Val<T, L>]) {
+        match comprehension_parts(name, args) {
+            Ok(c) => {
+                self.check_expr(c.seq);
+                let body = c.guard.into_iter().chain(c.body).cloned();
+                self.with_scope(vec![c.binding.clone()], &body.collect::<Vec<_>>());
+            }
+            Err(Error::InvalidExpression(msg)) => self.malformed(&msg),
+            Err(e) => self.malformed(&e.to_string()),
+        }
+    }
+
+    fn check_set(&mut self, args: &[Val<T, L>]) {
+        match args {
+            [Val::Symbol(s), value] => {
+                if self.lookup(s).is_none() {
+                    self.warn(Warning::UnboundSymbol(s.clone()));
+                }
+                self.check_expr(value);
+            }
+            _ => self.malformed("set expects a symbol and a value"),
+        }
+    }
+
+    fn check_if(&mut self, args: &[Val<T, L>]) {
+        self.check_nargs("if", args, Arity::range(2, 3));
+        self.check_body(args);
+    }
+
+    fn check_cond(&mut self, args: &[Val<T, L>]) {
+        for clause in args {
+            match clause {
+                Val::List(c) if c.len() == 2 => self.check_body(c),
+                _ => self.malformed("cond clauses should be lists with two elements"),
+            }
+        }
+    }
+
+    /// Check number of arguments to special form
+    fn check_nargs(&mut self, name: &str, args: &[Val<T, L>], arity: Arity) {
+        if !arity.accepts(args.len()) {
+            self.malformed(&format!("{name} expects {arity} - got {}", args.len()));
+        }
+    }
+
+    /// Check body within new scope binding given symbols
+    fn with_scope(&mut self, symbols: Vec<SymbolId>, body: &[Val<T, L>]) {
+        self.scopes
+            .push(symbols.into_iter().map(|s| (s, None)).collect());
+        for expr in body {
+            self.hoist(expr);
+        }
+        self.check_body(body);
+        self.scopes.pop();
+    }
+
+    /// Bind definitions in current scope ahead of checking, so forward references within a scope
+    /// (e.g. mutually recursive functions) are not reported as unbound
+    fn hoist(&mut self, expr: &Val<T, L>) {
+        let Val::List(l) = expr else {
+            return;
+        };
+        match &l[..] {
+            [Val::Symbol(f), Val::Symbol(s), value] if f.as_str() == "def" => {
+                self.bind(s.clone(), form_arity(value));
+                self.hoist(value);
+            }
+            [Val::Symbol(f), pat, value] if f.as_str() == "def" => {
+                for s in pattern_symbols(pat) {
+                    self.bind(s, None);
+                }
+                self.hoist(value);
+            }
+            [Val::Symbol(f), Val::Symbol(s), default] if f.as_str() == "defdynamic" => {
+                self.bind(s.clone(), None);
+                self.hoist(default);
+            }
+            [Val::Symbol(f), Val::Symbol(name), rest @ ..] if f.as_str() == "defn" => {
+                let arity = match (defn_clauses(rest), rest.first()) {
+                    (Some(Ok(defn)), _) => Some(Arity::exact(defn.clauses[0].patterns.len())),
+                    (None, Some(Val::List(params))) => Some(Arity::exact(params.len())),
+                    _ => None,
+                };
+                self.bind(name.clone(), arity);
+            }
+            [Val::Symbol(f), args @ ..] if f.as_str() == "defprotocol" => {
+                if let Ok(def) = defprotocol_parts(args) {
+                    self.bind(def.name.clone(), None);
+                    for m in def.methods {
+                        self.bind(m.name.clone(), Some(Arity::exact(m.params.len())));
+                    }
+                }
+            }
+            [Val::Symbol(f), args @ ..] if f.as_str() == "defrecord" => {
+                if let Ok(def) = defrecord_parts(args) {
+                    for (symbol, arity) in record_bindings(&def) {
+                        self.bind(symbol, Some(arity));
+                    }
+                }
+            }
+            [Val::Symbol(f), ..]
+                if matches!(
+                    f.as_str(),
+                    "quote"
+                        | "defn"
+                        | "fn"
+                        | "lambda"
+                        | "let"
+                        | "match"
+                        | "receive"
+                        | "select"
+                        | "defimpl"
+                        | "for"
+                        | "doseq"
+                ) => {}
+            _ => {
+                for expr in l {
+                    self.hoist(expr);
+                }
+            }
+        }
+    }
+
+    /// Bind symbol in current scope
+    fn bind(&mut self, symbol: SymbolId, arity: Option<Arity>) {
+        self.scopes
+            .last_mut()
+            .expect("Checker should have a scope")
+            .insert(symbol, arity);
+    }
+
+    /// Lookup binding for symbol, returning arity of binding if it is known
+    fn lookup(&self, symbol: &SymbolId) -> Option<Option<Arity>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.get(symbol).cloned())
+            .or_else(|| self.env.get(symbol).map(|v| Arity::of_val(&v)))
+    }
+
+    /// Warn if symbol defined in current scope shadows enclosing scope or environment
+    fn check_shadowing(&mut self, symbol: &SymbolId) {
+        let outer = &self.scopes[..self.scopes.len() - 1];
+        if outer.iter().any(|s| s.contains_key(symbol)) || self.env.get(symbol).is_some() {
+            self.warn(Warning::Shadowing(symbol.clone()));
+        }
+    }
+
+    fn malformed(&mut self, msg: &str) {
+        self.warn(Warning::MalformedForm(msg.to_string()));
+    }
+
+    fn warn(&mut self, warning: Warning) {
+        self.warnings.push(warning);
+    }
+}
+
+/// Check if expression is a list starting with given symbol
+fn is_form<T: Extern, L: Locals>(expr: &Val<T, L>, name: &str) -> bool {
+    matches!(expr, Val::List(l) if matches!(l.first(), Some(Val::Symbol(s)) if s.as_str() == name))
+}
+
+/// Arity of `fn` or `lambda` form, if given expression is one
+fn form_arity<T: Extern, L: Locals>(expr: &Val<T, L>) -> Option<Arity> {
+    match expr {
+        Val::List(l) if is_form(expr, "fn") || is_form(expr, "lambda") => match l.get(1) {
+            Some(Val::List(params)) => Some(Arity::exact(params.len())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Symbols defined by `defrecord`, with arity of each
+fn record_bindings(def: &RecordDef) -> Vec<(SymbolId, Arity)> {
+    let mut bindings = vec![
+        (def.name.clone(), Arity::exact(def.fields.len())),
+        (def.predicate(), Arity::exact(1)),
+        (def.updater(), Arity::exact(3)),
+    ];
+    bindings.extend(
+        def.fields
+            .iter()
+            .map(|f| (def.accessor(f), Arity::exact(1))),
+    );
+    bindings
+}
+
+/// Symbols bound by pattern
+fn pattern_symbols<T: Extern, L: Locals>(pat: &Val<T, L>) -> Vec<SymbolId> {
+    match pat {
+        Val::Symbol(s) if s.as_str() != "_" => vec![s.clone()],
+        Val::List(_) if is_form(pat, "quote") => vec![],
+        Val::List(l) if is_form(pat, "record") => {
+            l.iter().skip(2).flat_map(pattern_symbols).collect()
+        }
+        Val::List(l) => l.iter().flat_map(pattern_symbols).collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use void::Void;
+
+    type Val = super::Val<Void, Void>;
+    type Env = super::Env<Void, Void>;
+
+    fn check_expr(expr: &str) -> Vec<Warning> {
+        check(&Val::from_expr(expr).unwrap(), &Env::standard())
+    }
+
+    #[test]
+    fn no_warnings() {
+        assert_eq!(check_expr("(+ 1 2)"), vec![]);
+        assert_eq!(
+            check_expr(
+                "(begin
+                   (defn add (x y) \"Adds\" (+ x y))
+                   (def result (add 1 2))
+                   (let ((a 1) (b 2)) (add a b))
+                   (match '(:ok 1)
+                     ((:ok v) v)
+                     (_ nil))
+                   (receive
+                     ((:ok v) v)
+                     (after 10 nil))
+                   (select
+                     ((recv (:ok v)) v)
+                     ((topic :updates u) u)
+                     ((exit result (:error e)) e)
+                     (after 10 nil))
+                   '(undefined symbols (are quoted)))"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn unbound_symbol() {
+        assert_eq!(
+            check_expr("(foo bar)"),
+            vec![
+                Warning::UnboundSymbol(SymbolId::from("foo")),
+                Warning::UnboundSymbol(SymbolId::from("bar")),
+            ]
+        );
+        assert_eq!(
+            check_expr("(set x 1)"),
+            vec![Warning::UnboundSymbol(SymbolId::from("x"))]
+        );
+        assert_eq!(
+            check_expr("(begin (fn (x) x) x)"),
+            vec![Warning::UnboundSymbol(SymbolId::from("x"))],
+            "Parameters should only be bound within function body"
+        );
+        assert_eq!(
+            check_expr("(begin (for (x '(1 2) :when (eq? x y)) x) x)"),
+            vec![
+                Warning::UnboundSymbol(SymbolId::from("y")),
+                Warning::UnboundSymbol(SymbolId::from("x")),
+            ],
+            "Comprehension binding should only be bound within guard and body"
+        );
+        assert_eq!(
+            check_expr(
+                "(begin (defdynamic prefix \"\") (parameterize ((prefix \">\") (level 1)) prefix))"
+            ),
+            vec![Warning::UnboundSymbol(SymbolId::from("level"))]
+        );
+    }
+
+    #[test]
+    fn forward_reference() {
+        assert_eq!(
+            check_expr(
+                "(begin
+                   (defn is_even (n) (if (eq? n 0) true (is_odd (+ n -1))))
+                   (defn is_odd (n) (if (eq? n 0) false (is_even (+ n -1)))))"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn arity_mismatch() {
+        assert_eq!(
+            check_expr("(begin (defn f (a b) a) (f 1))"),
+            vec![Warning::ArityMismatch {
+                name: SymbolId::from("f"),
+                expected: Arity::exact(2),
+                got: 1
+            }]
+        );
+        assert_eq!(
+            check_expr("(len '(1) '(2))"),
+            vec![Warning::ArityMismatch {
+                name: SymbolId::from("len"),
+                expected: Arity::exact(1),
+                got: 2
+            }]
+        );
+        assert_eq!(
+            check_expr("(begin (defn f ((a) a) ((_) :when (eq? a 1) :one)) (f 1 2))"),
+            vec![
+                Warning::UnboundSymbol(SymbolId::from("a")),
+                Warning::ArityMismatch {
+                    name: SymbolId::from("f"),
+                    expected: Arity::exact(1),
+                    got: 2
+                }
+            ]
+        );
+        assert_eq!(
+            check_expr(
+                "(begin (defrecord point (x y))
+                        (match (point 1 2) ((record point :x x) x))
+                        (point? (point_update (point 1 2) :x 0))
+                        (point_x))"
+            ),
+            vec![Warning::ArityMismatch {
+                name: SymbolId::from("point_x"),
+                expected: Arity::exact(1),
+                got: 0
+            }]
+        );
+        assert_eq!(
+            check_expr(
+                "(begin (defprotocol item (describe (item)))
+                        (defimpl item :url (describe (item) (get item 1)))
+                        (defimpl items _ (describe (item) other))
+                        (describe))"
+            ),
+            vec![
+                Warning::UnboundSymbol(SymbolId::from("items")),
+                Warning::UnboundSymbol(SymbolId::from("other")),
+                Warning::ArityMismatch {
+                    name: SymbolId::from("describe"),
+                    expected: Arity::exact(1),
+                    got: 0
+                }
+            ]
+        );
+        assert_eq!(check_expr("(pprint 1)"), vec![]);
+        assert_eq!(check_expr("(pprint 1 80)"), vec![]);
+        assert_eq!(check_expr("(str)"), vec![]);
+        assert_eq!(
+            check_expr("(format)"),
+            vec![Warning::ArityMismatch {
+                name: SymbolId::from("format"),
+                expected: Arity::at_least(1),
+                got: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn arity_of_native_fns() {
+        let env = Env::standard();
+        let arity = |name: &str| Arity::of_val(&env.get(&SymbolId::from(name)).unwrap());
+        assert_eq!(arity("len"), Some(Arity::exact(1)));
+        assert_eq!(arity("pprint"), Some(Arity::range(1, 2)));
+        assert_eq!(arity("join"), Some(Arity::at_least(1)));
+        assert_eq!(arity("list"), Some(Arity::at_least(0)));
+        assert_eq!(
+            arity("json_encode"),
+            Some(Arity::range(1, 3)),
+            "Arity should be recorded for functions taking keyword arguments"
+        );
+    }
+
+    #[test]
+    fn malformed_form() {
+        assert_eq!(
+            check_expr("(if true)"),
+            vec![Warning::MalformedForm(
+                "if expects 2 to 3 arguments - got 1".to_string()
+            )]
+        );
+        assert_eq!(
+            check_expr("(defn f (x))"),
+            vec![Warning::MalformedForm(
+                "defn expects a parameter list and nonempty body".to_string()
+            )]
+        );
+        assert_eq!(
+            check_expr("(let (x 1) x)"),
+            vec![Warning::MalformedForm(
+                "let bindings should be lists".to_string()
+            )]
+        );
+        assert_eq!(
+            check_expr("(cond (true))"),
+            vec![Warning::MalformedForm(
+                "cond clauses should be lists with two elements".to_string()
+            )]
+        );
+        assert_eq!(
+            check_expr("(quote)"),
+            vec![Warning::MalformedForm(
+                "quote expects 1 argument - got 0".to_string()
+            )]
+        );
+        assert_eq!(
+            check_expr("()"),
+            vec![Warning::MalformedForm("Empty list expression".to_string())]
+        );
+        assert_eq!(
+            check_expr("(doseq (x) x)"),
+            vec![Warning::MalformedForm(
+                "doseq binding should be (SYMBOL SEQ) or (SYMBOL SEQ :when GUARD)".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn shadowing() {
+        assert_eq!(
+            check_expr("(def map 1)"),
+            vec![Warning::Shadowing(SymbolId::from("map"))]
+        );
+        assert_eq!(
+            check_expr("(begin (def x 1) (fn () (def x 2)))"),
+            vec![Warning::Shadowing(SymbolId::from("x"))]
+        );
+        assert_eq!(
+            check_expr("(begin (def x 1) (def x 2))"),
+            vec![],
+            "Redefinition in same scope is not shadowing"
+        );
+    }
+
+    #[test]
+    fn unreachable() {
+        assert_eq!(
+            check_expr("(begin (loop (recv_msg)) (display \"done\"))")
+                .into_iter()
+                .filter(|w| matches!(w, Warning::Unreachable(_)))
+                .collect::<Vec<_>>(),
+            vec![Warning::Unreachable("(display \"done\")".to_string())]
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            Warning::ArityMismatch {
+                name: SymbolId::from("f"),
+                expected: Arity::at_least(1),
+                got: 0
+            }
+            .to_string(),
+            "f expects at least 1 argument - got 0"
+        );
+        assert_eq!(
+            Warning::UnboundSymbol(SymbolId::from("x")).to_string(),
+            "Unbound symbol - x"
+        );
+    }
+}
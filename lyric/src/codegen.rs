@@ -1,5 +1,5 @@
 //! Compiler for Lyric Form AST
-use crate::{Bytecode, Error, Extern, Locals, Result, SymbolId, Val};
+use crate::{builtin, Arity, Bytecode, Error, Extern, Locals, NativeFn, Result, SymbolId, Val};
 
 // TODO: Compact bytecode repr
 /// Bytecode instructions
@@ -17,6 +17,9 @@ where
     DefSym(SymbolId),
     /// Pop TOS twice for pattern and constant value, and define symbols in env if pattern matches
     DefBind,
+    /// Pop TOS twice for pattern and value, and push whether pattern matches, defining symbols in
+    /// env if it does
+    MatchBind,
     /// Set given symbol to value popped from TOS
     SetSym(SymbolId),
     /// Pop parameter list and function body from stack, and pushes a new function onto stack
@@ -35,6 +38,10 @@ where
     YieldTop,
     /// Evaluate TOS and push value back onto stack. May be protected eval
     Eval(bool),
+    /// Evaluate form below TOS in environment at TOS and push value back onto stack
+    EvalIn,
+    /// Call bytecode at TOS with dynamic variables bound to values below it
+    Parameterize(Vec<SymbolId>),
 }
 
 /// Compile a value to bytecode representation
@@ -52,6 +59,9 @@ pub fn compile<T: Extern, L: Locals>(v: &Val<T, L>) -> Result<Bytecode<T, L>> {
                     "def" => return compile_def(args),
                     "fn" => return compile_fn(args),
                     "defn" => return compile_defn(args),
+                    "defrecord" => return compile_defrecord(args),
+                    "defprotocol" => return compile_defprotocol(args),
+                    "defimpl" => return compile_defimpl(args),
                     "if" => return compile_if(args),
                     "cond" => return compile_cond(args),
                     "lambda" => return compile_lambda(args),
@@ -62,7 +72,13 @@ pub fn compile<T: Extern, L: Locals>(v: &Val<T, L>) -> Result<Bytecode<T, L>> {
                     "eval" => return compile_eval(args),
                     "yield" => return compile_yield(args),
                     "loop" => return compile_loop(args),
+                    "for" => return compile_for(args),
+                    "doseq" => return compile_doseq(args),
                     "match" => return compile_match(args),
+                    "receive" => return compile_receive(args),
+                    "select" => return compile_select(args),
+                    "defdynamic" => return compile_defdynamic(args),
+                    "parameterize" => return compile_parameterize(args),
                     _ => (),
                 }
             }
@@ -135,9 +151,186 @@ fn compile_fn<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>
 
     Ok(inst)
 }
+
+/// Clause of multi-clause `defn`, i.e. `((PATTERN...) [:when GUARD] BODY...)`
+pub(crate) struct Clause<'a, T: Extern, L: Locals> {
+    pub patterns: &'a [Val<T, L>],
+    pub guard: Option<&'a Val<T, L>>,
+    pub body: &'a [Val<T, L>],
+}
+
+/// Docstring and clauses of multi-clause `defn`
+pub(crate) struct Clauses<'a, T: Extern, L: Locals> {
+    pub docs: Option<&'a String>,
+    pub clauses: Vec<Clause<'a, T, L>>,
+}
+
+/// Parse arguments of `defn` following name as docstring and clauses, if they are clauses.
+/// Clauses are distinguished from single parameter list by starting with a list of patterns.
+pub(crate) fn defn_clauses<T: Extern, L: Locals>(
+    args: &[Val<T, L>],
+) -> Option<Result<Clauses<'_, T, L>>> {
+    let (docs, clauses) = match args {
+        [Val::String(doc), clauses @ ..] => (Some(doc), clauses),
+        clauses => (None, clauses),
+    };
+    if !matches!(clauses.first(), Some(Val::List(c)) if matches!(c.first(), Some(Val::List(_)))) {
+        return None;
+    }
+
+    let clauses = clauses
+        .iter()
+        .map(|c| match c {
+            Val::List(c) => match &c[..] {
+                [Val::List(patterns), Val::Keyword(k), guard, body @ ..]
+                    if k.as_str() == "when" && !body.is_empty() =>
+                {
+                    Ok(Clause {
+                        patterns,
+                        guard: Some(guard),
+                        body,
+                    })
+                }
+                [Val::List(patterns), body @ ..] if !body.is_empty() => Ok(Clause {
+                    patterns,
+                    guard: None,
+                    body,
+                }),
+                _ => Err(Error::InvalidExpression(
+                    "defn clause expects a list of patterns, optional :when guard, and nonempty body"
+                        .to_string(),
+                )),
+            },
+            _ => Err(Error::InvalidExpression(
+                "defn clauses should be lists".to_string(),
+            )),
+        })
+        .collect::<Result<Vec<_>>>();
+
+    Some(clauses.and_then(|clauses| {
+        let arity = clauses[0].patterns.len();
+        if clauses.iter().any(|c| c.patterns.len() != arity) {
+            return Err(Error::InvalidExpression(
+                "defn clauses should have same number of patterns".to_string(),
+            ));
+        }
+        Ok(Clauses { docs, clauses })
+    }))
+}
+
+/// Parameter names for function with given clauses. Each parameter is a gensym named after first
+/// symbol pattern in its position, so exported signatures stay readable.
+pub(crate) fn clause_params<T: Extern, L: Locals>(clauses: &[Clause<'_, T, L>]) -> Vec<SymbolId> {
+    let arity = clauses.first().map(|c| c.patterns.len()).unwrap_or(0);
+    let mut params: Vec<SymbolId> = vec![];
+    for idx in 0..arity {
+        let name = clauses
+            .iter()
+            .find_map(|c| match &c.patterns[idx] {
+                Val::Symbol(s) if s.as_str() != "_" && !params.contains(s) => Some(s.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(|| SymbolId::from(format!("arg{}", idx + 1).as_str()));
+        params.push(name);
+    }
+    params
+        .iter()
+        .map(|p| SymbolId::gensym(p.as_str()))
+        .collect()
+}
+
+/// Clause matching a value against pattern, evaluating body if pattern matches and guard holds
+pub(crate) struct MatchClause<T: Extern, L: Locals> {
+    pub pattern: Val<T, L>,
+    pub guard: Option<Val<T, L>>,
+    pub body: Val<T, L>,
+}
+
+/// Compile matching `value` against clauses, evaluating body of first clause whose pattern
+/// matches and guard holds, or `fallback` if none does. Value is bound to `sym` while matching,
+/// and each clause is matched in its own scope, so its bindings are only seen by its guard and body.
+fn compile_match_clauses<T: Extern, L: Locals>(
+    sym: &SymbolId,
+    value: &Val<T, L>,
+    clauses: Vec<MatchClause<T, L>>,
+    fallback: &Val<T, L>,
+) -> Result<Bytecode<T, L>> {
+    // convert to:
+    // ((lambda (SYM)
+    //    (begin
+    //      (def CLAUSE1 (lambda () <if PAT1 matches SYM: (if GUARD1 BODY1 (CLAUSE2)), else (CLAUSE2)>))
+    //      (def CLAUSE2 (lambda () <if PAT2 matches SYM: BODY2, else FALLBACK>))
+    //      (...)
+    //      (CLAUSE1)))
+    //  VALUE)
+
+    let names: Vec<_> = clauses.iter().map(|_| SymbolId::gensym("clause")).collect();
+    let call = |name: &SymbolId| Val::List(vec![Val::Symbol(name.clone())]);
+    let mut bc = vec![];
+    for (idx, c) in clauses.into_iter().enumerate() {
+        let next = names.get(idx + 1).map(call).unwrap_or(fallback.clone());
+        let matched = match c.guard {
+            Some(guard) => compile(&Val::List(vec![
+                Val::symbol("if"),
+                guard,
+                c.body,
+                next.clone(),
+            ]))?,
+            None => compile(&c.body)?,
+        };
+        let unmatched = compile(&next)?;
+
+        let mut code = vec![
+            Inst::GetSym(sym.clone()),
+            Inst::PushConst(c.pattern),
+            Inst::MatchBind,
+            Inst::PopJumpFwdIfTrue(unmatched.len() + 1),
+        ];
+        code.extend(unmatched);
+        code.push(Inst::JumpFwd(matched.len()));
+        code.extend(matched);
+
+        bc.extend(lambda_code(vec![], None, code));
+        bc.push(Inst::DefSym(names[idx].clone()));
+        bc.push(Inst::PopTop);
+    }
+    bc.extend(compile(
+        &names.first().map(call).unwrap_or(fallback.clone()),
+    )?);
+
+    let mut bc = lambda_code(vec![sym.clone()], None, bc);
+    bc.extend(compile(value)?);
+    bc.push(Inst::CallFunc(1));
+    Ok(bc)
+}
+
+/// Bytecode making function with given parameters, docs and body bytecode
+fn lambda_code<T: Extern, L: Locals>(
+    params: Vec<SymbolId>,
+    docs: Option<&String>,
+    code: Bytecode<T, L>,
+) -> Bytecode<T, L> {
+    vec![
+        Inst::PushConst(Val::List(params.into_iter().map(Val::Symbol).collect())),
+        Inst::PushConst(match docs {
+            Some(docs) => Val::String(docs.clone()),
+            None => Val::Nil,
+        }),
+        Inst::PushConst(Val::Bytecode(code)),
+        Inst::MakeFunc,
+    ]
+}
+
 // TODO: Replace `defn` with a macro
 /// Compile defn
 fn compile_defn<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
+    if let [name, rest @ ..] = args {
+        if let Some(clauses) = defn_clauses(rest) {
+            let Clauses { docs, clauses } = clauses?;
+            return compile_defn_clauses(name, docs, &clauses);
+        }
+    }
+
     let (name, params, docs, body) = match args {
         [name, params, Val::String(doc), body @ ..] if !body.is_empty() => {
             (name, params, Some(doc), body)
@@ -169,6 +362,479 @@ fn compile_defn<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T,
     Ok(inst)
 }
 
+/// Compile multi-clause defn
+fn compile_defn_clauses<T: Extern, L: Locals>(
+    name: &Val<T, L>,
+    docs: Option<&String>,
+    clauses: &[Clause<'_, T, L>],
+) -> Result<Bytecode<T, L>> {
+    // convert to:
+    // (def NAME (lambda (P1 P2 ...) DOCS
+    //   <match (list P1 P2 ...) against clauses ((PAT1...) [:when GUARD1] BODY1...) ..., or
+    //    (no_matching_clause 'NAME ARGS) if none match>))
+
+    let Val::Symbol(name) = name else {
+        return Err(Error::InvalidExpression(
+            "defn expects a symbol as name".to_string(),
+        ));
+    };
+    let params = clause_params(clauses);
+    let args = SymbolId::gensym("args");
+
+    let value = Val::List(
+        std::iter::once(Val::symbol("list"))
+            .chain(params.iter().cloned().map(Val::Symbol))
+            .collect(),
+    );
+    let clauses = clauses
+        .iter()
+        .map(|c| MatchClause {
+            pattern: Val::List(c.patterns.to_vec()),
+            guard: c.guard.cloned(),
+            body: Val::List(
+                std::iter::once(Val::symbol("begin"))
+                    .chain(c.body.iter().cloned())
+                    .collect(),
+            ),
+        })
+        .collect();
+    let fallback = Val::List(vec![
+        Val::NativeFn(no_matching_clause_fn()),
+        Val::List(vec![Val::symbol("quote"), Val::Symbol(name.clone())]),
+        Val::Symbol(args.clone()),
+    ]);
+    let body = compile_match_clauses(&args, &value, clauses, &fallback)?;
+
+    let mut bc = lambda_code(params, docs, body);
+    bc.push(Inst::DefSym(name.clone()));
+    Ok(bc)
+}
+
+/// Raises error for call to multi-clause function without matching clause
+fn no_matching_clause_fn<T: Extern, L: Locals>() -> NativeFn<T, L> {
+    NativeFn {
+        doc: "(no_matching_clause NAME ARGS) - Raise error for call to NAME with ARGS".to_string(),
+        arity: Some(Arity::exact(2)),
+        func: |_, args| match args {
+            [name, Val::List(args)] => {
+                let call = std::iter::once(name.to_string())
+                    .chain(args.iter().map(|a| a.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Err(Error::NoMatchingClause(format!("({call})")))
+            }
+            _ => Err(Error::UnexpectedArguments(
+                "no_matching_clause expects a name and argument list".to_string(),
+            )),
+        },
+    }
+}
+
+/// Parts of `(defrecord NAME (FIELD ...) DOCS)` form
+pub(crate) struct RecordDef<'a> {
+    pub(crate) name: &'a SymbolId,
+    pub(crate) fields: Vec<SymbolId>,
+    pub(crate) docs: Option<&'a String>,
+}
+
+impl RecordDef<'_> {
+    /// Symbol bound to record predicate
+    pub(crate) fn predicate(&self) -> SymbolId {
+        SymbolId::from(format!("{}?", self.name))
+    }
+
+    /// Symbol bound to accessor of given field
+    pub(crate) fn accessor(&self, field: &SymbolId) -> SymbolId {
+        SymbolId::from(format!("{}_{}", self.name, field))
+    }
+
+    /// Symbol bound to record updater
+    pub(crate) fn updater(&self) -> SymbolId {
+        SymbolId::from(format!("{}_update", self.name))
+    }
+}
+
+/// Parse arguments of `defrecord` special form
+pub(crate) fn defrecord_parts<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<RecordDef<'_>> {
+    let (name, fields, docs) = match args {
+        [Val::Symbol(name), Val::List(fields), Val::String(docs)] => (name, fields, Some(docs)),
+        [Val::Symbol(name), Val::List(fields)] => (name, fields, None),
+        _ => {
+            return Err(Error::InvalidExpression(
+                "defrecord expects a symbol as name, a field list, and optional docs".to_string(),
+            ))
+        }
+    };
+    let mut symbols: Vec<SymbolId> = vec![];
+    for f in fields {
+        match f {
+            Val::Symbol(s) if !symbols.contains(s) => symbols.push(s.clone()),
+            _ => {
+                return Err(Error::InvalidExpression(
+                    "defrecord fields should be unique symbols".to_string(),
+                ))
+            }
+        }
+    }
+    Ok(RecordDef {
+        name,
+        fields: symbols,
+        docs,
+    })
+}
+
+/// Compile special form defrecord
+fn compile_defrecord<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
+    // convert to:
+    // (begin
+    //   (def NAME (lambda (F1 F2 ...) DOCS (make_record 'NAME '(:F1 :F2 ...) F1 F2 ...)))
+    //   (def NAME? (lambda (val) (is_record 'NAME val)))
+    //   (def NAME_F1 (lambda (record) (record_get 'NAME :F1 record)))
+    //   (...)
+    //   (def NAME_update (lambda (record field value) (record_update 'NAME record field value)))
+    //   'NAME)
+
+    let def = defrecord_parts(args)?;
+    let name = Val::Symbol(def.name.clone());
+    let quoted_name = Val::List(vec![Val::symbol("quote"), name.clone()]);
+    let usage = std::iter::once(def.name.to_string())
+        .chain(def.fields.iter().map(|f| f.as_str().to_uppercase()))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let define = |symbol: SymbolId, params: Vec<Val<T, L>>, docs: String, body: Val<T, L>| {
+        Val::List(vec![
+            Val::symbol("def"),
+            Val::Symbol(symbol),
+            Val::List(vec![
+                Val::symbol("lambda"),
+                Val::List(params),
+                Val::String(docs),
+                body,
+            ]),
+        ])
+    };
+
+    let mut forms = vec![Val::symbol("begin")];
+
+    let params = def
+        .fields
+        .iter()
+        .cloned()
+        .map(Val::Symbol)
+        .collect::<Vec<_>>();
+    let mut make = vec![
+        Val::NativeFn(builtin::record::make_record_fn()),
+        quoted_name.clone(),
+        Val::List(vec![
+            Val::symbol("quote"),
+            Val::List(
+                def.fields
+                    .iter()
+                    .map(|f| Val::Keyword(f.clone().to_keyword()))
+                    .collect(),
+            ),
+        ]),
+    ];
+    make.extend(params.iter().cloned());
+    forms.push(define(
+        def.name.clone(),
+        params,
+        match def.docs {
+            Some(docs) => docs.clone(),
+            None => format!("({usage}) - Create {} record", def.name),
+        },
+        Val::List(make),
+    ));
+
+    forms.push(define(
+        def.predicate(),
+        vec![Val::symbol("val")],
+        format!(
+            "({} VAL) - Returns true if VAL is {} record, otherwise false",
+            def.predicate(),
+            def.name
+        ),
+        Val::List(vec![
+            Val::NativeFn(builtin::record::is_record_fn()),
+            quoted_name.clone(),
+            Val::symbol("val"),
+        ]),
+    ));
+
+    for f in &def.fields {
+        forms.push(define(
+            def.accessor(f),
+            vec![Val::symbol("record")],
+            format!(
+                "({} RECORD) - Get {} field of {} record",
+                def.accessor(f),
+                f,
+                def.name
+            ),
+            Val::List(vec![
+                Val::NativeFn(builtin::record::record_get_fn()),
+                quoted_name.clone(),
+                Val::Keyword(f.clone().to_keyword()),
+                Val::symbol("record"),
+            ]),
+        ));
+    }
+
+    forms.push(define(
+        def.updater(),
+        vec![
+            Val::symbol("record"),
+            Val::symbol("field"),
+            Val::symbol("value"),
+        ],
+        format!(
+            "({} RECORD FIELD VALUE) - Copy of {} RECORD with FIELD set to VALUE",
+            def.updater(),
+            def.name
+        ),
+        Val::List(vec![
+            Val::NativeFn(builtin::record::record_update_fn()),
+            quoted_name.clone(),
+            Val::symbol("record"),
+            Val::symbol("field"),
+            Val::symbol("value"),
+        ]),
+    ));
+
+    forms.push(quoted_name);
+    compile(&Val::List(forms))
+}
+
+/// Parts of `(defprotocol NAME DOCS (METHOD (PARAM ...) DOCS) ...)` form
+pub(crate) struct ProtocolDef<'a> {
+    pub(crate) name: &'a SymbolId,
+    pub(crate) docs: Option<&'a String>,
+    pub(crate) methods: Vec<MethodDef<'a>>,
+}
+
+/// Method declared in `defprotocol`
+pub(crate) struct MethodDef<'a> {
+    pub(crate) name: &'a SymbolId,
+    pub(crate) params: Vec<SymbolId>,
+    pub(crate) docs: Option<&'a String>,
+}
+
+/// Parse arguments of `defprotocol` special form
+pub(crate) fn defprotocol_parts<T: Extern, L: Locals>(
+    args: &[Val<T, L>],
+) -> Result<ProtocolDef<'_>> {
+    let invalid = || {
+        Error::InvalidExpression(
+            "defprotocol expects a symbol as name, optional docs, and method declarations like (METHOD (PARAM ...) DOCS)"
+                .to_string(),
+        )
+    };
+    let (name, docs, methods) = match args {
+        [Val::Symbol(name), Val::String(docs), methods @ ..] => (name, Some(docs), methods),
+        [Val::Symbol(name), methods @ ..] => (name, None, methods),
+        _ => return Err(invalid()),
+    };
+    if methods.is_empty() {
+        return Err(invalid());
+    }
+
+    let methods = methods
+        .iter()
+        .map(|m| {
+            let (name, params, docs) = match m {
+                Val::List(m) => match &m[..] {
+                    [Val::Symbol(name), Val::List(params), Val::String(docs)] => {
+                        (name, params, Some(docs))
+                    }
+                    [Val::Symbol(name), Val::List(params)] => (name, params, None),
+                    _ => return Err(invalid()),
+                },
+                _ => return Err(invalid()),
+            };
+            let params = params
+                .iter()
+                .map(|p| p.as_symbol().cloned())
+                .collect::<Result<Vec<_>>>()
+                .map_err(|_| invalid())?;
+            if params.is_empty() {
+                return Err(Error::InvalidExpression(format!(
+                    "protocol method {name} should have at least one parameter to dispatch on"
+                )));
+            }
+            Ok(MethodDef { name, params, docs })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(ProtocolDef {
+        name,
+        docs,
+        methods,
+    })
+}
+
+/// Compile special form defprotocol
+fn compile_defprotocol<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
+    // convert to:
+    // (begin
+    //   (def NAME <protocol :name NAME :doc DOCS :methods ((M1 (P1 P2 ...) DOCS) ...) :impls ()>)
+    //   (def M1 (lambda (P1 P2 ...) DOCS (protocol_dispatch 'NAME 'M1 P1 P2 ...)))
+    //   (...)
+    //   'NAME)
+
+    let def = defprotocol_parts(args)?;
+    let quote = |v: Val<T, L>| Val::List(vec![Val::symbol("quote"), v]);
+
+    let methods = def
+        .methods
+        .iter()
+        .map(|m| {
+            Val::List(vec![
+                Val::Symbol(m.name.clone()),
+                Val::List(m.params.iter().cloned().map(Val::Symbol).collect()),
+                m.docs.map(|d| Val::String(d.clone())).unwrap_or(Val::Nil),
+            ])
+        })
+        .collect();
+
+    let mut forms = vec![
+        Val::symbol("begin"),
+        Val::List(vec![
+            Val::symbol("def"),
+            Val::Symbol(def.name.clone()),
+            builtin::protocol::protocol_val(def.name, def.docs, methods, vec![]),
+        ]),
+    ];
+
+    for m in &def.methods {
+        let params = m
+            .params
+            .iter()
+            .cloned()
+            .map(Val::Symbol)
+            .collect::<Vec<_>>();
+        let usage = std::iter::once(m.name.to_string())
+            .chain(m.params.iter().map(|p| p.as_str().to_uppercase()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let docs = match m.docs {
+            Some(docs) => format!("({usage}) - {docs} (method of protocol {})", def.name),
+            None => format!("({usage}) - Method of protocol {}", def.name),
+        };
+
+        let mut dispatch = vec![
+            Val::NativeFn(builtin::protocol::protocol_dispatch_fn()),
+            quote(Val::Symbol(def.name.clone())),
+            quote(Val::Symbol(m.name.clone())),
+        ];
+        dispatch.extend(params.iter().cloned());
+
+        forms.push(Val::List(vec![
+            Val::symbol("def"),
+            Val::Symbol(m.name.clone()),
+            Val::List(vec![
+                Val::symbol("lambda"),
+                Val::List(params),
+                Val::String(docs),
+                Val::List(dispatch),
+            ]),
+        ]));
+    }
+
+    forms.push(quote(Val::Symbol(def.name.clone())));
+    compile(&Val::List(forms))
+}
+
+/// Parts of `(defimpl PROTOCOL TYPE (METHOD (PARAM ...) BODY ...) ...)` form
+pub(crate) struct ImplDef<'a, T: Extern, L: Locals> {
+    pub(crate) protocol: &'a SymbolId,
+    pub(crate) ty: &'a Val<T, L>,
+    pub(crate) methods: Vec<ImplMethod<'a, T, L>>,
+}
+
+/// Method defined in `defimpl`
+pub(crate) struct ImplMethod<'a, T: Extern, L: Locals> {
+    pub(crate) name: &'a SymbolId,
+    pub(crate) params: &'a Val<T, L>,
+    pub(crate) body: &'a [Val<T, L>],
+}
+
+/// Parse arguments of `defimpl` special form
+pub(crate) fn defimpl_parts<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<ImplDef<'_, T, L>> {
+    let invalid = || {
+        Error::InvalidExpression(
+            "defimpl expects a protocol, a type symbol or keyword, and method definitions like (METHOD (PARAM ...) BODY)"
+                .to_string(),
+        )
+    };
+    let (protocol, ty, methods) = match args {
+        [Val::Symbol(protocol), ty @ (Val::Symbol(_) | Val::Keyword(_) | Val::Nil), methods @ ..]
+            if !methods.is_empty() =>
+        {
+            (protocol, ty, methods)
+        }
+        _ => return Err(invalid()),
+    };
+    let methods = methods
+        .iter()
+        .map(|m| match m {
+            Val::List(m) => match &m[..] {
+                [Val::Symbol(name), params @ Val::List(_), body @ ..] if !body.is_empty() => {
+                    Ok(ImplMethod { name, params, body })
+                }
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ImplDef {
+        protocol,
+        ty,
+        methods,
+    })
+}
+
+/// Compile special form defimpl
+fn compile_defimpl<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
+    // convert to:
+    // (begin
+    //   (set PROTOCOL (protocol_extend PROTOCOL 'TYPE
+    //                   'M1 (lambda (P1 ...) (begin BODY1...))
+    //                   ...))
+    //   'PROTOCOL)
+
+    let def = defimpl_parts(args)?;
+    let quote = |v: Val<T, L>| Val::List(vec![Val::symbol("quote"), v]);
+
+    let mut extend = vec![
+        Val::NativeFn(builtin::protocol::protocol_extend_fn()),
+        Val::Symbol(def.protocol.clone()),
+        quote(def.ty.clone()),
+    ];
+    for m in def.methods {
+        extend.push(quote(Val::Symbol(m.name.clone())));
+        extend.push(Val::List(vec![
+            Val::symbol("lambda"),
+            m.params.clone(),
+            Val::List(
+                std::iter::once(Val::symbol("begin"))
+                    .chain(m.body.iter().cloned())
+                    .collect(),
+            ),
+        ]));
+    }
+
+    compile(&Val::List(vec![
+        Val::symbol("begin"),
+        Val::List(vec![
+            Val::symbol("set"),
+            Val::Symbol(def.protocol.clone()),
+            Val::List(extend),
+        ]),
+        quote(Val::Symbol(def.protocol.clone())),
+    ]))
+}
+
 /// Compile special form lambda
 fn compile_lambda<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
     let (param, docs, body) = match args {
@@ -185,10 +851,9 @@ fn compile_lambda<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T
 
     Ok(vec![
         Inst::PushConst(param.clone()),
-        Inst::PushConst(if docs.is_some() {
-            Val::String(docs.unwrap().clone())
-        } else {
-            Val::Nil
+        Inst::PushConst(match docs {
+            Some(docs) => Val::String(docs.clone()),
+            None => Val::Nil,
         }),
         Inst::PushConst(Val::Bytecode(bytecode)),
         Inst::MakeFunc,
@@ -209,17 +874,88 @@ fn compile_quote<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T,
 }
 
 fn compile_eval<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
-    let v = match args {
-        [v] => v,
+    match args {
+        [v] => {
+            let mut bc = compile(v)?;
+            bc.push(Inst::Eval(false));
+            Ok(bc)
+        }
+        [v, env] => {
+            let mut bc = compile(v)?;
+            bc.extend(compile(env)?);
+            bc.push(Inst::EvalIn);
+            Ok(bc)
+        }
+        _ => Err(Error::InvalidExpression(
+            "eval expects a form and an optional environment".to_string(),
+        )),
+    }
+}
+
+/// Compile special form defdynamic
+fn compile_defdynamic<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
+    // convert to:
+    // (begin (def NAME (make_dynamic 'NAME DEFAULT)) 'NAME)
+    let (name, default) = match args {
+        [Val::Symbol(name), default] => (name, default),
+        _ => {
+            return Err(Error::InvalidExpression(
+                "defdynamic expects a symbol and a default value".to_string(),
+            ))
+        }
+    };
+    let quoted_name = Val::List(vec![Val::symbol("quote"), Val::Symbol(name.clone())]);
+    compile(&Val::List(vec![
+        Val::symbol("begin"),
+        Val::List(vec![
+            Val::symbol("def"),
+            Val::Symbol(name.clone()),
+            Val::List(vec![
+                Val::NativeFn(builtin::dynamic::make_dynamic_fn()),
+                quoted_name.clone(),
+                default.clone(),
+            ]),
+        ]),
+        quoted_name,
+    ]))
+}
+
+/// Compile special form parameterize
+fn compile_parameterize<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
+    let (bindings, body) = match args.split_first() {
+        Some((Val::List(bindings), body)) => (bindings, body),
         _ => {
             return Err(Error::InvalidExpression(
-                "eval expects one argument".to_string(),
+                "parameterize expects binding list and body expressions as args".to_string(),
             ))
         }
     };
 
-    let mut bc = compile(v)?;
-    bc.push(Inst::Eval(false));
+    let mut bc = vec![];
+    let mut symbols = vec![];
+    for b in bindings {
+        match b {
+            Val::List(pair) => {
+                match &pair[..] {
+                    [Val::Symbol(s), value] => {
+                        bc.extend(compile(value)?);
+                        symbols.push(s.clone());
+                    }
+                    _ => return Err(Error::InvalidExpression(
+                        "pair in parameterize bindings must contain one symbol and one expression"
+                            .to_string(),
+                    )),
+                }
+            }
+            _ => {
+                return Err(Error::InvalidExpression(
+                    "non-list in parameterize bindings".to_string(),
+                ))
+            }
+        }
+    }
+    bc.push(Inst::PushConst(Val::Bytecode(compile_begin(body)?)));
+    bc.push(Inst::Parameterize(symbols));
     Ok(bc)
 }
 
@@ -407,6 +1143,102 @@ fn compile_loop<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T,
     Ok(inst)
 }
 
+/// Binding and body of `for` or `doseq`, i.e. `((SYMBOL SEQ [:when GUARD]) BODY...)`
+pub(crate) struct Comprehension<'a, T: Extern, L: Locals> {
+    pub binding: &'a SymbolId,
+    pub seq: &'a Val<T, L>,
+    pub guard: Option<&'a Val<T, L>>,
+    pub body: &'a [Val<T, L>],
+}
+
+/// Parse arguments of `for` or `doseq` special form
+pub(crate) fn comprehension_parts<'a, T: Extern, L: Locals>(
+    form: &str,
+    args: &'a [Val<T, L>],
+) -> Result<Comprehension<'a, T, L>> {
+    let (binding, body) = match args {
+        [Val::List(binding), body @ ..] if !body.is_empty() => (binding, body),
+        _ => {
+            return Err(Error::InvalidExpression(format!(
+                "{form} expects a binding list and nonempty body"
+            )))
+        }
+    };
+    let (binding, seq, guard) = match &binding[..] {
+        [Val::Symbol(s), seq] => (s, seq, None),
+        [Val::Symbol(s), seq, Val::Keyword(k), guard] if k.as_str() == "when" => {
+            (s, seq, Some(guard))
+        }
+        _ => {
+            return Err(Error::InvalidExpression(format!(
+                "{form} binding should be (SYMBOL SEQ) or (SYMBOL SEQ :when GUARD)"
+            )))
+        }
+    };
+    Ok(Comprehension {
+        binding,
+        seq,
+        guard,
+        body,
+    })
+}
+
+impl<T: Extern, L: Locals> Comprehension<'_, T, L> {
+    /// Lambda of binding over given body
+    fn lambda(&self, body: Val<T, L>) -> Val<T, L> {
+        Val::List(vec![
+            Val::symbol("lambda"),
+            Val::List(vec![Val::Symbol(self.binding.clone())]),
+            body,
+        ])
+    }
+
+    /// Sequence expression, filtered by guard if any
+    fn filtered_seq(&self) -> Val<T, L> {
+        match self.guard {
+            Some(guard) => Val::List(vec![
+                Val::Lambda(builtin::seq::lazy_filter_fn()),
+                self.seq.clone(),
+                self.lambda(guard.clone()),
+            ]),
+            None => self.seq.clone(),
+        }
+    }
+
+    /// Body as a single expression
+    fn body(&self) -> Val<T, L> {
+        Val::List(
+            std::iter::once(Val::symbol("begin"))
+                .chain(self.body.iter().cloned())
+                .collect(),
+        )
+    }
+}
+
+/// Compile `for` expr
+fn compile_for<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
+    // convert to:
+    // (lazy_map [(lazy_filter SEQ (lambda (SYMBOL) GUARD))] (lambda (SYMBOL) (begin BODY...)))
+    let c = comprehension_parts("for", args)?;
+    compile(&Val::List(vec![
+        Val::Lambda(builtin::seq::lazy_map_fn()),
+        c.filtered_seq(),
+        c.lambda(c.body()),
+    ]))
+}
+
+/// Compile `doseq` expr
+fn compile_doseq<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
+    // convert to:
+    // (for_each [(lazy_filter SEQ (lambda (SYMBOL) GUARD))] (lambda (SYMBOL) (begin BODY...)))
+    let c = comprehension_parts("doseq", args)?;
+    compile(&Val::List(vec![
+        Val::Lambda(builtin::seq::for_each_fn()),
+        c.filtered_seq(),
+        c.lambda(c.body()),
+    ]))
+}
+
 // TODO: Implement `gensym`?
 // TODO: Replace `match` with macro
 /// Compile `match` expr
@@ -465,6 +1297,173 @@ fn compile_match<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T,
     compile(&ast)
 }
 
+/// Compile special form receive, polling mailbox with host binding `recv_match`
+fn compile_receive<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
+    // convert to:
+    // <match (recv_match '(PAT1 PAT2 ...) MS) against clauses
+    //   ((:timeout) AFTER_BODY)
+    //   ((:msg PAT1) BODY1)
+    //   ((:msg PAT2) BODY2)
+    //   (...)>
+
+    let (after, clauses) = match args.split_last() {
+        Some((Val::List(c), clauses)) if is_after_clause(c) => match &c[..] {
+            [_, timeout, body] => (Some((timeout.clone(), body.clone())), clauses),
+            _ => {
+                return Err(Error::UnexpectedArguments(
+                    "after clause expects a timeout and body".to_string(),
+                ))
+            }
+        },
+        _ => (None, args),
+    };
+
+    let mut pats = vec![];
+    let mut match_clauses = vec![];
+    if let Some((_, body)) = &after {
+        match_clauses.push(MatchClause {
+            pattern: Val::List(vec![Val::keyword("timeout")]),
+            guard: None,
+            body: body.clone(),
+        });
+    }
+    for c in clauses {
+        let (pat, body) = match c {
+            Val::List(c) if is_after_clause(c) => Err(Error::UnexpectedArguments(
+                "after clause should be last clause of receive".to_string(),
+            )),
+            Val::List(c) => match &c[..] {
+                [pat, body] => Ok((pat.clone(), body.clone())),
+                _ => Err(Error::UnexpectedArguments(
+                    "receive clause list expects two elements".to_string(),
+                )),
+            },
+            _ => Err(Error::UnexpectedArguments(
+                "receive clauses should be lists".to_string(),
+            )),
+        }?;
+        pats.push(pat.clone());
+        match_clauses.push(MatchClause {
+            pattern: Val::List(vec![Val::keyword("msg"), pat]),
+            guard: None,
+            body,
+        });
+    }
+
+    let recv = Val::List(vec![
+        Val::symbol("recv_match"),
+        Val::List(vec![Val::symbol("quote"), Val::List(pats)]),
+        after.map(|(timeout, _)| timeout).unwrap_or(Val::Nil),
+    ]);
+    compile_match_clauses(&SymbolId::gensym("msg"), &recv, match_clauses, &Val::Nil)
+}
+
+/// Compile special form select, waiting on sources with host binding `select_recv`
+fn compile_select<T: Extern, L: Locals>(args: &[Val<T, L>]) -> Result<Bytecode<T, L>> {
+    // convert to:
+    // <match (select_recv (list :recv 'PAT1) (list :topic TOPIC 'PAT2) ... :timeout MS)
+    //   against clauses
+    //   ((:timeout) AFTER_BODY)
+    //   ((0 PAT1) BODY1)
+    //   ((1 PAT2) BODY2)
+    //   (...)>
+
+    let (after, clauses) = match args.split_last() {
+        Some((Val::List(c), clauses)) if is_after_clause(c) => match &c[..] {
+            [_, timeout, body] => (Some((timeout.clone(), body.clone())), clauses),
+            _ => {
+                return Err(Error::UnexpectedArguments(
+                    "after clause expects a timeout and body".to_string(),
+                ))
+            }
+        },
+        _ => (None, args),
+    };
+
+    let mut select = vec![Val::symbol("select_recv")];
+    let mut match_clauses = vec![];
+    if let Some((_, body)) = &after {
+        match_clauses.push(MatchClause {
+            pattern: Val::List(vec![Val::keyword("timeout")]),
+            guard: None,
+            body: body.clone(),
+        });
+    }
+    for (idx, c) in clauses.iter().enumerate() {
+        let (source, pat, body) = match c {
+            Val::List(c) if is_after_clause(c) => Err(Error::UnexpectedArguments(
+                "after clause should be last clause of select".to_string(),
+            )),
+            Val::List(c) => match &c[..] {
+                [Val::List(source), body] => {
+                    let (source, pat) = select_source(source)?;
+                    Ok((source, pat, body.clone()))
+                }
+                _ => Err(Error::UnexpectedArguments(
+                    "select clause list expects a source and body".to_string(),
+                )),
+            },
+            _ => Err(Error::UnexpectedArguments(
+                "select clauses should be lists".to_string(),
+            )),
+        }?;
+        select.push(source);
+        match_clauses.push(MatchClause {
+            pattern: Val::List(vec![Val::Int(idx as i32), pat]),
+            guard: None,
+            body,
+        });
+    }
+    if let Some((timeout, _)) = after {
+        select.extend([Val::keyword("timeout"), timeout]);
+    }
+
+    compile_match_clauses(
+        &SymbolId::gensym("sel"),
+        &Val::List(select),
+        match_clauses,
+        &Val::Nil,
+    )
+}
+
+/// Source for `select_recv` from select clause source - `(recv PAT)`, `(topic TOPIC PAT)`,
+/// `(timer REF)` or `(exit PID [REASON])` - and pattern bound by clause
+fn select_source<T: Extern, L: Locals>(source: &[Val<T, L>]) -> Result<(Val<T, L>, Val<T, L>)> {
+    let quote = |pat: &Val<T, L>| Val::List(vec![Val::symbol("quote"), pat.clone()]);
+    let list = |elems: Vec<Val<T, L>>| {
+        Val::List(std::iter::once(Val::symbol("list")).chain(elems).collect())
+    };
+    let wildcard = Val::symbol("_");
+    match source {
+        [Val::Symbol(s), pat] if s.as_str() == "recv" => {
+            Ok((list(vec![Val::keyword("recv"), quote(pat)]), pat.clone()))
+        }
+        [Val::Symbol(s), topic, pat] if s.as_str() == "topic" => Ok((
+            list(vec![Val::keyword("topic"), topic.clone(), quote(pat)]),
+            pat.clone(),
+        )),
+        [Val::Symbol(s), tref] if s.as_str() == "timer" => {
+            Ok((list(vec![Val::keyword("timer"), tref.clone()]), wildcard))
+        }
+        [Val::Symbol(s), pid, rest @ ..] if s.as_str() == "exit" && rest.len() <= 1 => {
+            let pat = rest.first().cloned().unwrap_or(wildcard);
+            Ok((
+                list(vec![Val::keyword("exit"), pid.clone(), quote(&pat)]),
+                pat,
+            ))
+        }
+        _ => Err(Error::UnexpectedArguments(
+            "select source should be (recv PAT), (topic TOPIC PAT), (timer REF) or (exit PID [REASON])"
+                .to_string(),
+        )),
+    }
+}
+
+/// Whether clause of receive is an `(after TIMEOUT BODY)` clause
+fn is_after_clause<T: Extern, L: Locals>(clause: &[Val<T, L>]) -> bool {
+    matches!(clause.first(), Some(Val::Symbol(s)) if s.as_str() == "after")
+}
+
 impl<T: Extern, L: Locals> std::fmt::Display for Inst<T, L> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -472,6 +1471,7 @@ impl<T: Extern, L: Locals> std::fmt::Display for Inst<T, L> {
             Inst::GetSym(s) => write!(f, "getsym {s}"),
             Inst::DefSym(s) => write!(f, "defsym {s}"),
             Inst::DefBind => write!(f, "defbind"),
+            Inst::MatchBind => write!(f, "matchbind"),
             Inst::SetSym(s) => write!(f, "setsym {s}"),
             Inst::MakeFunc => write!(f, "makefn"),
             Inst::CallFunc(nargs) => write!(f, "callfn {nargs}"),
@@ -487,6 +1487,16 @@ impl<T: Extern, L: Locals> std::fmt::Display for Inst<T, L> {
                     write!(f, "peval")
                 }
             }
+            Inst::EvalIn => write!(f, "evalin"),
+            Inst::Parameterize(symbols) => write!(
+                f,
+                "params {}",
+                symbols
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
         }
     }
 }
@@ -849,6 +1859,35 @@ mod tests {
                 Eval(false),
             ])
         );
+        assert_eq!(
+            compile(&f("(eval 42 sandbox)")),
+            Ok(vec![
+                PushConst(Val::Int(42)),
+                GetSym(SymbolId::from("sandbox")),
+                EvalIn,
+            ])
+        );
+    }
+
+    #[test]
+    fn compile_parameterize() {
+        assert_eq!(
+            compile(&f("(parameterize ((x 1) (y 2)) x)")),
+            Ok(vec![
+                PushConst(Val::Int(1)),
+                PushConst(Val::Int(2)),
+                PushConst(Val::Bytecode(vec![GetSym(SymbolId::from("x"))])),
+                Parameterize(vec![SymbolId::from("x"), SymbolId::from("y")]),
+            ])
+        );
+        assert!(matches!(
+            compile(&f("(parameterize (x 1) x)")),
+            Err(Error::InvalidExpression(_))
+        ));
+        assert!(matches!(
+            compile(&f("(defdynamic x)")),
+            Err(Error::InvalidExpression(_))
+        ));
     }
 
     #[test]
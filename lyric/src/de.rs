@@ -0,0 +1,421 @@
+//! Deserialize Rust types from [Form]
+//!
+//! Inverse of mapping used by [crate::to_form]. Keywords, symbols, and strings are accepted as
+//! field and variant names.
+use crate::{Error, Form, Record, Result};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use std::vec::IntoIter;
+
+/// Deserialize value from given [Form]
+pub fn from_form<T: DeserializeOwned>(form: Form) -> Result<T> {
+    T::deserialize(Deserializer { form })
+}
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Serialization(msg.to_string())
+    }
+}
+
+/// Deserializer reading from [Form]
+struct Deserializer {
+    form: Form,
+}
+
+/// Access for elements of list
+struct ListAccess {
+    iter: IntoIter<Form>,
+}
+
+/// Access for key value pairs in keyword alist
+struct AlistAccess {
+    iter: IntoIter<Form>,
+    value: Option<Form>,
+}
+
+/// Access for enum variant and its contents
+struct Enum {
+    variant: String,
+    contents: Vec<Form>,
+}
+
+fn unexpected(expected: &str, form: &Form) -> Error {
+    Error::Serialization(format!("expected {expected} - got {form}"))
+}
+
+/// Fields of record as keyword alist
+fn record_alist(record: Record<Form>) -> Vec<Form> {
+    record
+        .fields
+        .into_iter()
+        .flat_map(|(k, v)| [Form::Keyword(k), v])
+        .collect()
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.form {
+            Form::Nil => visitor.visit_unit(),
+            Form::Bool(b) => visitor.visit_bool(b),
+            Form::Int(i) => visitor.visit_i32(i),
+            Form::String(s) | Form::RawString(s) => visitor.visit_string(s),
+            Form::Symbol(s) => visitor.visit_str(s.as_str()),
+            Form::Keyword(k) => visitor.visit_str(k.as_str()),
+            Form::List(l) => visitor.visit_seq(ListAccess {
+                iter: l.into_iter(),
+            }),
+            Form::Record(r) => visitor.visit_map(AlistAccess::new(record_alist(r))?),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.form {
+            Form::Nil => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.form {
+            Form::Nil => visitor.visit_unit(),
+            f => Err(unexpected("nil", &f)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.form {
+            Form::List(l) => {
+                let bytes = l
+                    .into_iter()
+                    .map(|f| match f {
+                        Form::Int(i) => u8::try_from(i).map_err(|_| unexpected("byte", &f)),
+                        f => Err(unexpected("byte", &f)),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                visitor.visit_byte_buf(bytes)
+            }
+            f => Err(unexpected("list of bytes", &f)),
+        }
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.form {
+            Form::List(l) => visitor.visit_map(AlistAccess::new(l)?),
+            Form::Record(r) => visitor.visit_map(AlistAccess::new(record_alist(r))?),
+            f => Err(unexpected("keyword alist", &f)),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(Enum::new(self.form)?)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        seq tuple tuple_struct identifier
+    }
+}
+
+impl<'de> SeqAccess<'de> for ListAccess {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        match self.iter.next() {
+            Some(form) => seed.deserialize(Deserializer { form }).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+impl AlistAccess {
+    fn new(items: Vec<Form>) -> Result<Self> {
+        if !items.len().is_multiple_of(2) {
+            return Err(Error::Serialization(format!(
+                "expected keyword alist with even number of elements - got {}",
+                Form::List(items)
+            )));
+        }
+        Ok(Self {
+            iter: items.into_iter(),
+            value: None,
+        })
+    }
+}
+
+impl<'de> MapAccess<'de> for AlistAccess {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let key = match self.iter.next() {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+        self.value = self.iter.next();
+        seed.deserialize(Deserializer { form: key }).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let form = self.value.take().ok_or(Error::Serialization(
+            "next_value called before next_key".to_string(),
+        ))?;
+        seed.deserialize(Deserializer { form })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len() / 2)
+    }
+}
+
+impl Enum {
+    fn new(form: Form) -> Result<Self> {
+        match form {
+            Form::List(mut l) => match l.first().and_then(variant_name) {
+                Some(variant) => {
+                    l.remove(0);
+                    Ok(Self {
+                        variant,
+                        contents: l,
+                    })
+                }
+                None => Err(unexpected("list tagged by variant name", &Form::List(l))),
+            },
+            f => match variant_name(&f) {
+                Some(variant) => Ok(Self {
+                    variant,
+                    contents: vec![],
+                }),
+                None => Err(unexpected("variant name or tagged list", &f)),
+            },
+        }
+    }
+}
+
+/// Variant name given as keyword, symbol or string
+fn variant_name(form: &Form) -> Option<String> {
+    match form {
+        Form::Keyword(k) => Some(k.as_str().to_string()),
+        Form::Symbol(s) => Some(s.as_str().to_string()),
+        Form::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let variant = seed.deserialize(self.variant.clone().into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Enum {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        if self.contents.is_empty() {
+            Ok(())
+        } else {
+            Err(unexpected("unit variant", &Form::List(self.contents)))
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(mut self, seed: T) -> Result<T::Value> {
+        if self.contents.len() != 1 {
+            return Err(unexpected(
+                "variant with single value",
+                &Form::List(self.contents),
+            ));
+        }
+        seed.deserialize(Deserializer {
+            form: self.contents.remove(0),
+        })
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(ListAccess {
+            iter: self.contents.into_iter(),
+        })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_map(AlistAccess::new(self.contents)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::to_form;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(i32),
+        Line(Point, Point),
+        Rect { width: i32, height: i32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        name: String,
+        tags: Vec<String>,
+        parent: Option<String>,
+        shapes: Vec<Shape>,
+    }
+
+    fn f(expr: &str) -> Form {
+        Form::from_expr(expr).unwrap()
+    }
+
+    #[test]
+    fn primitives() {
+        assert_eq!(from_form::<bool>(f("true")), Ok(true));
+        assert_eq!(from_form::<u8>(f("5")), Ok(5));
+        assert_eq!(from_form::<String>(f("\"hi\"")), Ok("hi".to_string()));
+        assert_eq!(from_form::<String>(f(":hi")), Ok("hi".to_string()));
+        assert_eq!(from_form::<()>(f("nil")), Ok(()));
+        assert!(from_form::<u8>(f("-1")).is_err());
+        assert!(from_form::<i32>(f("\"5\"")).is_err());
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(from_form::<Option<i32>>(f("nil")), Ok(None));
+        assert_eq!(from_form::<Option<i32>>(f("1")), Ok(Some(1)));
+    }
+
+    #[test]
+    fn structs() {
+        assert_eq!(
+            from_form::<Point>(f("(:x 1 :y 2)")),
+            Ok(Point { x: 1, y: 2 })
+        );
+        assert_eq!(
+            from_form::<Point>(f("(:y 2 :x 1)")),
+            Ok(Point { x: 1, y: 2 })
+        );
+        assert!(from_form::<Point>(f("(:x 1 :y)")).is_err());
+        assert!(from_form::<Point>(f("(:x 1)")).is_err());
+    }
+
+    #[test]
+    fn records() {
+        let record = Form::Record(Record {
+            name: "point".into(),
+            fields: vec![("x".into(), Form::Int(1)), ("y".into(), Form::Int(2))],
+        });
+        assert_eq!(from_form::<Point>(record), Ok(Point { x: 1, y: 2 }));
+    }
+
+    #[test]
+    fn maps() {
+        let map = from_form::<HashMap<String, i32>>(f("(:a 1 :b 2)")).unwrap();
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.get("b"), Some(&2));
+    }
+
+    #[test]
+    fn enums() {
+        assert_eq!(from_form::<Shape>(f(":Empty")), Ok(Shape::Empty));
+        assert_eq!(from_form::<Shape>(f("(:Circle 3)")), Ok(Shape::Circle(3)));
+        assert_eq!(
+            from_form::<Shape>(f("(:Rect :width 2 :height 3)")),
+            Ok(Shape::Rect {
+                width: 2,
+                height: 3
+            })
+        );
+        assert_eq!(from_form::<Shape>(f("Empty")), Ok(Shape::Empty));
+        assert_eq!(from_form::<Shape>(f("\"Empty\"")), Ok(Shape::Empty));
+        assert_eq!(from_form::<Shape>(f("(Circle 3)")), Ok(Shape::Circle(3)));
+        assert_eq!(
+            from_form::<Shape>(f("(\"Circle\" 3)")),
+            Ok(Shape::Circle(3))
+        );
+        assert!(from_form::<Shape>(f(":Unknown")).is_err());
+        assert!(from_form::<Shape>(f("(1 2)")).is_err());
+        assert!(from_form::<Shape>(f("(:Circle 1 2)")).is_err());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let config = Config {
+            name: "vrs".to_string(),
+            tags: vec!["a".to_string(), "b".to_string()],
+            parent: None,
+            shapes: vec![
+                Shape::Empty,
+                Shape::Line(Point { x: 0, y: 0 }, Point { x: 1, y: 1 }),
+            ],
+        };
+        let form = to_form(&config).unwrap();
+        assert_eq!(
+            form,
+            f(
+                r#"(:name "vrs" :tags ("a" "b") :parent nil :shapes (:Empty (:Line (:x 0 :y 0) (:x 1 :y 1))))"#
+            )
+        );
+        assert_eq!(from_form::<Config>(form), Ok(config));
+    }
+}
@@ -0,0 +1,354 @@
+//! Debugging utilities for bytecode and [Fiber] execution
+//!
+//! Lyric forms do not carry source positions, so locations are instruction offsets within
+//! bytecode of top-level program or function, as printed by [disassemble].
+use crate::{Bytecode, Env, Extern, Fiber, Inst, Locals, Result, Signal, SymbolId, Val};
+use std::cell::OnceCell;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Disassemble bytecode into listing of instructions with offsets, jump targets, and
+/// indented code of nested functions
+pub fn disassemble<T: Extern, L: Locals>(code: &Bytecode<T, L>) -> String {
+    let mut out = String::new();
+    disassemble_inner(code, 0, &mut out);
+    out
+}
+
+fn disassemble_inner<T: Extern, L: Locals>(code: &Bytecode<T, L>, depth: usize, out: &mut String) {
+    let indent = "    ".repeat(depth);
+    for (offset, inst) in code.iter().enumerate() {
+        let _ = write!(out, "{indent}{offset:>4} {inst}");
+        match inst {
+            Inst::JumpFwd(o) | Inst::PopJumpFwdIfTrue(o) => {
+                let _ = write!(out, " -> {}", offset + 1 + o);
+            }
+            Inst::JumpBck(o) => {
+                let _ = write!(out, " -> {}", (offset + 1).saturating_sub(*o));
+            }
+            _ => (),
+        }
+        out.push('\n');
+        match inst {
+            Inst::PushConst(Val::Bytecode(nested)) => disassemble_inner(nested, depth + 1, out),
+            Inst::PushConst(Val::Lambda(l)) => disassemble_inner(&l.code, depth + 1, out),
+            _ => (),
+        }
+    }
+}
+
+/// Condition to stop fiber execution in [Debugger]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Breakpoint {
+    /// Break before calling function bound to symbol
+    Function(SymbolId),
+    /// Break before executing instruction at offset within code of function bound to symbol, or
+    /// within top-level program if no function is given
+    Location {
+        function: Option<SymbolId>,
+        offset: usize,
+    },
+}
+
+/// Event that stopped fiber execution in [Debugger]
+#[derive(Debug, PartialEq)]
+pub enum Event<T: Extern, L: Locals> {
+    /// Fiber executed single instruction, and is stopped before next instruction
+    Step,
+    /// Fiber is stopped at breakpoint with given id
+    Break(usize),
+    /// Fiber yielded, awaited, or completed with signal
+    Signal(Signal<T, L>),
+}
+
+/// Breakpoint with function its symbol resolves to, once bound
+#[derive(Debug)]
+struct Entry<T: Extern, L: Locals> {
+    bp: Breakpoint,
+    target: OnceCell<Val<T, L>>,
+}
+
+impl<T: Extern, L: Locals> Entry<T, L> {
+    /// Function breakpoint is set on, resolving symbol in given environment if not yet resolved
+    fn target(&self, name: &SymbolId, env: &Mutex<Env<T, L>>) -> Option<&Val<T, L>> {
+        if self.target.get().is_none() {
+            if let Some(v) = env.lock().unwrap().get(name) {
+                let _ = self.target.set(v);
+            }
+        }
+        self.target.get()
+    }
+}
+
+/// Debugger driving [Fiber] one instruction at a time, stopping at breakpoints
+#[derive(Debug)]
+pub struct Debugger<T: Extern, L: Locals> {
+    breakpoints: Vec<Option<Entry<T, L>>>,
+}
+
+impl<T: Extern, L: Locals> Default for Debugger<T, L> {
+    fn default() -> Self {
+        Self {
+            breakpoints: vec![],
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> Debugger<T, L> {
+    /// Create a new debugger without breakpoints
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add breakpoint, returning its id. Function of breakpoint is resolved from the symbol
+    /// it is bound to the first time the symbol is found bound during execution.
+    pub fn add_breakpoint(&mut self, bp: Breakpoint) -> usize {
+        self.breakpoints.push(Some(Entry {
+            bp,
+            target: OnceCell::new(),
+        }));
+        self.breakpoints.len() - 1
+    }
+
+    /// Remove breakpoint with given id. Returns whether or not breakpoint existed
+    pub fn remove_breakpoint(&mut self, id: usize) -> bool {
+        self.breakpoints
+            .get_mut(id)
+            .and_then(|bp| bp.take())
+            .is_some()
+    }
+
+    /// Breakpoints with their ids
+    pub fn breakpoints(&self) -> impl Iterator<Item = (usize, &Breakpoint)> {
+        self.breakpoints
+            .iter()
+            .enumerate()
+            .filter_map(|(id, e)| e.as_ref().map(|e| (id, &e.bp)))
+    }
+
+    /// Execute a single instruction in new or stopped fiber
+    pub fn step(&self, f: &mut Fiber<T, L>) -> Result<Event<T, L>> {
+        Ok(match f.step_inst()? {
+            Some(signal) => Event::Signal(signal),
+            None => Event::Step,
+        })
+    }
+
+    /// Continue execution of new or stopped fiber until next breakpoint or signal
+    pub fn cont(&self, f: &mut Fiber<T, L>) -> Result<Event<T, L>> {
+        if let Some(signal) = f.step_inst()? {
+            return Ok(Event::Signal(signal));
+        }
+        self.run(f)
+    }
+
+    /// Resume fiber paused by yield or await with given value, and continue execution until
+    /// next breakpoint or signal
+    pub fn resume(
+        &self,
+        f: &mut Fiber<T, L>,
+        val_result: Result<Val<T, L>>,
+    ) -> Result<Event<T, L>> {
+        f.prepare_resume(val_result)?;
+        self.run(f)
+    }
+
+    /// Run fiber, checking breakpoints before each instruction
+    fn run(&self, f: &mut Fiber<T, L>) -> Result<Event<T, L>> {
+        loop {
+            if let Some(id) = self.hit_breakpoint(f) {
+                return Ok(Event::Break(id));
+            }
+            if let Some(signal) = f.step_inst()? {
+                return Ok(Event::Signal(signal));
+            }
+        }
+    }
+
+    /// Id of first breakpoint matching next instruction of fiber, if any
+    fn hit_breakpoint(&self, f: &Fiber<T, L>) -> Option<usize> {
+        let frames = f.frames();
+        let frame = frames.last()?;
+        self.breakpoints
+            .iter()
+            .enumerate()
+            .filter_map(|(id, e)| e.as_ref().map(|e| (id, e)))
+            .find_map(|(id, e)| {
+                let hit = match &e.bp {
+                    Breakpoint::Function(name) => match frame.inst() {
+                        Some(Inst::CallFunc(nargs)) => {
+                            let stack = f.stack();
+                            match stack.len().checked_sub(nargs + 1) {
+                                Some(idx) => e.target(name, frame.env)
+                                    .is_some_and(|t| same_function(&stack[idx], t)),
+                                None => false,
+                            }
+                        }
+                        _ => false,
+                    },
+                    Breakpoint::Location {
+                        function: None,
+                        offset,
+                    } => frames.len() == 1 && frame.ip == *offset,
+                    Breakpoint::Location {
+                        function: Some(name),
+                        offset,
+                    } => {
+                        frame.ip == *offset
+                            && matches!(e.target(name, frame.env), Some(Val::Lambda(l)) if Arc::ptr_eq(&l.code, frame.code))
+                    }
+                };
+                hit.then_some(id)
+            })
+    }
+}
+
+/// Whether or not callee is given function, comparing lambdas by their code
+fn same_function<T: Extern, L: Locals>(callee: &Val<T, L>, func: &Val<T, L>) -> bool {
+    match (callee, func) {
+        (Val::Lambda(a), Val::Lambda(b)) => Arc::ptr_eq(&a.code, &b.code),
+        _ => callee == func,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compile, Env, Error, NativeAsyncFn};
+    use assert_matches::assert_matches;
+    use void::Void;
+
+    type Fiber = crate::Fiber<Void, ()>;
+    type Val = crate::Val<Void, ()>;
+
+    #[test]
+    fn disassemble_jumps() {
+        let code = compile(&Val::from_expr("(if true 1 2)").unwrap()).unwrap();
+        assert_eq!(
+            disassemble(&code),
+            "   0 pushco true
+   1 jmpift 2 -> 4
+   2 pushco 2
+   3 jmpfwd 1 -> 5
+   4 pushco 1
+"
+        );
+    }
+
+    #[test]
+    fn disassemble_nested() {
+        let code = compile(&Val::from_expr("(fn (x) (loop x))").unwrap()).unwrap();
+        assert_eq!(
+            disassemble(&code),
+            "   0 pushco (x)
+   1 pushco nil
+   2 pushco <bytecode>
+       0 getsym x
+       1 poptop
+       2 jmpbck 3 -> 0
+   3 makefn
+"
+        );
+    }
+
+    #[test]
+    fn step() {
+        let mut f = Fiber::from_expr("(begin (def x 1) (+ x 2))", Env::standard(), ()).unwrap();
+        let dbg = Debugger::new();
+
+        assert_eq!(dbg.step(&mut f).unwrap(), Event::Step);
+        assert_eq!(f.stack(), &[Val::Int(1)]);
+        assert_eq!(dbg.step(&mut f).unwrap(), Event::Step);
+        assert_eq!(
+            f.frames()[0].bindings().last(),
+            Some(&(SymbolId::from("x"), Val::Int(1)))
+        );
+        assert_matches!(f.start(), Err(Error::UnexpectedResume(_)));
+
+        assert_eq!(
+            dbg.cont(&mut f).unwrap(),
+            Event::Signal(Signal::Done(Val::Int(3)))
+        );
+        assert!(f.is_done());
+        assert_matches!(dbg.step(&mut f), Err(Error::UnexpectedResume(_)));
+    }
+
+    #[test]
+    fn break_on_function() {
+        let prog = "(begin
+            (defn double (x) (+ x x))
+            (def a (double 1))
+            (double 2))";
+        let mut f = Fiber::from_expr(prog, Env::standard(), ()).unwrap();
+        let mut dbg = Debugger::new();
+        let bp = dbg.add_breakpoint(Breakpoint::Function(SymbolId::from("double")));
+
+        assert_eq!(dbg.cont(&mut f).unwrap(), Event::Break(bp));
+        assert_eq!(f.stack().last(), Some(&Val::Int(1)));
+        assert_eq!(dbg.cont(&mut f).unwrap(), Event::Break(bp));
+        assert_eq!(f.stack().last(), Some(&Val::Int(2)));
+
+        assert!(dbg.remove_breakpoint(bp));
+        assert!(!dbg.remove_breakpoint(bp));
+        assert_eq!(
+            dbg.cont(&mut f).unwrap(),
+            Event::Signal(Signal::Done(Val::Int(4)))
+        );
+    }
+
+    #[test]
+    fn break_on_location() {
+        let prog = "(begin
+            (defn inc (x) (+ x 1))
+            (inc 10))";
+        let mut f = Fiber::from_expr(prog, Env::standard(), ()).unwrap();
+        let mut dbg = Debugger::new();
+        let bp = dbg.add_breakpoint(Breakpoint::Location {
+            function: Some(SymbolId::from("inc")),
+            offset: 2,
+        });
+
+        assert_eq!(dbg.cont(&mut f).unwrap(), Event::Break(bp));
+        let frames = f.frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].inst(), Some(&Inst::PushConst(Val::Int(1))));
+        assert_eq!(
+            frames[1].bindings(),
+            vec![(SymbolId::from("x"), Val::Int(10))]
+        );
+        drop(frames);
+
+        assert_eq!(
+            dbg.cont(&mut f).unwrap(),
+            Event::Signal(Signal::Done(Val::Int(11)))
+        );
+    }
+
+    #[tokio::test]
+    async fn resume_after_await() {
+        let mut env = Env::standard();
+        env.bind_native_async(
+            SymbolId::from("async_one"),
+            NativeAsyncFn {
+                doc: "".to_string(),
+                arity: None,
+                func: |_, _| Box::new(async { Ok(Val::Int(1)) }),
+            },
+        );
+        let prog = "(begin (def x (async_one)) (+ x 1))";
+        let mut f = Fiber::from_expr(prog, env, ()).unwrap();
+        let mut dbg = Debugger::new();
+        let bp = dbg.add_breakpoint(Breakpoint::Function(SymbolId::from("+")));
+
+        let call = match dbg.cont(&mut f).unwrap() {
+            Event::Signal(Signal::Await(call)) => call,
+            e => panic!("Expected await - got {e:?}"),
+        };
+        let res = call.apply(&mut f).await;
+        assert_eq!(dbg.resume(&mut f, res).unwrap(), Event::Break(bp));
+        assert_eq!(
+            dbg.cont(&mut f).unwrap(),
+            Event::Signal(Signal::Done(Val::Int(2)))
+        );
+    }
+}
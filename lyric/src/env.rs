@@ -15,16 +15,30 @@ pub struct Env<T: Extern, L: Locals> {
 /// Reference to an environment
 pub type EnvRef<T, L> = Arc<Mutex<Env<T, L>>>;
 
+/// Environment as a first class value, compared by reference
+pub struct EnvVal<T: Extern, L: Locals>(pub EnvRef<T, L>);
+
 impl<T: Extern, L: Locals> Env<T, L> {
-    /// Create standard base env
-    pub fn standard() -> Self {
-        let mut e = Env {
+    /// Create empty env without bindings
+    pub fn empty() -> Self {
+        Env {
             bindings: HashMap::default(),
             parent: None,
-        };
+        }
+    }
+
+    /// Create base env with builtins for working with values only. Builtins that print, read the
+    /// clock, inspect environments, or create environments are left out.
+    pub fn core() -> Self {
+        let mut e = Env::empty();
         e.bind_native(SymbolId::from("contains?"), builtin::contains_fn())
             .bind_native(SymbolId::from("eq?"), builtin::eq_fn())
             .bind_native(SymbolId::from("+"), builtin::plus_fn())
+            .bind_native(SymbolId::from("-"), builtin::minus_fn())
+            .bind_native(SymbolId::from("<"), builtin::lt_fn())
+            .bind_native(SymbolId::from("<="), builtin::le_fn())
+            .bind_native(SymbolId::from(">"), builtin::gt_fn())
+            .bind_native(SymbolId::from(">="), builtin::ge_fn())
             .bind_native(SymbolId::from("ref"), builtin::ref_fn())
             .bind_native(SymbolId::from("list"), builtin::list_fn())
             .bind_native(SymbolId::from("push"), builtin::push_fn())
@@ -32,20 +46,49 @@ impl<T: Extern, L: Locals> Env<T, L> {
             .bind_native(SymbolId::from("map"), builtin::map_fn())
             .bind_native(SymbolId::from("len"), builtin::len_fn())
             .bind_lambda(SymbolId::from("filter"), builtin::filter_fn())
+            .bind_native(SymbolId::from("seq"), builtin::seq_fn())
+            .bind_native(SymbolId::from("next"), builtin::next_fn())
+            .bind_native(SymbolId::from("take"), builtin::take_fn())
+            .bind_lambda(SymbolId::from("lazy_map"), builtin::lazy_map_fn())
+            .bind_lambda(SymbolId::from("lazy_filter"), builtin::lazy_filter_fn())
+            .bind_lambda(SymbolId::from("iterate"), builtin::iterate_fn())
+            .bind_lambda(SymbolId::from("repeat"), builtin::repeat_fn())
+            .bind_lambda(SymbolId::from("realize"), builtin::realize_fn())
             .bind_native(SymbolId::from("not?"), builtin::not_fn())
             .bind_native(SymbolId::from("ok?"), builtin::ok_fn())
             .bind_native(SymbolId::from("empty?"), builtin::empty_fn())
             .bind_native(SymbolId::from("keyword?"), builtin::is_keyword_fn())
+            .bind_native(SymbolId::from("type_of"), builtin::type_of_fn())
             .bind_native(SymbolId::from("err?"), builtin::err_fn())
             .bind_native(SymbolId::from("str"), builtin::str_fn())
             .bind_native(SymbolId::from("join"), builtin::join_fn())
             .bind_native(SymbolId::from("split"), builtin::split_fn())
             .bind_native(SymbolId::from("format"), builtin::format_fn())
             .bind_native(SymbolId::from("display"), builtin::display_fn())
-            .bind_native(SymbolId::from("dbg"), builtin::dbg_fn())
+            .bind_native(SymbolId::from("pprint"), builtin::pprint_fn())
             .bind_native(SymbolId::from("read"), builtin::read_fn())
+            .bind_native(SymbolId::from("json_encode"), builtin::json_encode_fn())
+            .bind_native(SymbolId::from("json_decode"), builtin::json_decode_fn())
+            .bind_native(SymbolId::from("duration"), builtin::duration_fn())
+            .bind_native(SymbolId::from("time_parse"), builtin::time_parse_fn())
+            .bind_native(SymbolId::from("time_format"), builtin::time_format_fn())
+            .bind_native(SymbolId::from("time_in_tz"), builtin::time_in_tz_fn())
+            .bind_native(SymbolId::from("time_fields"), builtin::time_fields_fn());
+
+        e
+    }
+
+    /// Create standard base env
+    pub fn standard() -> Self {
+        let mut e = Env::core();
+        e.bind_native(SymbolId::from("dbg"), builtin::dbg_fn())
+            .bind_native(SymbolId::from("now"), builtin::now_fn())
             .bind_native(SymbolId::from("help"), builtin::help_fn())
-            .bind_native(SymbolId::from("ls_env"), builtin::ls_env_fn());
+            .bind_native(SymbolId::from("ls_env"), builtin::ls_env_fn())
+            .bind_native(SymbolId::from("check"), builtin::check_fn())
+            .bind_native(SymbolId::from("make_env"), builtin::make_env_fn())
+            .bind_native(SymbolId::from("env_get"), builtin::env_get_fn())
+            .bind_native(SymbolId::from("env_define"), builtin::env_define_fn());
 
         e
     }
@@ -123,12 +166,54 @@ impl<T: Extern, L: Locals> Env<T, L> {
         self
     }
 
+    /// Find symbol bound to given value in lexical scope, if any
+    pub(crate) fn name_of(&self, value: &Val<T, L>) -> Option<SymbolId> {
+        match self.bindings.iter().find(|(_, v)| *v == value) {
+            Some((s, _)) => Some(s.clone()),
+            None => self
+                .parent
+                .as_ref()
+                .and_then(|p| p.lock().unwrap().name_of(value)),
+        }
+    }
+
+    /// Parent environment, if any
+    pub(crate) fn parent(&self) -> Option<&EnvRef<T, L>> {
+        self.parent.as_ref()
+    }
+
     /// Iterate over all symbols and bindings
     pub fn iter(&self) -> EnvIter<'_, T, L> {
         EnvIter(self.bindings.iter())
     }
 }
 
+impl<T: Extern, L: Locals> EnvVal<T, L> {
+    /// Wrap environment as value
+    pub fn new(env: Env<T, L>) -> Self {
+        Self(Arc::new(Mutex::new(env)))
+    }
+}
+
+impl<T: Extern, L: Locals> Clone for EnvVal<T, L> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: Extern, L: Locals> PartialEq for EnvVal<T, L> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T: Extern, L: Locals> std::fmt::Debug for EnvVal<T, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // don't blow the stack via environments referring back to themselves
+        write!(f, "EnvVal(..)")
+    }
+}
+
 impl<T: Extern, L: Locals> std::clone::Clone for Env<T, L> {
     // Clone by value, not by ref via Arc::clone
     fn clone(&self) -> Self {
@@ -240,5 +325,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn core_without_side_effects() {
+        let env = Env::core();
+        assert!(env.get(&SymbolId::from("+")).is_some());
+        for sym in ["dbg", "now", "ls_env", "make_env"] {
+            assert_eq!(env.get(&SymbolId::from(sym)), None, "{sym} is not in core");
+        }
+        assert!(Env::standard().get(&SymbolId::from("make_env")).is_some());
+        assert_eq!(Env::empty().iter().count(), 0);
+    }
+
     // TODO: Test Clone Isolation
 }
@@ -28,9 +28,25 @@ pub enum Error {
     #[error("Invalid pattern match")]
     InvalidPatternMatch,
 
+    #[error("No matching clause for call - {0}")]
+    NoMatchingClause(String),
+
+    #[error("No implementation of protocol method - {0}")]
+    NoImplementation(String),
+
+    #[error("End of sequence")]
+    EndOfSeq,
+
+    /// Returned by host bindings like `recv` when a deadline passes
+    #[error("Timed out")]
+    Timeout,
+
     #[error("Unexpected top-level fiber yield")]
     UnexpectedTopLevelYield,
 
+    #[error("Serialization error - {0}")]
+    Serialization(String),
+
     #[error("Runtime error - {0}")]
     Runtime(String),
 }
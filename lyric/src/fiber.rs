@@ -1,10 +1,12 @@
 //! A fiber of execution that can be driven by caller as a coroutine.
 
 use super::{Env, Inst};
+use crate::profile::{self, CallTree, Profiler};
+use crate::seq::{State as SeqState, Suspended};
 use crate::types::NativeAsyncCall;
 use crate::{
-    builtin::cond::is_true, compile, parse, Bytecode, Error, Extern, Lambda, Locals, NativeFnOp,
-    Pattern, Result, Val,
+    builtin::cond::is_true, compile, parse, Bytecode, Dynamic, Error, Extern, Lambda, Locals,
+    NativeFnOp, Pattern, Result, Seq, SymbolId, Val,
 };
 use std::sync::{Arc, Mutex};
 use tracing::warn;
@@ -22,6 +24,11 @@ pub struct Fiber<T: Extern, L: Locals> {
     stack: Vec<Val<T, L>>,
     global: Arc<Mutex<Env<T, L>>>,
     locals: L,
+    profiler: Option<Profiler>,
+    /// Calls profiled in current profiling session
+    calls: CallTree<T, L>,
+    /// Instructions executed since counts were last flushed to profiler
+    uncounted_insts: u64,
 }
 
 /// The status of fiber
@@ -59,19 +66,38 @@ impl<T: Extern, L: Locals> std::cmp::PartialEq for Signal<T, L> {
     }
 }
 
+/// View of call frame in fiber for inspection
+#[derive(Debug)]
+pub struct Frame<'a, T: Extern, L: Locals> {
+    /// Offset of next instruction in code
+    pub ip: usize,
+    /// Code in callframe
+    pub code: &'a Arc<Bytecode<T, L>>,
+    /// Environment this callframe is operating in
+    pub env: &'a Arc<Mutex<Env<T, L>>>,
+}
+
 /// Single call frame of fiber
 #[derive(Debug)]
-struct CallFrame<T: Extern, L: Locals> {
+pub(crate) struct CallFrame<T: Extern, L: Locals> {
     /// instruction pointer in code
     ip: usize,
     /// Code in callframe
-    code: Bytecode<T, L>,
+    code: Arc<Bytecode<T, L>>,
     /// Environment this callframe is operating in
     env: Arc<Mutex<Env<T, L>>>,
     /// Length of stack when callframe was created
     stack_len: usize,
     /// Length of callframe of fiber to unwind to on error, if any
     unwind_cf_len: Option<usize>,
+    /// Fixed name of function callframe is executing, e.g. for outermost callframe
+    label: Option<&'static str>,
+    /// Profiling session and node of call tree for callframe, if profiled
+    node: Option<(u64, usize)>,
+    /// Sequence whose generator is entered by callframe, if any
+    seq: Option<Seq<T, L>>,
+    /// Dynamic variables rebound by callframe via `parameterize`
+    dynamics: Vec<(Dynamic<T, L>, Val<T, L>)>,
 }
 
 impl<T: Extern, L: Locals> Fiber<T, L> {
@@ -83,12 +109,16 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
             stack: vec![],
             cframes: vec![CallFrame::from_bytecode(
                 Arc::clone(&global),
-                bytecode,
+                Arc::new(bytecode),
                 0,
                 None,
-            )],
+            )
+            .labeled(profile::MAIN)],
             global,
             locals,
+            profiler: None,
+            calls: CallTree::new(0),
+            uncounted_insts: 0,
         }
     }
 
@@ -118,19 +148,52 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
 
     /// Resume a paused fiber execution
     pub fn resume(&mut self, val_result: Result<Val<T, L>>) -> Result<Signal<T, L>> {
+        self.prepare_resume(val_result)?;
+        self.run()
+    }
+
+    /// Prepare paused fiber to continue execution with given resume value
+    pub(crate) fn prepare_resume(&mut self, val_result: Result<Val<T, L>>) -> Result<()> {
         if self.status != Status::Paused {
             return Err(Error::UnexpectedResume(
                 "resuming a fiber that is not paused".to_string(),
             ));
         }
 
+        if let Some(p) = &self.profiler {
+            p.end_await();
+        }
+
         let val = match val_result {
             Ok(val) => val,
             Err(e) => self.maybe_catch_err(e)?,
         };
 
         self.stack.push(val);
-        self.run()
+        self.status = Status::Running;
+        Ok(())
+    }
+
+    /// Run a single instruction, returning signal if fiber stopped running as a result
+    pub(crate) fn step_inst(&mut self) -> Result<Option<Signal<T, L>>> {
+        match self.status {
+            Status::New | Status::Running => self.status = Status::Running,
+            _ => {
+                return Err(Error::UnexpectedResume(
+                    "stepping a fiber that is not running".to_string(),
+                ))
+            }
+        }
+        self.run_inst()?;
+        if self.status == Status::Running {
+            if let Err(e) = self.pop_returned_frames() {
+                let err_val = self.maybe_catch_err(e)?;
+                self.stack.push(err_val);
+            }
+            Ok(None)
+        } else {
+            self.signal().map(Some)
+        }
     }
 
     /// Whether or not fiber is done running
@@ -157,6 +220,34 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
     pub fn locals_mut(&mut self) -> &mut L {
         &mut self.locals
     }
+
+    /// Values on stack, from bottom to top
+    pub fn stack(&self) -> &[Val<T, L>] {
+        &self.stack
+    }
+
+    /// Call frames, from outermost to innermost
+    pub fn frames(&self) -> Vec<Frame<'_, T, L>> {
+        self.cframes
+            .iter()
+            .map(|cf| Frame {
+                ip: cf.ip,
+                code: &cf.code,
+                env: &cf.env,
+            })
+            .collect()
+    }
+
+    /// Attach profiler to fiber, or detach with `None`. Fiber is profiled while profiler is
+    /// started.
+    pub fn set_profiler(&mut self, profiler: Option<Profiler>) {
+        self.profiler = profiler;
+    }
+
+    /// Profiler attached to fiber, if any
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
 }
 
 impl<T: Extern, L: Locals> Fiber<T, L> {
@@ -170,12 +261,27 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
             // TODO(dev): Bytecode debugging utilities
             // tracing::debug!("{self:?}");
 
-            if let Err(e) = self.step() {
-                let err_val = self.maybe_catch_err(e)?;
-                self.stack.push(err_val);
+            if let Err(e) = self.run_inst() {
+                self.flush_profile();
+                return Err(e);
             }
         }
 
+        self.flush_profile();
+        self.signal()
+    }
+
+    /// Run a single instruction, catching errors if fiber is in protected callframe
+    fn run_inst(&mut self) -> Result<()> {
+        if let Err(e) = self.step() {
+            let err_val = self.maybe_catch_err(e)?;
+            self.stack.push(err_val);
+        }
+        Ok(())
+    }
+
+    /// Signal for fiber that stopped running
+    fn signal(&mut self) -> Result<Signal<T, L>> {
         match &self.status {
             Status::Paused => {
                 let res = self.stack.pop().ok_or(Error::UnexpectedStack(
@@ -206,7 +312,7 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
                 }
                 Ok(Signal::Done(res))
             }
-            s => panic!("Fiber::run exiting in unexpected state - {s:?}"),
+            s => panic!("Fiber::signal called in unexpected state - {s:?}"),
         }
     }
 
@@ -216,12 +322,14 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
         // Catch unwind or exit w/ error
         let unwind_len = match self.cf().unwind_cf_len {
             None => {
+                self.end_seqs(0);
                 self.status = Status::Done;
                 return Err(e); // no catching - propagate
             }
             Some(l) => l,
         };
         let stack_len = self.cframes[unwind_len].stack_len;
+        self.end_seqs(unwind_len);
         self.cframes.truncate(unwind_len);
         self.stack.truncate(stack_len);
         Ok(Val::Error(e)) // return as Val::Error
@@ -229,14 +337,7 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
 
     /// Run a single fetch-decode-execute cycle
     fn step(&mut self) -> Result<()> {
-        while self.cframes.len() > 1 && self.cf().at_return() {
-            let cf = self.cframes.last().unwrap();
-            if self.stack.len() != cf.stack_len + 1 {
-                // tracing::debug!("panic {:?}", self);
-                panic!("Unexpected state during execution - all function are expected to have stack effect of 1. Was {}", cf.stack_len + 1);
-            }
-            let _ = self.cframes.pop();
-        }
+        self.pop_returned_frames()?;
 
         let inst = match self.inst() {
             Some(i) => i.clone(),
@@ -253,6 +354,8 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
         //     self.id, inst, &self.stack
         // );
 
+        self.profile_inst();
+
         self.cf_mut().ip += 1;
 
         match inst {
@@ -285,11 +388,41 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
                     env.define(s, v.clone());
                 }
             }
+            Inst::MatchBind => {
+                let (pat, val) = match (self.stack.pop(), self.stack.pop()) {
+                    (Some(pat), Some(val)) => (pat, val),
+                    _ => {
+                        return Err(Error::UnexpectedStack(
+                            "Stack should contain pattern and value to match".to_string(),
+                        ))
+                    }
+                };
+
+                let matched = match Pattern::from_val(pat).matches(&val) {
+                    Some(m) => {
+                        let mut env = self.cur_env().lock().unwrap();
+                        for (s, v) in m.into_iter() {
+                            env.define(s, v);
+                        }
+                        true
+                    }
+                    None => false,
+                };
+                self.stack.push(Val::Bool(matched));
+            }
             Inst::SetSym(s) => {
-                let value = self.stack.last().ok_or(Error::UnexpectedStack(
-                    "Stack should contain value to bind".to_string(),
-                ))?;
-                self.cur_env().lock().unwrap().set(&s, value.clone())?
+                let value = self
+                    .stack
+                    .last()
+                    .ok_or(Error::UnexpectedStack(
+                        "Stack should contain value to bind".to_string(),
+                    ))?
+                    .clone();
+                let cur = self.cur_env().lock().unwrap().get(&s);
+                match cur {
+                    Some(Val::Dynamic(d)) => self.set_dynamic(&s, &d, value)?,
+                    _ => self.cur_env().lock().unwrap().set(&s, value)?,
+                }
             }
             Inst::GetSym(s) => {
                 let value = self
@@ -299,7 +432,11 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
                     .unwrap()
                     .get(&s)
                     .ok_or(Error::UndefinedSymbol(s))?;
-                self.stack.push(value.clone());
+                let value = match value {
+                    Val::Dynamic(d) => self.dynamic_value(&d),
+                    v => v,
+                };
+                self.stack.push(value);
             }
             Inst::MakeFunc => {
                 let code = match self.stack.pop() {
@@ -333,7 +470,7 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
                 self.stack.push(Val::Lambda(Lambda {
                     doc,
                     params,
-                    code,
+                    code: Arc::new(code),
                     parent: Some(Arc::clone(&self.cf().env)),
                 }));
             }
@@ -347,19 +484,28 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
                 }
                 let args = args.into_iter().rev();
 
-                match self.stack.pop() {
+                let callee = self.stack.pop();
+                let node = match (&callee, self.active_profiler()) {
+                    (Some(v), Some(_)) => Some(self.call_node(v)),
+                    _ => None,
+                };
+
+                match callee {
                     Some(Val::Lambda(l)) => {
                         let parent_env = l.parent.unwrap_or_else(|| Arc::clone(&self.global));
                         let mut fn_env = Env::extend(&parent_env);
                         for (s, arg) in l.params.into_iter().zip(args) {
                             fn_env.define(s, arg);
                         }
-                        self.cframes.push(CallFrame::from_bytecode(
-                            Arc::new(Mutex::new(fn_env)),
-                            l.code,
-                            self.stack.len(),
-                            self.cf().unwind_cf_len,
-                        ))
+                        self.cframes.push(
+                            CallFrame::from_bytecode(
+                                Arc::new(Mutex::new(fn_env)),
+                                l.code,
+                                self.stack.len(),
+                                self.cf().unwind_cf_len,
+                            )
+                            .profiled(node),
+                        )
                     }
                     Some(Val::NativeFn(n)) => {
                         let v = (n.func)(self, &args.collect::<Vec<_>>())?;
@@ -369,15 +515,23 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
                                 self.stack.push(v);
                                 self.status = Status::Paused;
                             }
-                            NativeFnOp::Exec(code) => self.cframes.push(CallFrame::from_bytecode(
-                                Arc::clone(self.cur_env()),
-                                code,
-                                self.stack.len(),
-                                self.cf().unwind_cf_len,
-                            )),
+                            NativeFnOp::Exec(code) => self.cframes.push(
+                                CallFrame::from_bytecode(
+                                    Arc::clone(self.cur_env()),
+                                    Arc::new(code),
+                                    self.stack.len(),
+                                    self.cf().unwind_cf_len,
+                                )
+                                .profiled(node),
+                            ),
+                            NativeFnOp::Next(seq) => self.next_seq(seq)?,
                         }
                     }
                     Some(Val::NativeAsyncFn(fun)) => {
+                        if let (Some(p), Some((_, node))) = (self.profiler.clone(), node) {
+                            self.flush_profile();
+                            p.begin_await(self.calls.stack(node));
+                        }
                         // TODO: Hack - pass to parent scope via stack
                         self.stack.push(Val::List(args.collect::<Vec<_>>()));
                         self.stack.push(Val::NativeAsyncFn(fun));
@@ -403,13 +557,68 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
                 let val = self.stack.pop().ok_or(Error::UnexpectedStack(
                     "Did not find form to eval on stack".to_string(),
                 ))?;
-                let bc = compile(&val)?;
-                self.cframes.push(CallFrame::from_bytecode(
+                self.push_eval(&val, Arc::clone(self.cur_env()), unwind_cf_len)?;
+            }
+            Inst::EvalIn => {
+                let env = match self.stack.pop() {
+                    Some(Val::Env(e)) => e.0,
+                    Some(v) => {
+                        return Err(Error::UnexpectedType(format!(
+                            "eval expects an environment - got {v}"
+                        )))
+                    }
+                    None => {
+                        return Err(Error::UnexpectedStack(
+                            "Did not find environment to eval in on stack".to_string(),
+                        ))
+                    }
+                };
+                let val = self.stack.pop().ok_or(Error::UnexpectedStack(
+                    "Did not find form to eval on stack".to_string(),
+                ))?;
+                let unwind_cf_len = self.cf().unwind_cf_len;
+                self.push_eval(&val, env, unwind_cf_len)?;
+            }
+            Inst::Parameterize(symbols) => {
+                let code = match self.stack.pop() {
+                    Some(Val::Bytecode(b)) => b,
+                    _ => {
+                        return Err(Error::UnexpectedStack(
+                            "Missing parameterize body bytecode".to_string(),
+                        ))
+                    }
+                };
+                let values = self
+                    .stack
+                    .len()
+                    .checked_sub(symbols.len())
+                    .map(|at| self.stack.split_off(at))
+                    .ok_or(Error::UnexpectedStack(
+                        "Missing values to bind dynamic variables to".to_string(),
+                    ))?;
+                let mut dynamics = vec![];
+                {
+                    let env = self.cur_env().lock().unwrap();
+                    for (s, v) in symbols.into_iter().zip(values) {
+                        match env.get(&s) {
+                            Some(Val::Dynamic(d)) => dynamics.push((d, v)),
+                            Some(_) => {
+                                return Err(Error::UnexpectedType(format!(
+                                    "{s} is not a dynamic variable"
+                                )))
+                            }
+                            None => return Err(Error::UndefinedSymbol(s)),
+                        }
+                    }
+                }
+                let mut cf = CallFrame::from_bytecode(
                     Arc::clone(self.cur_env()),
-                    bc,
+                    Arc::new(code),
                     self.stack.len(),
-                    unwind_cf_len,
-                ));
+                    self.cf().unwind_cf_len,
+                );
+                cf.dynamics = dynamics;
+                self.cframes.push(cf);
             }
             Inst::PopTop => {
                 if self.stack.pop().is_none() {
@@ -428,12 +637,249 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
                     self.cf_mut().ip += offset;
                 }
             }
-            Inst::YieldTop => self.status = Status::Paused,
+            Inst::YieldTop => match self.cframes.iter().rposition(|cf| cf.seq.is_some()) {
+                Some(entry) => self.suspend_seq(entry)?,
+                None => self.status = Status::Paused,
+            },
         };
 
         Ok(())
     }
 
+    /// Pop callframes that have returned. Returning from entry callframe of a sequence's
+    /// generator ends the sequence with [Error::EndOfSeq].
+    fn pop_returned_frames(&mut self) -> Result<()> {
+        while self.cframes.len() > 1 && self.cf().at_return() {
+            let cf = self.cframes.last().unwrap();
+            if self.stack.len() != cf.stack_len + 1 {
+                // tracing::debug!("panic {:?}", self);
+                panic!("Unexpected state during execution - all function are expected to have stack effect of 1. Was {}", cf.stack_len + 1);
+            }
+            if let Some(seq) = self.cframes.pop().and_then(|cf| cf.seq) {
+                self.stack.pop();
+                seq.replace(SeqState::Done);
+                return Err(Error::EndOfSeq);
+            }
+        }
+        Ok(())
+    }
+
+    /// Produce next element of sequence onto stack, entering or resuming its generator as needed
+    fn next_seq(&mut self, seq: Seq<T, L>) -> Result<()> {
+        match seq.replace(SeqState::Running) {
+            SeqState::New(callable) => {
+                let mut cf = CallFrame::from_bytecode(
+                    Arc::clone(self.cur_env()),
+                    Arc::new(vec![Inst::PushConst(callable), Inst::CallFunc(0)]),
+                    self.stack.len(),
+                    self.cf().unwind_cf_len,
+                );
+                cf.seq = Some(seq);
+                self.cframes.push(cf);
+            }
+            SeqState::Suspended(Suspended { frames, stack }) => {
+                let cf_len = self.cframes.len();
+                let stack_len = self.stack.len();
+                let unwind_cf_len = self.cf().unwind_cf_len;
+                self.cframes.extend(frames.into_iter().map(|mut cf| {
+                    cf.stack_len += stack_len;
+                    cf.unwind_cf_len = match cf.unwind_cf_len {
+                        Some(l) => Some(l + cf_len),
+                        None => unwind_cf_len,
+                    };
+                    cf
+                }));
+                self.stack.extend(stack);
+                self.reparent_profiled(cf_len);
+                // Result of yield in generator
+                self.stack.push(Val::Nil);
+            }
+            SeqState::Running => {
+                return Err(Error::Runtime(
+                    "Sequence is already producing next element".to_string(),
+                ))
+            }
+            SeqState::Items(mut items) => {
+                let item = items.pop_front();
+                match item {
+                    Some(item) => {
+                        seq.replace(SeqState::Items(items));
+                        self.stack.push(item);
+                    }
+                    None => {
+                        seq.replace(SeqState::Done);
+                        return Err(Error::EndOfSeq);
+                    }
+                }
+            }
+            SeqState::Take(inner, n) => {
+                if n == 0 {
+                    seq.replace(SeqState::Done);
+                    return Err(Error::EndOfSeq);
+                }
+                seq.replace(SeqState::Take(inner.clone(), n - 1));
+                self.next_seq(inner)?;
+            }
+            SeqState::Done => {
+                seq.replace(SeqState::Done);
+                return Err(Error::EndOfSeq);
+            }
+        }
+        Ok(())
+    }
+
+    /// Current value of dynamic variable - its innermost binding, or its default if not rebound
+    pub(crate) fn dynamic_value(&self, var: &Dynamic<T, L>) -> Val<T, L> {
+        self.cframes
+            .iter()
+            .rev()
+            .flat_map(|cf| cf.dynamics.iter().rev())
+            .find(|(d, _)| d.is(var))
+            .map(|(_, v)| v.clone())
+            .unwrap_or_else(|| var.default.as_ref().clone())
+    }
+
+    /// Set innermost binding of dynamic variable, or its default if it is not rebound
+    fn set_dynamic(
+        &mut self,
+        symbol: &SymbolId,
+        var: &Dynamic<T, L>,
+        value: Val<T, L>,
+    ) -> Result<()> {
+        let binding = self
+            .cframes
+            .iter_mut()
+            .rev()
+            .flat_map(|cf| cf.dynamics.iter_mut().rev())
+            .find(|(d, _)| d.is(var));
+        match binding {
+            Some((_, v)) => *v = value,
+            None => self
+                .cur_env()
+                .lock()
+                .unwrap()
+                .set(symbol, Val::Dynamic(var.with_default(value)))?,
+        }
+        Ok(())
+    }
+
+    /// Push callframe evaluating given form in given environment
+    fn push_eval(
+        &mut self,
+        form: &Val<T, L>,
+        env: Arc<Mutex<Env<T, L>>>,
+        unwind_cf_len: Option<usize>,
+    ) -> Result<()> {
+        let bc = compile(form)?;
+        self.cframes.push(
+            CallFrame::from_bytecode(env, Arc::new(bc), self.stack.len(), unwind_cf_len)
+                .labeled(profile::EVAL),
+        );
+        Ok(())
+    }
+
+    /// Suspend generator entered at given callframe, passing yielded value to its caller
+    fn suspend_seq(&mut self, entry: usize) -> Result<()> {
+        let val = self.stack.pop().ok_or(Error::UnexpectedStack(
+            "Stack should contain value to yield".to_string(),
+        ))?;
+        let mut frames = self.cframes.split_off(entry);
+        let stack_len = frames[0].stack_len;
+        let stack = self.stack.split_off(stack_len);
+        for cf in frames.iter_mut() {
+            cf.stack_len -= stack_len;
+            // Unwind within generator is kept relative to entry, otherwise inherited on resume
+            cf.unwind_cf_len = cf
+                .unwind_cf_len
+                .and_then(|l| l.checked_sub(entry))
+                .filter(|l| *l > 0);
+        }
+        let seq = frames[0].seq.clone().expect("entry callframe has sequence");
+        seq.replace(SeqState::Suspended(Suspended { frames, stack }));
+        self.stack.push(val);
+        Ok(())
+    }
+
+    /// End sequences whose generators are entered at or above given callframe
+    fn end_seqs(&mut self, cf_len: usize) {
+        for cf in self.cframes[cf_len..].iter() {
+            if let Some(seq) = &cf.seq {
+                seq.replace(SeqState::Done);
+            }
+        }
+    }
+
+    /// Attached profiler, if it is started
+    fn active_profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref().filter(|p| p.is_enabled())
+    }
+
+    /// Count instruction executed for attached profiler, flushing counts periodically
+    fn profile_inst(&mut self) {
+        let Some(p) = &self.profiler else {
+            return;
+        };
+        self.uncounted_insts += 1;
+        if p.is_enabled() {
+            let node = self.profile_node(self.cframes.len() - 1);
+            self.calls.count(node);
+        }
+        if self.uncounted_insts >= profile::FLUSH_INSTS {
+            self.flush_profile();
+        }
+    }
+
+    /// Flush instructions counted since last flush to attached profiler
+    fn flush_profile(&mut self) {
+        if let Some(p) = &self.profiler {
+            p.count_insts(std::mem::take(&mut self.uncounted_insts));
+            self.calls.flush(p);
+        }
+    }
+
+    /// Call tree node of callframe at given index. Callframes entered before profiling started
+    /// are attributed to unknown functions.
+    fn profile_node(&mut self, idx: usize) -> usize {
+        let session = self.profiler.as_ref().map(|p| p.session()).unwrap_or(0);
+        if self.calls.session() != session {
+            self.calls = CallTree::new(session);
+        }
+        if let Some((s, node)) = self.cframes[idx].node {
+            if s == session {
+                return node;
+            }
+        }
+        let parent = idx.checked_sub(1).map(|i| self.profile_node(i));
+        let label = self.cframes[idx].label.unwrap_or(profile::UNKNOWN);
+        let node = self.calls.label(parent, label);
+        self.cframes[idx].node = Some((session, node));
+        node
+    }
+
+    /// Call tree node for function being called from current callframe
+    fn call_node(&mut self, callee: &Val<T, L>) -> (u64, usize) {
+        let parent = self.profile_node(self.cframes.len() - 1);
+        let site = self.cf().ip - 1;
+        let node = self
+            .calls
+            .call(parent, callee, &Arc::clone(self.cur_env()), site);
+        (self.calls.session(), node)
+    }
+
+    /// Attribute callframes resumed from given index to functions called from callframe below
+    fn reparent_profiled(&mut self, from: usize) {
+        let session = self.calls.session();
+        for idx in from..self.cframes.len() {
+            if let Some((s, node)) = self.cframes[idx].node {
+                if s == session {
+                    let parent = idx.checked_sub(1).map(|i| self.profile_node(i));
+                    let node = self.calls.reparent(parent, node);
+                    self.cframes[idx].node = Some((session, node));
+                }
+            }
+        }
+    }
+
     /// Next instruction in fiber, or None if fiber is complete
     fn inst(&self) -> Option<&Inst<T, L>> {
         let cf = self.cf();
@@ -451,11 +897,31 @@ impl<T: Extern, L: Locals> Fiber<T, L> {
     }
 }
 
+impl<'a, T: Extern, L: Locals> Frame<'a, T, L> {
+    /// Next instruction in call frame, or None if call frame is at return
+    pub fn inst(&self) -> Option<&'a Inst<T, L>> {
+        self.code.get(self.ip)
+    }
+
+    /// Bindings defined in environment of call frame, excluding parent environments
+    pub fn bindings(&self) -> Vec<(SymbolId, Val<T, L>)> {
+        let mut bindings = self
+            .env
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(s, v)| (s.clone(), v.clone()))
+            .collect::<Vec<_>>();
+        bindings.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        bindings
+    }
+}
+
 impl<T: Extern, L: Locals> CallFrame<T, L> {
     /// Create a new callframe for executing given bytecode from start
     fn from_bytecode(
         env: Arc<Mutex<Env<T, L>>>,
-        code: Bytecode<T, L>,
+        code: Arc<Bytecode<T, L>>,
         stack_len: usize,
         unwind_cf_len: Option<usize>,
     ) -> Self {
@@ -465,9 +931,25 @@ impl<T: Extern, L: Locals> CallFrame<T, L> {
             code,
             stack_len,
             unwind_cf_len,
+            label: None,
+            node: None,
+            seq: None,
+            dynamics: vec![],
         }
     }
 
+    /// Set fixed name of function callframe is executing
+    fn labeled(mut self, label: &'static str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Set call tree node of callframe
+    fn profiled(mut self, node: Option<(u64, usize)>) -> Self {
+        self.node = node;
+        self
+    }
+
     /// Whether or not call frame is at implicit return
     fn at_return(&self) -> bool {
         self.ip == self.code.len()
@@ -644,7 +1126,7 @@ mod tests {
 
         assert_matches!(
             f.start().unwrap(),
-            Signal::Done(Val::Lambda(l)) if l.params == vec![SymbolId::from("x")] && l.code == vec![GetSym(SymbolId::from("x"))],
+            Signal::Done(Val::Lambda(l)) if l.params == vec![SymbolId::from("x")] && *l.code == vec![GetSym(SymbolId::from("x"))],
             "A function object was created"
         );
         assert!(f.is_done());
@@ -657,7 +1139,7 @@ mod tests {
                 PushConst(Val::Lambda(Lambda {
                     doc: None,
                     params: vec![SymbolId::from("x")],
-                    code: vec![GetSym(SymbolId::from("x"))],
+                    code: vec![GetSym(SymbolId::from("x"))].into(),
                     parent: None,
                 })),
                 PushConst(Val::string("hello")),
@@ -975,6 +1457,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn seq_generator_pauses_fiber() {
+        let mut env = Env::standard();
+        env.bind_native(
+            SymbolId::from("host_yield"),
+            crate::NativeFn {
+                doc: "".to_string(),
+                arity: None,
+                func: |_, args| Ok(NativeFnOp::Yield(args[0].clone())),
+            },
+        );
+        let mut f = Fiber::from_expr(
+            "(realize (take (seq (fn () (loop (yield (host_yield :tick))))) 2))",
+            env,
+            (),
+        )
+        .unwrap();
+
+        assert_eq!(f.start().unwrap(), Signal::Yield(Val::keyword("tick")));
+        assert_eq!(
+            f.resume(Ok(Val::Int(1))).unwrap(),
+            Signal::Yield(Val::keyword("tick"))
+        );
+        assert_eq!(
+            f.resume(Ok(Val::Int(2))).unwrap(),
+            Signal::Done(Val::List(vec![Val::Int(1), Val::Int(2)]))
+        );
+        assert!(f.stack().is_empty());
+    }
+
     // TODO: Add Test case for NativeFnOp::Call
     // TODO: Test that Fiber::resume w/ Err resume value (i.e. from nativeasyncfn err) is catch-able - (try (exec "jibberish"))
 }
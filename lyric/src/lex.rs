@@ -16,6 +16,10 @@ pub enum Token {
     ParenLeft,
     ParenRight,
     Quote,
+    /// `#_` discarding the next form
+    DatumComment,
+    /// `#(` opening a record like `#(point :x 1 :y 2)`
+    RecordStart,
 }
 
 impl std::fmt::Display for Token {
@@ -30,6 +34,8 @@ impl std::fmt::Display for Token {
             Token::ParenLeft => write!(f, "("),
             Token::ParenRight => write!(f, ")"),
             Token::Quote => write!(f, "'"),
+            Token::DatumComment => write!(f, "#_"),
+            Token::RecordStart => write!(f, "#("),
         }
     }
 }
@@ -42,6 +48,7 @@ pub(crate) fn lex(expr: &str) -> Result<Vec<Token>> {
             Ok(token) => tokens.push(token),
             Err(err) => {
                 error!("lexing failed - {}, tokens={:?}", err, tokens);
+                return Err(err);
             }
         }
     }
@@ -73,10 +80,15 @@ impl Tokens<'_> {
         }
     }
 
-    /// Pares the next int
+    /// Pares the next int, or symbol starting with `-` like `-` itself
     fn next_int(&mut self) -> Result<Token> {
         let expr: String =
             std::iter::from_fn(|| self.inner.next_if(|ch| !is_symbol_delimiter(ch))).collect();
+        if let Some(rest) = expr.strip_prefix('-') {
+            if !rest.starts_with(|ch: char| ch.is_numeric()) {
+                return Ok(Token::Symbol(expr));
+            }
+        }
         let num = expr.parse::<i32>().map_err(|_| {
             Error::IncompleteExpression(format!("Unable to parse integer - {expr}"))
         })?;
@@ -98,7 +110,7 @@ impl Tokens<'_> {
         }
     }
 
-    /// Parse next string
+    /// Parse next string, or triple-quoted string if it starts with `"""`
     fn next_string(&mut self) -> Result<Token> {
         let ch = self.inner.next().ok_or(Error::IncompleteExpression(
             "Expected opening string quotation".to_string(),
@@ -108,37 +120,131 @@ impl Tokens<'_> {
                 "Expected opening string quotation - found {ch}"
             )));
         }
+        if self.inner.next_if_eq(&'\"').is_some() {
+            if self.inner.next_if_eq(&'\"').is_none() {
+                return Ok(Token::String(String::new()));
+            }
+            return self.next_triple_quoted();
+        }
 
-        // TODO: Revisit iterators in lexer
-        let mut escaped = false;
-        let expr: String = std::iter::from_fn(|| {
-            while let Some(ch) = self.inner.next_if(|ch| *ch != '\"' || escaped) {
-                if !escaped && ch == '\\' {
-                    escaped = true;
-                } else {
-                    let actual_ch = match ch {
-                        'n' if escaped => '\n',
-                        '"' if escaped => '\"',
-                        _ => ch,
-                    };
-                    escaped = false;
-                    return Some(actual_ch);
+        // Escapes are kept as is until string is unescaped as a whole
+        let mut raw = String::new();
+        loop {
+            match self.inner.next() {
+                Some('\"') => break,
+                Some('\\') => {
+                    raw.push('\\');
+                    raw.extend(self.inner.next());
+                }
+                Some(ch) => raw.push(ch),
+                None => {
+                    return Err(Error::IncompleteExpression(
+                        "Expected closing string quotation".to_string(),
+                    ))
                 }
             }
-            None
-        })
-        .collect();
+        }
 
-        let ch = self.inner.next().ok_or(Error::IncompleteExpression(
-            "Expected closing string quotation".to_string(),
-        ))?;
-        if ch != '\"' {
-            return Err(Error::IncompleteExpression(format!(
-                "Expected closing string quotation - found {ch}"
-            )));
+        Ok(Token::String(unescape(&raw)?))
+    }
+
+    /// Parse rest of triple-quoted string after opening quotes
+    fn next_triple_quoted(&mut self) -> Result<Token> {
+        let mut raw = String::new();
+        let mut quotes = 0;
+        loop {
+            match self.inner.next() {
+                Some('\"') => {
+                    quotes += 1;
+                    if quotes == 3 {
+                        raw.truncate(raw.len() - 2);
+                        break;
+                    }
+                    raw.push('\"');
+                }
+                Some('\\') => {
+                    quotes = 0;
+                    raw.push('\\');
+                    raw.extend(self.inner.next());
+                }
+                Some(ch) => {
+                    quotes = 0;
+                    raw.push(ch);
+                }
+                None => {
+                    return Err(Error::IncompleteExpression(
+                        "Expected closing triple quotation".to_string(),
+                    ))
+                }
+            }
         }
 
-        Ok(Token::String(expr))
+        Ok(Token::String(unescape(&dedent(&raw))?))
+    }
+
+    /// Whether or not next characters start raw string, i.e. `r"` or `r#"`
+    fn at_raw_string(&self) -> bool {
+        let mut ahead = self.inner.clone();
+        ahead.next() == Some('r') && ahead.find(|ch| *ch != '#') == Some('\"')
+    }
+
+    /// Parse raw string like `r"C:\path"`, or `r#"say "hi""#` with as many `#` as needed to
+    /// include quotes. Raw strings have no escapes.
+    fn next_raw_string(&mut self) -> Result<Token> {
+        self.inner.next(); // r
+        let mut hashes = 0;
+        while self.inner.next_if_eq(&'#').is_some() {
+            hashes += 1;
+        }
+        self.inner.next(); // opening quote
+        let closing = format!("\"{}", "#".repeat(hashes));
+
+        let mut s = String::new();
+        loop {
+            match self.inner.next() {
+                Some(ch) => s.push(ch),
+                None => {
+                    return Err(Error::IncompleteExpression(format!(
+                        "Expected closing raw string quotation {closing}"
+                    )))
+                }
+            }
+            if s.ends_with(&closing) {
+                s.truncate(s.len() - closing.len());
+                return Ok(Token::String(s));
+            }
+        }
+    }
+
+    /// Skip next comment - `#` line comments, and `#| ... |#` block comments that may be nested.
+    /// Returns datum comment token for `#_`, and record start token for `#(`.
+    fn next_comment(&mut self) -> Result<Option<Token>> {
+        self.inner.next(); // #
+        if self.inner.next_if_eq(&'_').is_some() {
+            return Ok(Some(Token::DatumComment));
+        }
+        if self.inner.next_if_eq(&'(').is_some() {
+            return Ok(Some(Token::RecordStart));
+        }
+        if self.inner.next_if_eq(&'|').is_none() {
+            while self.inner.next_if(|ch| *ch != '\n').is_some() {}
+            return Ok(None);
+        }
+
+        let mut depth = 1;
+        while depth > 0 {
+            match self.inner.next() {
+                Some('|') if self.inner.next_if_eq(&'#').is_some() => depth -= 1,
+                Some('#') if self.inner.next_if_eq(&'|').is_some() => depth += 1,
+                Some(_) => (),
+                None => {
+                    return Err(Error::IncompleteExpression(
+                        "Expected closing |# of block comment".to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(None)
     }
 
     /// Parse keyword
@@ -164,24 +270,22 @@ impl<'a> Iterator for Tokens<'a> {
     type Item = Result<Token>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut is_comment = false;
-
-        while let Some(ch) = self.inner.peek() {
-            if *ch == '\n' && is_comment {
-                is_comment = false;
-            }
-            if *ch == '#' {
-                is_comment = true;
-            }
-            if ch.is_whitespace() || is_comment {
-                let _ = self.inner.next();
-                continue;
-            }
+        while let Some(&ch) = self.inner.peek() {
             let token = match ch {
+                ch if ch.is_whitespace() => {
+                    let _ = self.inner.next();
+                    continue;
+                }
+                '#' => match self.next_comment() {
+                    Ok(None) => continue,
+                    Ok(Some(token)) => Ok(token),
+                    Err(e) => Err(e),
+                },
                 '\"' => self.next_string(),
                 ':' => self.next_keyword(),
-                _ if is_punct(ch) => self.next_punct(),
-                _ if ch.is_numeric() || ch == &'-' => self.next_int(),
+                'r' if self.at_raw_string() => self.next_raw_string(),
+                ch if is_punct(&ch) => self.next_punct(),
+                ch if ch.is_numeric() || ch == '-' => self.next_int(),
                 _ => self.next_symbol(),
             };
             return Some(token);
@@ -190,6 +294,86 @@ impl<'a> Iterator for Tokens<'a> {
     }
 }
 
+/// Replace escape sequences in string - `\n`, `\t`, `\r`, `\0`, `\\`, `\"`, and unicode escapes
+/// like `\u{1F600}`
+fn unescape(raw: &str) -> Result<String> {
+    let mut s = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            s.push(ch);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('u') => {
+                let hex = chars.next().filter(|ch| *ch == '{').map(|_| {
+                    chars
+                        .by_ref()
+                        .take_while(|ch| *ch != '}')
+                        .collect::<String>()
+                });
+                hex.as_deref()
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| {
+                        Error::IncompleteExpression(format!(
+                            "Invalid unicode escape - \\u{{{}}}",
+                            hex.unwrap_or_default()
+                        ))
+                    })?
+            }
+            Some(ch) => {
+                return Err(Error::IncompleteExpression(format!(
+                    "Unknown escape sequence - \\{ch}"
+                )))
+            }
+            None => {
+                return Err(Error::IncompleteExpression(
+                    "Expected escaped character".to_string(),
+                ))
+            }
+        };
+        s.push(escaped);
+    }
+    Ok(s)
+}
+
+/// Strip indentation of triple-quoted string. A line break right after opening quotes is dropped,
+/// and indentation common to all lines is removed. Closing quotes on their own line count towards
+/// indentation, and leave string ending with a line break.
+fn dedent(raw: &str) -> String {
+    let raw = raw.strip_prefix('\n').unwrap_or(raw);
+    let lines = raw.split('\n').collect::<Vec<_>>();
+    let last = lines.len() - 1;
+    let closing_line = last > 0 && lines[last].trim().is_empty();
+    let indent = lines
+        .iter()
+        .enumerate()
+        .filter(|(i, l)| !l.trim().is_empty() || (closing_line && *i == last))
+        .map(|(_, l)| l.chars().take_while(|ch| ch.is_whitespace()).count())
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| {
+            if l.trim().is_empty() || (closing_line && i == last) {
+                ""
+            } else {
+                l.char_indices().nth(indent).map_or("", |(at, _)| &l[at..])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Return whether or not a given character is a symbol delimiter
 fn is_symbol_delimiter(ch: &char) -> bool {
     ch.is_whitespace() || is_punct(ch)
@@ -244,6 +428,17 @@ mod tests {
             lex("    hello    "),
             Ok(vec![Token::Symbol(String::from("hello"))])
         );
+        assert_eq!(
+            lex("(- 1 -2)"),
+            Ok(vec![
+                Token::ParenLeft,
+                Token::Symbol(String::from("-")),
+                Token::Int(1),
+                Token::Int(-2),
+                Token::ParenRight,
+            ])
+        );
+        assert_eq!(lex("-x"), Ok(vec![Token::Symbol(String::from("-x"))]));
     }
 
     #[test]
@@ -289,6 +484,116 @@ mod tests {
         }
     }
 
+    #[test]
+    fn lex_string_escapes() {
+        assert_eq!(
+            lex(r#""tab\there\\ \u{48}\u{1F600}\r\n""#),
+            Ok(vec![Token::String(
+                "tab\there\\ H\u{1F600}\r\n".to_string()
+            )])
+        );
+        assert!(matches!(
+            lex(r#""\d+""#),
+            Err(Error::IncompleteExpression(_))
+        ));
+        assert!(matches!(
+            lex(r#""\u{zz}""#),
+            Err(Error::IncompleteExpression(_))
+        ));
+        assert!(matches!(
+            lex(r#""unterminated"#),
+            Err(Error::IncompleteExpression(_))
+        ));
+    }
+
+    #[test]
+    fn lex_raw_string() {
+        assert_eq!(
+            lex(r#"r"C:\no\escapes""#),
+            Ok(vec![Token::String(r"C:\no\escapes".to_string())])
+        );
+        assert_eq!(
+            lex(r###"r##"say "#hi"#"## r"""###),
+            Ok(vec![
+                Token::String(r##"say "#hi"#"##.to_string()),
+                Token::String("".to_string())
+            ])
+        );
+        assert_eq!(
+            lex("(repeat r)"),
+            Ok(vec![
+                Token::ParenLeft,
+                Token::Symbol("repeat".to_string()),
+                Token::Symbol("r".to_string()),
+                Token::ParenRight,
+            ])
+        );
+    }
+
+    #[test]
+    fn lex_triple_quoted() {
+        let src = r#"(def script """
+            tell application "Finder"
+              activate\t# not a comment
+            end tell
+            """)"#;
+        assert_eq!(
+            lex(src),
+            Ok(vec![
+                Token::ParenLeft,
+                Token::Symbol("def".to_string()),
+                Token::Symbol("script".to_string()),
+                Token::String(
+                    "tell application \"Finder\"\n  activate\t# not a comment\nend tell\n"
+                        .to_string()
+                ),
+                Token::ParenRight,
+            ])
+        );
+        assert_eq!(
+            lex(r##""""one "quoted" line""" "" """
+                  a
+                    b""""##),
+            Ok(vec![
+                Token::String(r#"one "quoted" line"#.to_string()),
+                Token::String("".to_string()),
+                Token::String("a\n  b".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn lex_block_and_datum_comments() {
+        assert_eq!(
+            lex("1 #| block #| nested |# (comment |# 2 #_ 3 # line\n4"),
+            Ok(vec![
+                Token::Int(1),
+                Token::Int(2),
+                Token::DatumComment,
+                Token::Int(3),
+                Token::Int(4),
+            ])
+        );
+        assert!(matches!(
+            lex("#| unterminated"),
+            Err(Error::IncompleteExpression(_))
+        ));
+    }
+
+    #[test]
+    fn lex_record() {
+        assert_eq!(
+            lex("#(point :x 1)"),
+            Ok(vec![
+                Token::RecordStart,
+                Token::Symbol("point".to_string()),
+                Token::Keyword("x".to_string()),
+                Token::Int(1),
+                Token::ParenRight,
+            ])
+        );
+    }
+
     #[test]
     fn lex_list() {
         assert_eq!(
@@ -451,7 +756,3 @@ mod tests {
         );
     }
 }
-
-// TODO(bug): Cannot parse non-number "-" prefix:
-//     vrs> (- (read (get (exec "date" "+%s") 1)) 10)
-//     Incomplete expression - Unable to parse integer - -
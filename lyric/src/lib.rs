@@ -1,36 +1,58 @@
+// Allows generated code from lyric_macros to refer to `::lyric` within this crate
+extern crate self as lyric;
+
 mod codegen;
+mod de;
 mod error;
 mod lex;
 mod parse;
 mod run;
+mod seq;
+mod ser;
 
 pub mod builtin;
+pub mod check;
+pub mod debug;
 pub mod env;
 pub mod fiber;
 pub mod kwargs;
 pub mod pmatch;
+pub mod pprint;
+pub mod profile;
 pub mod types;
 
+pub use builtin::dynamic::Dynamic;
+pub use builtin::time::{Duration, Time};
 pub use builtin::Ref;
+pub use check::Arity;
 pub use codegen::compile;
 pub use codegen::Inst;
+pub use de::from_form;
 pub use env::Env;
+pub use env::EnvVal;
 pub use error::Error;
 pub use fiber::Fiber;
 pub use fiber::Signal;
 pub use fiber::Status;
+pub use lyric_macros::native_fn;
 pub use parse::parse;
 pub use pmatch::Pattern;
+pub use profile::Profiler;
 pub use run::run;
+pub use seq::Seq;
+pub use ser::to_form;
 pub use types::Bytecode;
 pub use types::Extern;
 pub use types::Form;
+pub use types::FromVal;
+pub use types::IntoVal;
 pub use types::KeywordId;
 pub use types::Lambda;
 pub use types::Locals;
 pub use types::NativeAsyncFn;
 pub use types::NativeFn;
 pub use types::NativeFnOp;
+pub use types::Record;
 pub use types::SymbolId;
 pub use types::Val;
 
@@ -0,0 +1,126 @@
+//! Command line tools for Lyric source files
+use std::io::{Read, Write};
+use std::num::NonZeroUsize;
+use std::process::ExitCode;
+
+use lyric::{check, pprint, Env, SymbolId, Val};
+
+const USAGE: &str = "\
+Usage: lyric <COMMAND>
+
+Commands:
+  fmt [--check] [--width WIDTH] [FILES...]
+      Format .ll files in place. Reads stdin and writes stdout if no FILES are given.
+      With --check, files are not modified and exit status is non-zero if any file is unformatted.
+  check [--allow SYMBOL]... FILES...
+      Statically check .ll files against the standard environment, reporting unbound symbols,
+      arity mismatches, malformed forms, shadowing definitions, and unreachable expressions.
+      Each SYMBOL given by --allow is treated as bound. Exit status is non-zero if any warning is found.
+      Programs for the vrs runtime should be checked with `vrsctl --check FILE` instead, which checks
+      against the environment of runtime processes.";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let res = match args.split_first() {
+        Some((cmd, rest)) if cmd == "fmt" => fmt(rest),
+        Some((cmd, rest)) if cmd == "check" => check(rest),
+        Some((cmd, _)) if cmd == "-h" || cmd == "--help" => {
+            println!("{USAGE}");
+            Ok(true)
+        }
+        _ => Err(USAGE.to_string()),
+    };
+    match res {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run `lyric fmt`. Returns whether or not all inputs were formatted
+fn fmt(args: &[String]) -> Result<bool, String> {
+    let mut check = false;
+    let mut width = pprint::DEFAULT_WIDTH;
+    let mut files = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--width" => {
+                width = args
+                    .next()
+                    .and_then(|w| w.parse::<NonZeroUsize>().ok())
+                    .ok_or("--width expects a positive integer")?
+                    .get();
+            }
+            _ => files.push(arg.clone()),
+        }
+    }
+
+    if files.is_empty() {
+        let mut src = String::new();
+        std::io::stdin()
+            .read_to_string(&mut src)
+            .map_err(|e| format!("Failed to read stdin - {e}"))?;
+        let formatted = pprint::format_source(&src, width).map_err(|e| format!("<stdin>: {e}"))?;
+        if check {
+            return Ok(formatted == src);
+        }
+        std::io::stdout()
+            .write_all(formatted.as_bytes())
+            .map_err(|e| format!("Failed to write stdout - {e}"))?;
+        return Ok(true);
+    }
+
+    let mut all_formatted = true;
+    for file in files {
+        let src = std::fs::read_to_string(&file).map_err(|e| format!("{file}: {e}"))?;
+        let formatted = pprint::format_source(&src, width).map_err(|e| format!("{file}: {e}"))?;
+        if formatted == src {
+            continue;
+        }
+        if check {
+            println!("{file}");
+            all_formatted = false;
+        } else {
+            std::fs::write(&file, formatted).map_err(|e| format!("{file}: {e}"))?;
+        }
+    }
+    Ok(all_formatted)
+}
+
+/// Run `lyric check`. Returns whether or not all inputs passed checks
+fn check(args: &[String]) -> Result<bool, String> {
+    let mut env = Env::<String, ()>::standard();
+    let mut files = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--allow" => {
+                let sym = args.next().ok_or("--allow expects a symbol")?;
+                env.define(SymbolId::from(sym.as_str()), Val::Nil);
+            }
+            _ => files.push(arg.clone()),
+        }
+    }
+    if files.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let mut passed = true;
+    for file in files {
+        let src = std::fs::read_to_string(&file).map_err(|e| format!("{file}: {e}"))?;
+        let form =
+            lyric::parse(&format!("(begin\n{src}\n)")).map_err(|e| format!("{file}: {e}"))?;
+        for warning in check::check(&Val::from(form), &env) {
+            println!("{file}: {warning}");
+            passed = false;
+        }
+    }
+    Ok(passed)
+}
@@ -1,7 +1,7 @@
 //! Parser for Lyric
 use crate::lex::{lex, Token};
 use crate::types::KeywordId;
-use crate::{Error, Result};
+use crate::{Error, Record, Result};
 use crate::{Form, SymbolId};
 
 use std::iter::Peekable;
@@ -10,6 +10,7 @@ use std::iter::Peekable;
 pub fn parse(expr: &str) -> Result<Form> {
     let mut tokens = lex(expr)?.into_iter().peekable();
     let form = parse_form(&mut tokens)?;
+    skip_datum_comments(&mut tokens)?;
     if tokens.peek().is_some() {
         return Err(Error::IncompleteExpression(
             "Unable to parse full expression - unbalanced trailing expressions".to_string(),
@@ -23,6 +24,7 @@ fn parse_form<I>(tokens: &mut Peekable<I>) -> Result<Form>
 where
     I: Iterator<Item = Token>,
 {
+    skip_datum_comments(tokens)?;
     let next = tokens
         .next()
         .ok_or(Error::IncompleteExpression("Expected a form".to_string()))?;
@@ -35,11 +37,12 @@ where
         Token::Keyword(k) => Form::Keyword(KeywordId::from(k)),
         Token::ParenLeft => {
             let mut items = vec![];
-            while let Some(next) = tokens.peek() {
-                if next == &Token::ParenRight {
-                    break;
+            loop {
+                skip_datum_comments(tokens)?;
+                match tokens.peek() {
+                    None | Some(Token::ParenRight) => break,
+                    Some(_) => items.push(parse_form(tokens)?),
                 }
-                items.push(parse_form(tokens)?);
             }
             if tokens.peek() != Some(&Token::ParenRight) {
                 return Err(Error::IncompleteExpression(
@@ -58,10 +61,53 @@ where
             let quoted = parse_form(tokens)?;
             Form::List(vec![Form::symbol("quote"), quoted])
         }
+        Token::RecordStart => parse_record(tokens)?,
+        Token::DatumComment => unreachable!("Datum comments should be skipped"),
     };
     Ok(form)
 }
 
+/// Parse rest of record after `#(`, i.e. `NAME :FIELD VALUE ...)`
+fn parse_record<I>(tokens: &mut Peekable<I>) -> Result<Form>
+where
+    I: Iterator<Item = Token>,
+{
+    skip_datum_comments(tokens)?;
+    let name = match tokens.next() {
+        Some(Token::Symbol(s)) => SymbolId::from(s),
+        _ => {
+            return Err(Error::IncompleteExpression(
+                "Expected record name after #(".to_string(),
+            ))
+        }
+    };
+    let mut fields = vec![];
+    loop {
+        skip_datum_comments(tokens)?;
+        match tokens.next() {
+            Some(Token::ParenRight) => break,
+            Some(Token::Keyword(k)) => fields.push((KeywordId::from(k), parse_form(tokens)?)),
+            _ => {
+                return Err(Error::IncompleteExpression(format!(
+                    "Expected field keyword or closing parenthesis in {name} record"
+                )))
+            }
+        }
+    }
+    Ok(Form::Record(Record { name, fields }))
+}
+
+/// Skip forms commented out by `#_`
+fn skip_datum_comments<I>(tokens: &mut Peekable<I>) -> Result<()>
+where
+    I: Iterator<Item = Token>,
+{
+    while tokens.next_if_eq(&Token::DatumComment).is_some() {
+        parse_form(tokens)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,4 +311,47 @@ mod tests {
             Err(Error::IncompleteExpression(_))
         ));
     }
+
+    #[test]
+    fn parse_datum_comments() {
+        assert_eq!(
+            parse("(a #_b c #_(d (e)) #_ #_ f g)"),
+            Ok(Form::List(vec![Form::symbol("a"), Form::symbol("c")]))
+        );
+        assert_eq!(parse("#_a b #_c"), Ok(Form::symbol("b")));
+        assert!(matches!(
+            parse("(a #_)"),
+            Err(Error::IncompleteExpression(_))
+        ));
+    }
+
+    #[test]
+    fn parse_record() {
+        assert_eq!(
+            parse("#(point :x 1 :y (2 3))"),
+            Ok(Form::Record(Record {
+                name: SymbolId::from("point"),
+                fields: vec![
+                    (KeywordId::from("x"), Form::Int(1)),
+                    (
+                        KeywordId::from("y"),
+                        Form::List(vec![Form::Int(2), Form::Int(3)])
+                    ),
+                ],
+            }))
+        );
+        assert_eq!(
+            parse("#(empty)"),
+            Ok(Form::Record(Record {
+                name: SymbolId::from("empty"),
+                fields: vec![],
+            }))
+        );
+        for invalid in ["#(:x 1)", "#(point x 1)", "#(point :x 1"] {
+            assert!(
+                matches!(parse(invalid), Err(Error::IncompleteExpression(_))),
+                "{invalid} should not parse"
+            );
+        }
+    }
 }
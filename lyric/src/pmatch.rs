@@ -1,5 +1,5 @@
 //! Pattern Matching
-use crate::{Extern, Locals, Result, SymbolId, Val};
+use crate::{Extern, KeywordId, Locals, Record, Result, SymbolId, Val};
 use std::collections::HashMap;
 
 /// Pattern matching predicate
@@ -15,6 +15,9 @@ enum Pat<T: Extern, L: Locals> {
     Multi(Vec<Val<T, L>>),
 }
 
+/// Patterns for fields of record, by field name
+type FieldPatterns<'a, T, L> = Vec<(&'a KeywordId, &'a Val<T, L>)>;
+
 /// Result of pattern match
 #[derive(Debug, PartialEq)]
 pub struct Matches<T: Extern, L: Locals> {
@@ -96,17 +99,62 @@ where
                 }
                 _ => false,
             },
-            List(pat) => match val {
-                List(val) if pat.len() == val.len() => pat
+            List(pat) => match (record_pattern(pat), val) {
+                (Some((name, fields)), Record(r)) => {
+                    &r.name == name && Self::matches_fields(fields, r, matches)
+                }
+                (Some(_), _) => false,
+                (None, List(val)) if pat.len() == val.len() => pat
                     .iter()
                     .zip(val.iter())
                     .all(|(lhs, rhs)| Self::matches_inner(lhs, rhs, matches)),
+                (None, _) => false,
+            },
+            Record(pat) => match val {
+                Record(r) => {
+                    let fields = pat.fields.iter().map(|(k, p)| (k, p)).collect();
+                    r.name == pat.name && Self::matches_fields(fields, r, matches)
+                }
                 _ => false,
             },
             Nil | Bool(_) | Int(_) | String(_) | Keyword(_) | Lambda(_) | NativeFn(_)
-            | NativeAsyncFn(_) | Bytecode(_) | Error(_) | Ref(_) | Extern(_) => pat == val,
+            | NativeAsyncFn(_) | Bytecode(_) | Error(_) | Ref(_) | Seq(_) | Env(_) | Dynamic(_)
+            | Time(_) | Duration(_) | Extern(_) => pat == val,
         }
     }
+
+    /// Match each field pattern against field of same name in record
+    fn matches_fields(
+        fields: FieldPatterns<'_, T, L>,
+        record: &Record<Val<T, L>>,
+        matches: &mut Matches<T, L>,
+    ) -> bool {
+        fields.into_iter().all(|(k, p)| match record.get(k) {
+            Some(v) => Self::matches_inner(p, v, matches),
+            None => false,
+        })
+    }
+}
+
+/// Name and field patterns of record pattern, written as `(record NAME :FIELD PAT ...)`
+fn record_pattern<T: Extern, L: Locals>(
+    pat: &[Val<T, L>],
+) -> Option<(&SymbolId, FieldPatterns<'_, T, L>)> {
+    match pat {
+        [Val::Symbol(head), Val::Symbol(name), fields @ ..]
+            if head.as_str() == "record" && fields.len().is_multiple_of(2) =>
+        {
+            let fields = fields
+                .chunks(2)
+                .map(|kv| match kv {
+                    [Val::Keyword(k), p] => Some((k, p)),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some((name, fields))
+        }
+        _ => None,
+    }
 }
 
 impl<T: Extern, L: Locals> IntoIterator for Matches<T, L> {
@@ -507,6 +555,32 @@ mod tests {
         assert_eq!(m.bindings.get(&SymbolId::from("b")), Some(&v("1")),);
     }
 
+    #[test]
+    fn records() {
+        let point = |x, y| {
+            Val::Record(crate::Record {
+                name: SymbolId::from("point"),
+                fields: vec![("x".into(), Val::Int(x)), ("y".into(), Val::Int(y))],
+            })
+        };
+
+        let pat = Pattern::from_val(v("(record point :y y)"));
+        let m = pat.matches(&point(1, 2)).expect("should match");
+        assert_eq!(m.bindings.len(), 1);
+        assert_eq!(m.bindings.get(&SymbolId::from("y")), Some(&Val::Int(2)));
+        assert!(!pat.is_match(&v("(record point :y 2)")));
+
+        let pat = Pattern::from_val(v("(record point :x 0 :y y)"));
+        assert!(pat.is_match(&point(0, 2)));
+        assert!(!pat.is_match(&point(1, 2)));
+        assert!(!Pattern::from_val(v("(record line :x x)")).is_match(&point(1, 2)));
+        assert!(!Pattern::from_val(v("(record point :z z)")).is_match(&point(1, 2)));
+
+        let pat = Pattern::from_val(point(0, 2));
+        assert!(pat.is_match(&point(0, 2)));
+        assert!(!pat.is_match(&point(1, 2)));
+    }
+
     fn v(expr: &str) -> Val {
         parse(expr).unwrap().into()
     }
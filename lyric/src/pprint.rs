@@ -0,0 +1,734 @@
+//! Pretty printer and source formatter for Lyric
+//!
+//! Lists that fit in remaining width are printed on a single line. Otherwise:
+//! - Special forms keep distinguished arguments on first line and indent body by two, e.g. `(defn NAME PARAMS` + body
+//! - Keyword alists print each key-value pair on its own line
+//! - Function calls align arguments under first argument
+//! - Other lists align elements under first element
+use crate::{Error, Extern, Form, Locals, Result, Val};
+
+/// Default line width used for pretty printing
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// Pretty print form within given width
+pub fn pretty(form: &Form, width: usize) -> String {
+    let mut p = Printer::new(width);
+    p.print(&Node::from(form));
+    p.finish()
+}
+
+/// Pretty print value within given width. Values without a readable form are printed as displayed
+pub fn pretty_val<T: Extern, L: Locals>(val: &Val<T, L>, width: usize) -> String {
+    match Form::try_from(val.clone()) {
+        Ok(form) => pretty(&form, width),
+        Err(_) => val.to_string(),
+    }
+}
+
+/// Format source code containing zero or more top-level forms. Comments, line breaks between
+/// list elements, and blank lines are preserved, and atoms are written as they appear in source.
+/// Block comments and forms commented out with `#_` are kept as written, in place of an atom.
+pub fn format_source(src: &str, width: usize) -> Result<String> {
+    let items = Reader::new(src).read_items(false)?;
+    let mut p = Printer::new(width);
+    for (idx, item) in items.iter().enumerate() {
+        match &item.node {
+            Node::Comment(c) if item.trailing && idx != 0 => {
+                p.push(" ");
+                p.push(c);
+            }
+            node => {
+                if idx != 0 {
+                    p.push("\n");
+                    if item.blank_before {
+                        p.push("\n");
+                    }
+                }
+                p.print(node);
+            }
+        }
+    }
+    let mut out = p.finish();
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Special forms with number of distinguished arguments kept on first line
+const SPECIAL_FORMS: &[(&str, usize)] = &[
+    ("begin", 0),
+    ("loop", 0),
+    ("try", 0),
+    ("cond", 0),
+    ("def", 1),
+    ("set", 1),
+    ("if", 1),
+    ("let", 1),
+    ("match", 1),
+    ("lambda", 1),
+    ("fn", 1),
+    ("defn", 2),
+    ("defrecord", 2),
+    ("defprotocol", 1),
+    ("defimpl", 2),
+    ("for", 1),
+    ("doseq", 1),
+    ("defdynamic", 1),
+    ("parameterize", 1),
+];
+
+/// Layout tree shared by forms and source code
+#[derive(Debug)]
+enum Node {
+    Atom(String),
+    List(Vec<Item>),
+    Quote(Box<Node>),
+    /// Record printed as `#` followed by list of name and fields
+    Record(Box<Node>),
+    Comment(String),
+}
+
+/// Element in a list or top-level source
+#[derive(Debug)]
+struct Item {
+    node: Node,
+    /// Whether or not source had a line break before item
+    newline_before: bool,
+    /// Whether or not source had a blank line before item
+    blank_before: bool,
+    /// Whether or not item is a comment on same line as previous item
+    trailing: bool,
+}
+
+/// How list elements following head are laid out when list is broken over lines
+enum Style {
+    /// Keep N arguments on first line, and indent rest as body
+    Special(usize),
+    /// Key value pairs on each line
+    Alist,
+    /// Align arguments under first argument
+    Call,
+    /// Align elements under first element
+    Data,
+}
+
+impl From<&Form> for Node {
+    fn from(form: &Form) -> Self {
+        match form {
+            Form::List(l) => match &l[..] {
+                [Form::Symbol(s), inner] if s.as_str() == "quote" => {
+                    Node::Quote(Box::new(Node::from(inner)))
+                }
+                _ => Node::List(l.iter().map(|f| Item::new(Node::from(f))).collect()),
+            },
+            Form::String(s) => Node::Atom(escape_string(s)),
+            Form::Record(r) => {
+                let mut items = vec![Item::new(Node::Atom(r.name.to_string()))];
+                for (k, v) in &r.fields {
+                    items.push(Item::new(Node::Atom(k.to_string())));
+                    items.push(Item::new(Node::from(v)));
+                }
+                Node::Record(Box::new(Node::List(items)))
+            }
+            f => Node::Atom(f.to_string()),
+        }
+    }
+}
+
+impl Item {
+    fn new(node: Node) -> Self {
+        Self {
+            node,
+            newline_before: false,
+            blank_before: false,
+            trailing: false,
+        }
+    }
+}
+
+/// Escape string as string literal
+fn escape_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl Node {
+    /// Single line representation, if node can be printed on single line
+    fn flat(&self) -> Option<String> {
+        match self {
+            Node::Atom(a) if a.contains('\n') => None,
+            Node::Atom(a) => Some(a.clone()),
+            Node::Comment(_) => None,
+            Node::Quote(inner) => Some(format!("'{}", inner.flat()?)),
+            Node::Record(inner) => Some(format!("#{}", inner.flat()?)),
+            Node::List(items) => {
+                let parts = items
+                    .iter()
+                    .map(|i| {
+                        if i.newline_before {
+                            None
+                        } else {
+                            i.node.flat()
+                        }
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(format!("({})", parts.join(" ")))
+            }
+        }
+    }
+
+    fn is_comment(&self) -> bool {
+        matches!(self, Node::Comment(_))
+    }
+
+    fn style(items: &[Item]) -> Style {
+        let head = match items.first().map(|i| &i.node) {
+            Some(Node::Atom(a)) => a,
+            _ => return Style::Data,
+        };
+        let is_clause = |item: Option<&Item>| {
+            matches!(item.map(|i| &i.node), Some(Node::List(c))
+                if matches!(c.first().map(|i| &i.node), Some(Node::List(_))))
+        };
+        if head == "defn" && is_clause(items.get(2)) {
+            return Style::Special(1); // multi-clause defn
+        }
+        if let Some((_, n)) = SPECIAL_FORMS.iter().find(|(name, _)| name == head) {
+            return Style::Special(*n);
+        }
+        let is_alist = items.len().is_multiple_of(2)
+            && items
+                .iter()
+                .step_by(2)
+                .all(|i| matches!(&i.node, Node::Atom(a) if a.starts_with(':')));
+        if is_alist {
+            Style::Alist
+        } else if head.starts_with(['(', '"', ':']) || head.parse::<i32>().is_ok() {
+            Style::Data
+        } else {
+            Style::Call
+        }
+    }
+}
+
+/// Printer accumulating output
+struct Printer {
+    out: String,
+    width: usize,
+}
+
+impl Printer {
+    fn new(width: usize) -> Self {
+        Self {
+            out: String::new(),
+            width,
+        }
+    }
+
+    fn finish(self) -> String {
+        self.out
+    }
+
+    fn push(&mut self, s: &str) {
+        self.out.push_str(s);
+    }
+
+    /// Current column
+    fn col(&self) -> usize {
+        let line_start = self.out.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        self.out[line_start..].chars().count()
+    }
+
+    fn newline(&mut self, indent: usize) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        self.out.push('\n');
+        self.out.push_str(&" ".repeat(indent));
+    }
+
+    fn print(&mut self, node: &Node) {
+        match node {
+            Node::Atom(a) | Node::Comment(a) => self.push(a),
+            Node::Quote(inner) => {
+                self.push("'");
+                self.print(inner);
+            }
+            Node::Record(inner) => {
+                self.push("#");
+                self.print(inner);
+            }
+            Node::List(items) => self.print_list(node, items),
+        }
+    }
+
+    fn print_list(&mut self, node: &Node, items: &[Item]) {
+        let start = self.col();
+        if let Some(flat) = node.flat() {
+            if start + flat.chars().count() <= self.width {
+                self.push(&flat);
+                return;
+            }
+        }
+
+        let style = Node::style(items);
+        let mut align = start + 1;
+        let mut broken = false;
+        let mut prev_comment = false;
+
+        self.push("(");
+        for (idx, item) in items.iter().enumerate() {
+            if idx == 0 {
+                self.print(&item.node);
+                prev_comment = item.node.is_comment();
+                if let Style::Call = style {
+                    align = self.col() + 1;
+                }
+                continue;
+            }
+
+            if item.trailing && item.node.is_comment() {
+                self.push(" ");
+                self.print(&item.node);
+                prev_comment = true;
+                continue;
+            }
+
+            let wants_same_line = match style {
+                Style::Special(n) => !broken && idx <= n,
+                Style::Alist => idx % 2 == 1,
+                Style::Call => !broken && idx == 1,
+                Style::Data => false,
+            };
+            let same_line = wants_same_line && !prev_comment && !item.newline_before;
+            if idx == 1 && !same_line {
+                align = start + 1;
+            }
+
+            if same_line {
+                self.push(" ");
+            } else {
+                broken = true;
+                let indent = match style {
+                    Style::Special(_) => start + 2,
+                    Style::Alist | Style::Data => start + 1,
+                    Style::Call => align,
+                };
+                if item.blank_before {
+                    self.newline(0);
+                }
+                self.newline(indent);
+            }
+            self.print(&item.node);
+            prev_comment = item.node.is_comment();
+        }
+
+        if prev_comment {
+            let indent = match style {
+                Style::Special(_) => start + 2,
+                Style::Call => align,
+                _ => start + 1,
+            };
+            self.newline(indent);
+        }
+        self.push(")");
+    }
+}
+
+/// Reader for source code preserving comments and atom text
+struct Reader<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    /// Skip whitespace, returning number of newlines skipped
+    fn skip_whitespace(&mut self) -> usize {
+        let mut newlines = 0;
+        while let Some(ch) = self.peek() {
+            if !ch.is_whitespace() {
+                break;
+            }
+            if ch == '\n' {
+                newlines += 1;
+            }
+            self.bump();
+        }
+        newlines
+    }
+
+    /// Read items until end of input, or closing paren if `in_list`
+    fn read_items(&mut self, in_list: bool) -> Result<Vec<Item>> {
+        let mut items = vec![];
+        loop {
+            let newlines = self.skip_whitespace();
+            let ch = match self.peek() {
+                Some(ch) => ch,
+                None if in_list => {
+                    return Err(Error::IncompleteExpression(
+                        "Expected closing parenthesis".to_string(),
+                    ))
+                }
+                None => return Ok(items),
+            };
+            if ch == ')' {
+                if in_list {
+                    self.bump();
+                    return Ok(items);
+                }
+                return Err(Error::IncompleteExpression(
+                    "Unexpected closing parenthesis while parsing expression".to_string(),
+                ));
+            }
+            let node = self.read_node()?;
+            items.push(Item {
+                trailing: node.is_comment() && newlines == 0 && !items.is_empty(),
+                newline_before: newlines > 0 && !items.is_empty(),
+                blank_before: newlines > 1 && !items.is_empty(),
+                node,
+            });
+        }
+    }
+
+    /// Whether or not remaining source starts with given prefix
+    fn at(&self, prefix: &str) -> bool {
+        self.src[self.pos..].starts_with(prefix)
+    }
+
+    /// Skip past given delimiter, failing with given message if source ends before it
+    fn skip_past(&mut self, delim: &str, msg: &str) -> Result<()> {
+        match self.src[self.pos..].find(delim) {
+            Some(at) => {
+                self.pos += at + delim.len();
+                Ok(())
+            }
+            None => Err(Error::IncompleteExpression(msg.to_string())),
+        }
+    }
+
+    fn read_node(&mut self) -> Result<Node> {
+        let start = self.pos;
+        match self.peek() {
+            Some('#') if self.at("#|") => {
+                let mut depth = 0;
+                loop {
+                    if self.at("#|") {
+                        depth += 1;
+                        self.pos += 2;
+                    } else if self.at("|#") {
+                        depth -= 1;
+                        self.pos += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else if self.bump().is_none() {
+                        return Err(Error::IncompleteExpression(
+                            "Expected closing |# of block comment".to_string(),
+                        ));
+                    }
+                }
+                Ok(Node::Atom(self.src[start..self.pos].to_string()))
+            }
+            Some('#') if self.at("#(") => {
+                self.bump();
+                Ok(Node::Record(Box::new(self.read_node()?)))
+            }
+            Some('#') if self.at("#_") => {
+                self.pos += 2;
+                self.skip_whitespace();
+                match self.peek() {
+                    None | Some(')') => {
+                        return Err(Error::IncompleteExpression(
+                            "Expected a form after #_".to_string(),
+                        ))
+                    }
+                    _ => self.read_node()?,
+                };
+                Ok(Node::Atom(self.src[start..self.pos].to_string()))
+            }
+            Some('#') => {
+                while self.peek().is_some_and(|ch| ch != '\n') {
+                    self.bump();
+                }
+                Ok(Node::Comment(
+                    self.src[start..self.pos].trim_end().to_string(),
+                ))
+            }
+            Some('r')
+                if self.src[self.pos + 1..]
+                    .trim_start_matches('#')
+                    .starts_with('"') =>
+            {
+                let hashes = self.src[self.pos + 1..].len()
+                    - self.src[self.pos + 1..].trim_start_matches('#').len();
+                self.pos += hashes + 2;
+                let closing = format!("\"{}", "#".repeat(hashes));
+                self.skip_past(&closing, "Expected closing raw string quotation")?;
+                Ok(Node::Atom(self.src[start..self.pos].to_string()))
+            }
+            Some('"') if self.at("\"\"\"") => {
+                self.pos += 3;
+                let mut quotes = 0;
+                while quotes < 3 {
+                    match self.bump() {
+                        Some('"') => quotes += 1,
+                        Some('\\') => {
+                            quotes = 0;
+                            self.bump();
+                        }
+                        Some(_) => quotes = 0,
+                        None => {
+                            return Err(Error::IncompleteExpression(
+                                "Expected closing triple quotation".to_string(),
+                            ))
+                        }
+                    }
+                }
+                Ok(Node::Atom(self.src[start..self.pos].to_string()))
+            }
+            Some('(') => {
+                self.bump();
+                Ok(Node::List(self.read_items(true)?))
+            }
+            Some('\'') => {
+                self.bump();
+                self.skip_whitespace();
+                match self.peek() {
+                    None | Some(')') | Some('#') if !self.at("#(") => Err(
+                        Error::IncompleteExpression("Expected a form after quote".to_string()),
+                    ),
+                    _ => Ok(Node::Quote(Box::new(self.read_node()?))),
+                }
+            }
+            Some('"') => {
+                self.bump();
+                let mut escaped = false;
+                loop {
+                    match self.bump() {
+                        Some('\\') if !escaped => escaped = true,
+                        Some('"') if !escaped => break,
+                        Some(_) => escaped = false,
+                        None => {
+                            return Err(Error::IncompleteExpression(
+                                "Expected closing string quotation".to_string(),
+                            ))
+                        }
+                    }
+                }
+                Ok(Node::Atom(self.src[start..self.pos].to_string()))
+            }
+            _ => {
+                while self
+                    .peek()
+                    .is_some_and(|ch| !ch.is_whitespace() && !matches!(ch, '(' | ')' | '\''))
+                {
+                    self.bump();
+                }
+                Ok(Node::Atom(self.src[start..self.pos].to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+    use void::Void;
+
+    fn pp(expr: &str, width: usize) -> String {
+        pretty(&Form::from_expr(expr).unwrap(), width)
+    }
+
+    #[test]
+    fn fits_on_line() {
+        assert_eq!(pp("(+ 1 2)", 80), "(+ 1 2)");
+        assert_eq!(pp("'(1 2 3)", 80), "'(1 2 3)");
+        assert_eq!(pp("\"hello\"", 80), "\"hello\"");
+    }
+
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(
+            pretty(&Form::string("say \"hi\"\n"), 80),
+            r#""say \"hi\"\n""#
+        );
+    }
+
+    #[test]
+    fn special_forms() {
+        assert_eq!(
+            pp("(defn add (a b) (begin (def c (+ a b)) c))", 20),
+            "(defn add (a b)\n  (begin\n    (def c (+ a b))\n    c))"
+        );
+        assert_eq!(
+            pp("(if (eq? a b) :same :different)", 20),
+            "(if (eq? a b)\n  :same\n  :different)"
+        );
+        assert_eq!(
+            pp("(defn handle ((:ping) :pong) (((:echo m)) m))", 20),
+            "(defn handle\n  ((:ping) :pong)\n  (((:echo m)) m))"
+        );
+    }
+
+    #[test]
+    fn calls() {
+        assert_eq!(
+            pp("(format \"{} {}\" first_arg second_arg)", 20),
+            "(format \"{} {}\"\n        first_arg\n        second_arg)"
+        );
+    }
+
+    #[test]
+    fn alists() {
+        assert_eq!(
+            pp("(:name :echo :pid 10 :interface ((:ping) (:echo msg)))", 40),
+            "(:name :echo\n :pid 10\n :interface ((:ping) (:echo msg)))"
+        );
+    }
+
+    #[test]
+    fn data() {
+        assert_eq!(
+            pp("((one two) (three four) (five six))", 15),
+            "((one two)\n (three four)\n (five six))"
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let expr = r#"(begin (def items '(1 2 3 "four" :five)) (defn sum (items) (loop (if (empty? items) (yield 0) (set items (rest items))))) (sum items))"#;
+        let form = Form::from_expr(expr).unwrap();
+        for width in [10, 20, 40, 80] {
+            assert_eq!(Form::from_expr(&pretty(&form, width)).unwrap(), form);
+        }
+    }
+
+    #[test]
+    fn records() {
+        let form =
+            Form::from_expr(r#"'(#(point :x 1 :y (2 3)) #(label :text "say \"hi\""))"#).unwrap();
+        assert_eq!(
+            pretty(&form, 80),
+            r#"'(#(point :x 1 :y (2 3)) #(label :text "say \"hi\""))"#
+        );
+        for width in [10, 20, 40] {
+            assert_eq!(Form::from_expr(&pretty(&form, width)).unwrap(), form);
+        }
+        assert_eq!(
+            format_source("'#(point :x 1   :y 2)", 80).unwrap(),
+            "'#(point :x 1 :y 2)\n"
+        );
+    }
+
+    #[test]
+    fn pretty_val_non_data() {
+        type Val = crate::Val<Void, ()>;
+        assert_eq!(
+            pretty_val(
+                &Val::List(vec![Val::Int(1), Val::Error(Error::InvalidPatternMatch)]),
+                80
+            ),
+            "(1 <error Invalid pattern match>)"
+        );
+    }
+
+    #[test]
+    fn format_preserves_comments() {
+        let src = r#"
+# Header comment
+
+(defn greet (name) # trailing comment
+  # leading comment
+  (format "Hello {}" name))
+(greet "world")"#;
+        assert_eq!(
+            format_source(src, 80).unwrap(),
+            "# Header comment\n\n(defn greet (name) # trailing comment\n  # leading comment\n  (format \"Hello {}\" name))\n(greet \"world\")\n"
+        );
+    }
+
+    #[test]
+    fn format_preserves_reader_extensions() {
+        let src = r##"#| block
+   comment |#
+(exec "osascript" "-e" """
+    tell application "Finder"
+      activate
+    end tell
+    """ #_ (unused arg) r#"raw "quoted""#)"##;
+        let formatted = format_source(src, 80).unwrap();
+        assert_eq!(parse(&formatted), parse(src));
+        assert_eq!(format_source(&formatted, 80).unwrap(), formatted);
+        assert_eq!(
+            format_source("(list 1   #_2 #| three |# 4)", 80).unwrap(),
+            "(list 1 #_2 #| three |# 4)\n"
+        );
+        assert!(format_source("#| unterminated", 80).is_err());
+        assert!(format_source("\"\"\" unterminated \"\"", 80).is_err());
+    }
+
+    #[test]
+    fn format_reflows() {
+        assert_eq!(
+            format_source("(def   x\n\n   (+ 1     2))", 80).unwrap(),
+            "(def x\n\n  (+ 1 2))\n"
+        );
+        assert_eq!(
+            format_source("(begin\n(def x 1)\nx)", 80).unwrap(),
+            "(begin\n  (def x 1)\n  x)\n"
+        );
+        assert_eq!(
+            format_source("(begin (def x 1) x)", 12).unwrap(),
+            "(begin\n  (def x 1)\n  x)\n"
+        );
+        assert_eq!(
+            format_source("(spawn_srv name\n:interface '(a b))", 80).unwrap(),
+            "(spawn_srv name\n           :interface\n           '(a b))\n"
+        );
+        assert_eq!(
+            format_source("(list 1 # one\n 2)", 80).unwrap(),
+            "(list 1 # one\n      2)\n"
+        );
+    }
+
+    #[test]
+    fn format_is_idempotent() {
+        let src = "(begin # start\n  (def x 1)\n\n  # compute\n  (+ x 1))\n";
+        let once = format_source(src, 80).unwrap();
+        assert_eq!(format_source(&once, 80).unwrap(), once);
+    }
+
+    #[test]
+    fn format_errors() {
+        assert!(format_source("(begin", 80).is_err());
+        assert!(format_source(")", 80).is_err());
+        assert!(format_source("\"unterminated", 80).is_err());
+    }
+}
@@ -0,0 +1,609 @@
+//! Profiling of [Fiber] execution
+//!
+//! A [Profiler] attached to fiber via [Fiber::set_profiler] attributes each executed instruction,
+//! and wall time spent awaiting native async functions, to stack of function names at the time.
+//! Functions are named by symbol they are bound to in calling environment, or by call site as
+//! `<lambda CALLER:OFFSET>` for anonymous functions, where offset is as printed by
+//! [crate::debug::disassemble].
+//!
+//! Fibers count instructions into a [CallTree] of their own, and flush counts to the shared
+//! profiler when they pause, complete, or every [FLUSH_INSTS] instructions. Functions are only
+//! named when counts are flushed.
+//!
+//! [Fiber]: crate::Fiber
+//! [Fiber::set_profiler]: crate::Fiber::set_profiler
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{Env, Extern, Locals, Val};
+
+/// Name of outermost call frame of fiber
+pub(crate) const MAIN: &str = "<main>";
+
+/// Name of call frames entered before profiling started, or without known name
+pub(crate) const UNKNOWN: &str = "<unknown>";
+
+/// Name of call frames evaluating forms via `eval` or `try`
+pub(crate) const EVAL: &str = "<eval>";
+
+/// Number of instructions fiber executes between flushes of counts to profiler
+pub(crate) const FLUSH_INSTS: u64 = 1 << 16;
+
+/// Stack of function names, from outermost to innermost
+pub(crate) type Stack = Vec<Arc<str>>;
+
+/// Handle to profiler that can be shared between fiber and controlling code. Profiling can be
+/// started and stopped while fiber is running or awaiting.
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    enabled: AtomicBool,
+    /// Incremented each time profiling starts, so counts from earlier runs are discarded
+    session: AtomicU64,
+    insts: AtomicU64,
+    data: Mutex<Data>,
+}
+
+#[derive(Debug, Default)]
+struct Data {
+    started: Option<Instant>,
+    stacks: HashMap<Stack, Sample>,
+    awaiting: Option<(Stack, Instant)>,
+}
+
+/// Profile collected between [Profiler::start] and [Profiler::stop]
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    /// Wall time profile was collected over
+    pub duration: Duration,
+    stacks: HashMap<Stack, Sample>,
+}
+
+/// Cost attributed to a single stack
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sample {
+    /// Number of instructions executed
+    pub insts: u64,
+    /// Wall time spent awaiting native async functions
+    pub await_time: Duration,
+}
+
+/// Cost attributed to a single function
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionStats {
+    /// Name of function
+    pub name: String,
+    /// Cost within function itself
+    pub own: Sample,
+    /// Cost within function and functions it called
+    pub total: Sample,
+}
+
+/// Weight of each stack in folded stack output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    /// Number of instructions executed
+    Insts,
+    /// Microseconds spent awaiting native async functions
+    AwaitMicros,
+}
+
+impl Profiler {
+    /// Create a new profiler that is not started
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start profiling, discarding any previously collected data
+    pub fn start(&self) {
+        let mut data = self.inner.data.lock().unwrap();
+        *data = Data {
+            started: Some(Instant::now()),
+            ..Data::default()
+        };
+        self.inner.session.fetch_add(1, Ordering::AcqRel);
+        self.inner.enabled.store(true, Ordering::Release);
+    }
+
+    /// Stop profiling, returning profile collected since start. Time of any await in progress
+    /// is counted up to now.
+    pub fn stop(&self) -> Profile {
+        self.inner.enabled.store(false, Ordering::Release);
+        let mut data = self.inner.data.lock().unwrap();
+        data.end_await();
+        Profile {
+            duration: data.started.take().map(|s| s.elapsed()).unwrap_or_default(),
+            stacks: std::mem::take(&mut data.stacks),
+        }
+    }
+
+    /// Whether or not profiler is started
+    pub fn is_enabled(&self) -> bool {
+        self.inner.enabled.load(Ordering::Acquire)
+    }
+
+    /// Total instructions executed by attached fiber, counted whether or not profiler is started
+    pub fn total_insts(&self) -> u64 {
+        self.inner.insts.load(Ordering::Relaxed)
+    }
+
+    /// Current profiling session
+    pub(crate) fn session(&self) -> u64 {
+        self.inner.session.load(Ordering::Acquire)
+    }
+
+    /// Count instructions executed by attached fiber
+    pub(crate) fn count_insts(&self, n: u64) {
+        self.inner.insts.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Attribute instructions counted during `session` to stacks
+    pub(crate) fn record_insts(&self, session: u64, stacks: Vec<(Stack, u64)>) {
+        let mut data = self.inner.data.lock().unwrap();
+        if data.started.is_some() && self.session() == session {
+            for (stack, insts) in stacks {
+                data.stacks.entry(stack).or_default().insts += insts;
+            }
+        }
+    }
+
+    /// Mark start of await on native async function at innermost frame of stack
+    pub(crate) fn begin_await(&self, stack: Stack) {
+        let mut data = self.inner.data.lock().unwrap();
+        if data.started.is_some() {
+            data.awaiting = Some((stack, Instant::now()));
+        }
+    }
+
+    /// Mark end of await in progress, if any
+    pub(crate) fn end_await(&self) {
+        self.inner.data.lock().unwrap().end_await();
+    }
+}
+
+/// Function a node of [CallTree] is attributed to
+#[derive(Debug, Clone)]
+pub(crate) enum Callee<T: Extern, L: Locals> {
+    /// Callframe with fixed name, e.g. [MAIN]
+    Label(&'static str),
+    /// Call to function value by instruction at offset `site` of caller, named by symbol it is
+    /// bound to in calling environment `env`
+    Call {
+        func: Val<T, L>,
+        env: Arc<Mutex<Env<T, L>>>,
+        site: usize,
+    },
+}
+
+/// Node of call tree, for a function called from function of parent node
+#[derive(Debug)]
+struct Node<T: Extern, L: Locals> {
+    parent: Option<usize>,
+    callee: Callee<T, L>,
+    children: Vec<usize>,
+    /// Name of function, resolved when first flushed
+    name: Option<Arc<str>>,
+    /// Instructions executed since last flush
+    insts: u64,
+}
+
+/// Calls made by a fiber during a profiling session, counting instructions executed by each
+/// function without synchronizing with the shared [Profiler]
+#[derive(Debug)]
+pub(crate) struct CallTree<T: Extern, L: Locals> {
+    session: u64,
+    nodes: Vec<Node<T, L>>,
+    roots: Vec<usize>,
+}
+
+impl<T: Extern, L: Locals> CallTree<T, L> {
+    /// Create empty call tree for profiling session
+    pub(crate) fn new(session: u64) -> Self {
+        Self {
+            session,
+            nodes: vec![],
+            roots: vec![],
+        }
+    }
+
+    /// Profiling session of call tree
+    pub(crate) fn session(&self) -> u64 {
+        self.session
+    }
+
+    /// Node for function labelled `label` called from `parent`
+    pub(crate) fn label(&mut self, parent: Option<usize>, label: &'static str) -> usize {
+        let found = self
+            .children(parent)
+            .iter()
+            .copied()
+            .find(|c| matches!(&self.nodes[*c].callee, Callee::Label(l) if *l == label));
+        match found {
+            Some(node) => node,
+            None => self.insert(parent, Callee::Label(label)),
+        }
+    }
+
+    /// Node for call to `func` in `env` by instruction at offset `site` of `parent`
+    pub(crate) fn call(
+        &mut self,
+        parent: usize,
+        func: &Val<T, L>,
+        env: &Arc<Mutex<Env<T, L>>>,
+        site: usize,
+    ) -> usize {
+        let found = self.nodes[parent].children.iter().copied().find(|c| {
+            matches!(&self.nodes[*c].callee, Callee::Call { func: f, site: s, .. } if *s == site && f == func)
+        });
+        match found {
+            Some(node) => node,
+            None => self.insert(
+                Some(parent),
+                Callee::Call {
+                    func: func.clone(),
+                    env: Arc::clone(env),
+                    site,
+                },
+            ),
+        }
+    }
+
+    /// Node for same function as `node`, called from `parent` instead
+    pub(crate) fn reparent(&mut self, parent: Option<usize>, node: usize) -> usize {
+        match self.nodes[node].callee.clone() {
+            Callee::Label(label) => self.label(parent, label),
+            Callee::Call { func, env, site } => match parent {
+                Some(parent) => self.call(parent, &func, &env, site),
+                None => self.label(None, UNKNOWN),
+            },
+        }
+    }
+
+    /// Count instruction executed by function of node
+    pub(crate) fn count(&mut self, node: usize) {
+        self.nodes[node].insts += 1;
+    }
+
+    /// Stack of function names from root to node
+    pub(crate) fn stack(&mut self, node: usize) -> Stack {
+        let mut stack = vec![];
+        let mut cur = Some(node);
+        while let Some(node) = cur {
+            stack.push(self.name(node));
+            cur = self.nodes[node].parent;
+        }
+        stack.reverse();
+        stack
+    }
+
+    /// Flush instructions counted since last flush to profiler
+    pub(crate) fn flush(&mut self, profiler: &Profiler) {
+        let counted = (0..self.nodes.len())
+            .filter(|n| self.nodes[*n].insts > 0)
+            .collect::<Vec<_>>();
+        if counted.is_empty() {
+            return;
+        }
+        let stacks = counted
+            .into_iter()
+            .map(|n| (self.stack(n), std::mem::take(&mut self.nodes[n].insts)))
+            .collect();
+        profiler.record_insts(self.session, stacks);
+    }
+
+    fn children(&self, parent: Option<usize>) -> &[usize] {
+        match parent {
+            Some(p) => &self.nodes[p].children,
+            None => &self.roots,
+        }
+    }
+
+    fn insert(&mut self, parent: Option<usize>, callee: Callee<T, L>) -> usize {
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            parent,
+            callee,
+            children: vec![],
+            name: None,
+            insts: 0,
+        });
+        match parent {
+            Some(p) => self.nodes[p].children.push(node),
+            None => self.roots.push(node),
+        }
+        node
+    }
+
+    /// Name of function of node - the symbol it is bound to, or its call site if it is anonymous
+    fn name(&mut self, node: usize) -> Arc<str> {
+        if let Some(name) = &self.nodes[node].name {
+            return Arc::clone(name);
+        }
+        let (bound, site) = match &self.nodes[node].callee {
+            Callee::Label(label) => (Some(Arc::from(*label)), 0),
+            Callee::Call { func, env, site } => (
+                env.lock()
+                    .unwrap()
+                    .name_of(func)
+                    .map(|s| Arc::from(s.as_str())),
+                *site,
+            ),
+        };
+        let name = match bound {
+            Some(name) => name,
+            None => {
+                let caller = match self.nodes[node].parent {
+                    Some(p) => self.name(p),
+                    None => Arc::from(UNKNOWN),
+                };
+                Arc::from(format!("<lambda {caller}:{site}>"))
+            }
+        };
+        self.nodes[node].name = Some(Arc::clone(&name));
+        name
+    }
+}
+
+impl Data {
+    fn end_await(&mut self) {
+        if let Some((stack, start)) = self.awaiting.take() {
+            self.stacks.entry(stack).or_default().await_time += start.elapsed();
+        }
+    }
+}
+
+impl Profile {
+    /// Stacks with their samples, from outermost to innermost function name
+    pub fn stacks(&self) -> impl Iterator<Item = (Vec<&str>, &Sample)> {
+        self.stacks
+            .iter()
+            .map(|(stack, sample)| (stack.iter().map(|s| s.as_ref()).collect(), sample))
+    }
+
+    /// Statistics of every function in profile, hottest function first. Functions are ordered
+    /// by total instructions, then by total await time.
+    pub fn functions(&self) -> Vec<FunctionStats> {
+        let mut stats: HashMap<&str, FunctionStats> = HashMap::new();
+        for (stack, sample) in &self.stacks {
+            for (idx, name) in stack.iter().enumerate() {
+                // count recursive functions once per stack
+                if stack[..idx].contains(name) {
+                    continue;
+                }
+                let entry = stats.entry(name.as_ref()).or_insert_with(|| FunctionStats {
+                    name: name.to_string(),
+                    own: Sample::default(),
+                    total: Sample::default(),
+                });
+                entry.total.add(sample);
+            }
+            if let Some(leaf) = stack.last() {
+                if let Some(entry) = stats.get_mut(leaf.as_ref()) {
+                    entry.own.add(sample);
+                }
+            }
+        }
+
+        let mut stats = stats.into_values().collect::<Vec<_>>();
+        stats.sort_by(|a, b| {
+            b.total
+                .insts
+                .cmp(&a.total.insts)
+                .then(b.total.await_time.cmp(&a.total.await_time))
+                .then(a.name.cmp(&b.name))
+        });
+        stats
+    }
+
+    /// Human readable report of up to `limit` hottest functions
+    pub fn report(&self, limit: usize) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:>10} {:>10} {:>10} {:>12}  function",
+            "insts", "own_insts", "await_ms", "own_await_ms"
+        );
+        for f in self.functions().into_iter().take(limit) {
+            let _ = writeln!(
+                out,
+                "{:>10} {:>10} {:>10} {:>12}  {}",
+                f.total.insts,
+                f.own.insts,
+                f.total.await_time.as_millis(),
+                f.own.await_time.as_millis(),
+                f.name
+            );
+        }
+        out
+    }
+
+    /// Stacks in folded format accepted by flamegraph tools, e.g. `<main>;outer;inner 42`,
+    /// weighted by given metric. Stacks with zero weight are omitted.
+    pub fn folded(&self, metric: Metric) -> String {
+        let mut lines = self
+            .stacks
+            .iter()
+            .filter_map(|(stack, sample)| {
+                let weight = match metric {
+                    Metric::Insts => sample.insts,
+                    Metric::AwaitMicros => sample.await_time.as_micros() as u64,
+                };
+                if weight == 0 {
+                    return None;
+                }
+                let names = stack
+                    .iter()
+                    .map(|name| name.replace(';', ":"))
+                    .collect::<Vec<_>>();
+                Some(format!("{} {weight}", names.join(";")))
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+        lines.into_iter().map(|l| l + "\n").collect()
+    }
+}
+
+impl Sample {
+    fn add(&mut self, other: &Sample) {
+        self.insts += other.insts;
+        self.await_time += other.await_time;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Env, NativeAsyncFn, SymbolId};
+    use void::Void;
+
+    type Fiber = crate::Fiber<Void, ()>;
+    type Val = crate::Val<Void, ()>;
+
+    fn profile(prog: &str) -> Profile {
+        let mut f = Fiber::from_expr(prog, Env::standard(), ()).unwrap();
+        let p = Profiler::new();
+        f.set_profiler(Some(p.clone()));
+        p.start();
+        f.start().unwrap();
+        p.stop()
+    }
+
+    fn stats<'a>(profile: &'a [FunctionStats], name: &str) -> &'a FunctionStats {
+        profile.iter().find(|f| f.name == name).unwrap()
+    }
+
+    #[test]
+    fn attribute_to_named_functions() {
+        let prog = "(begin
+            (defn inc (x) (+ x 1))
+            (defn inc_twice (x) (inc (inc x)))
+            (inc_twice 1)
+            (inc 1))";
+        let functions = profile(prog).functions();
+
+        assert_eq!(functions[0].name, MAIN, "main should be hottest by total");
+        let inc = stats(&functions, "inc");
+        let inc_twice = stats(&functions, "inc_twice");
+        assert_eq!(inc.own, inc.total, "inc calls no functions");
+        assert_eq!(
+            inc_twice.total.insts,
+            inc_twice.own.insts + inc.total.insts * 2 / 3,
+            "inc_twice includes two of three calls to inc"
+        );
+    }
+
+    #[test]
+    fn attribute_anonymous_to_call_site() {
+        let profile = profile("((fn (x) x) 1)");
+        let names = profile
+            .stacks()
+            .map(|(stack, _)| stack.join(";"))
+            .collect::<Vec<_>>();
+        assert!(
+            names.contains(&"<main>;<lambda <main>:5>".to_string()),
+            "{names:?}"
+        );
+    }
+
+    #[test]
+    fn recursive_counted_once() {
+        let prog = "(begin
+            (defn count (n) (if (eq? n 0) 0 (count (+ n -1))))
+            (count 3))";
+        let profile = profile(prog);
+        let total_insts = profile.stacks().map(|(_, s)| s.insts).sum::<u64>();
+        let count = stats(&profile.functions(), "count").clone();
+        assert!(count.total.insts < total_insts);
+        assert_eq!(count.total.insts, count.own.insts);
+    }
+
+    #[test]
+    fn folded() {
+        let profile = profile("(begin (defn one () 1) (one))");
+        let folded = profile.folded(Metric::Insts);
+        let lines = folded.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("<main> "));
+        assert_eq!(lines[1], "<main>;one 1");
+        assert_eq!(profile.folded(Metric::AwaitMicros), "");
+    }
+
+    #[test]
+    fn restart() {
+        let prog = "(begin (defn one () 1) (one) (yield 0) (one))";
+        let mut f = Fiber::from_expr(prog, Env::standard(), ()).unwrap();
+        let p = Profiler::new();
+        f.set_profiler(Some(p.clone()));
+
+        p.start();
+        f.start().unwrap();
+        let first = p.stop().functions();
+        p.start();
+        f.resume(Ok(Val::Nil)).unwrap();
+        let second = p.stop().functions();
+
+        assert_eq!(
+            stats(&first, "one").total,
+            stats(&second, "one").total,
+            "counts should not carry over between profiling sessions"
+        );
+        assert!(
+            stats(&second, MAIN).own.insts < stats(&first, MAIN).own.insts,
+            "{second:?}"
+        );
+    }
+
+    #[test]
+    fn not_started() {
+        let mut f = Fiber::from_expr("(+ 1 2)", Env::standard(), ()).unwrap();
+        let p = Profiler::new();
+        f.set_profiler(Some(p.clone()));
+        f.start().unwrap();
+        assert!(!p.is_enabled());
+        assert!(
+            p.total_insts() > 0,
+            "instructions are counted while not started"
+        );
+        assert_eq!(p.stop().stacks().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn await_time() {
+        let mut env = Env::standard();
+        env.bind_native_async(
+            SymbolId::from("nap"),
+            NativeAsyncFn {
+                doc: "".to_string(),
+                arity: None,
+                func: |_, _| {
+                    Box::new(async {
+                        std::thread::sleep(Duration::from_millis(5));
+                        Ok(Val::Nil)
+                    })
+                },
+            },
+        );
+        let mut f = Fiber::from_expr("(begin (defn f () (nap)) (f))", env, ()).unwrap();
+        let p = Profiler::new();
+        f.set_profiler(Some(p.clone()));
+        p.start();
+        crate::run(&mut f).await.unwrap();
+        let profile = p.stop();
+
+        let functions = profile.functions();
+        let nap = stats(&functions, "nap");
+        assert!(nap.own.await_time >= Duration::from_millis(5));
+        assert_eq!(nap.own.insts, 0);
+        assert_eq!(stats(&functions, "f").own.await_time, Duration::ZERO);
+        assert!(profile
+            .folded(Metric::AwaitMicros)
+            .starts_with("<main>;f;nap "));
+    }
+}
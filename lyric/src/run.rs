@@ -88,6 +88,7 @@ mod tests {
             SymbolId::from("async_call"),
             NativeAsyncFn {
                 doc: "".to_string(),
+                arity: None,
                 func: |_, _| {
                     Box::new(async {
                         yield_now().await;
@@ -110,6 +111,7 @@ mod tests {
             SymbolId::from("async_inc"),
             NativeAsyncFn {
                 doc: "".to_string(),
+                arity: None,
                 func: |_, args| {
                     let num = match args[..] {
                         [Val::Int(n)] => n,
@@ -136,6 +138,7 @@ mod tests {
             SymbolId::from("async_err"),
             NativeAsyncFn {
                 doc: "".to_string(),
+                arity: None,
                 func: |_, args| {
                     Box::new(async move {
                         yield_now().await;
@@ -161,6 +164,7 @@ mod tests {
             SymbolId::from("async_inc"),
             NativeAsyncFn {
                 doc: "".to_string(),
+                arity: None,
                 func: |_, args| {
                     let num = match args[..] {
                         [Val::Int(n)] => n,
@@ -0,0 +1,94 @@
+//! Lazy sequences backed by generator functions
+//!
+//! A generator sequence runs its function within the fiber requesting its next element. When the
+//! function yields, its call frames are suspended in the sequence until next element is
+//! requested, so generators may freely await native async functions like `recv`.
+use crate::fiber::CallFrame;
+use crate::{Extern, Locals, Val};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Lazy sequence of values
+pub struct Seq<T: Extern, L: Locals> {
+    state: Arc<Mutex<State<T, L>>>,
+}
+
+/// State of a lazy sequence
+pub(crate) enum State<T: Extern, L: Locals> {
+    /// Generator function that is not yet called
+    New(Val<T, L>),
+    /// Generator suspended at a yield
+    Suspended(Suspended<T, L>),
+    /// Generator currently producing next element
+    Running,
+    /// Remaining elements of a list
+    Items(VecDeque<Val<T, L>>),
+    /// Up to given number of remaining elements of inner sequence
+    Take(Seq<T, L>, usize),
+    /// Sequence is exhausted
+    Done,
+}
+
+/// Call frames and stack of suspended generator, relative to the generator's entry call frame
+pub(crate) struct Suspended<T: Extern, L: Locals> {
+    pub(crate) frames: Vec<CallFrame<T, L>>,
+    pub(crate) stack: Vec<Val<T, L>>,
+}
+
+impl<T: Extern, L: Locals> Seq<T, L> {
+    /// Sequence of values yielded by calling given callable with no arguments
+    pub fn from_fn(callable: Val<T, L>) -> Self {
+        Self::new(State::New(callable))
+    }
+
+    /// Sequence of elements of given list
+    pub fn from_list(items: Vec<Val<T, L>>) -> Self {
+        Self::new(State::Items(items.into()))
+    }
+
+    /// Sequence of up to n first elements of given sequence
+    pub fn take(seq: Seq<T, L>, n: usize) -> Self {
+        Self::new(State::Take(seq, n))
+    }
+
+    fn new(state: State<T, L>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Replace state of sequence, returning the previous state
+    pub(crate) fn replace(&self, state: State<T, L>) -> State<T, L> {
+        std::mem::replace(&mut self.state.lock().unwrap(), state)
+    }
+}
+
+impl<T: Extern, L: Locals> Clone for Seq<T, L> {
+    fn clone(&self) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> PartialEq for Seq<T, L> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl<T: Extern, L: Locals> std::fmt::Debug for Seq<T, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Suspended frames may refer back to sequence through their environments
+        let state = match self.state.try_lock().as_deref() {
+            Ok(State::New(_)) => "new",
+            Ok(State::Suspended(_)) => "suspended",
+            Ok(State::Running) => "running",
+            Ok(State::Items(_)) => "items",
+            Ok(State::Take(_, _)) => "take",
+            Ok(State::Done) => "done",
+            Err(_) => "locked",
+        };
+        write!(f, "Seq({state})")
+    }
+}
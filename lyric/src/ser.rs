@@ -0,0 +1,420 @@
+//! Serialize Rust types into [Form]
+//!
+//! - Structs and maps become keyword alists, e.g. `(:name "vrs" :version 1)`
+//! - Unit variants become keywords, e.g. `:ok`
+//! - Other enum variants become lists tagged by keyword, e.g. `(:err "reason")`
+//! - Sequences and tuples become lists
+//! - `None` and `()` become `nil`
+use crate::{Error, Form, KeywordId, Result};
+use serde::ser::{self, Serialize};
+
+/// Serialize given value into [Form]
+pub fn to_form<T: Serialize + ?Sized>(value: &T) -> Result<Form> {
+    value.serialize(Serializer)
+}
+
+impl ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Serialization(msg.to_string())
+    }
+}
+
+/// Serializer producing [Form]
+struct Serializer;
+
+/// Serializer for lists of forms, optionally tagged with leading keyword
+struct SerializeList {
+    items: Vec<Form>,
+}
+
+/// Serializer for keyword alists, optionally tagged with leading keyword
+struct SerializeAlist {
+    items: Vec<Form>,
+    next_key: Option<Form>,
+}
+
+fn int<T: TryInto<i32> + std::fmt::Display + Copy>(v: T) -> Result<Form> {
+    v.try_into()
+        .map(Form::Int)
+        .map_err(|_| Error::Serialization(format!("{v} does not fit in int")))
+}
+
+/// Coerce key to keyword if possible
+fn key_form(key: Form) -> Form {
+    match key {
+        Form::String(s) | Form::RawString(s) => Form::Keyword(KeywordId::from(s)),
+        Form::Symbol(s) => Form::Keyword(s.to_keyword()),
+        k => k,
+    }
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Form;
+    type Error = Error;
+
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+    type SerializeMap = SerializeAlist;
+    type SerializeStruct = SerializeAlist;
+    type SerializeStructVariant = SerializeAlist;
+
+    fn serialize_bool(self, v: bool) -> Result<Form> {
+        Ok(Form::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Form> {
+        int(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Form> {
+        int(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Form> {
+        int(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Form> {
+        int(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Form> {
+        int(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Form> {
+        int(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Form> {
+        int(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Form> {
+        int(v)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Form> {
+        Err(Error::Serialization("floats are not supported".to_string()))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Form> {
+        Err(Error::Serialization("floats are not supported".to_string()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Form> {
+        Ok(Form::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Form> {
+        Ok(Form::string(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Form> {
+        Ok(Form::List(v.iter().map(|b| Form::Int(*b as i32)).collect()))
+    }
+
+    fn serialize_none(self) -> Result<Form> {
+        Ok(Form::Nil)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Form> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Form> {
+        Ok(Form::Nil)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Form> {
+        Ok(Form::Nil)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Form> {
+        Ok(Form::keyword(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Form> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Form> {
+        Ok(Form::List(vec![Form::keyword(variant), to_form(value)?]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList> {
+        Ok(SerializeList {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeList> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeList> {
+        Ok(SerializeList {
+            items: vec![Form::keyword(variant)],
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeAlist> {
+        Ok(SerializeAlist {
+            items: vec![],
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeAlist> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeAlist> {
+        Ok(SerializeAlist {
+            items: vec![Form::keyword(variant)],
+            next_key: None,
+        })
+    }
+}
+
+impl SerializeList {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.items.push(to_form(value)?);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Form;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Form> {
+        Ok(Form::List(self.items))
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Form;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Form> {
+        Ok(Form::List(self.items))
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Form;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Form> {
+        Ok(Form::List(self.items))
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Ok = Form;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Form> {
+        Ok(Form::List(self.items))
+    }
+}
+
+impl ser::SerializeMap for SerializeAlist {
+    type Ok = Form;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.next_key = Some(key_form(to_form(key)?));
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self.next_key.take().ok_or(Error::Serialization(
+            "serialize_value called before serialize_key".to_string(),
+        ))?;
+        self.items.push(key);
+        self.items.push(to_form(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Form> {
+        Ok(Form::List(self.items))
+    }
+}
+
+impl ser::SerializeStruct for SerializeAlist {
+    type Ok = Form;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.items.push(Form::keyword(key));
+        self.items.push(to_form(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Form> {
+        Ok(Form::List(self.items))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeAlist {
+    type Ok = Form;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.items.push(Form::keyword(key));
+        self.items.push(to_form(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Form> {
+        Ok(Form::List(self.items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Serialize)]
+    enum Shape {
+        Empty,
+        Circle(i32),
+        Line(Point, Point),
+        Rect { width: i32, height: i32 },
+    }
+
+    #[test]
+    fn primitives() {
+        assert_eq!(to_form(&true).unwrap(), Form::Bool(true));
+        assert_eq!(to_form(&5u8).unwrap(), Form::Int(5));
+        assert_eq!(to_form("hello").unwrap(), Form::string("hello"));
+        assert_eq!(to_form(&()).unwrap(), Form::Nil);
+        assert!(to_form(&u64::MAX).is_err());
+        assert!(to_form(&1.5).is_err());
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(to_form(&Option::<i32>::None).unwrap(), Form::Nil);
+        assert_eq!(to_form(&Some(1)).unwrap(), Form::Int(1));
+    }
+
+    #[test]
+    fn seqs() {
+        assert_eq!(
+            to_form(&vec![1, 2]).unwrap(),
+            Form::List(vec![Form::Int(1), Form::Int(2)])
+        );
+        assert_eq!(
+            to_form(&(1, "a")).unwrap(),
+            Form::List(vec![Form::Int(1), Form::string("a")])
+        );
+    }
+
+    #[test]
+    fn structs() {
+        assert_eq!(
+            to_form(&Point { x: 1, y: 2 }).unwrap(),
+            Form::from_expr("(:x 1 :y 2)").unwrap()
+        );
+    }
+
+    #[test]
+    fn maps() {
+        let mut map = BTreeMap::new();
+        map.insert("a", 1);
+        map.insert("b", 2);
+        assert_eq!(
+            to_form(&map).unwrap(),
+            Form::from_expr("(:a 1 :b 2)").unwrap()
+        );
+    }
+
+    #[test]
+    fn enums() {
+        assert_eq!(to_form(&Shape::Empty).unwrap(), Form::keyword("Empty"));
+        assert_eq!(
+            to_form(&Shape::Circle(3)).unwrap(),
+            Form::from_expr("(:Circle 3)").unwrap()
+        );
+        assert_eq!(
+            to_form(&Shape::Line(Point { x: 0, y: 0 }, Point { x: 1, y: 1 })).unwrap(),
+            Form::from_expr("(:Line (:x 0 :y 0) (:x 1 :y 1))").unwrap()
+        );
+        assert_eq!(
+            to_form(&Shape::Rect {
+                width: 2,
+                height: 3
+            })
+            .unwrap(),
+            Form::from_expr("(:Rect :width 2 :height 3)").unwrap()
+        );
+    }
+}
@@ -1,9 +1,12 @@
 //! Types in Lisp virtual machine
+use crate::builtin::dynamic::Dynamic;
+use crate::builtin::time::{Duration, Time};
 use crate::codegen::Inst;
-use crate::{parse, Env, Error, Fiber, Ref, Result};
+use crate::{parse, Arity, Env, EnvVal, Error, Fiber, Ref, Result, Seq};
 use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 /// All values in VM that can be manipulated
@@ -23,6 +26,8 @@ pub enum Val<T: Extern, L: Locals> {
     Keyword(KeywordId),
     /// Lists
     List(Vec<Val<T, L>>),
+    /// Instances of record types defined by `defrecord`
+    Record(Record<Val<T, L>>),
     /// A callable function object
     Lambda(Lambda<T, L>),
     /// A callable native function object
@@ -35,6 +40,16 @@ pub enum Val<T: Extern, L: Locals> {
     Error(Error),
     /// References as a value
     Ref(Ref),
+    /// Lazy sequence of values
+    Seq(Seq<T, L>),
+    /// Environment of bindings as a value
+    Env(EnvVal<T, L>),
+    /// Dynamically scoped variable, resolved to its current binding when looked up
+    Dynamic(Dynamic<T, L>),
+    /// Instant in time within a time zone
+    Time(Time),
+    /// Span of calendar and clock units
+    Duration(Duration),
     /// Externally defined type as Val
     Extern(T),
 }
@@ -51,6 +66,14 @@ pub enum Form {
     Symbol(SymbolId),
     Keyword(KeywordId),
     List(Vec<Form>),
+    Record(Record<Form>),
+}
+
+/// Instance of a named record type, with fields in declared order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record<V> {
+    pub name: SymbolId,
+    pub fields: Vec<(KeywordId, V)>,
 }
 
 /// Bytecode sequence
@@ -61,7 +84,8 @@ pub type Bytecode<T, L> = Vec<Inst<T, L>>;
 pub struct Lambda<T: Extern, L: Locals> {
     pub doc: Option<String>,
     pub params: Vec<SymbolId>,
-    pub code: Bytecode<T, L>,
+    /// Code of lambda, shared by its copies and the call frames executing it
+    pub code: Arc<Bytecode<T, L>>,
     pub parent: Option<Arc<Mutex<Env<T, L>>>>,
 }
 
@@ -69,6 +93,8 @@ pub struct Lambda<T: Extern, L: Locals> {
 #[derive(Debug, Clone)]
 pub struct NativeFn<T: Extern, L: Locals> {
     pub doc: String,
+    /// Number of arguments accepted, if fixed or bounded
+    pub arity: Option<Arity>,
     pub func: NativeFnSig<T, L>,
 }
 
@@ -90,12 +116,16 @@ pub enum NativeFnOp<T: Extern, L: Locals> {
     Yield(Val<T, L>),
     /// Execute bytecode-level instructions
     Exec(Bytecode<T, L>),
+    /// Produce next element of sequence, or raise [Error::EndOfSeq] if it is exhausted
+    Next(Seq<T, L>),
 }
 
 /// A native async function
 #[derive(Debug, Clone)]
 pub struct NativeAsyncFn<T: Extern, L: Locals> {
     pub doc: String,
+    /// Number of arguments accepted, if fixed or bounded
+    pub arity: Option<Arity>,
     pub func: NativeAsyncFnSig<T, L>,
 }
 
@@ -133,6 +163,16 @@ pub struct SymbolId(String);
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct KeywordId(String);
 
+/// Conversion from [Val] into host types, e.g. native function arguments
+pub trait FromVal<T: Extern, L: Locals>: Sized {
+    fn from_val(val: Val<T, L>) -> Result<Self>;
+}
+
+/// Conversion from host types into [Val], e.g. native function return values
+pub trait IntoVal<T: Extern, L: Locals> {
+    fn into_val(self) -> Val<T, L>;
+}
+
 /// Trait alias for host defined type in Val (until unstable trait_alias)
 pub trait Extern:
     std::fmt::Display + std::fmt::Debug + std::cmp::PartialEq + std::clone::Clone
@@ -245,6 +285,43 @@ impl Form {
     }
 }
 
+impl<V> Record<V> {
+    /// Get value of given field, if record has field
+    pub fn get(&self, field: &KeywordId) -> Option<&V> {
+        self.fields.iter().find(|(k, _)| k == field).map(|(_, v)| v)
+    }
+
+    /// Map values of each field
+    fn map<U>(self, mut f: impl FnMut(V) -> U) -> Record<U> {
+        Record {
+            name: self.name,
+            fields: self.fields.into_iter().map(|(k, v)| (k, f(v))).collect(),
+        }
+    }
+
+    /// Map values of each field with fallible function
+    fn try_map<U>(self, mut f: impl FnMut(V) -> Result<U>) -> Result<Record<U>> {
+        Ok(Record {
+            name: self.name,
+            fields: self
+                .fields
+                .into_iter()
+                .map(|(k, v)| Ok((k, f(v)?)))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl<V: std::fmt::Display> std::fmt::Display for Record<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#({}", self.name)?;
+        for (k, v) in &self.fields {
+            write!(f, " {k} {v}")?;
+        }
+        write!(f, ")")
+    }
+}
+
 impl<T: Extern, L: Locals> Val<T, L> {
     /// Shorhand for constructing [Val::String]
     pub fn string(s: &str) -> Self {
@@ -277,6 +354,21 @@ impl SymbolId {
     pub fn to_keyword(self) -> KeywordId {
         KeywordId::from(self.0)
     }
+
+    /// Generate unique symbol named after `base` for use in expanded special forms. Generated
+    /// symbols cannot be read from source, so they never capture or shadow symbols of user code.
+    pub fn gensym(base: &str) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        Self(format!("{base}'{}", COUNT.fetch_add(1, Ordering::Relaxed)))
+    }
+
+    /// Returns name symbol was generated from by [SymbolId::gensym], or symbol itself otherwise
+    pub fn base(&self) -> Self {
+        match self.0.split_once('\'') {
+            Some((base, _)) => Self(base.to_string()),
+            None => self.clone(),
+        }
+    }
 }
 
 impl KeywordId {
@@ -309,7 +401,7 @@ impl<T: Extern, L: Locals> NativeAsyncCall<T, L> {
 impl<T: Extern, L: Locals> PartialEq for Lambda<T, L> {
     fn eq(&self, other: &Self) -> bool {
         self.params == other.params
-            && self.code == other.code
+            && (Arc::ptr_eq(&self.code, &other.code) || self.code == other.code)
             && ((self.parent.is_none() && other.parent.is_none())
                 || Arc::ptr_eq(
                     self.parent.as_ref().unwrap(),
@@ -343,6 +435,7 @@ where
                         .join(" ")
                 ),
             },
+            Val::Record(r) => write!(f, "{r}"),
             Val::Lambda(l) => write!(
                 f,
                 "<lambda ({})>",
@@ -357,6 +450,11 @@ where
             Val::Bytecode(_) => write!(f, "<bytecode>"),
             Val::Error(e) => write!(f, "<error {e}>"),
             Val::Ref(r) => write!(f, "<ref {}>", r.0),
+            Val::Seq(_) => write!(f, "<seq>"),
+            Val::Env(_) => write!(f, "<env>"),
+            Val::Dynamic(d) => write!(f, "<dynamic {}>", d.name),
+            Val::Time(t) => write!(f, "{t}"),
+            Val::Duration(d) => write!(f, "{d}"),
             Val::Extern(e) => write!(f, "{e}"),
         }
     }
@@ -384,6 +482,7 @@ impl std::fmt::Display for Form {
                         .join(" ")
                 ),
             },
+            Form::Record(r) => write!(f, "{r}"),
             Form::RawString(s) => write!(f, "{}", s),
         }
     }
@@ -432,6 +531,7 @@ impl<T: Extern, L: Locals> From<Form> for Val<T, L> {
             Form::Symbol(s) => Val::Symbol(s),
             Form::Keyword(k) => Val::Keyword(k),
             Form::List(l) => Val::List(l.into_iter().map(|e| e.into()).collect()),
+            Form::Record(r) => Val::Record(r.map(|e| e.into())),
             Form::RawString(s) => Val::String(s),
         }
     }
@@ -453,7 +553,13 @@ impl<T: Extern, L: Locals> TryFrom<Val<T, L>> for Form {
                     .map(|e| e.try_into())
                     .collect::<Result<Vec<_>>>()?,
             )),
+            Val::Record(r) => Ok(Form::Record(r.try_map(|e| e.try_into())?)),
             Val::Ref(_)
+            | Val::Seq(_)
+            | Val::Env(_)
+            | Val::Dynamic(_)
+            | Val::Time(_)
+            | Val::Duration(_)
             | Val::Error(_)
             | Val::Bytecode(_)
             | Val::Lambda(_)
@@ -488,8 +594,223 @@ impl From<&str> for KeywordId {
     }
 }
 
+impl<T: Extern, L: Locals> FromVal<T, L> for Val<T, L> {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        Ok(val)
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for bool {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        match val {
+            Val::Bool(b) => Ok(b),
+            _ => Err(Error::UnexpectedType("expected bool".to_string())),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for i32 {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        val.as_int().copied()
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for String {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        match val {
+            Val::String(s) => Ok(s),
+            _ => Err(Error::UnexpectedType("expected string".to_string())),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for SymbolId {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        match val {
+            Val::Symbol(s) => Ok(s),
+            _ => Err(Error::UnexpectedType("expected symbol".to_string())),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for KeywordId {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        match val {
+            Val::Keyword(k) => Ok(k),
+            _ => Err(Error::UnexpectedType("expected keyword".to_string())),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for Lambda<T, L> {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        match val {
+            Val::Lambda(l) => Ok(l),
+            _ => Err(Error::UnexpectedType("expected lambda".to_string())),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for Ref {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        match val {
+            Val::Ref(r) => Ok(r),
+            _ => Err(Error::UnexpectedType("expected ref".to_string())),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for EnvVal<T, L> {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        match val {
+            Val::Env(e) => Ok(e),
+            _ => Err(Error::UnexpectedType("expected environment".to_string())),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for Time {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        match val {
+            Val::Time(t) => Ok(t),
+            _ => Err(Error::UnexpectedType("expected time".to_string())),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for Duration {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        match val {
+            Val::Duration(d) => Ok(d),
+            _ => Err(Error::UnexpectedType("expected duration".to_string())),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> FromVal<T, L> for Form {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        Form::try_from(val)
+    }
+}
+
+impl<T: Extern, L: Locals, V: FromVal<T, L>> FromVal<T, L> for Vec<V> {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        val.to_list()?.into_iter().map(V::from_val).collect()
+    }
+}
+
+/// Nil is treated as missing value
+impl<T: Extern, L: Locals, V: FromVal<T, L>> FromVal<T, L> for Option<V> {
+    fn from_val(val: Val<T, L>) -> Result<Self> {
+        match val {
+            Val::Nil => Ok(None),
+            v => Ok(Some(V::from_val(v)?)),
+        }
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for Val<T, L> {
+    fn into_val(self) -> Val<T, L> {
+        self
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for () {
+    fn into_val(self) -> Val<T, L> {
+        Val::Nil
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for bool {
+    fn into_val(self) -> Val<T, L> {
+        Val::Bool(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for i32 {
+    fn into_val(self) -> Val<T, L> {
+        Val::Int(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for String {
+    fn into_val(self) -> Val<T, L> {
+        Val::String(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for &str {
+    fn into_val(self) -> Val<T, L> {
+        Val::string(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for SymbolId {
+    fn into_val(self) -> Val<T, L> {
+        Val::Symbol(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for KeywordId {
+    fn into_val(self) -> Val<T, L> {
+        Val::Keyword(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for Lambda<T, L> {
+    fn into_val(self) -> Val<T, L> {
+        Val::Lambda(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for Ref {
+    fn into_val(self) -> Val<T, L> {
+        Val::Ref(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for EnvVal<T, L> {
+    fn into_val(self) -> Val<T, L> {
+        Val::Env(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for Time {
+    fn into_val(self) -> Val<T, L> {
+        Val::Time(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for Duration {
+    fn into_val(self) -> Val<T, L> {
+        Val::Duration(self)
+    }
+}
+
+impl<T: Extern, L: Locals> IntoVal<T, L> for Form {
+    fn into_val(self) -> Val<T, L> {
+        self.into()
+    }
+}
+
+impl<T: Extern, L: Locals, V: IntoVal<T, L>> IntoVal<T, L> for Vec<V> {
+    fn into_val(self) -> Val<T, L> {
+        Val::List(self.into_iter().map(V::into_val).collect())
+    }
+}
+
+impl<T: Extern, L: Locals, V: IntoVal<T, L>> IntoVal<T, L> for Option<V> {
+    fn into_val(self) -> Val<T, L> {
+        match self {
+            Some(v) => v.into_val(),
+            None => Val::Nil,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{Form, SymbolId};
     use void::Void;
 
     type Val = super::Val<Void, Void>;
@@ -526,6 +847,17 @@ mod tests {
         assert_eq!(Val::symbol("hello").to_string(), "hello");
     }
 
+    #[test]
+    fn gensym() {
+        let sym = SymbolId::gensym("msg");
+        assert_ne!(sym, SymbolId::gensym("msg"));
+        assert_eq!(sym.base(), SymbolId::from("msg"));
+        assert!(
+            !matches!(crate::parse(sym.as_str()), Ok(Form::Symbol(s)) if s == sym),
+            "Generated symbol should not be readable"
+        );
+    }
+
     #[test]
     fn keyword_to_string() {
         assert_eq!(Val::keyword("hello").to_string(), ":hello");
@@ -593,4 +925,35 @@ mod tests {
             "'(1 2 3 '(4 5 6))"
         );
     }
+
+    #[test]
+    fn from_val() {
+        use super::{FromVal, KeywordId};
+
+        assert_eq!(i32::from_val(Val::Int(5)), Ok(5));
+        assert_eq!(String::from_val(Val::string("hi")), Ok("hi".to_string()));
+        assert_eq!(
+            KeywordId::from_val(Val::keyword("ok")),
+            Ok(KeywordId::from("ok"))
+        );
+        assert_eq!(
+            Vec::<i32>::from_val(Val::List(vec![Val::Int(1), Val::Int(2)])),
+            Ok(vec![1, 2])
+        );
+        assert_eq!(Option::<i32>::from_val(Val::Nil), Ok(None));
+        assert!(i32::from_val(Val::string("5")).is_err());
+        assert!(Vec::<i32>::from_val(Val::List(vec![Val::Nil])).is_err());
+    }
+
+    #[test]
+    fn into_val() {
+        use super::IntoVal;
+
+        let v: Val = 5.into_val();
+        assert_eq!(v, Val::Int(5));
+        let v: Val = vec!["a", "b"].into_val();
+        assert_eq!(v, Val::List(vec![Val::string("a"), Val::string("b")]));
+        let v: Val = Option::<i32>::None.into_val();
+        assert_eq!(v, Val::Nil);
+    }
 }
@@ -136,6 +136,7 @@ fn fiber_yielding_native_binding() {
         SymbolId::from("echo_yield"),
         NativeFn {
             doc: "".to_string(),
+            arity: None,
             func: |_, x| Ok(NativeFnOp::Yield(Val::Extern(Ext::Echo(x.to_vec())))),
         },
     );
@@ -192,6 +193,7 @@ fn fiber_conn_recv_try_eval_sim() {
         SymbolId::from("recv_conn"),
         NativeFn {
             doc: "".to_string(),
+            arity: None,
             func: |_, _| Ok(NativeFnOp::Yield(Val::Extern(Ext::RecvConn))),
         },
     );
@@ -199,6 +201,7 @@ fn fiber_conn_recv_try_eval_sim() {
         SymbolId::from("send_conn"),
         NativeFn {
             doc: "".to_string(),
+            arity: None,
             func: |_, args| Ok(NativeFnOp::Yield(Val::Extern(Ext::SendConn(args.to_vec())))),
         },
     );
@@ -263,6 +266,7 @@ fn get_set_locals() {
         SymbolId::from("get_local"),
         NativeFn {
             doc: "".to_string(),
+            arity: None,
             func: |f, _| {
                 let v = f.locals().val;
                 Ok(NativeFnOp::Return(Val::Int(v)))
@@ -273,6 +277,7 @@ fn get_set_locals() {
         SymbolId::from("inc_local"),
         NativeFn {
             doc: "".to_string(),
+            arity: None,
             func: |f, args| {
                 let v = match args {
                     [Val::Int(v)] => v,
@@ -15,6 +15,7 @@ fn eval_expr(e: &str) -> Result<Val> {
         SymbolId::from("echo_args"),
         NativeFn {
             doc: "".to_string(),
+            arity: None,
             func: |_, x| Ok(NativeFnOp::Return(Val::List(x.to_vec()))),
         },
     );
@@ -41,6 +42,14 @@ fn int() {
     assert_eq!(eval_expr("5").unwrap(), Val::Int(5));
 }
 
+#[test]
+fn int_overflow() {
+    assert_eq!(eval_expr("(- 0 2147483647 1)").unwrap(), Val::Int(i32::MIN));
+    assert_matches!(eval_expr("(- (- 0 2147483647 1))"), Err(Error::Runtime(_)));
+    assert_matches!(eval_expr("(- 0 2147483647 2)"), Err(Error::Runtime(_)));
+    assert_matches!(eval_expr("(+ 2147483647 1)"), Err(Error::Runtime(_)));
+}
+
 #[test]
 fn string() {
     assert_eq!(eval_expr("\"hello\"").unwrap(), Val::string("hello"));
@@ -847,6 +856,430 @@ fn eval_match() {
     }
 }
 
+#[test]
+fn defn_clauses() {
+    {
+        let prog = r#"(begin
+            (defn handle
+                "Handles messages"
+                (((:add a b)) (+ a b))
+                (((:neg n)) :when (eq? n 0) :zero)
+                (((:neg n)) (+ 0 n))
+                ((:ping) :pong))
+            (list (handle '(:add 1 2)) (handle '(:neg 0)) (handle '(:neg 3)) (handle :ping)))
+        "#;
+        assert_eq!(
+            eval_expr(prog).unwrap(),
+            Val::List(vec![
+                Val::Int(3),
+                Val::keyword("zero"),
+                Val::Int(3),
+                Val::keyword("pong"),
+            ])
+        );
+    }
+    {
+        let prog = r#"(begin
+            (defn same
+                ((x x) true)
+                ((_ _) false))
+            (list (same 1 1) (same 1 2)))
+        "#;
+        assert_eq!(
+            eval_expr(prog).unwrap(),
+            Val::List(vec![Val::Bool(true), Val::Bool(false)])
+        );
+    }
+    {
+        let prog = r#"(begin
+            (defn handle ((:ping) :pong))
+            (handle :pang))
+        "#;
+        assert_matches!(
+            eval_expr(prog),
+            Err(Error::NoMatchingClause(call)) if call == "(handle :pang)"
+        );
+    }
+    {
+        let prog = r#"(begin
+            (def x :global)
+            (def _args :global)
+            (defn pick
+                (((:a x)) :when (eq? x 0) :zero)
+                ((n) (list x _args)))
+            (list (pick '(:a 0)) (pick '(:a 1)) (pick :b)))
+        "#;
+        assert_eq!(
+            eval_expr(prog).unwrap(),
+            Val::from_expr("(:zero (:global :global) (:global :global))").unwrap(),
+            "Bindings of a clause should not be seen by other clauses"
+        );
+    }
+    {
+        let prog = r#"(defn handle ((a) a) ((a b) a))"#;
+        assert_matches!(eval_expr(prog), Err(Error::InvalidExpression(_)));
+    }
+}
+
+#[test]
+fn defrecord() {
+    let def = "(defrecord point (x y))";
+    {
+        let prog = format!(
+            "(begin {def}
+                (def p (point 1 2))
+                (list (point? p) (point? '(1 2)) (point_x p) (point_y p) (get p :y)))"
+        );
+        assert_eq!(
+            eval_expr(&prog).unwrap(),
+            Val::List(vec![
+                Val::Bool(true),
+                Val::Bool(false),
+                Val::Int(1),
+                Val::Int(2),
+                Val::Int(2),
+            ])
+        );
+    }
+    {
+        let prog = format!("(begin {def} (point_update (point 1 2) :x 10))");
+        let updated = eval_expr(&prog).unwrap();
+        assert_eq!(updated.to_string(), "#(point :x 10 :y 2)");
+        assert_eq!(Val::from_expr(&updated.to_string()).unwrap(), updated);
+        let prog = format!("(begin {def} (eq? (point 1 2) #(point :x 1 :y 2)))");
+        assert_eq!(eval_expr(&prog).unwrap(), Val::Bool(true));
+    }
+    {
+        let prog = format!("(begin {def} (get (point 1 2) :z))");
+        assert_matches!(eval_expr(&prog), Err(Error::UnexpectedArguments(_)));
+        let prog = format!("(begin {def} (point_update (point 1 2) :z 0))");
+        assert_matches!(eval_expr(&prog), Err(Error::UnexpectedArguments(_)));
+        let prog = format!("(begin {def} (defrecord line (a b)) (point_x (line 1 2)))");
+        assert_matches!(eval_expr(&prog), Err(Error::UnexpectedType(_)));
+    }
+    {
+        let prog = format!(
+            "(begin {def}
+                (defn describe
+                    (((record point :x 0 :y y)) (list :on_y_axis y))
+                    (((record point :x x)) (list :at x)))
+                (list (describe (point 0 5)) (describe (point 3 5))))"
+        );
+        assert_eq!(
+            eval_expr(&prog).unwrap(),
+            Val::from_expr("((:on_y_axis 5) (:at 3))").unwrap()
+        );
+        let prog = format!("(begin {def} (match '(point 1 2) ((record point :x x) x) (_ :no)))");
+        assert_eq!(eval_expr(&prog).unwrap(), Val::keyword("no"));
+    }
+    {
+        let prog = "(defrecord point (x x))";
+        assert_matches!(eval_expr(prog), Err(Error::InvalidExpression(_)));
+    }
+}
+
+#[test]
+fn protocols() {
+    let def = r#"
+        (defprotocol item "Items in launcher"
+            (describe (item) "Describe item")
+            (render (item width)))
+        (defrecord note (title))
+        (defimpl item :url
+            (describe (item) (list :url (get item 1)))
+            (render (item width) width))
+        (defimpl item note
+            (describe (item) (list :note (note_title item))))
+        (defimpl item string
+            (describe (item) (list :string item)))
+        (defimpl item list
+            (describe (item) :list))
+        (defimpl item _
+            (describe (item) :unknown))
+    "#;
+    {
+        let prog = format!(
+            r#"(begin {def}
+                (list (describe '(:url "http://example.com"))
+                      (describe (note "todo"))
+                      (describe "hi")
+                      (describe '(:app "Safari"))
+                      (describe '(1 2))
+                      (describe 42)
+                      (render '(:url "http://example.com") 80)))"#
+        );
+        assert_eq!(
+            eval_expr(&prog).unwrap(),
+            Val::from_expr(
+                r#"((:url "http://example.com") (:note "todo") (:string "hi") :list :list :unknown 80)"#
+            )
+            .unwrap()
+        );
+    }
+    {
+        let prog = format!("(begin {def} (render (note \"todo\") 80))");
+        assert_matches!(eval_expr(&prog), Err(Error::NoImplementation(_)));
+    }
+    {
+        let prog = format!("(begin {def} (defimpl item int (describe (a b) a)))");
+        assert_matches!(eval_expr(&prog), Err(Error::UnexpectedArguments(_)));
+        let prog = format!("(begin {def} (defimpl item int (open (a) a)))");
+        assert_matches!(eval_expr(&prog), Err(Error::UnexpectedArguments(_)));
+    }
+    {
+        let prog = format!(
+            "(begin {def} (defimpl item note (describe (n) :redefined)) (describe (note \"a\")))"
+        );
+        assert_eq!(eval_expr(&prog).unwrap(), Val::keyword("redefined"));
+    }
+    {
+        let prog = format!("(begin {def} (help item))");
+        assert_eq!(
+            eval_expr(&prog).unwrap(),
+            Val::string(
+                "(defprotocol item) - Items in launcher
+Methods:
+  (describe ITEM) - Describe item
+  (render ITEM WIDTH)
+Implementations:
+  :url - describe render
+  note - describe
+  string - describe
+  list - describe
+  _ - describe"
+            )
+        );
+    }
+    {
+        let prog =
+            "(list (type_of 1) (type_of '(:tag 1)) (type_of '(1)) (type_of :a) (type_of nil))";
+        assert_eq!(
+            eval_expr(prog).unwrap(),
+            Val::from_expr("(int :tag list keyword nil)").unwrap()
+        );
+    }
+    {
+        let prog = "(defprotocol item (describe ()))";
+        assert_matches!(eval_expr(prog), Err(Error::InvalidExpression(_)));
+    }
+}
+
+#[test]
+fn lazy_sequences() {
+    let cases = [
+        ("(realize (take (iterate (fn (x) (+ x 1)) 0) 5))", "(0 1 2 3 4)"),
+        (
+            "(realize (take (lazy_filter (lazy_map (iterate (fn (x) (+ x 1)) 1) (fn (x) (+ x x))) (fn (x) (not? (eq? x 4)))) 3))",
+            "(2 6 8)",
+        ),
+        ("(realize (take (repeat :a) 2))", "(:a :a)"),
+        ("(realize (take '(1 2) 5))", "(1 2)"),
+        (
+            "(begin (def s (seq '(1 2 3))) (next s) (realize s))",
+            "(2 3)",
+        ),
+        (
+            "(realize (for (x '(1 2 3 4) :when (not? (eq? x 2))) (+ x x)))",
+            "(2 6 8)",
+        ),
+        (
+            "(begin (def total 0) (doseq (x (take (repeat 2) 3)) (set total (+ total x))) total)",
+            "6",
+        ),
+        (
+            "(list 0 (realize (seq (fn () (try (begin (yield 1) (yield 2) (undefined_fn)))))))",
+            "(0 (1 2))",
+        ),
+    ];
+    for (expr, expected) in cases {
+        assert_eq!(
+            eval_expr(expr).unwrap(),
+            Val::from_expr(expected).unwrap(),
+            "{expr}"
+        );
+    }
+
+    assert_eq!(
+        eval_expr("(begin (def s (seq (fn () (yield 1)))) (list (next s) (try (next s))))")
+            .unwrap(),
+        Val::List(vec![Val::Int(1), Val::Error(Error::EndOfSeq)])
+    );
+    assert_matches!(eval_expr("(next (seq '()))"), Err(Error::EndOfSeq));
+    assert_matches!(
+        eval_expr("(realize (lazy_map '(1 2) (fn (x) (undefined_fn))))"),
+        Err(Error::UndefinedSymbol(_))
+    );
+    assert_matches!(eval_expr("(for (x) x)"), Err(Error::InvalidExpression(_)));
+}
+
+#[test]
+fn json() {
+    assert_eq!(
+        eval_expr(r#"(get (json_decode "{\"a\": [1, 2]}") :a)"#).unwrap(),
+        Val::from_expr("(1 2)").unwrap()
+    );
+    assert_eq!(
+        eval_expr(r#"(match (json_decode "{\"a\": {\"b\": 0.5}}") ((:a (:b x)) x))"#).unwrap(),
+        Val::string("0.5")
+    );
+    assert_eq!(
+        eval_expr("(json_encode '(:a (1 :b) :c nil) :pretty)").unwrap(),
+        Val::string("{\n  \"a\": [\n    1,\n    \"b\"\n  ],\n  \"c\": null\n}")
+    );
+    assert_eq!(
+        eval_expr("(json_encode (list 1 (fn () 1)) :lossy)").unwrap(),
+        Val::string(r#"[1,"<lambda ()>"]"#)
+    );
+    assert_matches!(
+        eval_expr("(json_encode (list 1 (fn () 1)))"),
+        Err(Error::Serialization(_))
+    );
+    assert_matches!(
+        eval_expr(r#"(json_decode "[1" )"#),
+        Err(Error::Serialization(_))
+    );
+}
+
+#[test]
+fn time() {
+    assert_eq!(
+        eval_expr(
+            r#"(time_format (+ (time_parse "2024-01-31T10:00:00+00:00[UTC]") (duration :months 1 :hours 2)))"#
+        )
+        .unwrap(),
+        Val::string("2024-02-29T12:00:00+00:00")
+    );
+    assert_eq!(
+        eval_expr(r#"(- (time_parse "2024-07-14T12:00:00Z") (time_parse "2024-07-14T10:30:00Z"))"#)
+            .unwrap(),
+        eval_expr(r#"(duration "1h 30m")"#).unwrap()
+    );
+    assert_eq!(
+        eval_expr(r#"(time_format (time_in_tz (time_parse "2024-07-14T12:00:00Z") "Asia/Seoul") "%H:%M %Z")"#)
+            .unwrap(),
+        Val::string("21:00 KST")
+    );
+    assert_eq!(
+        eval_expr(r#"(get (time_fields (time_parse "14/07/2024 09:05 +0000" "%d/%m/%Y %H:%M %z")) :weekday)"#)
+            .unwrap(),
+        Val::Int(7)
+    );
+    assert_eq!(
+        eval_expr("(list (< (now) (+ (now) (duration :seconds 1))) (> (duration :days 1) (duration :hours 25)) (<= 1 2 2) (< 3 2))")
+            .unwrap(),
+        Val::from_expr("(true false true false)").unwrap()
+    );
+    assert_matches!(
+        eval_expr(r#"(time_parse "yesterday")"#),
+        Err(Error::UnexpectedArguments(_))
+    );
+    assert_matches!(
+        eval_expr(r#"(< (now) 1)"#),
+        Err(Error::UnexpectedArguments(_) | Error::UnexpectedType(_))
+    );
+}
+
+#[test]
+fn dynamic_variables() {
+    assert_eq!(
+        eval_expr(
+            r#"(begin
+                (defdynamic prefix "")
+                (defn log (msg) (str prefix msg))
+                (list
+                    (log "a")
+                    (parameterize ((prefix "> ")) (log "b"))
+                    (parameterize ((prefix "> "))
+                        (parameterize ((prefix ">> ")) (log "c")))
+                    (log "d")))"#
+        )
+        .unwrap(),
+        Val::from_expr(r#"("a" "> b" ">> c" "d")"#).unwrap()
+    );
+    assert_eq!(
+        eval_expr(
+            r#"(begin
+                (defdynamic level 0)
+                (try (parameterize ((level 1)) (undefined_fn)))
+                (def rebound (parameterize ((level 2)) (set level 3) level))
+                (list level rebound (begin (set level 4) level)))"#
+        )
+        .unwrap(),
+        Val::from_expr("(0 3 4)").unwrap(),
+        "bindings should be restored after errors, and set should update innermost binding"
+    );
+    assert_eq!(
+        eval_expr(
+            r#"(begin
+                (defdynamic n 0)
+                (def gen (seq (fn () (loop (yield n)))))
+                (list (parameterize ((n 1)) (next gen)) (next gen)))"#
+        )
+        .unwrap(),
+        Val::from_expr("(1 0)").unwrap(),
+        "generators should see bindings of their callers"
+    );
+    assert_matches!(
+        eval_expr("(begin (def x 1) (parameterize ((x 2)) x))"),
+        Err(Error::UnexpectedType(_))
+    );
+}
+
+#[test]
+fn first_class_envs() {
+    assert_eq!(
+        eval_expr(
+            r#"(begin
+                (def x :outer)
+                (def sandbox (make_env))
+                (eval '(def x :inner) sandbox)
+                (list x (env_get sandbox 'x) (eval 'x sandbox)))"#
+        )
+        .unwrap(),
+        Val::from_expr("(:outer :inner :inner)").unwrap()
+    );
+    assert_eq!(
+        eval_expr(
+            r#"(begin
+                (def x 1)
+                (def child (make_env :base :current))
+                (env_define child 'y 2)
+                (eval '(+ x y) child))"#
+        )
+        .unwrap(),
+        Val::Int(3)
+    );
+    assert_eq!(
+        eval_expr(
+            r#"(begin
+                (def env (make_env :base :core :allow '(echo_args)))
+                (list (eval '(echo_args 1) env) (contains? (ls_env env) 'dbg)))"#
+        )
+        .unwrap(),
+        Val::from_expr("((1) false)").unwrap()
+    );
+    assert_matches!(
+        eval_expr("(eval '(echo_args 1) (make_env))"),
+        Err(Error::UndefinedSymbol(_))
+    );
+    assert_matches!(
+        eval_expr("(eval '(+ 1 1) (make_env :base :empty))"),
+        Err(Error::UndefinedSymbol(_))
+    );
+    assert_matches!(
+        eval_expr("(err? (try (eval '(undefined) (make_env))))"),
+        Ok(Val::Bool(true))
+    );
+    assert_matches!(
+        eval_expr("(eval 1 :not_env)"),
+        Err(Error::UnexpectedType(_))
+    );
+    assert_matches!(
+        eval_expr("(make_env :base :unknown)"),
+        Err(Error::UnexpectedArguments(_))
+    );
+}
+
 // TODO: Test - if with blocks
 
 //     #[test]
@@ -1162,3 +1595,35 @@ fn eval_match() {
 // }
 
 // TODO: Test Recursive Fibonacci
+
+#[test]
+fn eval_pprint() {
+    assert_eq!(
+        eval_expr("(pprint '(:name :echo :pid 10))").unwrap(),
+        Val::string("(:name :echo :pid 10)")
+    );
+    assert_eq!(
+        eval_expr("(pprint '(:name :echo :pid 10) 15)").unwrap(),
+        Val::string("(:name :echo\n :pid 10)")
+    );
+    assert_matches!(
+        eval_expr("(pprint '(1 2) 0)"),
+        Err(Error::UnexpectedArguments(_))
+    );
+}
+
+#[test]
+fn eval_check() {
+    assert_eq!(eval_expr("(check '(+ 1 2))").unwrap(), Val::List(vec![]));
+    assert_eq!(
+        eval_expr("(begin (def x 1) (check '(+ x y)))").unwrap(),
+        Val::from_expr("((:unbound_symbol \"Unbound symbol - y\"))").unwrap()
+    );
+    assert_eq!(
+        eval_expr("(check '(if))").unwrap(),
+        Val::from_expr(
+            "((:malformed_form \"Malformed expression - if expects 2 to 3 arguments - got 0\"))"
+        )
+        .unwrap()
+    );
+}
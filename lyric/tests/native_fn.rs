@@ -0,0 +1,129 @@
+//! Tests for native functions defined via `lyric::native_fn`
+
+use assert_matches::assert_matches;
+use lyric::{native_fn, Error, Result, SymbolId};
+use void::Void;
+
+type Fiber = lyric::Fiber<Void, ()>;
+type Val = lyric::Val<Void, ()>;
+type Env = lyric::Env<Void, ()>;
+type NativeFn = lyric::NativeFn<Void, ()>;
+type NativeAsyncFn = lyric::NativeAsyncFn<Void, ()>;
+
+/// (add A B) - Add two integers
+#[native_fn]
+fn add(a: i32, b: i32) -> Result<i32> {
+    Ok(a + b)
+}
+
+/// (greet NAME [GREETING]) - Greet NAME
+/// with optional GREETING
+#[native_fn]
+fn greet(name: String, greeting: Option<String>) -> Result<String> {
+    Ok(format!(
+        "{} {name}",
+        greeting.unwrap_or("hello".to_string())
+    ))
+}
+
+/// (join SEP ARGS...) - Join ARGS with SEP
+#[native_fn]
+async fn join(sep: String, #[rest] args: Vec<String>) -> Result<String> {
+    Ok(args.join(&sep))
+}
+
+/// (has_global SYM) - Whether or not SYM is defined in fiber's global environment
+#[native_fn]
+async fn has_global(fiber: &mut Fiber, sym: SymbolId) -> Result<bool> {
+    Ok(fiber.global_env().lock().unwrap().get(&sym).is_some())
+}
+
+mod generic {
+    use lyric::{native_fn, Extern, KeywordId, Locals, NativeFn, Result, Val};
+
+    /// (tag KEYWORD VAL) - Wrap VAL in list tagged with KEYWORD
+    #[native_fn]
+    pub fn tag<T: Extern, L: Locals>(kwd: KeywordId, val: Val<T, L>) -> Result<Vec<Val<T, L>>> {
+        Ok(vec![Val::Keyword(kwd), val])
+    }
+}
+
+async fn eval_expr(e: &str) -> Result<Val> {
+    let mut env = Env::standard();
+    env.bind_native(SymbolId::from("add"), add_fn())
+        .bind_native(SymbolId::from("greet"), greet_fn())
+        .bind_native(SymbolId::from("tag"), generic::tag_fn())
+        .bind_native_async(SymbolId::from("join"), join_fn())
+        .bind_native_async(SymbolId::from("has_global"), has_global_fn());
+    let mut f = Fiber::from_expr(e, env, ())?;
+    lyric::run(&mut f).await
+}
+
+#[tokio::test]
+async fn doc_from_doc_comment() {
+    assert_eq!(add_fn().doc, "(add A B) - Add two integers");
+    assert_eq!(
+        greet_fn().doc,
+        "(greet NAME [GREETING]) - Greet NAME with optional GREETING"
+    );
+}
+
+#[tokio::test]
+async fn sync_fn() {
+    assert_eq!(eval_expr("(add 1 2)").await, Ok(Val::Int(3)));
+    assert_eq!(
+        eval_expr("(tag :ok 1)").await,
+        Ok(Val::List(vec![Val::keyword("ok"), Val::Int(1)]))
+    );
+}
+
+#[tokio::test]
+async fn async_fn() {
+    assert_eq!(
+        eval_expr("(join \",\" \"a\" \"b\" \"c\")").await,
+        Ok(Val::string("a,b,c"))
+    );
+    assert_eq!(eval_expr("(join \",\")").await, Ok(Val::string("")));
+    assert_eq!(eval_expr("(has_global 'join)").await, Ok(Val::Bool(true)));
+    assert_eq!(eval_expr("(has_global 'nope)").await, Ok(Val::Bool(false)));
+}
+
+#[tokio::test]
+async fn optional_args() {
+    assert_eq!(
+        eval_expr("(greet \"bob\")").await,
+        Ok(Val::string("hello bob"))
+    );
+    assert_eq!(
+        eval_expr("(greet \"bob\" \"hi\")").await,
+        Ok(Val::string("hi bob"))
+    );
+}
+
+#[tokio::test]
+async fn arity_mismatch() {
+    assert_matches!(
+        eval_expr("(add 1)").await,
+        Err(Error::UnexpectedArguments(e)) if e == "add expects 2 argument(s) - got 1"
+    );
+    assert_matches!(
+        eval_expr("(greet \"a\" \"b\" \"c\")").await,
+        Err(Error::UnexpectedArguments(e)) if e == "greet expects 1 to 2 argument(s) - got 3"
+    );
+    assert_matches!(
+        eval_expr("(join)").await,
+        Err(Error::UnexpectedArguments(e)) if e == "join expects at least 1 argument(s) - got 0"
+    );
+}
+
+#[tokio::test]
+async fn type_mismatch() {
+    assert_matches!(
+        eval_expr("(add 1 \"two\")").await,
+        Err(Error::UnexpectedArguments(e)) if e.starts_with("add - invalid argument B")
+    );
+    assert_matches!(
+        eval_expr("(join \",\" \"a\" 1)").await,
+        Err(Error::UnexpectedArguments(e)) if e.starts_with("join - invalid argument ARGS")
+    );
+}
@@ -1,4 +1,5 @@
 mod editor;
+mod ps;
 mod repl;
 mod watch;
 
@@ -12,7 +13,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use tokio::net::UnixStream;
 use tracing::debug;
-use vrs::{Client, Connection, Form, KeywordId};
+use vrs::{Client, Connection, Form, KeywordId, Val};
 
 #[derive(clap::ValueEnum, Debug, Clone, PartialEq)]
 enum Format {
@@ -27,6 +28,16 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
     let args = cli().get_matches();
 
+    if args.get_flag("check") {
+        let file = args
+            .get_one::<String>("file")
+            .expect("file has a default value");
+        if !check_file(file)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let path = args
         .get_one::<String>("socket")
         .map(|s| PathBuf::from_str(s))
@@ -72,6 +83,8 @@ async fn main() -> Result<()> {
 
         if let Some(cmd) = args.get_one::<String>("command") {
             run_cmd(&client, cmd).await
+        } else if args.get_flag("ps") {
+            ps::run(&client).await
         } else if let Some(file) = file {
             run_file(&client, format, file).await
         } else if let Some(topic) = args.get_one::<String>("subscribe") {
@@ -113,8 +126,10 @@ fn cli() -> clap::Command {
              .default_value("-"))
         .arg(arg!(command: -c --command <EXPR> "If present, EXPR is sent as request, then program exits"))
         .arg(arg!(subscribe: -s --subscribe <TOPIC> "If present, watches a specific topic for data"))
+        .arg(arg!(ps: --ps "If present, lists running processes, then program exits"))
+        .arg(arg!(check: --check "If present, statically checks FILE against the environment of runtime processes without running it, then program exits"))
         .group(ArgGroup::new("main")
-               .args(["command", "subscribe"])
+               .args(["command", "subscribe", "ps", "check"])
                .required(false))
         .arg(arg!(follow: -f --follow "If present, continues polling subscription after first topic update")
              .requires("subscribe"))
@@ -148,6 +163,22 @@ fn open_file(file: &str) -> Result<Option<Box<dyn Read>>> {
     }
 }
 
+/// Statically check a script file, printing warnings. Returns whether or not checks passed
+fn check_file(file: &str) -> Result<bool> {
+    let mut src = String::new();
+    open_file(file)?
+        .with_context(|| "No FILE to check")?
+        .read_to_string(&mut src)
+        .with_context(|| format!("Failed to read {file}"))?;
+    let form = lyric::parse(&format!("(begin\n{src}\n)"))?;
+
+    let warnings = lyric::check::check(&Val::from(form), &vrs::proc_env());
+    for warning in &warnings {
+        println!("{file}: {warning}");
+    }
+    Ok(warnings.is_empty())
+}
+
 /// Run a single request
 async fn run_cmd(client: &Client, cmd: &str) -> Result<()> {
     let f = lyric::parse(cmd)?;
@@ -0,0 +1,74 @@
+//! Lists running processes in runtime
+use anyhow::{bail, Context, Result};
+use vrs::{Client, Form};
+
+/// Request for rows of process information, in order of [HEADERS]
+const QUERY: &str = r#"(map (ps :verbose) (lambda (p) (list
+    (get p :pid) (get p :parent) (get p :names) (get p :status) (get p :mailbox_len)
+    (get p :insts) (time_format (get p :spawned_at) "%H:%M:%S") (get p :source))))"#;
+
+const HEADERS: [&str; 8] = [
+    "PID", "PARENT", "NAMES", "STATUS", "MSGS", "INSTS", "STARTED", "SOURCE",
+];
+
+/// Maximum characters shown of process source
+const MAX_SOURCE_LEN: usize = 60;
+
+/// Print table of running processes
+pub(crate) async fn run(client: &Client) -> Result<()> {
+    let req = lyric::parse(QUERY).expect("ps query should parse");
+    let resp = client
+        .request(req)
+        .await
+        .with_context(|| "Failed to request process information")?;
+    let rows = match resp.contents {
+        Ok(Form::List(rows)) => rows,
+        Ok(f) => bail!("Unexpected process information - {f}"),
+        Err(e) => bail!("Failed to list processes - {e}"),
+    };
+
+    let mut table = vec![HEADERS.map(String::from).to_vec()];
+    for row in rows {
+        match row {
+            Form::List(cols) => table.push(cols.iter().map(cell).collect()),
+            f => bail!("Unexpected process information - {f}"),
+        }
+    }
+
+    let widths = (0..HEADERS.len())
+        .map(|col| table.iter().map(|row| row[col].len()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    for row in table {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{c:w$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+
+    Ok(())
+}
+
+/// Render a single column value of process information
+fn cell(form: &Form) -> String {
+    match form {
+        Form::Nil => "-".to_string(),
+        Form::RawString(s) if s.starts_with("<pid ") => s
+            .trim_start_matches("<pid ")
+            .trim_end_matches('>')
+            .to_string(),
+        Form::Keyword(k) => k.as_str().to_string(),
+        Form::List(l) if l.is_empty() => "-".to_string(),
+        Form::List(l) => l.iter().map(cell).collect::<Vec<_>>().join(","),
+        Form::String(s) => {
+            let s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+            match s.char_indices().nth(MAX_SOURCE_LEN) {
+                Some((idx, _)) => format!("{}...", &s[..idx]),
+                None => s,
+            }
+        }
+        f => f.to_string(),
+    }
+}
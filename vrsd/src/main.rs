@@ -15,6 +15,11 @@ async fn main() -> Result<()> {
     }
 
     let runtime = Runtime::new();
+    let jobs = vrs::scheduler_jobs_path();
+    runtime
+        .start_scheduler(&jobs)
+        .await
+        .with_context(|| format!("Failed to start scheduler with jobs {}", jobs.display()))?;
 
     let listener = UnixListener::bind(&path)
         .with_context(|| format!("Failed to start listener at {}", path.display()))?;